@@ -1,35 +1,47 @@
 #[cfg(not(feature = "tokio"))]
 use async_executor::Executor as AsyncExecutor;
-#[cfg(not(feature = "tokio"))]
-use async_task::Task as AsyncTask;
-#[cfg(not(feature = "tokio"))]
-use std::sync::Arc;
-#[cfg(feature = "tokio")]
-use std::{future::pending, marker::PhantomData};
 use std::{
-    future::Future,
+    future::{pending, Future},
+    marker::PhantomData,
     pin::Pin,
+    sync::Arc,
     task::{Context, Poll},
+    time::Duration,
 };
+
+use futures_util::future::{FutureExt, RemoteHandle};
+
+#[cfg(not(feature = "tokio"))]
+use super::default_sleep;
 #[cfg(feature = "tokio")]
-use tokio::task::JoinHandle;
+use super::TokioRuntime;
+use super::{default_spawn_blocking, Runtime};
 
 /// A wrapper around the underlying runtime/executor.
 ///
 /// This is used to run asynchronous tasks internally and allows integration with various runtimes.
-/// See [`crate::Connection::executor`] for an example of integration with external runtimes.
+/// By default, the tasks are run by an internal [`async_executor::Executor`] or by
+/// [`TokioRuntime`], depending on the enabled cargo features. Use
+/// [`crate::connection::Builder::runtime`] to have them run by any other [`Runtime`].
+///
+/// In the default non-tokio case, this type needs to be ticked. See [`crate::Connection::executor`]
+/// for an example of integration with external runtimes.
 ///
 /// **Note:** You can (and should) completely ignore this type when building with `tokio` feature
 /// enabled.
-#[cfg(not(feature = "tokio"))]
+///
+/// [`TokioRuntime`]: crate::TokioRuntime
 #[derive(Debug, Clone)]
 pub struct Executor<'a> {
-    executor: Arc<AsyncExecutor<'a>>,
+    inner: Inner,
+    phantom: PhantomData<&'a ()>,
 }
-#[cfg(feature = "tokio")]
+
 #[derive(Debug, Clone)]
-pub struct Executor<'a> {
-    phantom: PhantomData<&'a ()>,
+enum Inner {
+    #[cfg(not(feature = "tokio"))]
+    AsyncIo(Arc<AsyncExecutor<'static>>),
+    Runtime(Arc<dyn Runtime>),
 }
 
 impl<'a> Executor<'a> {
@@ -38,122 +50,123 @@ impl<'a> Executor<'a> {
     pub fn spawn<T: Send + 'static>(
         &self,
         future: impl Future<Output = T> + Send + 'static,
-        #[allow(unused)] name: &str,
+        name: &str,
     ) -> Task<T> {
-        #[cfg(not(feature = "tokio"))]
-        {
-            Task(Some(self.executor.spawn(future)))
+        let (remote, handle) = future.remote_handle();
+        match &self.inner {
+            #[cfg(not(feature = "tokio"))]
+            Inner::AsyncIo(executor) => executor.spawn(remote).detach(),
+            Inner::Runtime(runtime) => runtime.spawn(Box::pin(remote), name),
         }
 
-        #[cfg(feature = "tokio")]
-        {
-            #[cfg(tokio_unstable)]
-            {
-                Task(Some(
-                    tokio::task::Builder::new()
-                        .name(name)
-                        .spawn(future)
-                        // SAFETY: Looking at the code, this call always returns an `Ok`.
-                        .unwrap(),
-                ))
-            }
-            #[cfg(not(tokio_unstable))]
-            {
-                Task(Some(tokio::task::spawn(future)))
-            }
-        }
+        Task(handle)
     }
 
     /// Returns `true` if there are no unfinished tasks.
     ///
-    /// With `tokio` feature enabled, this always returns `true`.
+    /// If the tasks are run by a [`Runtime`], this always returns `true`.
     pub fn is_empty(&self) -> bool {
-        #[cfg(not(feature = "tokio"))]
-        {
-            self.executor.is_empty()
+        match &self.inner {
+            #[cfg(not(feature = "tokio"))]
+            Inner::AsyncIo(executor) => executor.is_empty(),
+            Inner::Runtime(_) => true,
         }
-
-        #[cfg(feature = "tokio")]
-        true
     }
 
     /// Runs a single task.
     ///
-    /// With `tokio` feature enabled, its a noop and never returns.
+    /// If the tasks are run by a [`Runtime`], its a noop and never returns.
     pub async fn tick(&self) {
-        #[cfg(not(feature = "tokio"))]
-        {
-            self.executor.tick().await
+        match &self.inner {
+            #[cfg(not(feature = "tokio"))]
+            Inner::AsyncIo(executor) => executor.tick().await,
+            Inner::Runtime(_) => pending().await,
         }
+    }
 
-        #[cfg(feature = "tokio")]
-        {
-            pending().await
+    /// Launch the given blocking function in a task, through the runtime.
+    ///
+    /// Not to be used for the blocking calls of the socket I/O. See [`Task::spawn_blocking`].
+    pub(crate) fn spawn_blocking<T, F>(&self, f: F, name: &str) -> Task<T>
+    where
+        T: Send + 'static,
+        F: FnOnce() -> T + Send + 'static,
+    {
+        let (f, task) = Task::blocking(f);
+        match &self.inner {
+            #[cfg(not(feature = "tokio"))]
+            Inner::AsyncIo(_) => default_spawn_blocking(f, name),
+            Inner::Runtime(runtime) => runtime.spawn_blocking(f, name),
+        }
+
+        task
+    }
+
+    /// Sleep for `duration`, using the timers of the runtime.
+    #[allow(unused)]
+    pub(crate) async fn sleep(&self, duration: Duration) {
+        match &self.inner {
+            #[cfg(not(feature = "tokio"))]
+            Inner::AsyncIo(_) => default_sleep(duration).await,
+            Inner::Runtime(runtime) => runtime.sleep(duration).await,
         }
     }
 
-    /// Create a new `Executor`.
+    /// Create a new `Executor`, using the default runtime.
     pub(crate) fn new() -> Self {
         #[cfg(not(feature = "tokio"))]
-        {
-            Self {
-                executor: Arc::new(AsyncExecutor::new()),
-            }
+        let inner = Inner::AsyncIo(Arc::new(AsyncExecutor::new()));
+        #[cfg(feature = "tokio")]
+        let inner = Inner::Runtime(Arc::new(TokioRuntime));
+
+        Self {
+            inner,
+            phantom: PhantomData,
+        }
+    }
+
+    /// Create a new `Executor` that runs its tasks on the given runtime.
+    pub(crate) fn with_runtime<R: Runtime>(runtime: R) -> Self {
+        Self {
+            inner: Inner::Runtime(Arc::new(runtime)),
+            phantom: PhantomData,
         }
+    }
 
-        #[cfg(feature = "tokio")]
-        {
-            Self {
-                phantom: PhantomData,
-            }
+    /// Whether the executor needs to be ticked for the tasks to make progress.
+    #[cfg(not(feature = "tokio"))]
+    pub(crate) fn needs_ticking(&self) -> bool {
+        match &self.inner {
+            #[cfg(not(feature = "tokio"))]
+            Inner::AsyncIo(_) => true,
+            Inner::Runtime(_) => false,
         }
     }
 
     /// Runs the executor until the given future completes.
     ///
-    /// With `tokio` feature enabled, it just awaits on the `future`.
+    /// If the tasks are run by a [`Runtime`], it just awaits on the `future`.
     pub(crate) async fn run<T>(&self, future: impl Future<Output = T>) -> T {
-        #[cfg(not(feature = "tokio"))]
-        {
-            self.executor.run(future).await
-        }
-        #[cfg(feature = "tokio")]
-        {
-            future.await
+        match &self.inner {
+            #[cfg(not(feature = "tokio"))]
+            Inner::AsyncIo(executor) => executor.run(future).await,
+            Inner::Runtime(_) => future.await,
         }
     }
 }
 
-/// A wrapper around the task API of the underlying runtime/executor.
+/// A handle to a task spawned on the runtime.
 ///
-/// This follows the semantics of `async_task::Task` on drop:
-///
-/// * it will be cancelled, rather than detached. For detaching, use the `detach` method.
-/// * errors from the task cancellation will will be ignored. If you need to know about task errors,
-///   convert the task to a `FallibleTask` using the `fallible` method.
-#[cfg(not(feature = "tokio"))]
+/// This follows the semantics of `async_task::Task` on drop: it will be cancelled, rather than
+/// detached. For detaching, use the `detach` method.
 #[doc(hidden)]
 #[derive(Debug)]
-pub struct Task<T>(Option<AsyncTask<T>>);
-#[cfg(feature = "tokio")]
-#[doc(hidden)]
-#[derive(Debug)]
-pub struct Task<T>(Option<JoinHandle<T>>);
+pub struct Task<T>(RemoteHandle<T>);
 
-impl<T> Task<T> {
+impl<T: 'static> Task<T> {
     /// Detaches the task to let it keep running in the background.
-    #[allow(unused_mut)]
-    #[allow(unused)]
-    pub fn detach(mut self) {
-        #[cfg(not(feature = "tokio"))]
-        {
-            self.0.take().expect("async_task::Task is none").detach()
-        }
-
-        #[cfg(feature = "tokio")]
-        {
-            self.0.take().expect("tokio::task::JoinHandle is none");
-        }
+    pub fn detach(self) {
+        self.0.forget();
     }
 }
 
@@ -162,67 +175,40 @@ where
     T: Send + 'static,
 {
     /// Launch the given blocking function in a task.
+    ///
+    /// This is meant for the blocking calls of the socket I/O and hence, just like the I/O itself,
+    /// doesn't go through the [`Runtime`] but the thread pool of `blocking` or `tokio`, depending
+    /// on the enabled cargo features. Use [`Executor::spawn_blocking`] for everything else.
     #[allow(unused)]
-    pub(crate) fn spawn_blocking<F>(f: F, #[allow(unused)] name: &str) -> Self
+    pub(crate) fn spawn_blocking<F>(f: F, name: &str) -> Self
     where
         F: FnOnce() -> T + Send + 'static,
     {
-        #[cfg(not(feature = "tokio"))]
-        {
-            Self(Some(blocking::unblock(f)))
-        }
+        let (f, task) = Self::blocking(f);
+        default_spawn_blocking(f, name);
 
-        #[cfg(feature = "tokio")]
-        {
-            #[cfg(tokio_unstable)]
-            {
-                Self(Some(
-                    tokio::task::Builder::new()
-                        .name(name)
-                        .spawn_blocking(f)
-                        // SAFETY: Looking at the code, this call always returns an `Ok`.
-                        .unwrap(),
-                ))
-            }
-            #[cfg(not(tokio_unstable))]
-            {
-                Self(Some(tokio::task::spawn_blocking(f)))
-            }
-        }
+        task
     }
-}
 
-impl<T> Drop for Task<T> {
-    fn drop(&mut self) {
-        #[cfg(feature = "tokio")]
-        {
-            if let Some(join_handle) = self.0.take() {
-                join_handle.abort();
-            }
-        }
+    /// Wrap the blocking function `f` into a type-erased function and the task for its result.
+    fn blocking<F>(f: F) -> (Box<dyn FnOnce() + Send>, Self)
+    where
+        F: FnOnce() -> T + Send + 'static,
+    {
+        let (remote, handle) = futures_util::future::lazy(move |_| f()).remote_handle();
+        // The future is ready on the first poll.
+        let f = move || {
+            remote.now_or_never();
+        };
+
+        (Box::new(f), Self(handle))
     }
 }
 
-impl<T> Future for Task<T> {
+impl<T: 'static> Future for Task<T> {
     type Output = T;
 
     fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
-        #[cfg(not(feature = "tokio"))]
-        {
-            Pin::new(&mut self.get_mut().0.as_mut().expect("async_task::Task is none")).poll(cx)
-        }
-
-        #[cfg(feature = "tokio")]
-        {
-            Pin::new(
-                &mut self
-                    .get_mut()
-                    .0
-                    .as_mut()
-                    .expect("tokio::task::JoinHandle is none"),
-            )
-            .poll(cx)
-            .map(|r| r.expect("tokio::task::JoinHandle error"))
-        }
+        Pin::new(&mut self.get_mut().0).poll(cx)
     }
 }
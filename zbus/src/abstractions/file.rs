@@ -1,6 +1,7 @@
 //! Runtime-agnostic File I/O abstractions.
 //!
-//! Proving only specific API that we need internally.
+//! Proving only specific API that we need internally. The blocking file system calls are run
+//! through the [`Executor`] and hence, on the [`crate::Runtime`] of the connection.
 
 #[cfg(unix)]
 use std::fs::Metadata;
@@ -13,53 +14,31 @@ use std::{
 
 use futures_core::Stream;
 
-#[cfg(not(feature = "tokio"))]
-#[derive(Debug)]
-pub struct FileLines(futures_util::io::Lines<futures_util::io::BufReader<async_fs::File>>);
-#[cfg(feature = "tokio")]
+use super::Executor;
+
 #[derive(Debug)]
-pub struct FileLines(tokio::io::Lines<tokio::io::BufReader<tokio::fs::File>>);
+pub struct FileLines(std::vec::IntoIter<String>);
 
 impl FileLines {
-    pub async fn open(path: impl AsRef<Path>) -> Result<Self> {
-        #[cfg(not(feature = "tokio"))]
-        {
-            async_fs::File::open(path)
-                .await
-                .map(futures_util::io::BufReader::new)
-                .map(futures_util::AsyncBufReadExt::lines)
-                .map(Self)
-        }
-
-        #[cfg(feature = "tokio")]
-        {
-            tokio::fs::File::open(path)
-                .await
-                .map(tokio::io::BufReader::new)
-                .map(tokio::io::AsyncBufReadExt::lines)
-                .map(Self)
-        }
+    pub async fn open(executor: &Executor<'_>, path: impl AsRef<Path>) -> Result<Self> {
+        let path = path.as_ref().to_owned();
+        // The files we read are tiny so we just read them in one go.
+        let contents = executor
+            .spawn_blocking(move || std::fs::read_to_string(path), "read file")
+            .await?;
+        let lines: Vec<_> = contents.lines().map(String::from).collect();
+
+        Ok(Self(lines.into_iter()))
     }
 }
 
 impl Stream for FileLines {
     type Item = Result<String>;
 
-    fn poll_next(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
-        #[cfg(not(feature = "tokio"))]
-        {
-            Stream::poll_next(Pin::new(&mut self.get_mut().0), cx)
-        }
-
-        #[cfg(feature = "tokio")]
-        {
-            let fut = self.get_mut().0.next_line();
-            futures_util::pin_mut!(fut);
-            std::future::Future::poll(Pin::new(&mut fut), cx).map(Result::transpose)
-        }
+    fn poll_next(self: Pin<&mut Self>, _cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        Poll::Ready(self.get_mut().0.next().map(Ok))
     }
 
-    #[cfg(not(feature = "tokio"))]
     fn size_hint(&self) -> (usize, Option<usize>) {
         self.0.size_hint()
     }
@@ -67,14 +46,10 @@ impl Stream for FileLines {
 
 // Not unix-specific itself but only used on unix.
 #[cfg(unix)]
-pub async fn metadata(path: impl AsRef<Path>) -> Result<Metadata> {
-    #[cfg(not(feature = "tokio"))]
-    {
-        async_fs::metadata(path).await
-    }
+pub async fn metadata(executor: &Executor<'_>, path: impl AsRef<Path>) -> Result<Metadata> {
+    let path = path.as_ref().to_owned();
 
-    #[cfg(feature = "tokio")]
-    {
-        tokio::fs::metadata(path).await
-    }
+    executor
+        .spawn_blocking(move || std::fs::metadata(path), "file metadata")
+        .await
 }
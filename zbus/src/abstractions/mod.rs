@@ -4,6 +4,8 @@
 /// enabled.
 mod executor;
 pub use executor::*;
+mod runtime;
+pub use runtime::*;
 mod async_drop;
 pub(crate) mod async_lock;
 pub use async_drop::*;
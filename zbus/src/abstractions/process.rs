@@ -1,20 +1,27 @@
-use std::{ffi::OsStr, io::Error, process::Output};
+use std::{
+    ffi::{OsStr, OsString},
+    io::Error,
+    process::{Command, Output},
+};
+
+use super::Executor;
 
 /// An asynchronous wrapper around running and getting command output
-pub async fn run<I, S>(program: S, args: I) -> Result<Output, Error>
+///
+/// The command is run through the [`Executor`] and hence, on the [`crate::Runtime`] of the
+/// connection.
+pub async fn run<I, S>(executor: &Executor<'_>, program: S, args: I) -> Result<Output, Error>
 where
     I: IntoIterator<Item = S>,
     S: AsRef<OsStr>,
 {
-    #[cfg(not(feature = "tokio"))]
-    return async_process::Command::new(program)
-        .args(args)
-        .output()
-        .await;
+    let program = program.as_ref().to_owned();
+    let args: Vec<OsString> = args.into_iter().map(|a| a.as_ref().to_owned()).collect();
 
-    #[cfg(feature = "tokio")]
-    return tokio::process::Command::new(program)
-        .args(args)
-        .output()
-        .await;
+    executor
+        .spawn_blocking(
+            move || Command::new(program).args(args).output(),
+            "run process",
+        )
+        .await
 }
@@ -0,0 +1,189 @@
+use futures_util::future::BoxFuture;
+use std::{fmt::Debug, time::Duration};
+
+/// An async runtime that zbus can run its tasks on.
+///
+/// zbus spawns a few tasks per connection (e.g to read from the socket and to dispatch method calls
+/// to the [`crate::ObjectServer`]). By default, these tasks are run on the runtime corresponding to
+/// the enabled cargo features, i-e an internal `async-executor` or [`TokioRuntime`]. Implement this
+/// trait to have them run on any other runtime and pass it to
+/// [`crate::connection::Builder::runtime`].
+///
+/// Besides the spawning of tasks, the runtime is also used for the blocking work zbus does while
+/// setting up the connection (e.g resolving host names, running `launchctl` on macOS and reading
+/// the cookie keyring) and for timers. The provided methods for those default to the thread pool
+/// and timers of `blocking` and `async-io`, or of `tokio` if the `tokio` feature is enabled. The
+/// socket I/O, and the few blocking calls it involves (e.g querying the peer credentials), are
+/// always handled by `async-io` and `blocking`, or by `tokio`.
+///
+/// # Example
+///
+/// A runtime that runs each task on its own thread:
+///
+/// ```no_run
+/// use futures_util::future::BoxFuture;
+/// use zbus::{connection::Builder, Runtime};
+///
+/// #[derive(Debug)]
+/// struct ThreadPerTask;
+///
+/// impl Runtime for ThreadPerTask {
+///     fn spawn(&self, future: BoxFuture<'static, ()>, _name: &str) {
+///         std::thread::spawn(move || zbus::block_on(future));
+///     }
+/// }
+///
+/// # zbus::block_on(async {
+/// let conn = Builder::session()?.runtime(ThreadPerTask).build().await?;
+/// # drop(conn);
+/// # Ok::<(), zbus::Error>(())
+/// # }).unwrap();
+/// ```
+pub trait Runtime: Debug + Send + Sync + 'static {
+    /// Spawn `future` to be run in the background, until completion.
+    ///
+    /// `name` is a human-readable name for the task, which the runtime can use for diagnostics.
+    fn spawn(&self, future: BoxFuture<'static, ()>, name: &str);
+
+    /// Run the blocking function `f` in the background, on a thread where blocking is acceptable.
+    ///
+    /// `name` is a human-readable name for the task, which the runtime can use for diagnostics.
+    ///
+    /// The default implementation runs `f` on the thread pool of `blocking` or `tokio`, depending
+    /// on the enabled cargo features.
+    fn spawn_blocking(&self, f: Box<dyn FnOnce() + Send>, name: &str) {
+        default_spawn_blocking(f, name)
+    }
+
+    /// Create a future that completes once `duration` has elapsed.
+    ///
+    /// The default implementation uses the timers of `async-io` or `tokio`, depending on the
+    /// enabled cargo features.
+    fn sleep(&self, duration: Duration) -> BoxFuture<'static, ()> {
+        Box::pin(default_sleep(duration))
+    }
+}
+
+/// Run `f` on the thread pool of the backend corresponding to the enabled cargo features.
+pub(crate) fn default_spawn_blocking(f: Box<dyn FnOnce() + Send>, #[allow(unused)] name: &str) {
+    #[cfg(not(feature = "tokio"))]
+    {
+        blocking::unblock(f).detach();
+    }
+
+    #[cfg(feature = "tokio")]
+    {
+        #[cfg(tokio_unstable)]
+        {
+            tokio::task::Builder::new()
+                .name(name)
+                .spawn_blocking(f)
+                // SAFETY: Looking at the code, this call always returns an `Ok`.
+                .unwrap();
+        }
+        #[cfg(not(tokio_unstable))]
+        {
+            tokio::task::spawn_blocking(f);
+        }
+    }
+}
+
+/// Sleep using the timers of the backend corresponding to the enabled cargo features.
+pub(crate) async fn default_sleep(duration: Duration) {
+    #[cfg(not(feature = "tokio"))]
+    {
+        async_io::Timer::after(duration).await;
+    }
+
+    #[cfg(feature = "tokio")]
+    {
+        tokio::time::sleep(duration).await;
+    }
+}
+
+/// The runtime used by default when the `tokio` feature is enabled.
+///
+/// The tasks are spawned on the current tokio runtime.
+#[cfg(feature = "tokio")]
+#[derive(Debug, Clone, Copy, Default)]
+pub struct TokioRuntime;
+
+#[cfg(feature = "tokio")]
+impl Runtime for TokioRuntime {
+    fn spawn(&self, future: BoxFuture<'static, ()>, #[allow(unused)] name: &str) {
+        #[cfg(tokio_unstable)]
+        {
+            tokio::task::Builder::new()
+                .name(name)
+                .spawn(future)
+                // SAFETY: Looking at the code, this call always returns an `Ok`.
+                .unwrap();
+        }
+        #[cfg(not(tokio_unstable))]
+        {
+            tokio::task::spawn(future);
+        }
+    }
+}
+
+/// A runtime that runs the tasks on the current [`tokio::task::LocalSet`].
+///
+/// The tasks are spawned with [`tokio::task::spawn_local`] and therefore all run on the thread
+/// driving the `LocalSet`. Hence, the connection must be created and used from within a
+/// `LocalSet`.
+#[cfg(feature = "tokio")]
+#[derive(Debug, Clone, Copy, Default)]
+pub struct TokioLocalRuntime;
+
+#[cfg(feature = "tokio")]
+impl Runtime for TokioLocalRuntime {
+    fn spawn(&self, future: BoxFuture<'static, ()>, #[allow(unused)] name: &str) {
+        #[cfg(tokio_unstable)]
+        {
+            tokio::task::Builder::new()
+                .name(name)
+                .spawn_local(future)
+                // SAFETY: Looking at the code, this call always returns an `Ok`.
+                .unwrap();
+        }
+        #[cfg(not(tokio_unstable))]
+        {
+            tokio::task::spawn_local(future);
+        }
+    }
+}
+
+/// A runtime that runs the tasks on a GLib main context.
+///
+/// The main context must be iterated (e.g by a running `glib::MainLoop`) for the tasks to make any
+/// progress.
+///
+/// The tasks are spawned with `glib::MainContext::spawn` and hence, run on the thread that iterates
+/// the main context. See [`crate::connection::Builder::glib_main_context`] for serving `!Send`
+/// interfaces this way.
+#[cfg(all(feature = "glib", not(feature = "tokio")))]
+#[derive(Debug, Clone)]
+pub struct GlibRuntime {
+    main_context: glib::MainContext,
+}
+
+#[cfg(all(feature = "glib", not(feature = "tokio")))]
+impl GlibRuntime {
+    /// Create a runtime for the given main context.
+    pub fn new(main_context: glib::MainContext) -> Self {
+        Self { main_context }
+    }
+
+    /// The main context the tasks are run on.
+    pub fn main_context(&self) -> &glib::MainContext {
+        &self.main_context
+    }
+}
+
+#[cfg(all(feature = "glib", not(feature = "tokio")))]
+impl Runtime for GlibRuntime {
+    fn spawn(&self, future: BoxFuture<'static, ()>, _name: &str) {
+        // Dropping the `JoinHandle` detaches the task.
+        self.main_context.spawn(future);
+    }
+}
@@ -13,7 +13,7 @@
 use crate::process::run;
 #[cfg(windows)]
 use crate::win32::windows_autolaunch_bus_address;
-use crate::{Error, Result};
+use crate::{Error, Executor, Result};
 #[cfg(not(feature = "tokio"))]
 use async_io::Async;
 #[cfg(all(unix, not(target_os = "macos")))]
@@ -212,26 +212,27 @@ pub(crate) enum Stream {
 }
 
 #[cfg(not(feature = "tokio"))]
-async fn connect_tcp(addr: TcpAddress) -> Result<Async<TcpStream>> {
-    let addrs = crate::Task::spawn_blocking(
-        move || -> Result<Vec<SocketAddr>> {
-            let addrs = (addr.host(), addr.port()).to_socket_addrs()?.filter(|a| {
-                if let Some(family) = addr.family() {
-                    if family == TcpAddressFamily::Ipv4 {
-                        a.is_ipv4()
+async fn connect_tcp(executor: &Executor<'_>, addr: TcpAddress) -> Result<Async<TcpStream>> {
+    let addrs = executor
+        .spawn_blocking(
+            move || -> Result<Vec<SocketAddr>> {
+                let addrs = (addr.host(), addr.port()).to_socket_addrs()?.filter(|a| {
+                    if let Some(family) = addr.family() {
+                        if family == TcpAddressFamily::Ipv4 {
+                            a.is_ipv4()
+                        } else {
+                            a.is_ipv6()
+                        }
                     } else {
-                        a.is_ipv6()
+                        true
                     }
-                } else {
-                    true
-                }
-            });
-            Ok(addrs.collect())
-        },
-        "connect tcp",
-    )
-    .await
-    .map_err(|e| Error::Address(format!("Failed to receive TCP addresses: {e}")))?;
+                });
+                Ok(addrs.collect())
+            },
+            "connect tcp",
+        )
+        .await
+        .map_err(|e| Error::Address(format!("Failed to receive TCP addresses: {e}")))?;
 
     // we could attempt connections in parallel?
     let mut last_err = Error::Address("Failed to connect".into());
@@ -246,15 +247,18 @@ async fn connect_tcp(addr: TcpAddress) -> Result<Async<TcpStream>> {
 }
 
 #[cfg(feature = "tokio")]
-async fn connect_tcp(addr: TcpAddress) -> Result<TcpStream> {
+async fn connect_tcp(_executor: &Executor<'_>, addr: TcpAddress) -> Result<TcpStream> {
     TcpStream::connect((addr.host(), addr.port()))
         .await
         .map_err(|e| Error::InputOutput(e.into()))
 }
 
 #[cfg(target_os = "macos")]
-pub(crate) async fn macos_launchd_bus_address(env_key: &str) -> Result<Address> {
-    let output = run("launchctl", ["getenv", env_key])
+pub(crate) async fn macos_launchd_bus_address(
+    executor: &Executor<'_>,
+    env_key: &str,
+) -> Result<Address> {
+    let output = run(executor, "launchctl", ["getenv", env_key])
         .await
         .expect("failed to wait on launchctl output");
 
@@ -274,18 +278,19 @@ pub(crate) async fn macos_launchd_bus_address(env_key: &str) -> Result<Address>
 
 impl Address {
     #[cfg_attr(any(target_os = "macos", windows), async_recursion::async_recursion)]
-    pub(crate) async fn connect(self) -> Result<Stream> {
+    pub(crate) async fn connect(self, executor: &Executor<'static>) -> Result<Stream> {
         match self {
             Address::Unix(p) => {
                 #[cfg(not(feature = "tokio"))]
                 {
                     #[cfg(windows)]
                     {
-                        let stream = crate::Task::spawn_blocking(
-                            move || UnixStream::connect(p),
-                            "unix stream connection",
-                        )
-                        .await?;
+                        let stream = executor
+                            .spawn_blocking(
+                                move || UnixStream::connect(p),
+                                "unix stream connection",
+                            )
+                            .await?;
                         Async::new(stream)
                             .map(Stream::Unix)
                             .map_err(|e| Error::InputOutput(e.into()))
@@ -330,24 +335,28 @@ impl Address {
                 .map(Stream::Vsock)
                 .map_err(Into::into),
 
-            Address::Tcp(addr) => connect_tcp(addr).await.map(Stream::Tcp),
+            Address::Tcp(addr) => connect_tcp(executor, addr).await.map(Stream::Tcp),
 
             Address::NonceTcp { addr, nonce_file } => {
-                let mut stream = connect_tcp(addr).await?;
+                let mut stream = connect_tcp(executor, addr).await?;
 
                 #[cfg(unix)]
                 let nonce_file = {
                     use std::os::unix::ffi::OsStrExt;
-                    std::ffi::OsStr::from_bytes(&nonce_file)
+                    std::ffi::OsStr::from_bytes(&nonce_file).to_owned()
                 };
 
                 #[cfg(windows)]
                 let nonce_file = std::str::from_utf8(&nonce_file)
-                    .map_err(|_| Error::Address("nonce file path is invalid UTF-8".to_owned()))?;
+                    .map_err(|_| Error::Address("nonce file path is invalid UTF-8".to_owned()))?
+                    .to_owned();
+
+                let nonce = executor
+                    .spawn_blocking(move || std::fs::read(nonce_file), "read nonce file")
+                    .await?;
 
                 #[cfg(not(feature = "tokio"))]
                 {
-                    let nonce = std::fs::read(nonce_file)?;
                     let mut nonce = &nonce[..];
 
                     while !nonce.is_empty() {
@@ -360,7 +369,6 @@ impl Address {
 
                 #[cfg(feature = "tokio")]
                 {
-                    let nonce = tokio::fs::read(nonce_file).await?;
                     tokio::io::AsyncWriteExt::write_all(&mut stream, &nonce).await?;
                 }
 
@@ -380,7 +388,7 @@ impl Address {
             #[cfg(windows)]
             Address::Autolaunch(None) => {
                 let addr = windows_autolaunch_bus_address()?;
-                addr.connect().await
+                addr.connect(executor).await
             }
 
             #[cfg(not(target_os = "macos"))]
@@ -390,8 +398,8 @@ impl Address {
 
             #[cfg(target_os = "macos")]
             Address::Launchd(env) => {
-                let addr = macos_launchd_bus_address(&env).await?;
-                addr.connect().await
+                let addr = macos_launchd_bus_address(executor, &env).await?;
+                addr.connect(executor).await
             }
             Address::UnixDir(_) | Address::UnixTmpDir(_) => {
                 // you can't connect to a unix:dir
@@ -768,7 +776,7 @@ impl TryFrom<&str> for Address {
 #[cfg(test)]
 mod tests {
     use super::{Address, TcpAddress, TcpAddressFamily};
-    use crate::Error;
+    use crate::{Error, Executor};
     use std::str::FromStr;
     use test_log::test;
 
@@ -996,7 +1004,7 @@ mod tests {
         let listener = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
         let port = listener.local_addr().unwrap().port();
         let addr = Address::from_str(&format!("tcp:host=localhost,port={port}")).unwrap();
-        crate::utils::block_on(async { addr.connect().await }).unwrap();
+        crate::utils::block_on(async { addr.connect(&Executor::new()).await }).unwrap();
     }
 
     #[test]
@@ -1042,7 +1050,7 @@ mod tests {
             sender.send(buf == TEST_COOKIE).unwrap();
         });
 
-        crate::utils::block_on(addr.connect()).unwrap();
+        crate::utils::block_on(addr.connect(&Executor::new())).unwrap();
 
         let saw_cookie = receiver
             .recv_timeout(std::time::Duration::from_millis(100))
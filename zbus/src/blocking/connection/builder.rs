@@ -10,7 +10,7 @@ use tokio::net::UnixStream;
 #[cfg(windows)]
 use uds_windows::UnixStream;
 
-use zvariant::{ObjectPath, Str};
+use zvariant::{serialized::Limits, ObjectPath, Str};
 
 use crate::{
     address::Address,
@@ -136,6 +136,13 @@ impl<'a> Builder<'a> {
         Self(self.0.max_queued(max))
     }
 
+    /// Set the limits to enforce on the messages received on the connection.
+    ///
+    /// See [`crate::connection::Builder::limits`] for details.
+    pub fn limits(self, limits: Limits) -> Self {
+        Self(self.0.limits(limits))
+    }
+
     /// Register a D-Bus [`Interface`] to be served at a given path.
     ///
     /// This is similar to [`zbus::blocking::ObjectServer::at`], except that it allows you to have
@@ -5,12 +5,13 @@ use event_listener::EventListener;
 use static_assertions::assert_impl_all;
 use std::{io, ops::Deref};
 use zbus_names::{BusName, ErrorName, InterfaceName, MemberName, OwnedUniqueName, WellKnownName};
-use zvariant::ObjectPath;
+use zvariant::{serialized::Limits, ObjectPath};
 
 use crate::{
     blocking::ObjectServer,
+    connection::{NameEvent, NameOwnershipEvent, WatchNameFlags},
     fdo::{ConnectionCredentials, RequestNameFlags, RequestNameReply},
-    message::Message,
+    message::{Header, Message},
     utils::block_on,
     DBusError, Error, Result,
 };
@@ -51,6 +52,11 @@ impl Connection {
         self.inner.set_max_queued(max)
     }
 
+    /// The limits enforced on the messages received on this connection.
+    pub fn limits(&self) -> Limits {
+        self.inner.limits()
+    }
+
     /// The server's GUID.
     pub fn server_guid(&self) -> &str {
         self.inner.server_guid()
@@ -190,12 +196,12 @@ impl Connection {
         &self,
         well_known_name: W,
         flags: BitFlags<RequestNameFlags>,
-    ) -> Result<RequestNameReply>
+    ) -> Result<NameOwnership>
     where
         W: TryInto<WellKnownName<'w>>,
         W::Error: Into<Error>,
     {
-        block_on(self.inner.request_name_with_flags(well_known_name, flags))
+        block_on(self.inner.request_name_with_flags(well_known_name, flags)).map(NameOwnership)
     }
 
     /// Deregister a previously registered well-known name for this service on the bus.
@@ -214,6 +220,32 @@ impl Connection {
         block_on(self.inner.release_name(well_known_name))
     }
 
+    /// Watch the ownership of a bus name.
+    ///
+    /// See [`crate::Connection::watch_name`] for details.
+    pub fn watch_name<'n, N>(&self, name: N) -> Result<NameWatchIterator>
+    where
+        N: TryInto<BusName<'n>>,
+        N::Error: Into<Error>,
+    {
+        block_on(self.inner.watch_name(name)).map(NameWatchIterator)
+    }
+
+    /// Watch the ownership of a bus name.
+    ///
+    /// See [`crate::Connection::watch_name_with_flags`] for details.
+    pub fn watch_name_with_flags<'n, N>(
+        &self,
+        name: N,
+        flags: BitFlags<WatchNameFlags>,
+    ) -> Result<NameWatchIterator>
+    where
+        N: TryInto<BusName<'n>>,
+        N::Error: Into<Error>,
+    {
+        block_on(self.inner.watch_name_with_flags(name, flags)).map(NameWatchIterator)
+    }
+
     /// Checks if `self` is a connection to a message bus.
     ///
     /// This will return `false` for p2p connections.
@@ -257,6 +289,13 @@ impl Connection {
         block_on(self.inner.peer_credentials())
     }
 
+    /// Returns the credentials of the sender of a message.
+    ///
+    /// See [`crate::Connection::sender_credentials`] for details.
+    pub fn sender_credentials(&self, hdr: &Header<'_>) -> Result<ConnectionCredentials> {
+        block_on(self.inner.sender_credentials(hdr))
+    }
+
     /// Close the connection.
     ///
     /// After this call, all reading and writing operations will fail.
@@ -265,6 +304,77 @@ impl Connection {
     }
 }
 
+/// An [`std::iter::Iterator`] implementation that yields [`NameEvent`]s for a bus name.
+///
+/// Use [`Connection::watch_name`] to create an instance of this type.
+#[derive(Debug)]
+pub struct NameWatchIterator(crate::connection::NameWatchStream);
+
+assert_impl_all!(NameWatchIterator: Send, Sync, Unpin);
+
+impl NameWatchIterator {
+    /// The bus name being watched.
+    pub fn name(&self) -> &BusName<'_> {
+        self.0.name()
+    }
+
+    /// The current owner of the name, as far as this iterator has reported.
+    pub fn owner(&self) -> Option<&OwnedUniqueName> {
+        self.0.owner()
+    }
+}
+
+impl std::iter::Iterator for NameWatchIterator {
+    type Item = NameEvent;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        block_on(futures_util::StreamExt::next(&mut self.0))
+    }
+}
+
+/// A handle to a bus name requested by a connection.
+///
+/// This is an [`std::iter::Iterator`] that yields [`NameOwnershipEvent`]s. Dropping the handle
+/// releases the name, in the background. Use [`NameOwnership::release`] to wait for the name to be
+/// released.
+///
+/// Use [`Connection::request_name_with_flags`] to create an instance of this type.
+#[derive(Debug)]
+#[must_use = "dropping the handle releases the name"]
+pub struct NameOwnership(crate::connection::NameOwnership);
+
+assert_impl_all!(NameOwnership: Send, Sync, Unpin);
+
+impl NameOwnership {
+    /// The name this handle is for.
+    pub fn name(&self) -> &WellKnownName<'_> {
+        self.0.name()
+    }
+
+    /// The reply of the bus to the request of the name.
+    pub fn reply(&self) -> &RequestNameReply {
+        self.0.reply()
+    }
+
+    /// Whether the name is currently owned by this connection, as far as this handle has reported.
+    pub fn is_owner(&self) -> bool {
+        self.0.is_owner()
+    }
+
+    /// Release the name, or cancel the request if it's still in the queue.
+    pub fn release(self) -> Result<()> {
+        block_on(self.0.release())
+    }
+}
+
+impl std::iter::Iterator for NameOwnership {
+    type Item = NameOwnershipEvent;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        block_on(futures_util::StreamExt::next(&mut self.0))
+    }
+}
+
 impl From<crate::Connection> for Connection {
     fn from(conn: crate::Connection) -> Self {
         Self { inner: conn }
@@ -51,3 +51,5 @@ pub use object_server::InterfaceRef;
 pub use connection::Builder;
 
 pub mod fdo;
+#[cfg(feature = "polkit")]
+pub mod polkit;
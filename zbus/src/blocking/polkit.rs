@@ -0,0 +1,16 @@
+//! Polkit authorization.
+//!
+//! Provides blocking versions of the proxy types in [`zbus::polkit`] module.
+
+use enumflags2::BitFlags;
+use static_assertions::assert_impl_all;
+use std::collections::HashMap;
+
+use crate::{
+    dbus_proxy,
+    polkit::{AuthorizationResult, CheckAuthorizationFlags, Subject},
+    Result,
+};
+
+gen_authority_proxy!(false, true);
+assert_impl_all!(AuthorityProxy<'_>: Send, Sync, Unpin);
@@ -21,14 +21,17 @@ use uds_windows::UnixStream;
 #[cfg(all(feature = "vsock", not(feature = "tokio")))]
 use vsock::VsockStream;
 
-use zvariant::{ObjectPath, Str};
+use zvariant::{serialized::Limits, ObjectPath, Str};
 
+#[cfg(all(feature = "glib", not(feature = "tokio")))]
+use crate::GlibRuntime;
 use crate::{
     address::{self, Address},
     async_lock::RwLock,
+    message::BufferPool,
     names::{InterfaceName, UniqueName, WellKnownName},
     object_server::Interface,
-    Connection, Error, Executor, Guid, Result,
+    Connection, Error, Executor, Guid, Result, Runtime,
 };
 
 use super::{
@@ -61,9 +64,12 @@ type Interfaces<'a> =
 pub struct Builder<'a> {
     target: Option<Target>,
     max_queued: Option<usize>,
+    buffer_pool: Option<BufferPool>,
+    limits: Option<Limits>,
     guid: Option<&'a Guid>,
     p2p: bool,
     internal_executor: bool,
+    executor: Option<Executor<'static>>,
     #[derivative(Debug = "ignore")]
     interfaces: Interfaces<'a>,
     names: HashSet<WellKnownName<'a>>,
@@ -249,6 +255,56 @@ impl<'a> Builder<'a> {
         self
     }
 
+    /// Use `pool` for the buffers of the messages of the connection.
+    ///
+    /// By default, each connection gets its own [`BufferPool`]. Use this method to customize its
+    /// limits or to share a pool between connections.
+    pub fn buffer_pool(mut self, pool: BufferPool) -> Self {
+        self.buffer_pool = Some(pool);
+
+        self
+    }
+
+    /// Set the limits to enforce on the messages received on the connection.
+    ///
+    /// Messages exceeding the size and file descriptor limits are dropped (and logged) without
+    /// reading their body into memory or keeping their file descriptors open. Method calls among
+    /// them get an [`fdo::Error::LimitsExceeded`] reply, unless no reply is expected. The other
+    /// limits are enforced when decoding the messages. By default, the limits imposed by the D-Bus
+    /// specification are used. See [`Limits`] for details.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use std::error::Error;
+    /// # use zbus::connection::Builder;
+    /// # use zbus::block_on;
+    /// use zvariant::serialized::Limits;
+    ///
+    /// # block_on(async {
+    /// let limits = Limits::default()
+    ///     .set_max_message_size(1024 * 1024)
+    ///     .set_max_fds(4);
+    /// let conn = Builder::session()?
+    ///     .limits(limits)
+    ///     .build()
+    ///     .await?;
+    /// assert_eq!(conn.limits(), limits);
+    ///
+    /// #     Ok::<(), zbus::Error>(())
+    /// # }).unwrap();
+    /// #
+    /// // Do something useful with `conn`..
+    /// # Ok::<_, Box<dyn Error + Send + Sync>>(())
+    /// ```
+    ///
+    /// [`fdo::Error::LimitsExceeded`]: crate::fdo::Error::LimitsExceeded
+    pub fn limits(mut self, limits: Limits) -> Self {
+        self.limits = Some(limits);
+
+        self
+    }
+
     /// Enable or disable the internal executor thread.
     ///
     /// The thread is enabled by default.
@@ -260,6 +316,169 @@ impl<'a> Builder<'a> {
         self
     }
 
+    /// Run the tasks of the connection on the given runtime.
+    ///
+    /// By default, the tasks are run by an internal executor, or a [`TokioRuntime`] if the `tokio`
+    /// feature is enabled. Use this method to have them run by any other implementation of
+    /// [`Runtime`] instead, e.g for integrating with an async runtime that zbus doesn't provide
+    /// support for.
+    ///
+    /// Since the runtime runs the tasks itself, the internal executor thread is not started when
+    /// this method is used.
+    ///
+    /// [`TokioRuntime`]: crate::TokioRuntime
+    pub fn runtime<R: Runtime>(mut self, runtime: R) -> Self {
+        self.executor = Some(Executor::with_runtime(runtime));
+
+        self
+    }
+
+    /// Drive the connection from the current thread only.
+    ///
+    /// This makes it possible to serve interfaces that are neither `Send` nor `Sync` (see the
+    /// `local` attribute of [`dbus_interface`]), since all the method calls are then dispatched on
+    /// the current thread:
+    ///
+    /// * With the `tokio` feature enabled, the tasks of the connection are spawned on the current
+    ///   [`tokio::task::LocalSet`] (see [`TokioLocalRuntime`]). The connection must therefore be
+    ///   built and used from within a `LocalSet`.
+    /// * Otherwise, the internal executor thread is disabled and you must keep ticking the
+    ///   [executor of the connection][Connection::executor] from the current thread, typically from
+    ///   an `async_executor::LocalExecutor` that also runs your `!Send` tasks. Calls to [`Local`]
+    ///   interfaces dispatched while ticking it from any other thread fail with an
+    ///   [`fdo::Error::Failed`] error.
+    ///
+    /// This method is not needed, and has no effect, when the connection is run on a GLib main
+    /// context (see `Builder::glib_main_context`).
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # #[cfg(not(feature = "tokio"))]
+    /// # zbus::block_on(async {
+    /// use std::{cell::Cell, rc::Rc};
+    /// use futures_util::future::{select, Either};
+    /// use zbus::{connection::Builder, dbus_interface, object_server::Local};
+    ///
+    /// struct Counter {
+    ///     count: Rc<Cell<u32>>,
+    /// }
+    ///
+    /// #[dbus_interface(name = "org.zbus.Counter1", local)]
+    /// impl Counter {
+    ///     fn increment(&self) -> u32 {
+    ///         self.count.set(self.count.get() + 1);
+    ///
+    ///         self.count.get()
+    ///     }
+    /// }
+    ///
+    /// let count = Rc::new(Cell::new(0));
+    /// let conn = Builder::session()?
+    ///     .local()
+    ///     .serve_at("/org/zbus/Counter", Local::new(Counter { count: count.clone() }))?
+    ///     .build()
+    ///     .await?;
+    ///
+    /// let app = async {
+    ///     // Do something useful..
+    /// #   conn.call_method(
+    /// #       Some(conn.unique_name().unwrap()),
+    /// #       "/org/zbus/Counter",
+    /// #       Some("org.zbus.Counter1"),
+    /// #       "Increment",
+    /// #       &(),
+    /// #   )
+    /// #   .await
+    /// };
+    /// let ticker = async {
+    ///     loop {
+    ///         conn.executor().tick().await;
+    ///     }
+    /// };
+    /// futures_util::pin_mut!(app, ticker);
+    /// match select(app, ticker).await {
+    ///     Either::Left((res, _)) => res?,
+    ///     Either::Right(_) => unreachable!(),
+    /// };
+    /// # assert_eq!(count.get(), 1);
+    /// # Ok::<(), zbus::Error>(())
+    /// # }).unwrap();
+    /// ```
+    ///
+    /// [`dbus_interface`]: macro@crate::dbus_interface
+    /// [`TokioLocalRuntime`]: crate::TokioLocalRuntime
+    /// [`Local`]: crate::object_server::Local
+    /// [`fdo::Error::Failed`]: crate::fdo::Error::Failed
+    pub fn local(mut self) -> Self {
+        #[cfg(feature = "tokio")]
+        {
+            self = self.runtime(crate::TokioLocalRuntime);
+        }
+
+        #[cfg(not(feature = "tokio"))]
+        {
+            self.internal_executor = false;
+        }
+
+        self
+    }
+
+    /// Run the connection on the given GLib main context.
+    ///
+    /// All the tasks of the connection, including the socket reader and the dispatching of method
+    /// calls to the [`zbus::ObjectServer`], are spawned on `main_context`, so interface methods get
+    /// called from the thread running the main context (typically the main thread of a GTK
+    /// application). The internal executor thread is not started in this case, regardless of
+    /// [`Builder::internal_executor`].
+    ///
+    /// This is a shorthand for [`Builder::runtime`] with a [`GlibRuntime`].
+    ///
+    /// The main context must be iterated (e.g by a running `glib::MainLoop`) for the connection to
+    /// make any progress, including while [`Builder::build`] is being awaited. Hence you must not
+    /// use the blocking API from the thread running the main context.
+    ///
+    /// # `!Send` interfaces
+    ///
+    /// Interfaces that are neither `Send` nor `Sync` (see the `local` attribute of
+    /// [`dbus_interface`]) can be served without calling [`Builder::local`], as long as:
+    ///
+    /// * the [`Local`] wrapper around the interface is created on the thread that iterates
+    ///   `main_context`, e.g from a future spawned with `glib::MainContext::spawn_local` or run
+    ///   with `glib::MainContext::block_on` on it, and
+    /// * `main_context` is only ever iterated from that thread, which is the case for the default
+    ///   main context of a GTK application.
+    ///
+    /// Calls to the interface dispatched from any other thread fail with an
+    /// [`fdo::Error::Failed`] error.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// # use zbus::connection::Builder;
+    /// let main_context = glib::MainContext::default();
+    /// main_context.spawn_local(async {
+    ///     let conn = Builder::session()?
+    ///         .glib_main_context(glib::MainContext::default())
+    ///         .build()
+    ///         .await?;
+    ///
+    ///     // Do something useful with `conn`..
+    /// #   drop(conn);
+    ///     Ok::<(), zbus::Error>(())
+    /// });
+    ///
+    /// glib::MainLoop::new(Some(&main_context), false).run();
+    /// ```
+    ///
+    /// [`dbus_interface`]: macro@crate::dbus_interface
+    /// [`Local`]: crate::object_server::Local
+    /// [`fdo::Error::Failed`]: crate::fdo::Error::Failed
+    #[cfg(all(feature = "glib", not(feature = "tokio")))]
+    pub fn glib_main_context(self, main_context: glib::MainContext) -> Self {
+        self.runtime(GlibRuntime::new(main_context))
+    }
+
     /// Register a D-Bus [`Interface`] to be served at a given path.
     ///
     /// This is similar to [`zbus::ObjectServer::at`], except that it allows you to have your
@@ -325,7 +544,7 @@ impl<'a> Builder<'a> {
     /// Until server-side bus connection is supported, attempting to build such a connection will
     /// result in [`Error::Unsupported`] error.
     pub async fn build(self) -> Result<Connection> {
-        let executor = Executor::new();
+        let executor = self.executor();
         #[cfg(not(feature = "tokio"))]
         let internal_executor = self.internal_executor;
         // Box the future as it's large and can cause stack overflow.
@@ -338,11 +557,11 @@ impl<'a> Builder<'a> {
     }
 
     async fn build_(mut self, executor: Executor<'static>) -> Result<Connection> {
-        let mut stream = self.stream_for_target().await?;
+        let mut stream = self.stream_for_target(&executor).await?;
         let mut auth = match self.guid {
             None => {
                 // SASL Handshake
-                Authenticated::client(stream, self.auth_mechanisms).await?
+                Authenticated::client(stream, self.auth_mechanisms, executor.clone()).await?
             }
             Some(guid) => {
                 if !self.p2p {
@@ -365,6 +584,7 @@ impl<'a> Builder<'a> {
                     self.auth_mechanisms,
                     self.cookie_id,
                     self.cookie_context.unwrap_or_default(),
+                    executor.clone(),
                 )
                 .await?
             }
@@ -373,7 +593,9 @@ impl<'a> Builder<'a> {
         let socket_read = auth.socket_read.take().unwrap();
         let already_received_bytes = auth.already_received_bytes.take().unwrap();
 
-        let mut conn = Connection::new(auth, !self.p2p, executor).await?;
+        let buffer_pool = self.buffer_pool.unwrap_or_default();
+        let limits = self.limits.unwrap_or_default();
+        let mut conn = Connection::new(auth, !self.p2p, executor, buffer_pool, limits).await?;
         conn.set_max_queued(self.max_queued.unwrap_or(DEFAULT_MAX_QUEUED));
         if let Some(unique_name) = self.unique_name {
             conn.set_unique_name(unique_name)?;
@@ -412,13 +634,20 @@ impl<'a> Builder<'a> {
         Ok(conn)
     }
 
+    fn executor(&self) -> Executor<'static> {
+        self.executor.clone().unwrap_or_else(Executor::new)
+    }
+
     fn new(target: Target) -> Self {
         Self {
             target: Some(target),
             p2p: false,
             max_queued: None,
+            buffer_pool: None,
+            limits: None,
             guid: None,
             internal_executor: true,
+            executor: None,
             interfaces: HashMap::new(),
             names: HashSet::new(),
             auth_mechanisms: None,
@@ -428,7 +657,7 @@ impl<'a> Builder<'a> {
         }
     }
 
-    async fn stream_for_target(&mut self) -> Result<BoxedSplit> {
+    async fn stream_for_target(&mut self, executor: &Executor<'static>) -> Result<BoxedSplit> {
         // SAFETY: `self.target` is always `Some` from the beginning and this methos is only called
         // once.
         Ok(match self.target.take().unwrap() {
@@ -446,7 +675,7 @@ impl<'a> Builder<'a> {
             Target::VsockStream(stream) => Split::new_boxed(Async::new(stream)?),
             #[cfg(feature = "tokio-vsock")]
             Target::VsockStream(stream) => Split::new_boxed(stream),
-            Target::Address(address) => match address.connect().await? {
+            Target::Address(address) => match address.connect(executor).await? {
                 #[cfg(any(unix, not(feature = "tokio")))]
                 address::Stream::Unix(stream) => Split::new_boxed(stream),
                 address::Stream::Tcp(stream) => Split::new_boxed(stream),
@@ -467,7 +696,8 @@ impl<'a> Builder<'a> {
 /// tasks until socket reader task kicks in.
 #[cfg(not(feature = "tokio"))]
 fn start_internal_executor(executor: &Executor<'static>, internal_executor: bool) -> Result<()> {
-    if internal_executor {
+    // A GLib main context runs the tasks itself.
+    if internal_executor && executor.needs_ticking() {
         let executor = executor.clone();
         std::thread::Builder::new()
             .name("zbus::Connection executor".into())
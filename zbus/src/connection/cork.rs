@@ -0,0 +1,94 @@
+use static_assertions::assert_impl_all;
+use tracing::{debug, info_span, Instrument};
+
+use crate::{Connection, Result};
+
+/// A guard holding back the outgoing messages of a connection.
+///
+/// While a connection is corked, the messages sent on it are only queued and [`Connection::send`]
+/// (and the other methods sending messages) return immediately. The queued messages are written
+/// out together (using as few writes as possible), once the connection is uncorked. This is useful
+/// to reduce the number of system calls when sending a burst of messages, e.g emitting many
+/// signals at once.
+///
+/// The connection is uncorked once all the `Cork`s of the connection are gone. Dropping the guard
+/// requires the executor of the connection to write out the queued messages, so use
+/// [`Cork::uncork`] if you want to make sure they are written (or to find out about failures)
+/// before moving on.
+///
+/// Note that while the connection is corked, method calls won't get any reply since they aren't
+/// sent yet, so don't wait for one while holding a `Cork`. [`Connection::flush`] can be used to
+/// write out the queued messages without uncorking.
+///
+/// Use [`Connection::cork`] to create an instance of this type.
+///
+/// # Example
+///
+/// ```no_run
+/// # zbus::block_on(async {
+/// use zbus::Connection;
+///
+/// let conn = Connection::session().await?;
+/// let cork = conn.cork();
+/// for i in 0..100u32 {
+///     conn.emit_signal(None::<()>, "/org/zbus/Counter", "org.zbus.Counter", "Tick", &i)
+///         .await?;
+/// }
+/// // Write all the signals out at once.
+/// cork.uncork().await?;
+/// # Ok::<(), zbus::Error>(())
+/// # }).unwrap();
+/// ```
+#[derive(Debug)]
+#[must_use = "the connection is uncorked immediately if the guard is dropped"]
+pub struct Cork {
+    conn: Connection,
+    uncorked: bool,
+}
+
+assert_impl_all!(Cork: Send, Sync, Unpin);
+
+impl Cork {
+    pub(crate) fn new(conn: &Connection) -> Self {
+        conn.inner.socket_writer.cork();
+
+        Self {
+            conn: conn.clone(),
+            uncorked: false,
+        }
+    }
+
+    /// Uncork the connection and write out the queued messages.
+    ///
+    /// If the connection is still corked by another `Cork`, this doesn't write anything.
+    pub async fn uncork(mut self) -> Result<()> {
+        self.uncorked = true;
+        if self.conn.inner.socket_writer.uncork() {
+            self.conn.inner.socket_writer.flush().await?;
+        }
+
+        Ok(())
+    }
+}
+
+impl Drop for Cork {
+    fn drop(&mut self) {
+        if self.uncorked || !self.conn.inner.socket_writer.uncork() {
+            return;
+        }
+
+        let conn = self.conn.clone();
+        self.conn
+            .executor()
+            .spawn(
+                async move {
+                    if let Err(e) = conn.inner.socket_writer.flush().await {
+                        debug!("Failed to write out queued messages: {}", e);
+                    }
+                }
+                .instrument(info_span!("flush queued messages")),
+                "flush queued messages",
+            )
+            .detach();
+    }
+}
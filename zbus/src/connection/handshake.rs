@@ -17,7 +17,7 @@ use xdg_home::home_dir;
 
 #[cfg(windows)]
 use crate::win32;
-use crate::{file::FileLines, guid::Guid, Error, Result};
+use crate::{file::FileLines, guid::Guid, Error, Executor, Result};
 
 use super::socket::{BoxedSplit, ReadHalf, WriteHalf};
 
@@ -66,8 +66,11 @@ impl Authenticated {
     pub async fn client(
         socket: BoxedSplit,
         mechanisms: Option<VecDeque<AuthMechanism>>,
+        executor: Executor<'static>,
     ) -> Result<Self> {
-        ClientHandshake::new(socket, mechanisms).perform().await
+        ClientHandshake::new(socket, mechanisms, executor)
+            .perform()
+            .await
     }
 
     /// Create a server-side `Authenticated` for the given `socket`.
@@ -81,6 +84,7 @@ impl Authenticated {
         auth_mechanisms: Option<VecDeque<AuthMechanism>>,
         cookie_id: Option<usize>,
         cookie_context: CookieContext<'_>,
+        executor: Executor<'static>,
     ) -> Result<Self> {
         ServerHandshake::new(
             socket,
@@ -92,6 +96,7 @@ impl Authenticated {
             auth_mechanisms,
             cookie_id,
             cookie_context,
+            executor,
         )?
         .perform()
         .await
@@ -161,7 +166,11 @@ pub trait Handshake {
 
 impl ClientHandshake {
     /// Start a handshake on this client socket
-    pub fn new(socket: BoxedSplit, mechanisms: Option<VecDeque<AuthMechanism>>) -> ClientHandshake {
+    pub fn new(
+        socket: BoxedSplit,
+        mechanisms: Option<VecDeque<AuthMechanism>>,
+        executor: Executor<'static>,
+    ) -> ClientHandshake {
         let mechanisms = mechanisms.unwrap_or_else(|| {
             let mut mechanisms = VecDeque::new();
             mechanisms.push_back(AuthMechanism::External);
@@ -171,7 +180,7 @@ impl ClientHandshake {
         });
 
         ClientHandshake {
-            common: HandshakeCommon::new(socket, mechanisms, None),
+            common: HandshakeCommon::new(socket, mechanisms, None, executor),
             step: ClientHandshakeStep::Init,
         }
     }
@@ -216,7 +225,9 @@ impl ClientHandshake {
                     .next()
                     .ok_or_else(|| Error::Handshake("Missing cookie challenge".into()))?;
 
-                let cookie = Cookie::lookup(&context, id).await?.cookie;
+                let cookie = Cookie::lookup(&self.common.executor, &context, id)
+                    .await?
+                    .cookie;
                 let client_challenge = random_ascii(16);
                 let sec = format!("{server_challenge}:{client_challenge}:{cookie}");
                 let sha1 = hex::encode(Sha1::digest(sec));
@@ -273,13 +284,19 @@ impl Cookie {
         Ok(path)
     }
 
-    async fn read_keyring(context: &CookieContext<'_>) -> Result<Vec<Cookie>> {
+    async fn read_keyring(
+        executor: &Executor<'_>,
+        context: &CookieContext<'_>,
+    ) -> Result<Vec<Cookie>> {
         let mut path = Cookie::keyring_path()?;
         #[cfg(unix)]
         {
             use std::os::unix::fs::PermissionsExt;
 
-            let perms = crate::file::metadata(&path).await?.permissions().mode();
+            let perms = crate::file::metadata(executor, &path)
+                .await?
+                .permissions()
+                .mode();
             if perms & 0o066 != 0 {
                 return Err(Error::Handshake(
                     "DBus keyring has invalid permissions".into(),
@@ -292,7 +309,7 @@ impl Cookie {
         }
         path.push(&*context.0);
         trace!("Reading keyring {:?}", path);
-        let mut lines = FileLines::open(&path).await?.enumerate();
+        let mut lines = FileLines::open(executor, &path).await?.enumerate();
         let mut cookies = vec![];
         while let Some((n, line)) = lines.next().await {
             let line = line?;
@@ -334,16 +351,20 @@ impl Cookie {
         Ok(cookies)
     }
 
-    async fn lookup(context: &CookieContext<'_>, id: usize) -> Result<Cookie> {
-        let keyring = Self::read_keyring(context).await?;
+    async fn lookup(
+        executor: &Executor<'_>,
+        context: &CookieContext<'_>,
+        id: usize,
+    ) -> Result<Cookie> {
+        let keyring = Self::read_keyring(executor, context).await?;
         keyring
             .into_iter()
             .find(|c| c.id == id)
             .ok_or_else(|| Error::Handshake(format!("DBus cookie ID {id} not found")))
     }
 
-    async fn first(context: &CookieContext<'_>) -> Result<Cookie> {
-        let keyring = Self::read_keyring(context).await?;
+    async fn first(executor: &Executor<'_>, context: &CookieContext<'_>) -> Result<Cookie> {
+        let keyring = Self::read_keyring(executor, context).await?;
         keyring
             .into_iter()
             .next()
@@ -556,6 +577,7 @@ impl<'s> ServerHandshake<'s> {
         mechanisms: Option<VecDeque<AuthMechanism>>,
         cookie_id: Option<usize>,
         cookie_context: CookieContext<'s>,
+        executor: Executor<'static>,
     ) -> Result<ServerHandshake<'s>> {
         let mechanisms = match mechanisms {
             Some(mechanisms) => mechanisms,
@@ -568,7 +590,7 @@ impl<'s> ServerHandshake<'s> {
         };
 
         Ok(ServerHandshake {
-            common: HandshakeCommon::new(socket, mechanisms, Some(guid)),
+            common: HandshakeCommon::new(socket, mechanisms, Some(guid), executor),
             step: ServerHandshakeStep::WaitingForNull,
             #[cfg(unix)]
             client_uid,
@@ -614,8 +636,10 @@ impl<'s> ServerHandshake<'s> {
 
     async fn check_cookie_auth(&mut self, sasl_id: &[u8]) -> Result<()> {
         let cookie = match self.cookie_id {
-            Some(cookie_id) => Cookie::lookup(&self.cookie_context, cookie_id).await?,
-            None => Cookie::first(&self.cookie_context).await?,
+            Some(cookie_id) => {
+                Cookie::lookup(&self.common.executor, &self.cookie_context, cookie_id).await?
+            }
+            None => Cookie::first(&self.common.executor, &self.cookie_context).await?,
         };
         let id = std::str::from_utf8(sasl_id)
             .map_err(|e| Error::Handshake(format!("Invalid ID: {e}")))?;
@@ -935,6 +959,8 @@ pub struct HandshakeCommon {
     cap_unix_fd: bool,
     // the current AUTH mechanism is front, ordered by priority
     mechanisms: VecDeque<AuthMechanism>,
+    // for the blocking calls, e.g reading the cookie keyring
+    executor: Executor<'static>,
 }
 
 impl HandshakeCommon {
@@ -943,6 +969,7 @@ impl HandshakeCommon {
         socket: BoxedSplit,
         mechanisms: VecDeque<AuthMechanism>,
         server_guid: Option<Guid>,
+        executor: Executor<'static>,
     ) -> Self {
         Self {
             socket,
@@ -950,6 +977,7 @@ impl HandshakeCommon {
             server_guid,
             cap_unix_fd: false,
             mechanisms,
+            executor,
         }
     }
 
@@ -1066,7 +1094,7 @@ mod tests {
     fn handshake() {
         let (p0, p1) = create_async_socket_pair();
 
-        let client = ClientHandshake::new(Split::new_boxed(p0), None);
+        let client = ClientHandshake::new(Split::new_boxed(p0), None, Executor::new());
         let server = ServerHandshake::new(
             Split::new_boxed(p1),
             Guid::generate(),
@@ -1074,6 +1102,7 @@ mod tests {
             None,
             None,
             CookieContext::default(),
+            Executor::new(),
         )
         .unwrap();
 
@@ -1098,6 +1127,7 @@ mod tests {
             None,
             None,
             CookieContext::default(),
+            Executor::new(),
         )
         .unwrap();
 
@@ -1127,6 +1157,7 @@ mod tests {
             None,
             None,
             CookieContext::default(),
+            Executor::new(),
         )
         .unwrap();
 
@@ -1154,6 +1185,7 @@ mod tests {
             None,
             None,
             CookieContext::default(),
+            Executor::new(),
         )
         .unwrap();
 
@@ -1172,6 +1204,7 @@ mod tests {
             Some(vec![AuthMechanism::Anonymous].into()),
             None,
             CookieContext::default(),
+            Executor::new(),
         )
         .unwrap();
 
@@ -1190,6 +1223,7 @@ mod tests {
             Some(vec![AuthMechanism::Anonymous].into()),
             None,
             CookieContext::default(),
+            Executor::new(),
         )
         .unwrap();
 
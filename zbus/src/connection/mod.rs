@@ -5,20 +5,20 @@ use event_listener::{Event, EventListener};
 use once_cell::sync::OnceCell;
 use ordered_stream::{OrderedFuture, OrderedStream, PollResult};
 use static_assertions::assert_impl_all;
-#[cfg(unix)]
-use std::os::fd::AsFd;
 use std::{
     collections::HashMap,
     io::{self, ErrorKind},
     num::NonZeroU32,
     ops::Deref,
     pin::Pin,
-    sync::{Arc, Weak},
+    sync::{self, Arc, Weak},
     task::{Context, Poll},
 };
 use tracing::{debug, info_span, instrument, trace, trace_span, warn, Instrument};
-use zbus_names::{BusName, ErrorName, InterfaceName, MemberName, OwnedUniqueName, WellKnownName};
-use zvariant::ObjectPath;
+use zbus_names::{
+    BusName, ErrorName, InterfaceName, MemberName, OwnedUniqueName, UniqueName, WellKnownName,
+};
+use zvariant::{serialized::Limits, ObjectPath};
 
 use futures_core::Future;
 use futures_util::StreamExt;
@@ -27,7 +27,7 @@ use crate::{
     async_lock::Mutex,
     blocking,
     fdo::{self, ConnectionCredentials, RequestNameFlags, RequestNameReply},
-    message::{Flags, Message, Type},
+    message::{BufferPool, Flags, Header, Message, Type},
     proxy::CacheProperties,
     DBusError, Error, Executor, Guid, MatchRule, MessageStream, ObjectServer, OwnedMatchRule,
     Result, Task,
@@ -42,6 +42,18 @@ pub use socket::Socket;
 mod socket_reader;
 use socket_reader::SocketReader;
 
+mod socket_writer;
+use socket_writer::SocketWriter;
+
+mod name_watch;
+pub use name_watch::{NameEvent, NameWatchStream, WatchNameFlags};
+
+mod name_ownership;
+pub use name_ownership::{NameOwnership, NameOwnershipEvent};
+
+mod cork;
+pub use cork::Cork;
+
 pub(crate) mod handshake;
 use handshake::Authenticated;
 
@@ -59,7 +71,7 @@ pub(crate) struct ConnectionInner {
     registered_names: Mutex<HashMap<WellKnownName<'static>, NameStatus>>,
 
     activity_event: Arc<Event>,
-    socket_write: Mutex<Box<dyn socket::WriteHalf>>,
+    socket_writer: SocketWriter,
 
     // Our executor
     executor: Executor<'static>,
@@ -76,6 +88,12 @@ pub(crate) struct ConnectionInner {
 
     object_server: OnceCell<blocking::ObjectServer>,
     object_server_dispatch_task: OnceCell<Task<()>>,
+
+    sender_credentials: sync::Mutex<CredentialsCache>,
+    buffer_pool: BufferPool,
+    limits: Limits,
+    // Evicts the entries from `sender_credentials` of the peers that leave the bus.
+    credentials_eviction_task: Mutex<Option<Task<()>>>,
 }
 
 type Subscriptions = HashMap<OwnedMatchRule, (u64, InactiveReceiver<Result<Message>>)>;
@@ -273,36 +291,36 @@ impl OrderedFuture for PendingMethodCall {
 
 impl Connection {
     /// Send `msg` to the peer.
+    ///
+    /// Messages sent concurrently (e.g from different tasks) are queued and written out together,
+    /// using as few writes as possible. If the connection is corked (see [`Connection::cork`]),
+    /// this only queues the message and returns immediately.
+    ///
+    /// The message is queued on the first poll of the returned future. From then on, it's sent
+    /// even if the future is dropped before completion, in which case any failure to write it goes
+    /// unreported.
     pub async fn send(&self, msg: &Message) -> Result<()> {
-        let data = msg.data();
         #[cfg(unix)]
-        if !data.fds().is_empty() && !self.inner.cap_unix_fd {
+        if !msg.data().fds().is_empty() && !self.inner.cap_unix_fd {
             return Err(Error::Unsupported);
         }
-        let serial = msg.primary_header().serial_num();
 
         trace!("Sending message: {:?}", msg);
         self.inner.activity_event.notify(usize::MAX);
-        let mut write = self.inner.socket_write.lock().await;
-        let mut pos = 0;
-        while pos < data.len() {
-            #[cfg(unix)]
-            let fds = if pos == 0 {
-                data.fds().iter().map(|f| f.as_fd()).collect()
-            } else {
-                vec![]
-            };
-            pos += write
-                .sendmsg(
-                    &data[pos..],
-                    #[cfg(unix)]
-                    &fds,
-                )
-                .await?;
-        }
-        trace!("Sent message with serial: {}", serial);
+        self.inner.socket_writer.send(msg).await
+    }
 
-        Ok(())
+    /// Write out all the messages queued for sending, even if the connection is corked.
+    pub async fn flush(&self) -> Result<()> {
+        self.inner.socket_writer.flush().await
+    }
+
+    /// Cork the connection.
+    ///
+    /// Until the returned guard is dropped or [`Cork::uncork`] is called, the messages sent on this
+    /// connection are only queued, to be written out at once later. See [`Cork`] for details.
+    pub fn cork(&self) -> Cork {
+        Cork::new(self)
     }
 
     /// Send a method call.
@@ -373,7 +391,7 @@ impl Connection {
         M::Error: Into<Error>,
         B: serde::ser::Serialize + zvariant::DynamicType,
     {
-        let mut builder = Message::method(path, method_name)?;
+        let mut builder = Message::method(path, method_name)?.buffer_pool(&self.inner.buffer_pool);
         if let Some(sender) = self.unique_name() {
             builder = builder.sender(sender)?
         }
@@ -426,7 +444,8 @@ impl Connection {
         M::Error: Into<Error>,
         B: serde::ser::Serialize + zvariant::DynamicType,
     {
-        let mut b = Message::signal(path, interface, signal_name)?;
+        let mut b =
+            Message::signal(path, interface, signal_name)?.buffer_pool(&self.inner.buffer_pool);
         if let Some(sender) = self.unique_name() {
             b = b.sender(sender)?;
         }
@@ -446,7 +465,7 @@ impl Connection {
     where
         B: serde::ser::Serialize + zvariant::DynamicType,
     {
-        let mut b = Message::method_reply(call)?;
+        let mut b = Message::method_reply(call)?.buffer_pool(&self.inner.buffer_pool);
         if let Some(sender) = self.unique_name() {
             b = b.sender(sender)?;
         }
@@ -464,7 +483,7 @@ impl Connection {
         E: TryInto<ErrorName<'e>>,
         E::Error: Into<Error>,
     {
-        let mut b = Message::method_error(call, error_name)?;
+        let mut b = Message::method_error(call, error_name)?.buffer_pool(&self.inner.buffer_pool);
         if let Some(sender) = self.unique_name() {
             b = b.sender(sender)?;
         }
@@ -519,7 +538,9 @@ impl Connection {
         W: TryInto<WellKnownName<'w>>,
         W::Error: Into<Error>,
     {
-        self.request_name_with_flags(
+        let well_known_name = well_known_name.try_into().map_err(Into::into)?;
+
+        self.request_name_reply(
             well_known_name,
             RequestNameFlags::ReplaceExisting | RequestNameFlags::DoNotQueue,
         )
@@ -530,23 +551,28 @@ impl Connection {
     /// Register a well-known name for this connection.
     ///
     /// This is the same as [`Connection::request_name`] but allows to specify the flags to use when
-    /// requesting the name.
+    /// requesting the name, and returns a [`NameOwnership`] handle to keep track of the name.
     ///
-    /// If the [`RequestNameFlags::DoNotQueue`] flag is not specified and request ends up in the
-    /// queue, you can use [`fdo::NameAcquiredStream`] to be notified when the name is acquired. A
-    /// queued name request can be cancelled using [`Connection::release_name`].
+    /// The handle is a stream of [`NameOwnershipEvent`]s. The first one is the result of the
+    /// request ([`NameOwnershipEvent::Acquired`] or [`NameOwnershipEvent::Queued`]) and the
+    /// following ones inform about the name getting acquired after being in the queue, replaced or
+    /// lost. This allows services that specify [`RequestNameFlags::AllowReplacement`] and/or
+    /// [`RequestNameFlags::ReplaceExisting`] to hand over the name cleanly between instances, e.g
+    /// during upgrades. The reply of the bus is available through [`NameOwnership::reply`].
     ///
-    /// If the [`RequestNameFlags::AllowReplacement`] flag is specified, the requested name can be
-    /// lost if another peer requests the same name. You can use [`fdo::NameLostStream`] to be
-    /// notified when the name is lost
+    /// Dropping the handle releases the name, or cancels the request if it's still in the queue.
+    /// Use [`NameOwnership::release`] to do that explicitly and wait for it to be done.
     ///
     /// # Example
     ///
     /// ```
     /// #
     /// # zbus::block_on(async {
-    /// use zbus::{Connection, fdo::{DBusProxy, RequestNameFlags, RequestNameReply}};
-    /// use enumflags2::BitFlags;
+    /// use zbus::{
+    ///     connection::NameOwnershipEvent,
+    ///     fdo::{RequestNameFlags, RequestNameReply},
+    ///     Connection,
+    /// };
     /// use futures_util::stream::StreamExt;
     ///
     /// let name = "org.freedesktop.zbus.QueuedNameTest";
@@ -560,34 +586,26 @@ impl Connection {
     /// assert!(conn2.request_name(name).await.is_err());
     ///
     /// // Now let's try w/o `DoNotQueue` and we should be queued.
-    /// let reply = conn2
+    /// let mut ownership = conn2
     ///     .request_name_with_flags(name, RequestNameFlags::AllowReplacement.into())
     ///     .await?;
-    /// assert_eq!(reply, RequestNameReply::InQueue);
-    /// // Another request should just give us the same response.
-    /// let reply = conn2
-    ///     // The flags on subsequent requests will however be ignored.
-    ///     .request_name_with_flags(name, BitFlags::empty())
-    ///     .await?;
-    /// assert_eq!(reply, RequestNameReply::InQueue);
-    /// let mut acquired_stream = DBusProxy::new(&conn2)
-    ///     .await?
-    ///     .receive_name_acquired()
-    ///     .await?;
+    /// assert_eq!(ownership.reply(), &RequestNameReply::InQueue);
+    /// assert_eq!(ownership.next().await, Some(NameOwnershipEvent::Queued));
     /// assert!(conn1.release_name(name).await?);
     /// // This would have waited forever if `conn1` hadn't just release the name.
-    /// let acquired = acquired_stream.next().await.unwrap();
-    /// assert_eq!(acquired.args().unwrap().name, name);
+    /// assert_eq!(ownership.next().await, Some(NameOwnershipEvent::Acquired));
     ///
     /// // conn2 made the mistake of being too nice and allowed name replacemnt, so conn1 should be
     /// // able to take it back.
-    /// let mut lost_stream = DBusProxy::new(&conn2)
-    ///     .await?
-    ///     .receive_name_lost()
-    ///     .await?;
     /// conn1.request_name(name).await?;
-    /// let lost = lost_stream.next().await.unwrap();
-    /// assert_eq!(lost.args().unwrap().name, name);
+    /// assert_eq!(ownership.next().await, Some(NameOwnershipEvent::Replaced));
+    ///
+    /// // conn2 is still in the queue and gets the name back once conn1 is gone.
+    /// drop(conn1);
+    /// assert_eq!(ownership.next().await, Some(NameOwnershipEvent::Acquired));
+    ///
+    /// // Finally, give up the name.
+    /// ownership.release().await?;
     ///
     /// # Ok::<(), zbus::Error>(())
     /// # }).unwrap();
@@ -595,33 +613,39 @@ impl Connection {
     ///
     /// # Caveats
     ///
-    /// * Same as that of [`Connection::request_name`].
-    /// * If you wish to track changes to name ownership after this call, make sure that the
-    /// [`fdo::NameAcquired`] and/or [`fdo::NameLostStream`] instance(s) are created **before**
-    /// calling this method. Otherwise, you may loose the signal if it's emitted after this call but
-    /// just before the stream instance get created.
+    /// Same as that of [`Connection::request_name`].
     pub async fn request_name_with_flags<'w, W>(
         &self,
         well_known_name: W,
         flags: BitFlags<RequestNameFlags>,
-    ) -> Result<RequestNameReply>
+    ) -> Result<NameOwnership>
     where
         W: TryInto<WellKnownName<'w>>,
         W::Error: Into<Error>,
     {
         let well_known_name = well_known_name.try_into().map_err(Into::into)?;
+
+        NameOwnership::new(self, well_known_name, flags).await
+    }
+
+    /// Register a well-known name and return the reply of the bus.
+    pub(crate) async fn request_name_reply(
+        &self,
+        well_known_name: WellKnownName<'_>,
+        flags: BitFlags<RequestNameFlags>,
+    ) -> Result<RequestNameReply> {
         // We keep the lock until the end of this function so that the (possibly) spawned task
         // doesn't end up accessing the name entry before it's inserted.
         let mut names = self.inner.registered_names.lock().await;
 
-        match names.get(&well_known_name) {
-            Some(NameStatus::Owner(_)) => return Ok(RequestNameReply::AlreadyOwner),
-            Some(NameStatus::Queued(_)) => return Ok(RequestNameReply::InQueue),
+        match names.get(&well_known_name).map(|status| status.owner) {
+            Some(true) => return Ok(RequestNameReply::AlreadyOwner),
+            Some(false) => return Ok(RequestNameReply::InQueue),
             None => (),
         }
 
         if !self.is_bus() {
-            names.insert(well_known_name.to_owned(), NameStatus::Owner(None));
+            names.insert(well_known_name.to_owned(), NameStatus::owner(None));
 
             return Ok(RequestNameReply::PrimaryOwner);
         }
@@ -706,7 +730,7 @@ impl Connection {
                                             let task = name_lost_fut.map(|fut| {
                                                 inner.executor.spawn(fut, &lost_task_name)
                                             });
-                                            *status = NameStatus::Owner(task);
+                                            *status = NameStatus::owner(task);
 
                                             break;
                                         }
@@ -728,12 +752,15 @@ impl Connection {
                     &task_name,
                 );
 
-                NameStatus::Queued(task)
+                NameStatus {
+                    owner: false,
+                    _monitor: Some(task),
+                }
             }
             RequestNameReply::PrimaryOwner | RequestNameReply::AlreadyOwner => {
                 let task = name_lost_fut.map(|fut| self.executor().spawn(fut, &lost_task_name));
 
-                NameStatus::Owner(task)
+                NameStatus::owner(task)
             }
             RequestNameReply::Exists => return Err(Error::NameTaken),
         };
@@ -777,6 +804,68 @@ impl Connection {
             .map_err(Into::into)
     }
 
+    /// Watch the ownership of a bus name.
+    ///
+    /// The returned stream first yields the current state of the name, i-e
+    /// [`NameEvent::Appeared`] with the unique name of the current owner, or
+    /// [`NameEvent::Vanished`] if the name has no owner. After that, it yields an event every time
+    /// the name changes hands. Since the subscription to the ownership changes is made before the
+    /// current owner is queried, no change is missed.
+    ///
+    /// This is the equivalent of `g_bus_watch_name` from GIO. Use
+    /// [`Connection::watch_name_with_flags`] if you want the service to be started automatically.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # zbus::block_on(async {
+    /// use zbus::{connection::NameEvent, Connection};
+    /// use futures_util::stream::StreamExt;
+    ///
+    /// let name = "org.freedesktop.zbus.WatchNameDocTest";
+    /// let conn = Connection::session().await?;
+    /// let mut stream = conn.watch_name(name).await?;
+    /// assert_eq!(stream.next().await, Some(NameEvent::Vanished));
+    ///
+    /// let service = Connection::session().await?;
+    /// service.request_name(name).await?;
+    /// let owner = service.unique_name().unwrap().clone();
+    /// assert_eq!(stream.next().await, Some(NameEvent::Appeared(owner)));
+    ///
+    /// drop(service);
+    /// assert_eq!(stream.next().await, Some(NameEvent::Vanished));
+    /// # Ok::<(), zbus::Error>(())
+    /// # }).unwrap();
+    /// ```
+    ///
+    /// # Errors
+    ///
+    /// Fails with `zbus::Error::Unsupported` if `self` is not a bus connection.
+    pub async fn watch_name<'n, N>(&self, name: N) -> Result<NameWatchStream>
+    where
+        N: TryInto<BusName<'n>>,
+        N::Error: Into<Error>,
+    {
+        self.watch_name_with_flags(name, BitFlags::empty()).await
+    }
+
+    /// Watch the ownership of a bus name.
+    ///
+    /// This is the same as [`Connection::watch_name`] but allows to specify the flags to use.
+    pub async fn watch_name_with_flags<'n, N>(
+        &self,
+        name: N,
+        flags: BitFlags<WatchNameFlags>,
+    ) -> Result<NameWatchStream>
+    where
+        N: TryInto<BusName<'n>>,
+        N::Error: Into<Error>,
+    {
+        let name = name.try_into().map_err(Into::into)?;
+
+        NameWatchStream::new(self, name, flags).await
+    }
+
     /// Checks if `self` is a connection to a message bus.
     ///
     /// This will return `false` for p2p connections.
@@ -813,6 +902,19 @@ impl Connection {
         Ok(())
     }
 
+    /// The pool of buffers for the messages of this connection.
+    ///
+    /// All the messages received on this connection, as well as the ones built by its methods
+    /// (e.g [`Connection::emit_signal`]), use this pool. See [`BufferPool`] for details.
+    pub fn buffer_pool(&self) -> &BufferPool {
+        &self.inner.buffer_pool
+    }
+
+    /// The limits enforced on the messages received on this connection.
+    pub fn limits(&self) -> Limits {
+        self.inner.limits
+    }
+
     /// The capacity of the main (unfiltered) queue.
     pub fn max_queued(&self) -> usize {
         self.inner.msg_receiver.capacity()
@@ -1144,6 +1246,8 @@ impl Connection {
         auth: Authenticated,
         bus_connection: bool,
         executor: Executor<'static>,
+        buffer_pool: BufferPool,
+        limits: Limits,
     ) -> Result<Self> {
         #[cfg(unix)]
         let cap_unix_fd = auth.cap_unix_fd;
@@ -1178,7 +1282,7 @@ impl Connection {
         let connection = Self {
             inner: Arc::new(ConnectionInner {
                 activity_event: Arc::new(Event::new()),
-                socket_write: Mutex::new(auth.socket_write),
+                socket_writer: SocketWriter::new(auth.socket_write),
                 server_guid: auth.server_guid,
                 #[cfg(unix)]
                 cap_unix_fd,
@@ -1193,6 +1297,10 @@ impl Connection {
                 msg_receiver,
                 method_return_receiver,
                 registered_names: Mutex::new(HashMap::new()),
+                sender_credentials: sync::Mutex::new(CredentialsCache::default()),
+                buffer_pool,
+                limits,
+                credentials_eviction_task: Mutex::new(None),
             }),
         };
 
@@ -1226,25 +1334,168 @@ impl Connection {
     /// Currently `unix_group_ids` and `linux_security_label` fields are not populated.
     pub async fn peer_credentials(&self) -> io::Result<ConnectionCredentials> {
         self.inner
-            .socket_write
-            .lock()
+            .socket_writer
+            .socket()
             .await
             .peer_credentials()
             .await
     }
 
+    /// Returns the credentials of the sender of a message.
+    ///
+    /// On a bus connection, the credentials are requested from the bus (see
+    /// [`fdo::DBusProxy::get_connection_credentials`]) and cached until the bus reports, through
+    /// the `NameOwnerChanged` signal, that the sender has left the bus. On a peer-to-peer
+    /// connection, these are the same as [`Connection::peer_credentials`], which are only
+    /// retrieved once.
+    ///
+    /// This is what the `credentials` argument attribute of [`dbus_interface`] methods uses.
+    ///
+    /// # Errors
+    ///
+    /// Fails with [`Error::MissingField`] if the message was received on a bus connection and it
+    /// doesn't have a sender.
+    ///
+    /// [`dbus_interface`]: crate::dbus_interface
+    pub async fn sender_credentials(&self, hdr: &Header<'_>) -> Result<ConnectionCredentials> {
+        if !self.is_bus() {
+            let cached = self
+                .inner
+                .sender_credentials
+                .lock()
+                .expect("lock poisoned")
+                .peer
+                .clone();
+            if let Some(creds) = cached {
+                return Ok(creds);
+            }
+
+            let creds = self.peer_credentials().await?;
+            let mut cache = self.inner.sender_credentials.lock().expect("lock poisoned");
+
+            return Ok(cache.peer.get_or_insert(creds).clone());
+        }
+
+        let sender = hdr.sender().ok_or(Error::MissingField)?;
+        {
+            let mut cache = self.inner.sender_credentials.lock().expect("lock poisoned");
+            if let Some(creds) = cache.entries.get(sender.as_str()) {
+                return Ok(creds.clone());
+            }
+            // Keep track of the request so that the eviction task knows if the sender leaves the
+            // bus while we're waiting for the reply.
+            cache.pending.entry(sender.to_owned().into()).or_default().0 += 1;
+        }
+
+        let res = self.request_sender_credentials(sender).await;
+
+        let mut cache = self.inner.sender_credentials.lock().expect("lock poisoned");
+        let vanished = match cache.pending.get_mut(sender.as_str()) {
+            Some((count, vanished)) => {
+                let was_vanished = *vanished;
+                *count -= 1;
+                if *count == 0 {
+                    cache.pending.remove(sender.as_str());
+                }
+
+                was_vanished
+            }
+            None => false,
+        };
+        let creds = res?;
+        if !vanished {
+            cache
+                .entries
+                .insert(sender.to_owned().into(), creds.clone());
+        }
+
+        Ok(creds)
+    }
+
+    async fn request_sender_credentials(
+        &self,
+        sender: &UniqueName<'_>,
+    ) -> Result<ConnectionCredentials> {
+        {
+            // The eviction task must be subscribed to `NameOwnerChanged` before we make the request
+            // so that we can't miss the sender leaving the bus.
+            let mut eviction_task = self.inner.credentials_eviction_task.lock().await;
+            if eviction_task.is_none() {
+                *eviction_task = Some(self.start_credentials_eviction().await?);
+            }
+        }
+
+        fdo::DBusProxy::builder(self)
+            .cache_properties(CacheProperties::No)
+            .build()
+            .await?
+            .get_connection_credentials(BusName::Unique(sender.clone()))
+            .await
+            .map_err(Into::into)
+    }
+
+    async fn start_credentials_eviction(&self) -> Result<Task<()>> {
+        let rule = MatchRule::builder()
+            .msg_type(Type::Signal)
+            .sender("org.freedesktop.DBus")?
+            .interface("org.freedesktop.DBus")?
+            .member("NameOwnerChanged")?
+            .arg(2, "")?
+            .build();
+        // We use the subscription channel directly, rather than a `MessageStream`, since the latter
+        // keeps a strong reference to the connection.
+        let mut receiver = self.add_match(rule.into(), None).await?;
+        let weak_conn = WeakConnection::from(self);
+        let task_name = "credentials cache eviction";
+
+        Ok(self.executor().spawn(
+            async move {
+                while let Some(msg) = receiver.next().await {
+                    let signal = match msg.map(fdo::NameOwnerChanged::from_message) {
+                        Ok(Some(signal)) => signal,
+                        Ok(None) => continue,
+                        Err(e) => {
+                            debug!("Error receiving `NameOwnerChanged` signal: {}", e);
+
+                            break;
+                        }
+                    };
+                    let args = match signal.args() {
+                        Ok(args) => args,
+                        Err(e) => {
+                            warn!("Failed to parse `NameOwnerChanged` signal: {}", e);
+
+                            continue;
+                        }
+                    };
+                    let name = match args.name() {
+                        BusName::Unique(name) => name,
+                        BusName::WellKnown(_) => continue,
+                    };
+                    let conn = match weak_conn.upgrade() {
+                        Some(conn) => conn,
+                        None => break,
+                    };
+
+                    let mut cache = conn.inner.sender_credentials.lock().expect("lock poisoned");
+                    trace!("Evicting credentials of `{}` from the cache", name);
+                    cache.entries.remove(name.as_str());
+                    if let Some((_, vanished)) = cache.pending.get_mut(name.as_str()) {
+                        *vanished = true;
+                    }
+                }
+            }
+            .instrument(info_span!("{}", task_name)),
+            task_name,
+        ))
+    }
+
     /// Close the connection.
     ///
     /// After this call, all reading and writing operations will fail.
     pub async fn close(self) -> Result<()> {
         self.inner.activity_event.notify(usize::MAX);
-        self.inner
-            .socket_write
-            .lock()
-            .await
-            .close()
-            .await
-            .map_err(Into::into)
+        self.inner.socket_writer.close().await
     }
 
     pub(crate) fn init_socket_reader(
@@ -1261,6 +1512,9 @@ impl Connection {
                     inner.msg_senders.clone(),
                     already_read,
                     inner.activity_event.clone(),
+                    inner.buffer_pool.clone(),
+                    inner.limits,
+                    WeakConnection::from(self),
                 )
                 .spawn(&inner.executor),
             )
@@ -1296,11 +1550,31 @@ impl From<&Connection> for WeakConnection {
 }
 
 #[derive(Debug)]
-enum NameStatus {
-    // The task waits for name lost signal if owner allows replacement.
-    Owner(Option<Task<()>>),
-    // The task waits for name acquisition signal.
-    Queued(Task<()>),
+struct NameStatus {
+    // Whether the name is owned, or the request is in the queue.
+    owner: bool,
+    // The task waiting for the name acquisition signal if queued, or the name lost signal if the
+    // owner allows replacement. It's only kept so that it's cancelled along with the status.
+    _monitor: Option<Task<()>>,
+}
+
+impl NameStatus {
+    fn owner(monitor: Option<Task<()>>) -> Self {
+        Self {
+            owner: true,
+            _monitor: monitor,
+        }
+    }
+}
+
+#[derive(Debug, Default)]
+struct CredentialsCache {
+    // Credentials of the peer on a p2p connection.
+    peer: Option<ConnectionCredentials>,
+    // Credentials of the peers on the bus, keyed by their unique name.
+    entries: HashMap<OwnedUniqueName, ConnectionCredentials>,
+    // The number of in-flight credentials requests for a peer and whether it left the bus since.
+    pending: HashMap<OwnedUniqueName, (usize, bool)>,
 }
 
 #[cfg(test)]
@@ -1473,6 +1747,345 @@ mod tests {
         )
     }
 
+    #[cfg(unix)]
+    #[test]
+    #[timeout(15000)]
+    fn buffer_pool() {
+        crate::utils::block_on(test_buffer_pool()).unwrap();
+    }
+
+    #[cfg(unix)]
+    async fn test_buffer_pool() -> Result<()> {
+        let (server, client) = unix_p2p_pipe().await?;
+        let mut stream = MessageStream::from(&server);
+        for i in 0..3u32 {
+            client
+                .emit_signal(None::<()>, "/", "org.zbus.p2p", "ASignalForYou", &i)
+                .await?;
+            // The sent message has been dropped already.
+            assert_eq!(client.buffer_pool().len(), 1);
+
+            let msg = stream.try_next().await?.unwrap();
+            assert_eq!(msg.body().deserialize::<u32>()?, i);
+        }
+
+        Ok(())
+    }
+
+    #[cfg(unix)]
+    #[test]
+    #[timeout(15000)]
+    fn limits() {
+        crate::utils::block_on(test_limits()).unwrap();
+    }
+
+    #[cfg(unix)]
+    async fn test_limits() -> Result<()> {
+        #[cfg(not(feature = "tokio"))]
+        use std::os::unix::net::UnixStream;
+        #[cfg(feature = "tokio")]
+        use tokio::net::UnixStream;
+        use zvariant::MaxDepthExceeded;
+
+        let guid = Guid::generate();
+        let (p0, p1) = UnixStream::pair().unwrap();
+        let limits = Limits::default()
+            .set_max_array_depth(1)
+            .set_max_message_size(1024)
+            .set_max_fds(0);
+        let (client, server) = futures_util::try_join!(
+            Builder::unix_stream(p1).p2p().build(),
+            Builder::unix_stream(p0)
+                .server(&guid)
+                .p2p()
+                .limits(limits)
+                .build(),
+        )?;
+        assert_eq!(server.limits(), limits);
+        assert_eq!(client.limits(), Limits::default());
+        let mut stream = MessageStream::from(&server);
+
+        // Decoding the body fails but the connection is still usable.
+        client
+            .emit_signal(None::<()>, "/", "org.zbus.p2p", "Nested", &vec![vec![0u8]])
+            .await?;
+        let msg = stream.try_next().await?.unwrap();
+        assert_eq!(
+            msg.body().deserialize::<Vec<Vec<u8>>>().unwrap_err(),
+            Error::Variant(zvariant::Error::MaxDepthExceeded(MaxDepthExceeded::Array))
+        );
+        client
+            .emit_signal(None::<()>, "/", "org.zbus.p2p", "Flat", &vec![0u8])
+            .await?;
+        let msg = stream.try_next().await?.unwrap();
+        assert_eq!(msg.body().deserialize::<Vec<u8>>()?, [0]);
+
+        // The messages exceeding the limits are dropped, without affecting the following ones.
+        let stdout = std::io::stdout();
+        let fd = zvariant::Fd::from(&stdout);
+        client
+            .emit_signal(None::<()>, "/", "org.zbus.p2p", "Large", &vec![0u8; 1024])
+            .await?;
+        client
+            .emit_signal(None::<()>, "/", "org.zbus.p2p", "Huge", &vec![0u8; 200_000])
+            .await?;
+        client
+            .emit_signal(None::<()>, "/", "org.zbus.p2p", "Fd", &fd)
+            .await?;
+        client
+            .emit_signal(None::<()>, "/", "org.zbus.p2p", "Small", &1u8)
+            .await?;
+        let msg = stream.try_next().await?.unwrap();
+        assert_eq!(msg.header().member().unwrap(), "Small");
+        assert_eq!(msg.body().deserialize::<u8>()?, 1);
+
+        // Method calls exceeding the limits get an error reply.
+        let e = client
+            .call_method(
+                None::<()>,
+                "/",
+                Some("org.zbus.p2p"),
+                "Large",
+                &vec![0u8; 1024],
+            )
+            .await
+            .unwrap_err();
+        assert!(matches!(
+            crate::fdo::Error::from(e),
+            crate::fdo::Error::LimitsExceeded(_)
+        ));
+        let e = client
+            .call_method(None::<()>, "/", Some("org.zbus.p2p"), "Fd", &fd)
+            .await
+            .unwrap_err();
+        assert!(matches!(
+            crate::fdo::Error::from(e),
+            crate::fdo::Error::LimitsExceeded(_)
+        ));
+
+        Ok(())
+    }
+
+    #[cfg(unix)]
+    #[test]
+    #[timeout(15000)]
+    fn cork() {
+        crate::utils::block_on(test_cork()).unwrap();
+    }
+
+    #[cfg(unix)]
+    async fn test_cork() -> Result<()> {
+        let (server, client) = unix_p2p_pipe().await?;
+        let mut stream = MessageStream::from(&server);
+
+        let cork = client.cork();
+        let cork2 = client.cork();
+        for i in 0..10u32 {
+            // Returns immediately, without writing anything.
+            client
+                .emit_signal(None::<()>, "/", "org.zbus.p2p", "ASignalForYou", &i)
+                .await?;
+        }
+        assert_eq!(client.inner.socket_writer.queue_len(), 10);
+        // Still corked by `cork2`.
+        cork.uncork().await?;
+        assert_eq!(client.inner.socket_writer.queue_len(), 10);
+        client.flush().await?;
+        assert_eq!(client.inner.socket_writer.queue_len(), 0);
+
+        client
+            .emit_signal(None::<()>, "/", "org.zbus.p2p", "ASignalForYou", &10u32)
+            .await?;
+        assert_eq!(client.inner.socket_writer.queue_len(), 1);
+        cork2.uncork().await?;
+        assert_eq!(client.inner.socket_writer.queue_len(), 0);
+
+        for i in 0..11u32 {
+            let msg = stream.try_next().await?.unwrap();
+            assert_eq!(msg.body().deserialize::<u32>()?, i);
+        }
+
+        // Dropping the guard writes the queued messages out as well.
+        let cork = client.cork();
+        client
+            .emit_signal(None::<()>, "/", "org.zbus.p2p", "ASignalForYou", &11u32)
+            .await?;
+        drop(cork);
+        let msg = stream.try_next().await?.unwrap();
+        assert_eq!(msg.body().deserialize::<u32>()?, 11);
+
+        Ok(())
+    }
+
+    #[cfg(unix)]
+    #[test]
+    #[timeout(15000)]
+    fn concurrent_sends() {
+        crate::utils::block_on(test_concurrent_sends()).unwrap();
+    }
+
+    #[cfg(unix)]
+    async fn test_concurrent_sends() -> Result<()> {
+        let (server, client) = unix_p2p_pipe().await?;
+        let mut stream = MessageStream::from(&server);
+        let file = std::fs::File::open("/dev/null").unwrap();
+
+        // Every third message carries a file descriptor, which must be received along with it and
+        // not with any of the messages written in the same batch. Corking ensures the messages
+        // are all queued before being written out.
+        let cork = client.cork();
+        let sends = (0..100u32).map(|i| {
+            let client = &client;
+            let file = &file;
+            async move {
+                if i % 3 == 0 {
+                    client
+                        .emit_signal(
+                            None::<()>,
+                            "/",
+                            "org.zbus.p2p",
+                            "Fd",
+                            &(i, zvariant::Fd::from(file)),
+                        )
+                        .await
+                } else {
+                    client
+                        .emit_signal(None::<()>, "/", "org.zbus.p2p", "NoFd", &i)
+                        .await
+                }
+            }
+        });
+        futures_util::future::try_join_all(sends).await?;
+        cork.uncork().await?;
+
+        let mut received = Vec::new();
+        for _ in 0..100 {
+            let msg = stream.try_next().await?.unwrap();
+            let i = if msg.header().member().unwrap() == "Fd" {
+                assert_eq!(msg.data().fds().len(), 1);
+                msg.body().deserialize::<(u32, zvariant::Fd<'_>)>()?.0
+            } else {
+                assert!(msg.data().fds().is_empty());
+                msg.body().deserialize::<u32>()?
+            };
+            received.push(i);
+        }
+        received.sort_unstable();
+        assert_eq!(received, (0..100).collect::<Vec<_>>());
+
+        Ok(())
+    }
+
+    #[cfg(all(target_os = "linux", feature = "memfd"))]
+    #[test]
+    #[timeout(15000)]
+    fn sealed_bytes_p2p() {
+        crate::utils::block_on(test_sealed_bytes_p2p()).unwrap();
+    }
+
+    #[cfg(all(target_os = "linux", feature = "memfd"))]
+    async fn test_sealed_bytes_p2p() -> Result<()> {
+        use zvariant::SealedBytes;
+
+        let (server, client) = unix_p2p_pipe().await?;
+        let mut stream = MessageStream::from(&server);
+        let frame = vec![7u8; 8 * 1024 * 1024];
+        let sealed = SealedBytes::new(&frame)?;
+        client
+            .emit_signal(None::<()>, "/", "org.zbus.p2p", "Frame", &sealed)
+            .await?;
+
+        let msg = stream.try_next().await?.unwrap();
+        let received: SealedBytes = msg.body().deserialize()?;
+        assert_eq!(received.as_bytes(), &frame[..]);
+        // Only the file descriptor index made it through the socket.
+        assert_eq!(msg.body().len(), 4);
+
+        Ok(())
+    }
+
+    #[cfg(all(target_os = "linux", feature = "io-uring"))]
+    #[test]
+    #[timeout(15000)]
+    fn uring_p2p() {
+        crate::utils::block_on(test_uring_p2p()).unwrap();
+    }
+
+    #[cfg(all(target_os = "linux", feature = "io-uring"))]
+    async fn test_uring_p2p() -> Result<()> {
+        use std::os::{fd::AsFd, unix::fs::MetadataExt};
+
+        let (server1, client1) = uring_p2p_pipe().await?;
+        let (server2, client2) = uring_p2p_pipe().await?;
+
+        test_p2p(server1, client1, server2, client2).await?;
+
+        // Now check that file descriptors and messages bigger than the receive buffers make it.
+        let (server, client) = uring_p2p_pipe().await?;
+        let mut stream = MessageStream::from(&server);
+        let payload = "a".repeat(100_000);
+        let file = std::fs::File::open("/dev/null").unwrap();
+        for _ in 0..3 {
+            client
+                .emit_signal(
+                    None::<()>,
+                    "/",
+                    "org.zbus.p2p",
+                    "Fd",
+                    &(zvariant::Fd::from(&file), &payload),
+                )
+                .await?;
+        }
+        for _ in 0..3 {
+            let msg = stream.try_next().await?.unwrap();
+            let body = msg.body();
+            let (fd, body): (zvariant::Fd<'_>, String) = body.deserialize()?;
+            assert_eq!(body, payload);
+            let received = std::fs::File::from(fd.as_fd().try_clone_to_owned().unwrap());
+            assert_eq!(
+                received.metadata().unwrap().rdev(),
+                file.metadata().unwrap().rdev()
+            );
+        }
+
+        // Messages written in batches (i-e linked chains of sends) arrive in order.
+        let cork = client.cork();
+        let sends = (0..200u32).map(|i| {
+            let client = &client;
+            async move {
+                client
+                    .emit_signal(None::<()>, "/", "org.zbus.p2p", "Batched", &i)
+                    .await
+            }
+        });
+        futures_util::future::try_join_all(sends).await?;
+        cork.uncork().await?;
+        for i in 0..200u32 {
+            let msg = stream.try_next().await?.unwrap();
+            assert_eq!(msg.body().deserialize::<u32>()?, i);
+        }
+
+        Ok(())
+    }
+
+    #[cfg(all(target_os = "linux", feature = "io-uring"))]
+    async fn uring_p2p_pipe() -> Result<(Connection, Connection)> {
+        use socket::UringSocket;
+        use std::os::unix::net::UnixStream;
+
+        let guid = Guid::generate();
+
+        let (p0, p1) = UnixStream::pair().unwrap();
+
+        futures_util::try_join!(
+            Builder::socket(UringSocket::new(p1)?).p2p().build(),
+            Builder::socket(UringSocket::new(p0)?)
+                .server(&guid)
+                .p2p()
+                .build(),
+        )
+    }
+
     // Compile-test only since we don't have a VM setup to run this with/in.
     #[cfg(any(
         all(feature = "vsock", not(feature = "tokio")),
@@ -1539,17 +2152,22 @@ mod tests {
         let addr = crate::win32::windows_autolaunch_bus_address()
             .expect("Unable to get GDBus session bus address");
 
-        crate::block_on(async { addr.connect().await }).expect("Unable to connect to session bus");
+        crate::block_on(async { addr.connect(&Executor::new()).await })
+            .expect("Unable to connect to session bus");
     }
 
     #[cfg(target_os = "macos")]
     #[test]
     fn connect_launchd_session_bus() {
         crate::block_on(async {
-            let addr = crate::address::macos_launchd_bus_address("DBUS_LAUNCHD_SESSION_BUS_SOCKET")
-                .await
-                .expect("Unable to get Launchd session bus address");
-            addr.connect().await
+            let executor = Executor::new();
+            let addr = crate::address::macos_launchd_bus_address(
+                &executor,
+                "DBUS_LAUNCHD_SESSION_BUS_SOCKET",
+            )
+            .await
+            .expect("Unable to get Launchd session bus address");
+            addr.connect(&executor).await
         })
         .expect("Unable to connect to session bus");
     }
@@ -1598,6 +2216,286 @@ mod tests {
         assert!(!name_has_owner);
     }
 
+    #[test]
+    #[timeout(15000)]
+    fn sender_credentials_cache() {
+        crate::utils::block_on(test_sender_credentials_cache()).unwrap();
+    }
+
+    async fn test_sender_credentials_cache() -> Result<()> {
+        let conn = Connection::session().await?;
+        let peer = Connection::session().await?;
+        let peer_name = peer.unique_name().unwrap().to_owned();
+        let msg = Message::method("/", "Whatever")?
+            .sender(peer_name.clone())?
+            .build(&())?;
+
+        let creds = conn.sender_credentials(&msg.header()).await?;
+        assert_eq!(creds, peer.peer_credentials_from_bus().await?);
+        assert!(conn.cached_credentials(&peer_name).is_some());
+
+        let dbus = DBusProxy::new(&conn).await?;
+        let mut stream = dbus
+            .receive_name_owner_changed_with_args(&[(0, peer_name.as_str()), (2, "")])
+            .await?;
+        drop(peer);
+        stream.next().await.unwrap();
+
+        // The eviction task might not have gotten to the signal yet.
+        while conn.cached_credentials(&peer_name).is_some() {
+            conn.executor()
+                .sleep(std::time::Duration::from_millis(10))
+                .await;
+        }
+
+        Ok(())
+    }
+
+    impl Connection {
+        async fn peer_credentials_from_bus(&self) -> Result<ConnectionCredentials> {
+            DBusProxy::new(self)
+                .await?
+                .get_connection_credentials(self.unique_name().unwrap().into())
+                .await
+                .map_err(Into::into)
+        }
+
+        fn cached_credentials(&self, name: &OwnedUniqueName) -> Option<ConnectionCredentials> {
+            let cache = self.inner.sender_credentials.lock().expect("lock poisoned");
+
+            cache.entries.get(name.as_str()).cloned()
+        }
+    }
+
+    #[test]
+    #[timeout(15000)]
+    fn watch_name() {
+        crate::utils::block_on(test_watch_name()).unwrap();
+    }
+
+    async fn test_watch_name() -> Result<()> {
+        let name = "org.freedesktop.zbus.WatchNameTest";
+        let owner1 = Connection::session().await?;
+        let ownership1 = owner1
+            .request_name_with_flags(name, RequestNameFlags::AllowReplacement.into())
+            .await?;
+        let owner1_name = owner1.unique_name().unwrap().clone();
+
+        // The service isn't activatable so auto-starting should just be a no-op.
+        let conn = Connection::session().await?;
+        let mut stream = conn
+            .watch_name_with_flags(name, WatchNameFlags::AutoStart.into())
+            .await?;
+        assert_eq!(
+            stream.next().await,
+            Some(NameEvent::Appeared(owner1_name.clone()))
+        );
+
+        // `owner1` gets queued when replaced.
+        let owner2 = Connection::session().await?;
+        owner2.request_name(name).await?;
+        let owner2_name = owner2.unique_name().unwrap().clone();
+        assert_eq!(stream.next().await, Some(NameEvent::Appeared(owner2_name)));
+
+        drop(owner2);
+        assert_eq!(
+            stream.next().await,
+            Some(NameEvent::Appeared(owner1_name.clone()))
+        );
+        assert_eq!(stream.owner(), Some(&owner1_name));
+
+        drop(ownership1);
+        assert_eq!(stream.next().await, Some(NameEvent::Vanished));
+        assert_eq!(stream.owner(), None);
+
+        Ok(())
+    }
+
+    #[cfg(all(feature = "glib", not(feature = "tokio")))]
+    #[test]
+    #[timeout(15000)]
+    fn glib_main_context() {
+        use std::thread::{self, ThreadId};
+
+        struct ThreadCheck {
+            thread: ThreadId,
+        }
+
+        #[crate::dbus_interface(name = "org.freedesktop.zbus.ThreadCheck")]
+        impl ThreadCheck {
+            fn is_main_context_thread(&self) -> bool {
+                thread::current().id() == self.thread
+            }
+        }
+
+        let main_context = glib::MainContext::new();
+        let ctx = main_context.clone();
+        main_context
+            .block_on(async move {
+                let service = Builder::session()?
+                    .glib_main_context(ctx)
+                    .serve_at(
+                        "/org/freedesktop/zbus/ThreadCheck",
+                        ThreadCheck {
+                            thread: thread::current().id(),
+                        },
+                    )?
+                    .build()
+                    .await?;
+                assert!(!service.executor().needs_ticking());
+
+                let client = Connection::session().await?;
+                let reply: bool = client
+                    .call_method(
+                        service.unique_name(),
+                        "/org/freedesktop/zbus/ThreadCheck",
+                        Some("org.freedesktop.zbus.ThreadCheck"),
+                        "IsMainContextThread",
+                        &(),
+                    )
+                    .await?
+                    .body()
+                    .deserialize()?;
+                assert!(reply);
+
+                Ok::<(), Error>(())
+            })
+            .unwrap();
+    }
+
+    #[cfg(not(feature = "tokio"))]
+    #[test]
+    #[timeout(15000)]
+    fn custom_runtime() {
+        crate::utils::block_on(test_custom_runtime()).unwrap();
+    }
+
+    #[cfg(not(feature = "tokio"))]
+    async fn test_custom_runtime() -> Result<()> {
+        use futures_util::future::BoxFuture;
+        use std::{
+            sync::atomic::{AtomicUsize, Ordering},
+            time::Duration,
+        };
+
+        #[derive(Debug, Default)]
+        struct ThreadPerTask {
+            spawned: Arc<AtomicUsize>,
+            blocking: Arc<AtomicUsize>,
+            slept: Arc<AtomicUsize>,
+        }
+
+        impl crate::Runtime for ThreadPerTask {
+            fn spawn(&self, future: BoxFuture<'static, ()>, _name: &str) {
+                self.spawned.fetch_add(1, Ordering::SeqCst);
+                std::thread::spawn(move || crate::block_on(future));
+            }
+
+            fn spawn_blocking(&self, f: Box<dyn FnOnce() + Send>, _name: &str) {
+                self.blocking.fetch_add(1, Ordering::SeqCst);
+                std::thread::spawn(f);
+            }
+
+            fn sleep(&self, duration: Duration) -> BoxFuture<'static, ()> {
+                self.slept.fetch_add(1, Ordering::SeqCst);
+                let event = Event::new();
+                let listener = event.listen();
+                std::thread::spawn(move || {
+                    std::thread::sleep(duration);
+                    event.notify(1);
+                });
+
+                Box::pin(listener)
+            }
+        }
+
+        let runtime = ThreadPerTask::default();
+        let spawned = runtime.spawned.clone();
+        let blocking = runtime.blocking.clone();
+        let slept = runtime.slept.clone();
+        let conn = Builder::session()?.runtime(runtime).build().await?;
+        assert!(!conn.executor().needs_ticking());
+
+        let id = DBusProxy::new(&conn).await?.get_id().await?;
+        assert!(!id.as_str().is_empty());
+        assert!(spawned.load(Ordering::SeqCst) > 0);
+
+        // Blocking work and timers also go through the runtime.
+        let thread = conn
+            .executor()
+            .spawn_blocking(|| std::thread::current().id(), "thread id")
+            .await;
+        assert_ne!(thread, std::thread::current().id());
+        assert_eq!(blocking.load(Ordering::SeqCst), 1);
+        conn.executor().sleep(Duration::from_millis(1)).await;
+        assert_eq!(slept.load(Ordering::SeqCst), 1);
+
+        Ok(())
+    }
+
+    #[test]
+    #[timeout(15000)]
+    fn name_ownership() {
+        crate::utils::block_on(test_name_ownership()).unwrap();
+    }
+
+    async fn test_name_ownership() -> Result<()> {
+        let name = "org.freedesktop.zbus.OwnNameTest";
+        let mut watch_stream = Connection::session().await?.watch_name(name).await?;
+        assert_eq!(watch_stream.next().await, Some(NameEvent::Vanished));
+
+        let conn1 = Connection::session().await?;
+        let mut ownership1 = conn1
+            .request_name_with_flags(
+                name,
+                RequestNameFlags::AllowReplacement | RequestNameFlags::DoNotQueue,
+            )
+            .await?;
+        assert_eq!(ownership1.reply(), &RequestNameReply::PrimaryOwner);
+        assert!(ownership1.is_owner());
+        assert_eq!(ownership1.next().await, Some(NameOwnershipEvent::Acquired));
+        assert_eq!(
+            watch_stream.next().await,
+            Some(NameEvent::Appeared(conn1.unique_name().unwrap().clone()))
+        );
+
+        // Another connection can only queue up since replacement isn't requested.
+        let conn2 = Connection::session().await?;
+        let mut ownership2 = conn2
+            .request_name_with_flags(name, BitFlags::empty())
+            .await?;
+        assert_eq!(ownership2.reply(), &RequestNameReply::InQueue);
+        assert_eq!(ownership2.next().await, Some(NameOwnershipEvent::Queued));
+        assert!(!ownership2.is_owner());
+
+        // `conn1` didn't want to be queued so it's out for good once replaced.
+        let conn3 = Connection::session().await?;
+        let mut ownership3 = conn3
+            .request_name_with_flags(name, RequestNameFlags::ReplaceExisting.into())
+            .await?;
+        assert_eq!(ownership3.next().await, Some(NameOwnershipEvent::Acquired));
+        assert_eq!(ownership1.next().await, Some(NameOwnershipEvent::Lost));
+        assert!(!ownership1.is_owner());
+        assert_eq!(
+            watch_stream.next().await,
+            Some(NameEvent::Appeared(conn3.unique_name().unwrap().clone()))
+        );
+
+        // Dropping the handle releases the name, so the next in the queue gets it.
+        drop(ownership3);
+        assert_eq!(ownership2.next().await, Some(NameOwnershipEvent::Acquired));
+        assert!(ownership2.is_owner());
+        assert_eq!(
+            watch_stream.next().await,
+            Some(NameEvent::Appeared(conn2.unique_name().unwrap().clone()))
+        );
+
+        ownership2.release().await?;
+        assert_eq!(watch_stream.next().await, Some(NameEvent::Vanished));
+
+        Ok(())
+    }
+
     #[cfg(any(unix, not(feature = "tokio")))]
     #[test]
     #[timeout(15000)]
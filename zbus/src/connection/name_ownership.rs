@@ -0,0 +1,232 @@
+use enumflags2::BitFlags;
+use futures_core::stream;
+use futures_util::StreamExt;
+use static_assertions::assert_impl_all;
+use std::{
+    pin::Pin,
+    task::{Context, Poll},
+};
+use tracing::warn;
+use zbus_names::{OwnedWellKnownName, WellKnownName};
+
+use crate::{
+    fdo::{self, RequestNameFlags, RequestNameReply},
+    message::{Message, Type},
+    CacheProperties, Connection, MatchRule, MessageStream, Result,
+};
+
+/// An event yielded by [`NameOwnership`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum NameOwnershipEvent {
+    /// The request for the name was put in the queue since the name is already owned by another
+    /// peer.
+    Queued,
+    /// The name is now owned by this connection.
+    Acquired,
+    /// Another peer took over the name and this connection has been put back in the queue. It will
+    /// get the name again once the new owner releases it.
+    Replaced,
+    /// The name was lost and this connection is not in the queue anymore. This happens when the
+    /// name was requested with [`RequestNameFlags::DoNotQueue`] and another peer took over the
+    /// name.
+    Lost,
+}
+
+assert_impl_all!(NameOwnershipEvent: Send, Sync, Unpin);
+
+/// A handle to a bus name requested by a connection.
+///
+/// This is a [`stream::Stream`] that yields [`NameOwnershipEvent`]s, describing the transitions of
+/// the ownership of the name. The first item is always the result of the request, i-e
+/// [`NameOwnershipEvent::Acquired`] or [`NameOwnershipEvent::Queued`].
+///
+/// Dropping the handle releases the name (or cancels the request if it's still in the queue), in
+/// the background. Use [`NameOwnership::release`] to wait for the name to be released and get
+/// informed of any failure. Since the name is only requested once per connection, all handles of
+/// a connection for the same name share the ownership: dropping any of them releases the name.
+///
+/// Use [`Connection::request_name_with_flags`] to create an instance of this type.
+#[must_use = "dropping the handle releases the name"]
+pub struct NameOwnership {
+    conn: Connection,
+    name: OwnedWellKnownName,
+    flags: BitFlags<RequestNameFlags>,
+    reply: RequestNameReply,
+    stream: Option<MessageStream>,
+    last_event: Option<NameOwnershipEvent>,
+    pending_event: Option<NameOwnershipEvent>,
+    released: bool,
+}
+
+assert_impl_all!(NameOwnership: Send, Sync, Unpin);
+
+impl NameOwnership {
+    pub(crate) async fn new(
+        conn: &Connection,
+        name: WellKnownName<'_>,
+        flags: BitFlags<RequestNameFlags>,
+    ) -> Result<Self> {
+        // Subscribe before requesting the name so that we don't miss any signals about it.
+        let stream = if conn.is_bus() {
+            let rule = MatchRule::builder()
+                .msg_type(Type::Signal)
+                .sender("org.freedesktop.DBus")?
+                .interface("org.freedesktop.DBus")?
+                .path("/org/freedesktop/DBus")?
+                .arg(0, name.as_str())?
+                .build();
+
+            Some(MessageStream::for_match_rule(rule, conn, None).await?)
+        } else {
+            // There is no bus to take the name away from us so no signals to handle either.
+            None
+        };
+
+        let reply = conn.request_name_reply(name.clone(), flags).await?;
+        let event = match reply {
+            RequestNameReply::InQueue => NameOwnershipEvent::Queued,
+            _ => NameOwnershipEvent::Acquired,
+        };
+
+        Ok(Self {
+            conn: conn.clone(),
+            name: name.into(),
+            flags,
+            reply,
+            stream,
+            last_event: None,
+            pending_event: Some(event),
+            released: false,
+        })
+    }
+
+    /// The name this handle is for.
+    pub fn name(&self) -> &WellKnownName<'_> {
+        &self.name
+    }
+
+    /// The reply of the bus to the request of the name.
+    pub fn reply(&self) -> &RequestNameReply {
+        &self.reply
+    }
+
+    /// Whether the name is currently owned by this connection, as far as this handle has reported.
+    pub fn is_owner(&self) -> bool {
+        let event = self.pending_event.or(self.last_event);
+
+        event == Some(NameOwnershipEvent::Acquired)
+    }
+
+    /// Release the name, or cancel the request if it's still in the queue.
+    pub async fn release(mut self) -> Result<()> {
+        self.released = true;
+
+        release(&self.conn, &self.name).await
+    }
+}
+
+impl Drop for NameOwnership {
+    fn drop(&mut self) {
+        if self.released {
+            return;
+        }
+
+        let conn = self.conn.clone();
+        let name = self.name.clone();
+        let task_name = format!("release name `{name}`");
+        self.conn
+            .executor()
+            .spawn(
+                async move {
+                    if let Err(e) = release(&conn, &name).await {
+                        warn!("Failed to release name `{}`: {}", name, e);
+                    }
+                },
+                &task_name,
+            )
+            .detach();
+    }
+}
+
+async fn release(conn: &Connection, name: &OwnedWellKnownName) -> Result<()> {
+    if conn.release_name(name).await? || !conn.is_bus() {
+        return Ok(());
+    }
+
+    // The connection doesn't consider the name as registered anymore, after losing it but we
+    // might still be in the queue for it.
+    fdo::DBusProxy::builder(conn)
+        .cache_properties(CacheProperties::No)
+        .build()
+        .await?
+        .release_name(name.inner().clone())
+        .await
+        .map(|_| ())
+        .map_err(Into::into)
+}
+
+impl stream::Stream for NameOwnership {
+    type Item = NameOwnershipEvent;
+
+    fn poll_next(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        let this = self.get_mut();
+        if let Some(event) = this.pending_event.take() {
+            this.last_event = Some(event);
+
+            return Poll::Ready(Some(event));
+        }
+
+        let stream = match &mut this.stream {
+            Some(stream) => stream,
+            // Nothing will ever change.
+            None => return Poll::Pending,
+        };
+        loop {
+            let msg = match futures_core::ready!(stream.poll_next_unpin(cx)) {
+                Some(Ok(msg)) => msg,
+                Some(Err(e)) => {
+                    warn!("Error receiving signals for name `{}`: {}", this.name, e);
+
+                    continue;
+                }
+                None => return Poll::Ready(None),
+            };
+            if let Some(event) = handle_signal(&msg, this.last_event, this.flags) {
+                this.last_event = Some(event);
+
+                return Poll::Ready(Some(event));
+            }
+        }
+    }
+}
+
+impl std::fmt::Debug for NameOwnership {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("NameOwnership")
+            .field("name", &self.name)
+            .field("flags", &self.flags)
+            .field("reply", &self.reply)
+            .finish_non_exhaustive()
+    }
+}
+
+fn handle_signal(
+    msg: &Message,
+    last_event: Option<NameOwnershipEvent>,
+    flags: BitFlags<RequestNameFlags>,
+) -> Option<NameOwnershipEvent> {
+    let acquired = last_event == Some(NameOwnershipEvent::Acquired);
+    if fdo::NameAcquired::from_message(msg.clone()).is_some() {
+        (!acquired).then_some(NameOwnershipEvent::Acquired)
+    } else if fdo::NameLost::from_message(msg.clone()).is_some() {
+        if !acquired {
+            None
+        } else if flags.contains(RequestNameFlags::DoNotQueue) {
+            Some(NameOwnershipEvent::Lost)
+        } else {
+            Some(NameOwnershipEvent::Replaced)
+        }
+    } else {
+        None
+    }
+}
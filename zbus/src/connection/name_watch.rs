@@ -0,0 +1,170 @@
+use enumflags2::{bitflags, BitFlags};
+use futures_core::stream;
+use futures_util::StreamExt;
+use static_assertions::assert_impl_all;
+use std::{
+    pin::Pin,
+    task::{Context, Poll},
+};
+use tracing::{debug, warn};
+use zbus_names::{BusName, OwnedBusName, OwnedUniqueName};
+
+use crate::{fdo, CacheProperties, Connection, Error, Result};
+
+/// Flags to use with [`Connection::watch_name_with_flags`].
+#[bitflags]
+#[repr(u8)]
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum WatchNameFlags {
+    /// Ask the bus to launch the service (if it's activatable) before querying the current owner
+    /// of the name.
+    AutoStart = 0x1,
+}
+
+assert_impl_all!(WatchNameFlags: Send, Sync, Unpin);
+
+/// An event yielded by [`NameWatchStream`].
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum NameEvent {
+    /// The name is now owned by the peer with the given unique name.
+    Appeared(OwnedUniqueName),
+    /// The name has no owner anymore.
+    Vanished,
+}
+
+assert_impl_all!(NameEvent: Send, Sync, Unpin);
+
+/// A [`stream::Stream`] implementation that yields [`NameEvent`]s for a bus name.
+///
+/// The first item is always the state of the name at the time of the creation of the stream, and
+/// every subsequent item is a change of ownership of the name. Changes that happened before the
+/// initial state was determined are not reported.
+///
+/// Use [`Connection::watch_name`] to create an instance of this type.
+pub struct NameWatchStream {
+    stream: fdo::NameOwnerChangedStream<'static>,
+    name: OwnedBusName,
+    owner: Option<OwnedUniqueName>,
+    initial: Option<NameEvent>,
+}
+
+assert_impl_all!(NameWatchStream: Send, Sync, Unpin);
+
+impl NameWatchStream {
+    pub(crate) async fn new(
+        conn: &Connection,
+        name: BusName<'_>,
+        flags: BitFlags<WatchNameFlags>,
+    ) -> Result<Self> {
+        if !conn.is_bus() {
+            return Err(Error::Unsupported);
+        }
+
+        let dbus_proxy = fdo::DBusProxy::builder(conn)
+            .cache_properties(CacheProperties::No)
+            .build()
+            .await?;
+        // Subscribe first so that no change of ownership gets lost between querying the owner and
+        // the subscription. Any signals about changes that already happened before the owner is
+        // queried, are filtered out later on based on the `old_owner` field.
+        let stream = dbus_proxy
+            .receive_name_owner_changed_with_args(&[(0, name.as_str())])
+            .await?;
+
+        if flags.contains(WatchNameFlags::AutoStart) {
+            match &name {
+                BusName::WellKnown(well_known) => {
+                    if let Err(e) = dbus_proxy
+                        .start_service_by_name(well_known.clone(), 0)
+                        .await
+                    {
+                        debug!("Failed to start service for `{name}`: {e}");
+                    }
+                }
+                BusName::Unique(_) => debug!("Can't auto-start unique name `{name}`"),
+            }
+        }
+
+        let owner = match dbus_proxy.get_name_owner(name.clone()).await {
+            Ok(owner) => Some(owner),
+            Err(fdo::Error::NameHasNoOwner(_)) => None,
+            Err(e) => return Err(e.into()),
+        };
+        let initial = match &owner {
+            Some(owner) => NameEvent::Appeared(owner.clone()),
+            None => NameEvent::Vanished,
+        };
+
+        Ok(Self {
+            stream,
+            name: name.into(),
+            owner,
+            initial: Some(initial),
+        })
+    }
+
+    /// The bus name being watched.
+    pub fn name(&self) -> &BusName<'_> {
+        &self.name
+    }
+
+    /// The current owner of the name, as far as this stream has reported.
+    pub fn owner(&self) -> Option<&OwnedUniqueName> {
+        self.owner.as_ref()
+    }
+}
+
+impl stream::Stream for NameWatchStream {
+    type Item = NameEvent;
+
+    fn poll_next(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        let this = self.get_mut();
+        if let Some(initial) = this.initial.take() {
+            return Poll::Ready(Some(initial));
+        }
+
+        loop {
+            let signal = match futures_core::ready!(this.stream.poll_next_unpin(cx)) {
+                Some(signal) => signal,
+                None => return Poll::Ready(None),
+            };
+            let args = match signal.args() {
+                Ok(args) => args,
+                Err(e) => {
+                    warn!("Failed to parse `NameOwnerChanged` signal: {}", e);
+
+                    continue;
+                }
+            };
+
+            // Skip the changes that happened before we queried the owner.
+            let old_owner = args.old_owner().as_ref().map(|o| o.as_str());
+            if old_owner != this.owner.as_ref().map(|o| o.as_str()) {
+                continue;
+            }
+
+            let new_owner = args
+                .new_owner()
+                .as_ref()
+                .map(|o| OwnedUniqueName::from(o.to_owned()));
+            if new_owner == this.owner {
+                continue;
+            }
+            this.owner = new_owner.clone();
+
+            return Poll::Ready(Some(match new_owner {
+                Some(owner) => NameEvent::Appeared(owner),
+                None => NameEvent::Vanished,
+            }));
+        }
+    }
+}
+
+impl std::fmt::Debug for NameWatchStream {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("NameWatchStream")
+            .field("name", &self.name)
+            .field("owner", &self.owner)
+            .finish_non_exhaustive()
+    }
+}
@@ -3,11 +3,15 @@ pub use split::{BoxedSplit, Split};
 
 mod tcp;
 mod unix;
+#[cfg(all(target_os = "linux", feature = "io-uring"))]
+mod uring;
+#[cfg(all(target_os = "linux", feature = "io-uring"))]
+pub use uring::{UringReadHalf, UringSocket, UringWriteHalf};
 mod vsock;
 
 #[cfg(not(feature = "tokio"))]
 use async_io::Async;
-use std::io;
+use std::io::{self, IoSlice};
 #[cfg(not(feature = "tokio"))]
 use std::sync::Arc;
 
@@ -90,6 +94,31 @@ pub trait WriteHalf: std::fmt::Debug + Send + Sync + 'static {
         #[cfg(unix)] fds: &[BorrowedFd<'_>],
     ) -> io::Result<usize>;
 
+    /// Attempt to send the concatenation of `buffers` on the socket.
+    ///
+    /// This is the vectored version of [`WriteHalf::sendmsg`], with the same semantics otherwise.
+    /// It allows writing several messages at once. Socket implementations that support vectored
+    /// I/O should implement this method.
+    ///
+    /// The default implementation only sends the first non-empty buffer, using `sendmsg`.
+    async fn sendmsg_vectored(
+        &mut self,
+        buffers: &[IoSlice<'_>],
+        #[cfg(unix)] fds: &[BorrowedFd<'_>],
+    ) -> io::Result<usize> {
+        let buffer = buffers
+            .iter()
+            .find(|b| !b.is_empty())
+            .map_or(&[][..], |b| &**b);
+
+        self.sendmsg(
+            buffer,
+            #[cfg(unix)]
+            fds,
+        )
+        .await
+    }
+
     /// The dbus daemon on `freebsd` and `dragonfly` currently requires sending the zero byte
     /// as a separate message with SCM_CREDS, as part of the `EXTERNAL` authentication on unix
     /// sockets. This method is used by the authentication machinery in zbus to send this
@@ -148,6 +177,20 @@ impl WriteHalf for Box<dyn WriteHalf> {
             .await
     }
 
+    async fn sendmsg_vectored(
+        &mut self,
+        buffers: &[IoSlice<'_>],
+        #[cfg(unix)] fds: &[BorrowedFd<'_>],
+    ) -> io::Result<usize> {
+        (**self)
+            .sendmsg_vectored(
+                buffers,
+                #[cfg(unix)]
+                fds,
+            )
+            .await
+    }
+
     #[cfg(any(target_os = "freebsd", target_os = "dragonfly"))]
     async fn send_zero_byte(&mut self) -> io::Result<Option<usize>> {
         (**self).send_zero_byte().await
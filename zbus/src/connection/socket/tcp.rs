@@ -2,7 +2,7 @@
 use crate::fdo::ConnectionCredentials;
 #[cfg(not(feature = "tokio"))]
 use async_io::Async;
-use std::io;
+use std::io::{self, IoSlice};
 #[cfg(unix)]
 use std::os::fd::BorrowedFd;
 #[cfg(not(feature = "tokio"))]
@@ -76,6 +76,22 @@ impl WriteHalf for Arc<Async<TcpStream>> {
         futures_util::AsyncWriteExt::write(&mut self.as_ref(), buf).await
     }
 
+    async fn sendmsg_vectored(
+        &mut self,
+        buffers: &[IoSlice<'_>],
+        #[cfg(unix)] fds: &[BorrowedFd<'_>],
+    ) -> io::Result<usize> {
+        #[cfg(unix)]
+        if !fds.is_empty() {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                "fds cannot be sent with a tcp stream",
+            ));
+        }
+
+        futures_util::AsyncWriteExt::write_vectored(&mut self.as_ref(), buffers).await
+    }
+
     async fn close(&mut self) -> io::Result<()> {
         let stream = self.clone();
         crate::Task::spawn_blocking(
@@ -158,6 +174,24 @@ impl WriteHalf for tokio::net::tcp::OwnedWriteHalf {
         self.write(buf).await
     }
 
+    async fn sendmsg_vectored(
+        &mut self,
+        buffers: &[IoSlice<'_>],
+        #[cfg(unix)] fds: &[BorrowedFd<'_>],
+    ) -> io::Result<usize> {
+        use tokio::io::AsyncWriteExt;
+
+        #[cfg(unix)]
+        if !fds.is_empty() {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                "fds cannot be sent with a tcp stream",
+            ));
+        }
+
+        self.write_vectored(buffers).await
+    }
+
     async fn close(&mut self) -> io::Result<()> {
         tokio::io::AsyncWriteExt::shutdown(self).await
     }
@@ -68,11 +68,19 @@ impl WriteHalf for Arc<Async<UnixStream>> {
         &mut self,
         buffer: &[u8],
         #[cfg(unix)] fds: &[BorrowedFd<'_>],
+    ) -> io::Result<usize> {
+        self.sendmsg_vectored(&[IoSlice::new(buffer)], fds).await
+    }
+
+    async fn sendmsg_vectored(
+        &mut self,
+        buffers: &[IoSlice<'_>],
+        #[cfg(unix)] fds: &[BorrowedFd<'_>],
     ) -> io::Result<usize> {
         poll_fn(|cx| loop {
             match fd_sendmsg(
                 self.as_raw_fd(),
-                buffer,
+                buffers,
                 #[cfg(unix)]
                 fds,
             ) {
@@ -166,13 +174,21 @@ impl WriteHalf for tokio::net::unix::OwnedWriteHalf {
         &mut self,
         buffer: &[u8],
         #[cfg(unix)] fds: &[BorrowedFd<'_>],
+    ) -> io::Result<usize> {
+        self.sendmsg_vectored(&[IoSlice::new(buffer)], fds).await
+    }
+
+    async fn sendmsg_vectored(
+        &mut self,
+        buffers: &[IoSlice<'_>],
+        #[cfg(unix)] fds: &[BorrowedFd<'_>],
     ) -> io::Result<usize> {
         let stream = self.as_ref();
         poll_fn(|cx| loop {
             match stream.try_io(tokio::io::Interest::WRITABLE, || {
                 fd_sendmsg(
                     stream.as_raw_fd(),
-                    buffer,
+                    buffers,
                     #[cfg(unix)]
                     fds,
                 )
@@ -269,6 +285,10 @@ impl WriteHalf for Arc<Async<UnixStream>> {
     }
 }
 
+// The value of `IOV_MAX` on Linux, macOS and the BSDs.
+#[cfg(unix)]
+const IOV_MAX: usize = 1024;
+
 #[cfg(unix)]
 fn fd_recvmsg(fd: RawFd, buffer: &mut [u8]) -> io::Result<(usize, Vec<OwnedFd>)> {
     let mut iov = [IoSliceMut::new(buffer)];
@@ -300,7 +320,7 @@ fn fd_recvmsg(fd: RawFd, buffer: &mut [u8]) -> io::Result<(usize, Vec<OwnedFd>)>
 }
 
 #[cfg(unix)]
-fn fd_sendmsg(fd: RawFd, buffer: &[u8], fds: &[BorrowedFd<'_>]) -> io::Result<usize> {
+fn fd_sendmsg(fd: RawFd, buffers: &[IoSlice<'_>], fds: &[BorrowedFd<'_>]) -> io::Result<usize> {
     // FIXME: Remove this conversion once nix supports BorrowedFd here.
     //
     // Tracking issue: https://github.com/nix-rust/nix/issues/1750
@@ -310,8 +330,9 @@ fn fd_sendmsg(fd: RawFd, buffer: &[u8], fds: &[BorrowedFd<'_>]) -> io::Result<us
     } else {
         vec![]
     };
-    let iov = [IoSlice::new(buffer)];
-    match sendmsg::<UnixAddr>(fd, &iov, &cmsg, MsgFlags::empty(), None) {
+    // Don't exceed the maximum number of buffers the kernel accepts at once.
+    let buffers = &buffers[..buffers.len().min(IOV_MAX)];
+    match sendmsg::<UnixAddr>(fd, buffers, &cmsg, MsgFlags::empty(), None) {
         // can it really happen?
         Ok(0) => Err(io::Error::new(
             io::ErrorKind::WriteZero,
@@ -323,7 +344,7 @@ fn fd_sendmsg(fd: RawFd, buffer: &[u8], fds: &[BorrowedFd<'_>]) -> io::Result<us
 }
 
 #[cfg(unix)]
-async fn get_unix_peer_creds(fd: &impl AsRawFd) -> io::Result<ConnectionCredentials> {
+pub(super) async fn get_unix_peer_creds(fd: &impl AsRawFd) -> io::Result<ConnectionCredentials> {
     let fd = fd.as_raw_fd();
     // FIXME: Is it likely enough for sending of 1 byte to block, to justify a task (possibly
     // launching a thread in turn)?
@@ -1,17 +1,26 @@
-use std::{collections::HashMap, sync::Arc};
+#[cfg(unix)]
+use std::os::fd::OwnedFd;
+use std::{collections::HashMap, num::NonZeroU32, sync::Arc};
 
 use event_listener::Event;
-use tracing::{debug, instrument, trace};
-use zvariant::serialized::{self, Context};
+use tracing::{debug, instrument, trace, warn};
+use zvariant::serialized::{self, Context, Limits};
 
 use crate::{
     async_lock::Mutex,
     connection::MsgBroadcaster,
-    message::header::{PrimaryHeader, MAX_MESSAGE_SIZE, MIN_MESSAGE_SIZE},
-    padding_for_8_bytes, Executor, Message, OwnedMatchRule, Task,
+    fdo,
+    message::{
+        header::{PrimaryHeader, MAX_MESSAGE_SIZE, MIN_MESSAGE_SIZE},
+        BufferPool, EndianSig, Flags, Header, Type, NATIVE_ENDIAN_SIG,
+    },
+    padding_for_8_bytes, DBusError, Executor, Message, OwnedMatchRule, Task,
 };
 
-use super::socket::ReadHalf;
+use super::{socket::ReadHalf, WeakConnection};
+
+// The size of the chunks in which the messages that exceed the limits are read and dropped.
+const DISCARD_CHUNK_SIZE: usize = 64 * 1024;
 
 #[derive(Debug)]
 pub(crate) struct SocketReader {
@@ -20,6 +29,10 @@ pub(crate) struct SocketReader {
     already_received_bytes: Option<Vec<u8>>,
     prev_seq: u64,
     activity_event: Arc<Event>,
+    buffer_pool: BufferPool,
+    limits: Limits,
+    // To reply to the method calls exceeding the limits.
+    conn: WeakConnection,
 }
 
 impl SocketReader {
@@ -28,6 +41,9 @@ impl SocketReader {
         senders: Arc<Mutex<HashMap<Option<OwnedMatchRule>, MsgBroadcaster>>>,
         already_received_bytes: Vec<u8>,
         activity_event: Arc<Event>,
+        buffer_pool: BufferPool,
+        limits: Limits,
+        conn: WeakConnection,
     ) -> Self {
         Self {
             socket,
@@ -35,6 +51,9 @@ impl SocketReader {
             already_received_bytes: Some(already_received_bytes),
             prev_seq: 0,
             activity_event,
+            buffer_pool,
+            limits,
+            conn,
         }
     }
 
@@ -100,91 +119,224 @@ impl SocketReader {
         let mut bytes = self
             .already_received_bytes
             .take()
-            .unwrap_or_else(|| Vec::with_capacity(MIN_MESSAGE_SIZE));
-        let mut pos = bytes.len();
-        #[cfg(unix)]
-        let mut fds = vec![];
-        if pos < MIN_MESSAGE_SIZE {
-            bytes.resize(MIN_MESSAGE_SIZE, 0);
-            // We don't have enough data to make a proper message header yet.
-            // Some partial read may be in raw_in_buffer, so we try to complete it
-            // until we have MIN_MESSAGE_SIZE bytes
-            //
-            // Given that MIN_MESSAGE_SIZE is 16, this codepath is actually extremely unlikely
-            // to be taken more than once
-            while pos < MIN_MESSAGE_SIZE {
-                let res = self.socket.recvmsg(&mut bytes[pos..]).await?;
-                let len = {
-                    #[cfg(unix)]
-                    {
-                        fds.extend(res.1);
-                        res.0
-                    }
-                    #[cfg(not(unix))]
-                    {
-                        res
-                    }
-                };
-                pos += len;
-                if len == 0 {
-                    return Err(crate::Error::InputOutput(
-                        std::io::Error::new(
-                            std::io::ErrorKind::UnexpectedEof,
-                            "failed to receive message",
+            .unwrap_or_else(|| self.buffer_pool.get());
+        loop {
+            let mut pos = bytes.len();
+            #[cfg(unix)]
+            let mut fds = vec![];
+            if pos < MIN_MESSAGE_SIZE {
+                bytes.resize(MIN_MESSAGE_SIZE, 0);
+                // We don't have enough data to make a proper message header yet.
+                // Some partial read may be in raw_in_buffer, so we try to complete it
+                // until we have MIN_MESSAGE_SIZE bytes
+                //
+                // Given that MIN_MESSAGE_SIZE is 16, this codepath is actually extremely unlikely
+                // to be taken more than once
+                while pos < MIN_MESSAGE_SIZE {
+                    pos += self
+                        .recv(
+                            &mut bytes[pos..],
+                            #[cfg(unix)]
+                            &mut fds,
                         )
-                        .into(),
-                    ));
+                        .await?;
                 }
             }
-        }
 
-        let (primary_header, fields_len) = PrimaryHeader::read(&bytes)?;
-        let header_len = MIN_MESSAGE_SIZE + fields_len as usize;
-        let body_padding = padding_for_8_bytes(header_len);
-        let body_len = primary_header.body_len() as usize;
-        let total_len = header_len + body_padding + body_len;
-        if total_len > MAX_MESSAGE_SIZE {
-            return Err(crate::Error::ExcessData);
-        }
+            let (primary_header, fields_len) = PrimaryHeader::read(&bytes)?;
+            let header_len = MIN_MESSAGE_SIZE + fields_len as usize;
+            let body_padding = padding_for_8_bytes(header_len);
+            let body_len = primary_header.body_len() as usize;
+            let total_len = header_len + body_padding + body_len;
+            let serial = primary_header.serial_num();
+            let max_len = MAX_MESSAGE_SIZE.min(self.limits.max_message_size());
+            if total_len > max_len {
+                warn!(
+                    "Discarding message with serial {serial} as it's too large ({total_len} bytes)"
+                );
+                // Read the header, unless it's too large itself, to be able to reply.
+                if header_len <= max_len {
+                    bytes.resize(header_len.max(pos), 0);
+                    while pos < header_len {
+                        pos += self
+                            .recv(
+                                &mut bytes[pos..],
+                                #[cfg(unix)]
+                                &mut fds,
+                            )
+                            .await?;
+                    }
+                    self.reply_limits_exceeded(
+                        &bytes[..header_len],
+                        format!("Message is too large ({total_len} bytes)"),
+                    );
+                }
+                self.discard(&mut bytes, total_len.saturating_sub(pos))
+                    .await?;
 
-        // By this point we have a full primary header, so we know the exact length of the complete
-        // message.
-        bytes.resize(total_len, 0);
+                continue;
+            }
+            // The file descriptors are sent along with the first byte of the message, so we should
+            // have them all already.
+            #[cfg(unix)]
+            let mut too_many_fds = self.too_many_fds(serial, &mut fds);
+
+            // By this point we have a full primary header, so we know the exact length of the
+            // complete message.
+            bytes.resize(total_len, 0);
 
-        // Now we have an incomplete message; read the rest
-        while pos < total_len {
-            let res = self.socket.recvmsg(&mut bytes[pos..]).await?;
-            let read = {
+            // Now we have an incomplete message; read the rest
+            while pos < total_len {
+                pos += self
+                    .recv(
+                        &mut bytes[pos..],
+                        #[cfg(unix)]
+                        &mut fds,
+                    )
+                    .await?;
                 #[cfg(unix)]
                 {
-                    fds.extend(res.1);
-                    res.0
+                    too_many_fds |= self.too_many_fds(serial, &mut fds);
                 }
-                #[cfg(not(unix))]
-                {
-                    res
-                }
-            };
-            pos += read;
-            if read == 0 {
-                return Err(crate::Error::InputOutput(
-                    std::io::Error::new(
-                        std::io::ErrorKind::UnexpectedEof,
-                        "failed to receive message",
-                    )
-                    .into(),
-                ));
             }
+            #[cfg(unix)]
+            if too_many_fds {
+                self.reply_limits_exceeded(
+                    &bytes[..header_len],
+                    "Message comes with too many file descriptors".to_string(),
+                );
+                bytes.clear();
+
+                continue;
+            }
+
+            // If we reach here, the message is complete; return it
+            let seq = self.prev_seq + 1;
+            self.prev_seq = seq;
+            let ctxt = Context::<byteorder::NativeEndian>::new_dbus(0).with_limits(self.limits);
+            #[cfg(unix)]
+            let bytes = serialized::Data::new_fds(bytes, ctxt, fds);
+            #[cfg(not(unix))]
+            let bytes = serialized::Data::new(bytes, ctxt);
+
+            return Message::from_raw_parts(bytes, seq, Some(self.buffer_pool.clone()));
+        }
+    }
+
+    // Receive some bytes into `buf`, appending the file descriptors received with them to `fds`.
+    async fn recv(
+        &mut self,
+        buf: &mut [u8],
+        #[cfg(unix)] fds: &mut Vec<OwnedFd>,
+    ) -> crate::Result<usize> {
+        let res = self.socket.recvmsg(buf).await?;
+        let len = {
+            #[cfg(unix)]
+            {
+                fds.extend(res.1);
+                res.0
+            }
+            #[cfg(not(unix))]
+            {
+                res
+            }
+        };
+        if len == 0 {
+            return Err(crate::Error::InputOutput(
+                std::io::Error::new(
+                    std::io::ErrorKind::UnexpectedEof,
+                    "failed to receive message",
+                )
+                .into(),
+            ));
         }
 
-        // If we reach here, the message is complete; return it
-        let seq = self.prev_seq + 1;
-        self.prev_seq = seq;
-        let ctxt = Context::<byteorder::NativeEndian>::new_dbus(0);
-        #[cfg(unix)]
-        let bytes = serialized::Data::new_fds(bytes, ctxt, fds);
-        #[cfg(not(unix))]
-        let bytes = serialized::Data::new(bytes, ctxt);
-        Message::from_raw_parts(bytes, seq)
+        Ok(len)
+    }
+
+    // Read and drop the next `len` bytes, along with any file descriptors sent with them. `buf` is
+    // used as scratch space and left empty.
+    async fn discard(&mut self, buf: &mut Vec<u8>, mut len: usize) -> crate::Result<()> {
+        buf.resize(len.min(DISCARD_CHUNK_SIZE), 0);
+        while len > 0 {
+            let max = len.min(buf.len());
+            len -= self
+                .recv(
+                    &mut buf[..max],
+                    #[cfg(unix)]
+                    &mut vec![],
+                )
+                .await?;
+        }
+        buf.clear();
+
+        Ok(())
+    }
+
+    // Reply with an error to the method call with the given encoded `header`, which exceeds the
+    // limits, unless no reply is expected.
+    //
+    // The reply is sent from another task, so the peer not reading its messages can't block us.
+    fn reply_limits_exceeded(&self, header: &[u8], reason: String) {
+        if EndianSig::try_from(header[0]).ok() != Some(NATIVE_ENDIAN_SIG) {
+            return;
+        }
+        let ctxt = Context::<byteorder::NativeEndian>::new_dbus(0).with_limits(self.limits);
+        let data = serialized::Data::new(header, ctxt);
+        let header: Header<'_> = match data.deserialize() {
+            Ok((header, _)) => header,
+            Err(e) => {
+                debug!("Failed to parse the header of the discarded message: {}", e);
+
+                return;
+            }
+        };
+        if header.message_type() != Type::MethodCall
+            || header.primary().flags().contains(Flags::NoReplyExpected)
+        {
+            return;
+        }
+        let Some(conn) = self.conn.upgrade() else {
+            return;
+        };
+        let reply = match fdo::Error::LimitsExceeded(reason).create_reply(&header) {
+            Ok(reply) => reply,
+            Err(e) => {
+                debug!("Failed to create the reply to the discarded message: {}", e);
+
+                return;
+            }
+        };
+
+        conn.executor()
+            .spawn(
+                {
+                    let conn = conn.clone();
+                    async move {
+                        if let Err(e) = conn.send(&reply).await {
+                            debug!("Failed to reply to the discarded message: {}", e);
+                        }
+                    }
+                },
+                "limits exceeded reply",
+            )
+            .detach();
+    }
+
+    // Check the number of file descriptors received with the message with `serial` so far,
+    // closing them all if there are too many.
+    #[cfg(unix)]
+    fn too_many_fds(&self, serial: NonZeroU32, fds: &mut Vec<OwnedFd>) -> bool {
+        if fds.len() <= self.limits.max_fds() {
+            return false;
+        }
+
+        warn!(
+            "Discarding message with serial {serial} as it comes with too many file descriptors ({})",
+            fds.len()
+        );
+        fds.clear();
+
+        true
     }
 }
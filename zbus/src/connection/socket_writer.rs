@@ -0,0 +1,474 @@
+use std::{
+    collections::VecDeque,
+    io::{self, IoSlice},
+    sync::{self, Arc},
+};
+
+#[cfg(unix)]
+use std::os::fd::AsFd;
+use tracing::{debug, trace};
+
+use crate::{
+    async_lock::{Mutex, MutexGuard},
+    Error, Message, Result,
+};
+
+use super::socket::WriteHalf;
+
+// The maximum number of messages written at once.
+const MAX_BATCH_LEN: usize = 64;
+
+/// Writes outgoing messages to the socket, coalescing the ones that are queued concurrently.
+///
+/// Messages are first put in a queue. The task that gets hold of the socket then writes all the
+/// queued messages (in batches), with a single vectored write per batch whenever possible. So under
+/// load, tasks sending messages concurrently share the cost of writing them, while in the absence
+/// of contention, each message is written right away.
+///
+/// The queue can also be corked, in which case messages are only queued (and sending them returns
+/// immediately) until it's uncorked or explicitly flushed.
+#[derive(Debug)]
+pub(crate) struct SocketWriter {
+    socket: Mutex<Box<dyn WriteHalf>>,
+    queue: sync::Mutex<Queue>,
+}
+
+#[derive(Debug, Default)]
+struct Queue {
+    messages: VecDeque<Queued>,
+    // The ticket of the first message in `messages`. Tickets are assigned in queueing order.
+    next_written: u64,
+    // How many bytes of the first message in `messages` have been written already. Messages are
+    // only removed from the queue once completely written, so that if the writing task is
+    // cancelled, the next one picks up where it left off.
+    front_written: usize,
+    corked: usize,
+    // Set once writing failed in the middle of a message. The peer would misinterpret anything
+    // written after that, so the socket is shut down and nothing can be sent anymore.
+    broken: Option<Error>,
+}
+
+#[derive(Debug)]
+struct Queued {
+    msg: Message,
+    // The result of writing the message, set once it's been written or has failed to be.
+    result: WriteResult,
+}
+
+type WriteResult = Arc<sync::Mutex<Option<Result<()>>>>;
+
+impl Queue {
+    fn next_ticket(&self) -> u64 {
+        self.next_written + self.messages.len() as u64
+    }
+
+    /// The next messages to write at once.
+    fn batch(&self) -> Vec<Message> {
+        let mut batch = Vec::with_capacity(self.messages.len().min(MAX_BATCH_LEN));
+        for queued in self.messages.iter().take(MAX_BATCH_LEN) {
+            // File descriptors are attached to the first byte written along with them, so a
+            // message carrying any must start a new write. Otherwise, the peer could receive them
+            // along with the previous message.
+            #[cfg(unix)]
+            if !batch.is_empty() && !queued.msg.data().fds().is_empty() {
+                break;
+            }
+
+            batch.push(queued.msg.clone());
+        }
+
+        batch
+    }
+
+    /// Mark `len` more bytes as written, completing the messages that are now fully written.
+    fn advance(&mut self, len: usize) {
+        self.front_written += len;
+        while let Some(queued) = self.messages.front() {
+            let msg_len = queued.msg.data().len();
+            if self.front_written < msg_len {
+                break;
+            }
+            trace!(
+                "Sent message with serial: {}",
+                queued.msg.primary_header().serial_num()
+            );
+            self.front_written -= msg_len;
+            self.complete(Ok(()));
+        }
+    }
+
+    /// Fail the first `count` messages with `e`.
+    fn fail(&mut self, count: usize, e: Error) {
+        for _ in 0..count {
+            self.complete(Err(e.clone()));
+        }
+        self.front_written = 0;
+    }
+
+    /// Fail all the messages with `e`, and refuse any new ones.
+    fn fail_all(&mut self, e: Error) {
+        self.fail(self.messages.len(), e.clone());
+        self.broken = Some(e);
+    }
+
+    fn complete(&mut self, result: Result<()>) {
+        if let Some(queued) = self.messages.pop_front() {
+            *queued.result.lock().expect("lock poisoned") = Some(result);
+            self.next_written += 1;
+        }
+    }
+}
+
+impl SocketWriter {
+    pub fn new(socket: Box<dyn WriteHalf>) -> Self {
+        Self {
+            socket: Mutex::new(socket),
+            queue: sync::Mutex::new(Queue::default()),
+        }
+    }
+
+    /// Queue `msg` and, unless corked, write it out.
+    ///
+    /// If corked, this returns immediately and any failure to write the message is reported by the
+    /// [`SocketWriter::flush`] call that writes it out.
+    ///
+    /// Once queued, the message is written out even if the returned future is dropped before
+    /// completion. Dropping it only gives up on the result.
+    pub async fn send(&self, msg: &Message) -> Result<()> {
+        let result = WriteResult::default();
+        let ticket = {
+            let mut queue = self.queue();
+            if let Some(e) = &queue.broken {
+                return Err(e.clone());
+            }
+            let ticket = queue.next_ticket();
+            queue.messages.push_back(Queued {
+                msg: msg.clone(),
+                result: result.clone(),
+            });
+            if queue.corked > 0 {
+                trace!(
+                    "Queued message with serial: {}",
+                    msg.primary_header().serial_num()
+                );
+
+                return Ok(());
+            }
+
+            ticket
+        };
+
+        self.flush_until(ticket).await;
+
+        written_result(&result)
+    }
+
+    /// Write out all the queued messages, even if corked.
+    ///
+    /// Returns the first error encountered writing any of them.
+    pub async fn flush(&self) -> Result<()> {
+        let (results, ticket) = {
+            let queue = self.queue();
+            let results: Vec<_> = queue.messages.iter().map(|q| q.result.clone()).collect();
+            match queue.next_ticket().checked_sub(1) {
+                Some(ticket) => (results, ticket),
+                None => return Ok(()),
+            }
+        };
+
+        self.flush_until(ticket).await;
+
+        results.iter().try_for_each(written_result)
+    }
+
+    /// Cork the queue.
+    pub fn cork(&self) {
+        self.queue().corked += 1;
+    }
+
+    /// Uncork the queue, returning `true` if it's not corked anymore and there are messages to
+    /// write out.
+    pub fn uncork(&self) -> bool {
+        let mut queue = self.queue();
+        queue.corked -= 1;
+
+        queue.corked == 0 && !queue.messages.is_empty()
+    }
+
+    pub async fn close(&self) -> Result<()> {
+        let res = self.flush().await;
+        let mut socket = self.socket.lock().await;
+        // Already shut down if broken.
+        if self.queue().broken.is_none() {
+            socket.close().await?;
+        }
+
+        res
+    }
+
+    pub async fn socket(&self) -> MutexGuard<'_, Box<dyn WriteHalf>> {
+        self.socket.lock().await
+    }
+
+    // Write all the messages up to the one with `ticket`.
+    //
+    // The queue is updated after each write so this can be cancelled at any point: the messages
+    // that haven't been completely written yet stay in the queue.
+    //
+    // A failure only fails the messages of the batch, unless it happened after the first one was
+    // partially written. In that case, the socket is shut down and all the messages are failed.
+    async fn flush_until(&self, ticket: u64) {
+        let mut socket = self.socket.lock().await;
+        loop {
+            let (batch, pos) = {
+                let queue = self.queue();
+                if ticket < queue.next_written {
+                    // Written by us or by another task in the meantime.
+                    return;
+                }
+
+                (queue.batch(), queue.front_written)
+            };
+
+            match write_batch(&mut **socket, &batch, pos).await {
+                Ok(len) => self.queue().advance(len),
+                Err(e) if pos > 0 => {
+                    debug!(
+                        "Failed to write the rest of a partially written message: {}",
+                        e
+                    );
+                    self.queue().fail_all(e);
+                    if let Err(e) = socket.close().await {
+                        debug!("Failed to shut down the socket: {}", e);
+                    }
+
+                    return;
+                }
+                Err(e) => self.queue().fail(batch.len(), e),
+            }
+        }
+    }
+
+    #[cfg(test)]
+    pub fn queue_len(&self) -> usize {
+        self.queue().messages.len()
+    }
+
+    fn queue(&self) -> sync::MutexGuard<'_, Queue> {
+        self.queue.lock().expect("lock poisoned")
+    }
+}
+
+fn written_result(result: &WriteResult) -> Result<()> {
+    result
+        .lock()
+        .expect("lock poisoned")
+        .clone()
+        .expect("message not written")
+}
+
+// Write (some of) `batch`, the first message of which has been written up to `pos` already.
+async fn write_batch(socket: &mut dyn WriteHalf, batch: &[Message], pos: usize) -> Result<usize> {
+    // File descriptors are sent along with the first byte of the message carrying them.
+    #[cfg(unix)]
+    let fds: Vec<_> = if pos == 0 {
+        batch[0].data().fds().iter().map(|f| f.as_fd()).collect()
+    } else {
+        vec![]
+    };
+    let buffers: Vec<_> = batch
+        .iter()
+        .enumerate()
+        .map(|(i, msg)| {
+            let data = msg.data();
+            IoSlice::new(if i == 0 { &data[pos..] } else { &data[..] })
+        })
+        .collect();
+    let len = socket
+        .sendmsg_vectored(
+            &buffers,
+            #[cfg(unix)]
+            &fds,
+        )
+        .await?;
+    if len == 0 {
+        return Err(io::Error::new(io::ErrorKind::WriteZero, "failed to write message").into());
+    }
+
+    Ok(len)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{
+        collections::VecDeque,
+        io::{self, IoSlice},
+        sync::{Arc, Mutex},
+    };
+
+    #[cfg(unix)]
+    use std::os::fd::BorrowedFd;
+
+    use futures_util::FutureExt;
+    use test_log::test;
+
+    use super::{SocketWriter, WriteHalf};
+    use crate::{Error, Message};
+
+    #[derive(Debug)]
+    enum Step {
+        Write(usize),
+        Block,
+        Fail,
+    }
+
+    // A socket that follows a script of `Step`s, and writes everything once it runs out of them.
+    #[derive(Debug, Default, Clone)]
+    struct ScriptedSocket {
+        steps: Arc<Mutex<VecDeque<Step>>>,
+        written: Arc<Mutex<Vec<u8>>>,
+        closed: Arc<Mutex<bool>>,
+    }
+
+    #[async_trait::async_trait]
+    impl WriteHalf for ScriptedSocket {
+        async fn sendmsg(
+            &mut self,
+            buffer: &[u8],
+            #[cfg(unix)] _fds: &[BorrowedFd<'_>],
+        ) -> io::Result<usize> {
+            self.sendmsg_vectored(
+                &[IoSlice::new(buffer)],
+                #[cfg(unix)]
+                &[],
+            )
+            .await
+        }
+
+        async fn sendmsg_vectored(
+            &mut self,
+            buffers: &[IoSlice<'_>],
+            #[cfg(unix)] _fds: &[BorrowedFd<'_>],
+        ) -> io::Result<usize> {
+            let step = self.steps.lock().unwrap().pop_front();
+            let max = match step {
+                Some(Step::Write(max)) => max,
+                Some(Step::Block) => return std::future::pending().await,
+                Some(Step::Fail) => return Err(io::ErrorKind::BrokenPipe.into()),
+                None => usize::MAX,
+            };
+            let mut written = self.written.lock().unwrap();
+            let start = written.len();
+            for buffer in buffers {
+                let len = buffer.len().min(max - (written.len() - start));
+                written.extend_from_slice(&buffer[..len]);
+            }
+
+            Ok(written.len() - start)
+        }
+
+        async fn close(&mut self) -> io::Result<()> {
+            *self.closed.lock().unwrap() = true;
+
+            Ok(())
+        }
+    }
+
+    fn messages(count: u32) -> Vec<Message> {
+        (0..count)
+            .map(|i| {
+                Message::signal("/", "org.zbus.Test", "Signal")
+                    .unwrap()
+                    .build(&i)
+                    .unwrap()
+            })
+            .collect()
+    }
+
+    #[test]
+    fn cancelled_flush() {
+        let socket = ScriptedSocket::default();
+        let writer = SocketWriter::new(Box::new(socket.clone()));
+        let msgs = messages(3);
+        let expected: Vec<u8> = msgs.iter().flat_map(|m| m.data().to_vec()).collect();
+
+        writer.cork();
+        for msg in &msgs {
+            writer.send(msg).now_or_never().unwrap().unwrap();
+        }
+        // Write the first message and a part of the second one, then block until cancelled.
+        let first_len = msgs[0].data().len();
+        socket
+            .steps
+            .lock()
+            .unwrap()
+            .extend([Step::Write(first_len + 10), Step::Block]);
+        assert!(writer.flush().now_or_never().is_none());
+        assert_eq!(writer.queue_len(), 2);
+        assert_eq!(socket.written.lock().unwrap().len(), first_len + 10);
+
+        // The next flush picks up where the cancelled one left off.
+        socket.steps.lock().unwrap().push_back(Step::Write(1));
+        writer.flush().now_or_never().unwrap().unwrap();
+        assert_eq!(writer.queue_len(), 0);
+        assert_eq!(*socket.written.lock().unwrap(), expected);
+    }
+
+    #[test]
+    fn write_errors() {
+        let socket = ScriptedSocket::default();
+        let writer = SocketWriter::new(Box::new(socket.clone()));
+        let msgs = messages(3);
+
+        // The failure of a corked message is reported by the flush writing it.
+        writer.cork();
+        writer.send(&msgs[0]).now_or_never().unwrap().unwrap();
+        socket.steps.lock().unwrap().push_back(Step::Fail);
+        assert!(matches!(
+            writer.flush().now_or_never().unwrap(),
+            Err(Error::InputOutput(_))
+        ));
+        assert!(!writer.uncork());
+
+        // Failures don't affect the following messages.
+        socket.steps.lock().unwrap().push_back(Step::Fail);
+        assert!(matches!(
+            writer.send(&msgs[1]).now_or_never().unwrap(),
+            Err(Error::InputOutput(_))
+        ));
+        writer.send(&msgs[2]).now_or_never().unwrap().unwrap();
+        assert_eq!(*socket.written.lock().unwrap(), msgs[2].data().to_vec());
+    }
+
+    #[test]
+    fn write_error_after_partial_write() {
+        let socket = ScriptedSocket::default();
+        let writer = SocketWriter::new(Box::new(socket.clone()));
+        let msgs = messages(3);
+
+        // Write a part of the first message, then fail.
+        writer.cork();
+        for msg in &msgs {
+            writer.send(msg).now_or_never().unwrap().unwrap();
+        }
+        socket
+            .steps
+            .lock()
+            .unwrap()
+            .extend([Step::Write(10), Step::Fail]);
+        assert!(matches!(
+            writer.flush().now_or_never().unwrap(),
+            Err(Error::InputOutput(_))
+        ));
+
+        // All the messages failed, the socket is shut down and nothing can be sent anymore.
+        assert_eq!(writer.queue_len(), 0);
+        assert!(*socket.closed.lock().unwrap());
+        assert!(!writer.uncork());
+        assert!(matches!(
+            writer.send(&msgs[0]).now_or_never().unwrap(),
+            Err(Error::InputOutput(_))
+        ));
+        assert_eq!(socket.written.lock().unwrap().len(), 10);
+    }
+}
@@ -166,6 +166,30 @@ pub use utils::*;
 #[macro_use]
 pub mod fdo;
 
+#[cfg(feature = "polkit")]
+#[macro_use]
+pub mod polkit;
+
+// Used by the code generated for `polkit_action` methods, so that forgetting to enable the
+// `polkit` feature results in a clear error rather than an unresolved `zbus::polkit` path.
+#[cfg(feature = "polkit")]
+#[doc(hidden)]
+#[macro_export]
+macro_rules! __polkit_check_authorization {
+    ($conn:expr, $hdr:expr, $action:expr) => {
+        $crate::polkit::check_authorization($conn, $hdr, $action)
+    };
+}
+
+#[cfg(not(feature = "polkit"))]
+#[doc(hidden)]
+#[macro_export]
+macro_rules! __polkit_check_authorization {
+    ($conn:expr, $hdr:expr, $action:expr) => {
+        ::std::compile_error!("`polkit_action` requires the `polkit` feature of zbus")
+    };
+}
+
 #[deprecated(note = "Use `connection::Socket` instead")]
 #[doc(hidden)]
 pub use connection::Socket;
@@ -0,0 +1,130 @@
+use static_assertions::assert_impl_all;
+use std::sync::{Arc, Mutex};
+
+const DEFAULT_MAX_BUFFERS: usize = 32;
+const DEFAULT_MAX_BUFFER_CAPACITY: usize = 64 * 1024;
+
+/// A pool of reusable buffers for serialized messages.
+///
+/// Every [`Message`] keeps its serialized contents in a buffer. A message that is built or received
+/// with a pool takes its buffer from the pool and gives it back once it's dropped (i-e once all its
+/// clones and its [`Body`]s are dropped), so that the allocation can be reused by the next
+/// message.
+///
+/// Each [`Connection`] has a pool (see [`Connection::buffer_pool`]) that is used for all the
+/// messages it receives and the ones it builds itself (e.g in [`Connection::emit_signal`]). Use
+/// [`Builder::buffer_pool`] to build your own messages with a pool.
+///
+/// To keep the memory usage in check, the pool only keeps a limited number of buffers, and drops
+/// the buffers that grew too big (e.g for an exceptionally large message) instead of keeping them.
+///
+/// Cloning a pool is cheap and the clones share the same buffers.
+///
+/// # Example
+///
+/// ```
+/// use zbus::message::{BufferPool, Message};
+///
+/// let pool = BufferPool::default();
+/// // Pre-allocate a buffer.
+/// pool.put(Vec::with_capacity(1024));
+///
+/// let msg = Message::signal("/org/zbus/Frames", "org.zbus.Frames", "NewFrame")?
+///     .buffer_pool(&pool)
+///     .build(&(42u32, "frame"))?;
+/// assert!(pool.is_empty());
+///
+/// drop(msg);
+/// // The buffer is back in the pool, ready to be used for the next message.
+/// assert_eq!(pool.len(), 1);
+/// # Ok::<(), zbus::Error>(())
+/// ```
+///
+/// [`Message`]: super::Message
+/// [`Body`]: super::Body
+/// [`Builder::buffer_pool`]: super::Builder::buffer_pool
+/// [`Connection`]: crate::Connection
+/// [`Connection::buffer_pool`]: crate::Connection::buffer_pool
+/// [`Connection::emit_signal`]: crate::Connection::emit_signal
+#[derive(Debug, Clone)]
+pub struct BufferPool {
+    inner: Arc<Inner>,
+}
+
+#[derive(Debug)]
+struct Inner {
+    buffers: Mutex<Vec<Vec<u8>>>,
+    max_buffers: usize,
+    max_buffer_capacity: usize,
+}
+
+assert_impl_all!(BufferPool: Send, Sync, Unpin);
+
+impl BufferPool {
+    /// Create a pool that keeps at most `max_buffers` buffers, of at most `max_buffer_capacity`
+    /// bytes each.
+    pub fn new(max_buffers: usize, max_buffer_capacity: usize) -> Self {
+        Self {
+            inner: Arc::new(Inner {
+                buffers: Mutex::new(Vec::with_capacity(max_buffers)),
+                max_buffers,
+                max_buffer_capacity,
+            }),
+        }
+    }
+
+    /// Take a buffer from the pool.
+    ///
+    /// The returned buffer is empty but keeps the capacity it had when it was put in the pool. If
+    /// the pool is empty, a new buffer is returned.
+    pub fn get(&self) -> Vec<u8> {
+        self.buffers().pop().unwrap_or_default()
+    }
+
+    /// Put `buffer` in the pool, for it to be reused.
+    ///
+    /// The buffer is dropped instead if the pool is full or if its capacity is over the limit.
+    pub fn put(&self, mut buffer: Vec<u8>) {
+        if buffer.capacity() == 0 || buffer.capacity() > self.inner.max_buffer_capacity {
+            return;
+        }
+
+        let mut buffers = self.buffers();
+        if buffers.len() < self.inner.max_buffers {
+            buffer.clear();
+            buffers.push(buffer);
+        }
+    }
+
+    /// The number of buffers in the pool.
+    pub fn len(&self) -> usize {
+        self.buffers().len()
+    }
+
+    /// Whether the pool is empty.
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// The maximum number of buffers the pool keeps.
+    pub fn max_buffers(&self) -> usize {
+        self.inner.max_buffers
+    }
+
+    /// The maximum capacity of the buffers the pool keeps.
+    pub fn max_buffer_capacity(&self) -> usize {
+        self.inner.max_buffer_capacity
+    }
+
+    fn buffers(&self) -> std::sync::MutexGuard<'_, Vec<Vec<u8>>> {
+        // The lock is never held across any code that could panic.
+        self.inner.buffers.lock().expect("lock poisoned")
+    }
+}
+
+impl Default for BufferPool {
+    /// A pool of 32 buffers of at most 64 KiB each.
+    fn default() -> Self {
+        Self::new(DEFAULT_MAX_BUFFERS, DEFAULT_MAX_BUFFER_CAPACITY)
+    }
+}
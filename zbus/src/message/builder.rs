@@ -2,6 +2,7 @@
 use std::os::fd::OwnedFd;
 use std::{
     io::{Cursor, Write},
+    mem::ManuallyDrop,
     sync::Arc,
 };
 
@@ -10,7 +11,9 @@ use zbus_names::{BusName, ErrorName, InterfaceName, MemberName, UniqueName};
 use zvariant::serialized;
 
 use crate::{
-    message::{Field, FieldCode, Fields, Flags, Header, Message, PrimaryHeader, Sequence, Type},
+    message::{
+        BufferPool, Field, FieldCode, Fields, Flags, Header, Message, PrimaryHeader, Sequence, Type,
+    },
     utils::padding_for_8_bytes,
     zvariant::{serialized::Context, DynamicType, ObjectPath, Signature},
     Error, Result,
@@ -34,6 +37,7 @@ macro_rules! dbus_context {
 #[derive(Debug, Clone)]
 pub struct Builder<'a> {
     header: Header<'a>,
+    buffer_pool: Option<BufferPool>,
 }
 
 impl<'a> Builder<'a> {
@@ -41,7 +45,10 @@ impl<'a> Builder<'a> {
         let primary = PrimaryHeader::new(msg_type, 0);
         let fields = Fields::new();
         let header = Header::new(primary, fields);
-        Self { header }
+        Self {
+            header,
+            buffer_pool: None,
+        }
     }
 
     /// Create a message of type [`Type::MethodCall`].
@@ -105,6 +112,16 @@ impl<'a> Builder<'a> {
         Ok(self)
     }
 
+    /// Serialize the message into a buffer from `pool`.
+    ///
+    /// The buffer is given back to the pool once the message is dropped. See [`BufferPool`] for
+    /// details.
+    pub fn buffer_pool(mut self, pool: &BufferPool) -> Self {
+        self.buffer_pool = Some(pool.clone());
+
+        self
+    }
+
     /// Set the unique name of the sending connection.
     pub fn sender<'s: 'a, S>(mut self, sender: S) -> Result<Self>
     where
@@ -305,7 +322,11 @@ impl<'a> Builder<'a> {
         if total_len > MAX_MESSAGE_SIZE {
             return Err(Error::ExcessData);
         }
-        let mut bytes: Vec<u8> = Vec::with_capacity(total_len);
+        let mut bytes = match &self.buffer_pool {
+            Some(pool) => pool.get(),
+            None => Vec::new(),
+        };
+        bytes.reserve_exact(total_len);
         let mut cursor = Cursor::new(&mut bytes);
 
         // SAFETY: There are no FDs involved.
@@ -331,9 +352,10 @@ impl<'a> Builder<'a> {
             inner: Arc::new(super::Inner {
                 primary_header,
                 quick_fields,
-                bytes,
+                bytes: ManuallyDrop::new(bytes),
                 body_offset,
                 recv_seq: Sequence::default(),
+                buffer_pool: self.buffer_pool,
             }),
         })
     }
@@ -346,7 +368,10 @@ impl<'m> From<Header<'m>> for Builder<'m> {
         fields.remove(FieldCode::Signature);
         fields.remove(FieldCode::UnixFDs);
 
-        Self { header }
+        Self {
+            header,
+            buffer_pool: None,
+        }
     }
 }
 
@@ -8,7 +8,7 @@ use serde_repr::{Deserialize_repr, Serialize_repr};
 
 use static_assertions::assert_impl_all;
 use zbus_names::{BusName, ErrorName, InterfaceName, MemberName, UniqueName};
-use zvariant::{ObjectPath, Signature, Type, Value};
+use zvariant::{ObjectPath, ParsedSignature, Signature, Type, Value};
 
 /// The message field code.
 ///
@@ -97,9 +97,8 @@ pub(super) enum Field<'f> {
 assert_impl_all!(Field<'_>: Send, Sync, Unpin);
 
 impl<'f> Type for Field<'f> {
-    fn signature() -> Signature<'static> {
-        Signature::from_static_str_unchecked("(yv)")
-    }
+    const SIGNATURE: &'static ParsedSignature =
+        &ParsedSignature::static_structure(&[&ParsedSignature::U8, &ParsedSignature::Variant]);
 }
 
 impl<'f> Serialize for Field<'f> {
@@ -1,5 +1,5 @@
 //! D-Bus Message.
-use std::{fmt, num::NonZeroU32, sync::Arc};
+use std::{fmt, mem::ManuallyDrop, num::NonZeroU32, sync::Arc};
 
 use byteorder::NativeEndian;
 use static_assertions::assert_impl_all;
@@ -11,6 +11,9 @@ use crate::{utils::padding_for_8_bytes, zvariant::ObjectPath, Error, Result};
 mod builder;
 pub use builder::Builder;
 
+mod buffer_pool;
+pub use buffer_pool::BufferPool;
+
 mod field;
 use field::{Field, FieldCode};
 
@@ -61,9 +64,25 @@ pub struct Message {
 pub(super) struct Inner {
     pub(crate) primary_header: PrimaryHeader,
     pub(crate) quick_fields: QuickFields,
-    pub(crate) bytes: serialized::Data<'static, 'static, NativeEndian>,
+    // Only taken out on drop.
+    pub(crate) bytes: ManuallyDrop<serialized::Data<'static, 'static, NativeEndian>>,
     pub(crate) body_offset: usize,
     pub(crate) recv_seq: Sequence,
+    // The pool to give the buffer back to, once the message is dropped.
+    pub(crate) buffer_pool: Option<BufferPool>,
+}
+
+impl Drop for Inner {
+    fn drop(&mut self) {
+        // SAFETY: `self.bytes` is never used again.
+        let bytes = unsafe { ManuallyDrop::take(&mut self.bytes) };
+        if let Some(pool) = &self.buffer_pool {
+            // This fails if any `Body` (or a clone of the data) is still around.
+            if let Ok(buffer) = bytes.try_into_vec() {
+                pool.put(buffer);
+            }
+        }
+    }
 }
 
 assert_impl_all!(Message: Send, Sync, Unpin);
@@ -133,13 +152,14 @@ impl Message {
     pub unsafe fn from_bytes(
         bytes: serialized::Data<'static, 'static, NativeEndian>,
     ) -> Result<Self> {
-        Self::from_raw_parts(bytes, 0)
+        Self::from_raw_parts(bytes, 0, None)
     }
 
     /// Create a message from its full contents
     pub(crate) fn from_raw_parts(
         bytes: serialized::Data<'static, 'static, NativeEndian>,
         recv_seq: u64,
+        buffer_pool: Option<BufferPool>,
     ) -> Result<Self> {
         if EndianSig::try_from(bytes[0])? != NATIVE_ENDIAN_SIG {
             return Err(Error::IncorrectEndian);
@@ -156,9 +176,10 @@ impl Message {
             inner: Arc::new(Inner {
                 primary_header,
                 quick_fields,
-                bytes,
+                bytes: ManuallyDrop::new(bytes),
                 body_offset,
                 recv_seq: Sequence { recv_seq },
+                buffer_pool,
             }),
         })
     }
@@ -372,7 +393,7 @@ mod tests {
     #[cfg(unix)]
     use zvariant::Fd;
 
-    use super::Message;
+    use super::{BufferPool, Message};
     use crate::Error;
 
     #[test]
@@ -419,4 +440,46 @@ mod tests {
             .unwrap();
         assert_eq!(e.to_string(), "Error org.freedesktop.zbus.Error: kaboom!");
     }
+
+    #[test]
+    fn buffer_pool() {
+        let pool = BufferPool::new(2, 1024);
+        let build = |body: &str| {
+            Message::signal("/", "org.zbus.Test", "Test")
+                .unwrap()
+                .buffer_pool(&pool)
+                .build(&body)
+                .unwrap()
+        };
+
+        let m = build("first");
+        let buffer = m.data().bytes().as_ptr();
+        let body = m.body();
+        drop(m);
+        // The body still references the buffer.
+        assert!(pool.is_empty());
+        assert_eq!(body.deserialize::<&str>().unwrap(), "first");
+        drop(body);
+        assert_eq!(pool.len(), 1);
+
+        // The buffer gets reused.
+        let m = build("again");
+        assert!(pool.is_empty());
+        assert_eq!(m.data().bytes().as_ptr(), buffer);
+        assert_eq!(m.body().deserialize::<&str>().unwrap(), "again");
+        let clone = m.clone();
+        drop(m);
+        assert!(pool.is_empty());
+        drop(clone);
+        assert_eq!(pool.len(), 1);
+
+        // Buffers that are too big aren't kept.
+        drop(build(&"a".repeat(2048)));
+        assert_eq!(pool.len(), 0);
+        pool.put(vec![0; 10]);
+        pool.put(vec![0; 10]);
+        pool.put(vec![0; 10]);
+        assert_eq!(pool.len(), 2);
+        assert!(pool.get().is_empty());
+    }
 }
@@ -130,6 +130,98 @@ pub trait Interface: Any + Send + Sync {
     fn introspect_to_writer(&self, writer: &mut dyn Write, level: usize);
 }
 
+/// The `!Send` counterpart of [`DispatchResult`], returned by [`LocalInterface`] callbacks.
+pub enum LocalDispatchResult<'a> {
+    /// This interface does not support the given method
+    NotFound,
+
+    /// Retry with [LocalInterface::call_mut].
+    ///
+    /// This is equivalent to NotFound if returned by call_mut.
+    RequiresMut,
+
+    /// The method was found and will be completed by running this Future
+    Async(Pin<Box<dyn Future<Output = Result<()>> + 'a>>),
+}
+
+/// The counterpart of [`Interface`] for types that are not `Send` or `Sync`.
+///
+/// [`Interface`] is implemented for [`Local<T>`] if `T` implements this trait, so a `Local<T>` can
+/// be served like any other interface, as long as it's only accessed from the thread that created
+/// it.
+///
+/// Note: It is not recommended to manually implement this trait. The [`dbus_interface`] macro
+/// implements it for you, if the `local` attribute is specified.
+///
+/// [`Local<T>`]: crate::object_server::Local
+/// [`dbus_interface`]: attr.dbus_interface.html
+#[async_trait(?Send)]
+pub trait LocalInterface: Any {
+    /// Return the name of the interface. Ex: "org.foo.MyInterface"
+    fn name() -> InterfaceName<'static>
+    where
+        Self: Sized;
+
+    /// Get a property value. Returns `None` if the property doesn't exist.
+    async fn get(&self, property_name: &str) -> Option<fdo::Result<OwnedValue>>;
+
+    /// Return all the properties.
+    async fn get_all(&self) -> fdo::Result<HashMap<String, OwnedValue>>;
+
+    /// Set a property value.
+    ///
+    /// Return [`LocalDispatchResult::NotFound`] if the property doesn't exist, or
+    /// [`LocalDispatchResult::RequiresMut`] if `set_mut` should be used instead.  The default
+    /// implementation just returns `RequiresMut`.
+    fn set<'call>(
+        &'call self,
+        property_name: &'call str,
+        value: &'call Value<'_>,
+        ctxt: &'call SignalContext<'_>,
+    ) -> LocalDispatchResult<'call> {
+        let _ = (property_name, value, ctxt);
+        LocalDispatchResult::RequiresMut
+    }
+
+    /// Set a property value.
+    ///
+    /// Returns `None` if the property doesn't exist.
+    ///
+    /// This will only be invoked if `set` returned `RequiresMut`.
+    async fn set_mut(
+        &mut self,
+        property_name: &str,
+        value: &Value<'_>,
+        ctxt: &SignalContext<'_>,
+    ) -> Option<fdo::Result<()>>;
+
+    /// Call a method.
+    ///
+    /// Return [`LocalDispatchResult::NotFound`] if the method doesn't exist, or
+    /// [`LocalDispatchResult::RequiresMut`] if `call_mut` should be used instead.
+    fn call<'call>(
+        &'call self,
+        server: &'call ObjectServer,
+        connection: &'call Connection,
+        msg: &'call Message,
+        name: MemberName<'call>,
+    ) -> LocalDispatchResult<'call>;
+
+    /// Call a `&mut self` method.
+    ///
+    /// This will only be invoked if `call` returned `RequiresMut`.
+    fn call_mut<'call>(
+        &'call mut self,
+        server: &'call ObjectServer,
+        connection: &'call Connection,
+        msg: &'call Message,
+        name: MemberName<'call>,
+    ) -> LocalDispatchResult<'call>;
+
+    /// Write introspection XML to the writer, with the given indentation level.
+    fn introspect_to_writer(&self, writer: &mut dyn Write, level: usize);
+}
+
 // Note: while it is possible to implement this without `unsafe`, it currently requires a helper
 // trait with a blanket impl that creates `dyn Any` refs.  It's simpler (and more performant) to
 // just check the type ID and do the downcast ourself.
@@ -0,0 +1,272 @@
+use async_trait::async_trait;
+use static_assertions::assert_impl_all;
+use std::{
+    collections::HashMap,
+    fmt::{self, Write},
+    future::Future,
+    mem::ManuallyDrop,
+    ops::{Deref, DerefMut},
+    pin::Pin,
+    task::{Context, Poll},
+    thread::{self, ThreadId},
+};
+use tracing::warn;
+use zbus_names::{InterfaceName, MemberName};
+use zvariant::{OwnedValue, Value};
+
+use crate::{
+    fdo,
+    message::Message,
+    object_server::{
+        DispatchResult, Interface, LocalDispatchResult, LocalInterface, SignalContext,
+    },
+    Connection, ObjectServer,
+};
+
+/// A wrapper that makes a value usable where `Send` and `Sync` are required, by only allowing
+/// access to it from the thread it was created on.
+///
+/// This is what allows serving interfaces that are neither `Send` nor `Sync` (e.g because they hold
+/// an `Rc` or some GUI state): [`Interface`] is implemented for `Local<T>` if `T` implements
+/// [`LocalInterface`] (see the `local` attribute of [`dbus_interface`]). Serve the wrapped value on
+/// a connection that is driven from the same thread (see [`crate::connection::Builder::local`]).
+///
+/// It can also wrap a [`Future`], to run it on an executor that requires `Send` futures.
+///
+/// # Panics
+///
+/// Accessing the wrapped value or polling the wrapped future from any other thread panics. If
+/// dropped from any other thread, the wrapped value is leaked instead of being dropped.
+///
+/// The exception is the [`Interface`] implementation: method calls and property accesses
+/// dispatched from any other thread (e.g if the executor of the connection is ticked from another
+/// thread) fail with an [`fdo::Error::Failed`] error instead.
+///
+/// [`dbus_interface`]: macro@crate::dbus_interface
+pub struct Local<T> {
+    value: ManuallyDrop<T>,
+    thread: ThreadId,
+}
+
+// SAFETY: The wrapped value is only ever accessed (including being dropped) from the thread that
+// created it.
+unsafe impl<T> Send for Local<T> {}
+unsafe impl<T> Sync for Local<T> {}
+
+assert_impl_all!(Local<std::rc::Rc<()>>: Send, Sync);
+
+impl<T> Local<T> {
+    /// Wrap `value`, making it only accessible from the current thread.
+    pub fn new(value: T) -> Self {
+        Self {
+            value: ManuallyDrop::new(value),
+            thread: thread::current().id(),
+        }
+    }
+
+    /// Whether the wrapped value can be accessed from the current thread.
+    pub fn is_valid(&self) -> bool {
+        thread::current().id() == self.thread
+    }
+
+    /// A reference to the wrapped value.
+    ///
+    /// # Panics
+    ///
+    /// If called from any thread other than the one that created `self`.
+    pub fn get(&self) -> &T {
+        self.assert_valid();
+
+        &self.value
+    }
+
+    /// A mutable reference to the wrapped value.
+    ///
+    /// # Panics
+    ///
+    /// If called from any thread other than the one that created `self`.
+    pub fn get_mut(&mut self) -> &mut T {
+        self.assert_valid();
+
+        &mut self.value
+    }
+
+    /// Unwrap the value.
+    ///
+    /// # Panics
+    ///
+    /// If called from any thread other than the one that created `self`.
+    pub fn into_inner(self) -> T {
+        self.assert_valid();
+
+        let mut this = ManuallyDrop::new(self);
+        // SAFETY: `this` is never used (or dropped) again.
+        unsafe { ManuallyDrop::take(&mut this.value) }
+    }
+
+    fn assert_valid(&self) {
+        if !self.is_valid() {
+            panic!("`zbus::object_server::Local` value accessed from a different thread");
+        }
+    }
+}
+
+impl<T> Deref for Local<T> {
+    type Target = T;
+
+    fn deref(&self) -> &T {
+        self.get()
+    }
+}
+
+impl<T> DerefMut for Local<T> {
+    fn deref_mut(&mut self) -> &mut T {
+        self.get_mut()
+    }
+}
+
+impl<F: Future> Future for Local<F> {
+    type Output = F::Output;
+
+    fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        self.assert_valid();
+
+        // SAFETY: The wrapped future is never moved out of `self`, unless `self` itself is moved
+        // (through `into_inner`), which isn't possible once pinned.
+        unsafe { self.map_unchecked_mut(|this| &mut *this.value) }.poll(cx)
+    }
+}
+
+impl<T> Drop for Local<T> {
+    fn drop(&mut self) {
+        if self.is_valid() {
+            // SAFETY: `self.value` is never used again.
+            unsafe { ManuallyDrop::drop(&mut self.value) }
+        } else {
+            warn!("`zbus::object_server::Local` value dropped from a different thread, leaking it");
+        }
+    }
+}
+
+impl<T> fmt::Debug for Local<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Local")
+            .field("thread", &self.thread)
+            .finish_non_exhaustive()
+    }
+}
+
+#[async_trait]
+impl<T: LocalInterface> Interface for Local<T> {
+    fn name() -> InterfaceName<'static> {
+        T::name()
+    }
+
+    async fn get(&self, property_name: &str) -> Option<fdo::Result<OwnedValue>> {
+        if !self.is_valid() {
+            return Some(Err(wrong_thread()));
+        }
+        let future = Local::new(LocalInterface::get(Local::get(self), property_name));
+
+        future.await
+    }
+
+    async fn get_all(&self) -> fdo::Result<HashMap<String, OwnedValue>> {
+        if !self.is_valid() {
+            return Err(wrong_thread());
+        }
+        let future = Local::new(LocalInterface::get_all(Local::get(self)));
+
+        future.await
+    }
+
+    fn set<'call>(
+        &'call self,
+        property_name: &'call str,
+        value: &'call Value<'_>,
+        ctxt: &'call SignalContext<'_>,
+    ) -> DispatchResult<'call> {
+        if !self.is_valid() {
+            return DispatchResult::Async(Box::pin(async { Err(wrong_thread().into()) }));
+        }
+        LocalInterface::set(Local::get(self), property_name, value, ctxt).into()
+    }
+
+    async fn set_mut(
+        &mut self,
+        property_name: &str,
+        value: &Value<'_>,
+        ctxt: &SignalContext<'_>,
+    ) -> Option<fdo::Result<()>> {
+        if !self.is_valid() {
+            return Some(Err(wrong_thread()));
+        }
+        let future = Local::new(LocalInterface::set_mut(
+            Local::get_mut(self),
+            property_name,
+            value,
+            ctxt,
+        ));
+
+        future.await
+    }
+
+    fn call<'call>(
+        &'call self,
+        server: &'call ObjectServer,
+        connection: &'call Connection,
+        msg: &'call Message,
+        name: MemberName<'call>,
+    ) -> DispatchResult<'call> {
+        if !self.is_valid() {
+            return DispatchResult::new_async(connection, msg, async {
+                Err::<(), _>(wrong_thread())
+            });
+        }
+        LocalInterface::call(Local::get(self), server, connection, msg, name).into()
+    }
+
+    fn call_mut<'call>(
+        &'call mut self,
+        server: &'call ObjectServer,
+        connection: &'call Connection,
+        msg: &'call Message,
+        name: MemberName<'call>,
+    ) -> DispatchResult<'call> {
+        if !self.is_valid() {
+            return DispatchResult::new_async(connection, msg, async {
+                Err::<(), _>(wrong_thread())
+            });
+        }
+        LocalInterface::call_mut(Local::get_mut(self), server, connection, msg, name).into()
+    }
+
+    fn introspect_to_writer(&self, writer: &mut dyn Write, level: usize) {
+        if !self.is_valid() {
+            warn!("`zbus::object_server::Local` interface introspected from a different thread");
+
+            return;
+        }
+        LocalInterface::introspect_to_writer(Local::get(self), writer, level)
+    }
+}
+
+// The error returned to callers of an interface dispatched from the wrong thread, e.g if the
+// executor of a `local` connection is ticked from another thread.
+fn wrong_thread() -> fdo::Error {
+    fdo::Error::Failed(
+        "`zbus::object_server::Local` interface dispatched from a different thread".to_string(),
+    )
+}
+
+impl<'a> From<LocalDispatchResult<'a>> for DispatchResult<'a> {
+    fn from(result: LocalDispatchResult<'a>) -> Self {
+        match result {
+            LocalDispatchResult::NotFound => DispatchResult::NotFound,
+            LocalDispatchResult::RequiresMut => DispatchResult::RequiresMut,
+            LocalDispatchResult::Async(future) => {
+                DispatchResult::Async(Box::pin(Local::new(future)))
+            }
+        }
+    }
+}
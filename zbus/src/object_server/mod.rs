@@ -13,7 +13,7 @@ use tracing::{debug, instrument, trace};
 
 use static_assertions::assert_impl_all;
 use zbus_names::InterfaceName;
-use zvariant::{ObjectPath, OwnedObjectPath, OwnedValue, Signature, Type, Value};
+use zvariant::{ObjectPath, OwnedObjectPath, OwnedValue, ParsedSignature, Type, Value};
 
 use crate::{
     async_lock::{RwLock, RwLockReadGuard, RwLockWriteGuard},
@@ -25,11 +25,14 @@ use crate::{
 };
 
 mod interface;
-pub use interface::{DispatchResult, Interface};
+pub use interface::{DispatchResult, Interface, LocalDispatchResult, LocalInterface};
 
 mod signal_context;
 pub use signal_context::SignalContext;
 
+mod local;
+pub use local::Local;
+
 /// Opaque structure that derefs to an `Interface` type.
 pub struct InterfaceDeref<'d, I> {
     iface: RwLockReadGuard<'d, dyn Interface>,
@@ -838,9 +841,7 @@ impl<R> Type for ResponseDispatchNotifier<R>
 where
     R: Type,
 {
-    fn signature() -> Signature<'static> {
-        R::signature()
-    }
+    const SIGNATURE: &'static ParsedSignature = R::SIGNATURE;
 }
 
 impl<T> Drop for ResponseDispatchNotifier<T> {
@@ -0,0 +1,344 @@
+//! Polkit authorization.
+//!
+//! This module provides a proxy for the `org.freedesktop.PolicyKit1.Authority` interface and
+//! convenient API to check if the sender of a method call is authorized to perform an action.
+//! Services typically don't need to use it directly, but rather make use of the `polkit_action`
+//! method attribute of [`crate::dbus_interface`].
+//!
+//! This module is only available when the `polkit` feature is enabled.
+
+use enumflags2::{bitflags, BitFlags};
+use serde::{Deserialize, Serialize};
+use static_assertions::assert_impl_all;
+use std::collections::HashMap;
+use zbus_names::UniqueName;
+use zvariant::{OwnedValue, Type, Value};
+
+use crate::{
+    dbus_proxy, fdo,
+    message::{Flags, Header},
+    Connection, Error, Result,
+};
+
+#[rustfmt::skip]
+macro_rules! gen_authority_proxy {
+    ($gen_async:literal, $gen_blocking:literal) => {
+        /// Proxy for the `org.freedesktop.PolicyKit1.Authority` interface.
+        #[dbus_proxy(
+            interface = "org.freedesktop.PolicyKit1.Authority",
+            default_service = "org.freedesktop.PolicyKit1",
+            default_path = "/org/freedesktop/PolicyKit1/Authority",
+            gen_async = $gen_async,
+            gen_blocking = $gen_blocking,
+        )]
+        trait Authority {
+            /// Checks if `subject` is authorized to perform the action with identifier
+            /// `action_id`.
+            ///
+            /// If [`CheckAuthorizationFlags::AllowUserInteraction`] is passed in `flags`, the
+            /// authority may interact with the user to obtain authorization, in which case the call
+            /// might take a long time to complete. `cancellation_id` can be used to cancel the
+            /// check through `CancelCheckAuthorization`. Pass an empty string if you don't need it.
+            fn check_authorization(
+                &self,
+                subject: &Subject,
+                action_id: &str,
+                details: &HashMap<&str, &str>,
+                flags: BitFlags<CheckAuthorizationFlags>,
+                cancellation_id: &str,
+            ) -> Result<AuthorizationResult>;
+
+            /// Cancels an authorization check started with `cancellation_id`.
+            fn cancel_check_authorization(&self, cancellation_id: &str) -> Result<()>;
+
+            /// The name of the currently used authority backend.
+            #[dbus_proxy(property)]
+            fn backend_name(&self) -> Result<String>;
+
+            /// The version of the currently used authority backend.
+            #[dbus_proxy(property)]
+            fn backend_version(&self) -> Result<String>;
+        }
+    };
+}
+
+gen_authority_proxy!(true, false);
+assert_impl_all!(AuthorityProxy<'_>: Send, Sync, Unpin);
+
+/// Flags for the [`AuthorityProxy::check_authorization`] method.
+#[bitflags]
+#[repr(u32)]
+#[derive(Type, Debug, PartialEq, Eq, Copy, Clone, Serialize, Deserialize)]
+pub enum CheckAuthorizationFlags {
+    /// If the subject can obtain the authorization through authentication, and an authentication
+    /// agent is available, then attempt to do so. Note, this means that the method used for
+    /// checking authorization is likely to block for a long time.
+    AllowUserInteraction = 0x01,
+}
+
+assert_impl_all!(CheckAuthorizationFlags: Send, Sync, Unpin);
+
+/// The entity an authorization check is done for.
+#[derive(Debug, PartialEq, Serialize, Deserialize, Type)]
+pub struct Subject {
+    kind: String,
+    details: HashMap<String, OwnedValue>,
+}
+
+assert_impl_all!(Subject: Send, Sync, Unpin);
+
+impl Subject {
+    /// Create a subject of the given `kind`, with the given `details`.
+    ///
+    /// See the polkit documentation for the kinds of subjects and the details they require.
+    pub fn new(kind: &str, details: HashMap<String, OwnedValue>) -> Self {
+        Self {
+            kind: kind.to_string(),
+            details,
+        }
+    }
+
+    /// Create a subject for the peer owning the unique bus name `name`.
+    pub fn system_bus_name(name: &UniqueName<'_>) -> Self {
+        let mut details = HashMap::new();
+        details.insert(
+            "name".to_string(),
+            Value::from(name.as_str())
+                .try_into()
+                .expect("Infallible conversion failed"),
+        );
+
+        Self::new("system-bus-name", details)
+    }
+
+    /// The kind of the subject.
+    pub fn kind(&self) -> &str {
+        &self.kind
+    }
+
+    /// The details of the subject.
+    pub fn details(&self) -> &HashMap<String, OwnedValue> {
+        &self.details
+    }
+}
+
+impl<'h> TryFrom<&Header<'h>> for Subject {
+    type Error = Error;
+
+    /// Create a subject for the sender of the message.
+    fn try_from(hdr: &Header<'h>) -> Result<Self> {
+        let sender = hdr.sender().ok_or(Error::MissingField)?;
+
+        Ok(Self::system_bus_name(sender))
+    }
+}
+
+/// The result of an authorization check.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, Type)]
+pub struct AuthorizationResult {
+    is_authorized: bool,
+    is_challenge: bool,
+    details: HashMap<String, String>,
+}
+
+assert_impl_all!(AuthorizationResult: Send, Sync, Unpin);
+
+impl AuthorizationResult {
+    /// Create a new authorization result.
+    pub fn new(is_authorized: bool, is_challenge: bool, details: HashMap<String, String>) -> Self {
+        Self {
+            is_authorized,
+            is_challenge,
+            details,
+        }
+    }
+
+    /// Whether the subject is authorized for the action.
+    pub fn is_authorized(&self) -> bool {
+        self.is_authorized
+    }
+
+    /// Whether the subject could be authorized if it authenticated itself.
+    pub fn is_challenge(&self) -> bool {
+        self.is_challenge
+    }
+
+    /// Details about the result.
+    pub fn details(&self) -> &HashMap<String, String> {
+        &self.details
+    }
+}
+
+/// Check if the sender of a method call is authorized to perform the action `action_id`.
+///
+/// User interaction is allowed only if the method call has the
+/// [`Flags::AllowInteractiveAuth`] flag set.
+///
+/// # Errors
+///
+/// * [`fdo::Error::InteractiveAuthorizationRequired`] if the sender could be authorized through
+///   user interaction but the method call didn't allow it.
+/// * [`fdo::Error::AccessDenied`] if the sender is not authorized, or the authorization could not
+///   be checked.
+pub async fn check_authorization(
+    conn: &Connection,
+    hdr: &Header<'_>,
+    action_id: &str,
+) -> fdo::Result<()> {
+    let subject = Subject::try_from(hdr)
+        .map_err(|_| fdo::Error::AccessDenied("Message has no sender".to_string()))?;
+    let mut flags = BitFlags::empty();
+    if hdr.primary().flags().contains(Flags::AllowInteractiveAuth) {
+        flags |= CheckAuthorizationFlags::AllowUserInteraction;
+    }
+
+    let result = async {
+        AuthorityProxy::new(conn)
+            .await?
+            .check_authorization(&subject, action_id, &HashMap::new(), flags, "")
+            .await
+    }
+    .await
+    .map_err(|e| fdo::Error::AccessDenied(format!("Failed to check authorization: {e}")))?;
+
+    if result.is_authorized() {
+        Ok(())
+    } else if result.is_challenge() {
+        Err(fdo::Error::InteractiveAuthorizationRequired(format!(
+            "Interactive authorization required for `{action_id}`"
+        )))
+    } else {
+        Err(fdo::Error::AccessDenied(format!(
+            "Not authorized for `{action_id}`"
+        )))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+
+    use enumflags2::BitFlags;
+    use ntest::timeout;
+    use test_log::test;
+
+    use super::{AuthorizationResult, CheckAuthorizationFlags, Subject};
+    use zvariant::{OwnedValue, Str};
+
+    use crate::{dbus_interface, fdo, proxy::MethodFlags, Proxy};
+
+    // A stand-in for the polkit authority: authorizes `org.zbus.Allowed` for everyone and
+    // `org.zbus.Interactive` only if user interaction is allowed.
+    struct Authority;
+
+    #[dbus_interface(name = "org.freedesktop.PolicyKit1.Authority")]
+    impl Authority {
+        fn check_authorization(
+            &self,
+            subject: Subject,
+            action_id: &str,
+            _details: HashMap<&str, &str>,
+            flags: BitFlags<CheckAuthorizationFlags>,
+            _cancellation_id: &str,
+        ) -> AuthorizationResult {
+            assert_eq!(subject.kind(), "system-bus-name");
+            assert_eq!(
+                subject.details().get("name"),
+                Some(&OwnedValue::from(Str::from_static(":1.42")))
+            );
+
+            let interactive = flags.contains(CheckAuthorizationFlags::AllowUserInteraction);
+            let (is_authorized, is_challenge) = match action_id {
+                "org.zbus.Allowed" => (true, false),
+                "org.zbus.Interactive" => (interactive, !interactive),
+                _ => (false, false),
+            };
+
+            AuthorizationResult::new(is_authorized, is_challenge, HashMap::new())
+        }
+    }
+
+    struct Privileged;
+
+    #[dbus_interface(name = "org.zbus.Privileged")]
+    impl Privileged {
+        #[dbus_interface(polkit_action = "org.zbus.Allowed")]
+        fn allowed(&self) -> u32 {
+            1
+        }
+
+        #[dbus_interface(polkit_action = "org.zbus.Interactive")]
+        fn interactive(&self) -> u32 {
+            2
+        }
+
+        #[dbus_interface(polkit_action = "org.zbus.Denied")]
+        fn denied(&self) -> u32 {
+            3
+        }
+    }
+
+    #[cfg(unix)]
+    #[test]
+    #[timeout(15000)]
+    fn polkit_action() {
+        crate::utils::block_on(test_polkit_action());
+    }
+
+    // The service and the client talk over a peer-to-peer connection, with the client also
+    // standing in for the polkit authority, so no name is claimed on a shared bus.
+    #[cfg(unix)]
+    async fn test_polkit_action() {
+        #[cfg(not(feature = "tokio"))]
+        use std::os::unix::net::UnixStream;
+        #[cfg(feature = "tokio")]
+        use tokio::net::UnixStream;
+
+        let guid = crate::Guid::generate();
+        let (p0, p1) = UnixStream::pair().unwrap();
+        let (_service, client) = futures_util::try_join!(
+            crate::ConnectionBuilder::unix_stream(p0)
+                .server(&guid)
+                .p2p()
+                .serve_at("/org/zbus/Privileged", Privileged)
+                .unwrap()
+                .build(),
+            crate::ConnectionBuilder::unix_stream(p1)
+                .p2p()
+                .serve_at("/org/freedesktop/PolicyKit1/Authority", Authority)
+                .unwrap()
+                .build(),
+        )
+        .unwrap();
+        // Polkit identifies the caller by its unique name.
+        client.set_unique_name(":1.42").unwrap();
+        let proxy = Proxy::new(
+            &client,
+            "org.zbus.Privileged",
+            "/org/zbus/Privileged",
+            "org.zbus.Privileged",
+        )
+        .await
+        .unwrap();
+
+        let reply: u32 = proxy.call("Allowed", &()).await.unwrap();
+        assert_eq!(reply, 1);
+
+        let err = proxy
+            .call::<_, _, u32>("Interactive", &())
+            .await
+            .unwrap_err();
+        assert!(matches!(
+            fdo::Error::from(err),
+            fdo::Error::InteractiveAuthorizationRequired(_)
+        ));
+        let reply: Option<u32> = proxy
+            .call_with_flags("Interactive", MethodFlags::AllowInteractiveAuth.into(), &())
+            .await
+            .unwrap();
+        assert_eq!(reply, Some(2));
+
+        let err = proxy.call::<_, _, u32>("Denied", &()).await.unwrap_err();
+        assert!(matches!(fdo::Error::from(err), fdo::Error::AccessDenied(_)));
+    }
+}
@@ -1461,12 +1461,6 @@ mod tests {
             let server_fut = async move {
                 use std::time::Duration;
 
-                #[cfg(not(feature = "tokio"))]
-                use async_io::Timer;
-
-                #[cfg(feature = "tokio")]
-                use tokio::time::sleep;
-
                 let iface_ref = conn
                     .object_server()
                     .interface::<_, TestIface>("/org/zbus/Test")
@@ -1481,11 +1475,7 @@ mod tests {
                             .unwrap();
                     }
 
-                    #[cfg(not(feature = "tokio"))]
-                    Timer::after(Duration::from_millis(5)).await;
-
-                    #[cfg(feature = "tokio")]
-                    sleep(Duration::from_millis(5)).await;
+                    conn.executor().sleep(Duration::from_millis(5)).await;
                 }
             };
             server_conn.executor().spawn(server_fut, "server_task")
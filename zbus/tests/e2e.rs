@@ -123,6 +123,8 @@ trait MyIface {
 
     #[dbus_proxy(allow_interactive_auth)]
     fn test_interactive_auth(&self) -> zbus::Result<()>;
+
+    fn test_credentials(&self) -> zbus::Result<u32>;
 }
 
 #[derive(Debug, Clone)]
@@ -426,6 +428,17 @@ impl MyIfaceImpl {
             .contains(zbus::message::Flags::AllowInteractiveAuth));
     }
 
+    #[instrument]
+    fn test_credentials(
+        &self,
+        #[zbus(credentials)] credentials: zbus::fdo::ConnectionCredentials,
+    ) -> zbus::fdo::Result<u32> {
+        debug!("`TestCredentials` called");
+        credentials
+            .unix_user_id()
+            .ok_or_else(|| zbus::fdo::Error::Failed("no Unix user ID".to_string()))
+    }
+
     #[dbus_interface(signal)]
     async fn alert_count(ctxt: &SignalContext<'_>, val: u32) -> zbus::Result<()>;
 }
@@ -556,6 +569,14 @@ async fn my_iface_test(conn: Connection, event: Event) -> zbus::Result<u32> {
     proxy.test_no_reply().await?;
     proxy.test_no_autostart().await?;
     proxy.test_interactive_auth().await?;
+    #[cfg(unix)]
+    for _ in 0..2 {
+        // The second call gets the credentials from the cache.
+        assert_eq!(
+            proxy.test_credentials().await?,
+            nix::unistd::Uid::current().as_raw()
+        );
+    }
 
     let err = proxy.fail_property().await;
     assert_eq!(
@@ -905,3 +926,201 @@ async fn iface_and_proxy_(p2p: bool) {
     );
     debug!("Bus confirmed that all names were definitely released.");
 }
+
+struct LocalCounter {
+    // Deliberately not `Send` or `Sync`.
+    count: std::rc::Rc<std::cell::Cell<u32>>,
+}
+
+#[dbus_interface(name = "org.freedesktop.zbus.LocalCounter", local)]
+impl LocalCounter {
+    async fn increment(&self) -> u32 {
+        let count = self.count.clone();
+        // Hold the `Rc` across an await point.
+        futures_util::future::ready(()).await;
+        count.set(count.get() + 1);
+
+        count.get()
+    }
+
+    #[dbus_interface(property)]
+    fn count(&self) -> u32 {
+        self.count.get()
+    }
+
+    #[dbus_interface(property)]
+    fn set_count(&mut self, count: u32) {
+        self.count.set(count);
+    }
+}
+
+#[dbus_proxy(
+    interface = "org.freedesktop.zbus.LocalCounter",
+    default_path = "/org/freedesktop/zbus/LocalCounter",
+    gen_blocking = false
+)]
+trait LocalCounter {
+    fn increment(&self) -> zbus::Result<u32>;
+
+    #[dbus_proxy(property)]
+    fn count(&self) -> zbus::Result<u32>;
+
+    #[dbus_proxy(property)]
+    fn set_count(&self, count: u32) -> zbus::Result<()>;
+}
+
+#[test]
+#[timeout(15000)]
+fn local_iface() {
+    #[cfg(not(feature = "tokio"))]
+    block_on(local_iface_()).unwrap();
+    #[cfg(feature = "tokio")]
+    block_on(tokio::task::LocalSet::new().run_until(local_iface_())).unwrap();
+}
+
+async fn local_iface_() -> zbus::Result<()> {
+    let count = std::rc::Rc::new(std::cell::Cell::new(0));
+    let service = connection::Builder::session()?
+        .local()
+        .serve_at(
+            "/org/freedesktop/zbus/LocalCounter",
+            zbus::object_server::Local::new(LocalCounter {
+                count: count.clone(),
+            }),
+        )?
+        .build()
+        .await?;
+    let client = Connection::session().await?;
+
+    let test = async {
+        let proxy = LocalCounterProxy::builder(&client)
+            .destination(service.unique_name().unwrap())?
+            .cache_properties(CacheProperties::No)
+            .build()
+            .await?;
+        assert_eq!(proxy.increment().await?, 1);
+        assert_eq!(proxy.increment().await?, 2);
+        proxy.set_count(5).await?;
+        assert_eq!(proxy.count().await?, 5);
+        assert_eq!(count.get(), 5);
+
+        Ok(())
+    };
+
+    #[cfg(not(feature = "tokio"))]
+    {
+        // The service connection is only driven by this thread.
+        let ticker = async {
+            loop {
+                service.executor().tick().await;
+            }
+        };
+        futures_util::pin_mut!(test, ticker);
+        match futures_util::future::select(test, ticker).await {
+            futures_util::future::Either::Left((res, _)) => res,
+            futures_util::future::Either::Right(_) => unreachable!(),
+        }
+    }
+
+    #[cfg(feature = "tokio")]
+    test.await
+}
+
+#[cfg(all(feature = "glib", not(feature = "tokio")))]
+#[test]
+#[timeout(15000)]
+fn local_iface_glib() {
+    let main_context = glib::MainContext::new();
+    let ctx = main_context.clone();
+    main_context
+        .block_on(async move {
+            // No need for `local()`: the `Local` is created on, and the calls dispatched to, the
+            // thread iterating the main context.
+            let count = std::rc::Rc::new(std::cell::Cell::new(0));
+            let service = connection::Builder::session()?
+                .glib_main_context(ctx)
+                .serve_at(
+                    "/org/freedesktop/zbus/LocalCounter",
+                    zbus::object_server::Local::new(LocalCounter {
+                        count: count.clone(),
+                    }),
+                )?
+                .build()
+                .await?;
+
+            let client = Connection::session().await?;
+            let proxy = LocalCounterProxy::builder(&client)
+                .destination(service.unique_name().unwrap())?
+                .cache_properties(CacheProperties::No)
+                .build()
+                .await?;
+            assert_eq!(proxy.increment().await?, 1);
+            proxy.set_count(5).await?;
+            assert_eq!(proxy.count().await?, 5);
+            assert_eq!(count.get(), 5);
+
+            Ok::<(), zbus::Error>(())
+        })
+        .unwrap();
+}
+
+#[cfg(not(feature = "tokio"))]
+#[test]
+#[timeout(15000)]
+fn local_iface_wrong_thread() {
+    block_on(local_iface_wrong_thread_()).unwrap();
+}
+
+#[cfg(not(feature = "tokio"))]
+async fn local_iface_wrong_thread_() -> zbus::Result<()> {
+    let count = std::rc::Rc::new(std::cell::Cell::new(0));
+    let service = connection::Builder::session()?
+        .local()
+        .serve_at(
+            "/org/freedesktop/zbus/LocalCounter",
+            zbus::object_server::Local::new(LocalCounter {
+                count: count.clone(),
+            }),
+        )?
+        .build()
+        .await?;
+    let client = Connection::session().await?;
+
+    // Mistakenly drive the service connection from another thread.
+    let stop = Event::new();
+    let stopped = stop.listen();
+    let ticker = std::thread::spawn({
+        let service = service.clone();
+        move || {
+            block_on(async {
+                let ticker = async {
+                    loop {
+                        service.executor().tick().await;
+                    }
+                };
+                futures_util::pin_mut!(ticker);
+                futures_util::future::select(ticker, stopped).await;
+            })
+        }
+    });
+
+    let proxy = LocalCounterProxy::builder(&client)
+        .destination(service.unique_name().unwrap())?
+        .cache_properties(CacheProperties::No)
+        .build()
+        .await?;
+    // The calls fail instead of the other thread panicking.
+    let err = zbus::fdo::Error::from(proxy.increment().await.unwrap_err());
+    assert!(matches!(err, zbus::fdo::Error::Failed(_)), "{err:?}");
+    let err = proxy.count().await.unwrap_err();
+    assert!(
+        matches!(&err, Error::FDO(e) if matches!(**e, zbus::fdo::Error::Failed(_))),
+        "{err:?}"
+    );
+    assert_eq!(count.get(), 0);
+
+    stop.notify(1);
+    ticker.join().unwrap();
+
+    Ok(())
+}
@@ -0,0 +1,232 @@
+//! Parsing of D-Bus values from command-line arguments.
+//!
+//! The syntax is the same as the one of `busctl`: the values are given as a sequence of arguments,
+//! following a signature. Basic types take a single argument, arrays and dictionaries take the
+//! number of elements followed by the elements, structures take their fields in order and variants
+//! take the signature of their contents followed by the contents.
+
+use std::{error::Error, fmt::Display, result::Result, str::FromStr};
+
+use zvariant::{Array, Dict, ObjectPath, Signature, StructureBuilder, Value};
+
+/// Parse `args` as a sequence of values of the types in `signature`.
+///
+/// All the arguments must be consumed.
+pub fn parse<'a, I>(signature: &str, args: I) -> Result<Vec<Value<'static>>, Box<dyn Error>>
+where
+    I: IntoIterator<Item = &'a str>,
+{
+    if !signature.is_empty() {
+        // Validates the signature, so the parsing below doesn't need to.
+        Signature::try_from(signature)
+            .map_err(|e| format!("Invalid signature `{signature}`: {e}"))?;
+    }
+
+    let mut args = args.into_iter();
+    let mut values = vec![];
+    let mut rest = signature;
+    while !rest.is_empty() {
+        let (ty, next) = split_complete_type(rest);
+        values.push(parse_value(ty, &mut args)?);
+        rest = next;
+    }
+    if let Some(arg) = args.next() {
+        return Err(format!("Too many arguments for signature `{signature}`, at `{arg}`").into());
+    }
+
+    Ok(values)
+}
+
+fn parse_value<'a, I>(ty: &str, args: &mut I) -> Result<Value<'static>, Box<dyn Error>>
+where
+    I: Iterator<Item = &'a str>,
+{
+    let value = match ty.as_bytes()[0] {
+        b'y' => Value::U8(parse_arg(ty, args)?),
+        b'b' => {
+            let arg = next_arg(ty, args)?;
+            let b = match arg {
+                "true" | "yes" | "on" | "1" => true,
+                "false" | "no" | "off" | "0" => false,
+                _ => return Err(format!("Invalid boolean `{arg}`").into()),
+            };
+
+            Value::Bool(b)
+        }
+        b'n' => Value::I16(parse_arg(ty, args)?),
+        b'q' => Value::U16(parse_arg(ty, args)?),
+        b'i' => Value::I32(parse_arg(ty, args)?),
+        b'u' => Value::U32(parse_arg(ty, args)?),
+        b'x' => Value::I64(parse_arg(ty, args)?),
+        b't' => Value::U64(parse_arg(ty, args)?),
+        b'd' => Value::F64(parse_arg(ty, args)?),
+        b's' => Value::from(next_arg(ty, args)?.to_string()),
+        b'o' => Value::ObjectPath(ObjectPath::try_from(next_arg(ty, args)?.to_string())?),
+        b'g' => Value::Signature(Signature::try_from(next_arg(ty, args)?.to_string())?),
+        b'v' => {
+            let signature = next_arg(ty, args)?;
+            let valid = !signature.is_empty()
+                && Signature::try_from(signature).is_ok()
+                && split_complete_type(signature).1.is_empty();
+            if !valid {
+                return Err(format!(
+                    "Invalid variant signature `{signature}`, expected a single complete type"
+                )
+                .into());
+            }
+
+            Value::Value(Box::new(parse_value(signature, args)?))
+        }
+        b'a' => {
+            let len: usize = parse_arg(ty, args)?;
+            let element = &ty[1..];
+            if let Some(entry) = element.strip_prefix('{') {
+                let (key, value) = split_complete_type(&entry[..entry.len() - 1]);
+                if key.len() != 1 || key == "v" {
+                    return Err(format!("Invalid dictionary key type `{key}`").into());
+                }
+                let mut dict = Dict::new(
+                    Signature::try_from(key.to_string())?,
+                    Signature::try_from(value.to_string())?,
+                );
+                for _ in 0..len {
+                    let k = parse_value(key, args)?;
+                    let v = parse_value(value, args)?;
+                    dict.append(k, v)?;
+                }
+
+                Value::Dict(dict)
+            } else {
+                let mut array = Array::new(Signature::try_from(element.to_string())?);
+                for _ in 0..len {
+                    array.append(parse_value(element, args)?)?;
+                }
+
+                Value::Array(array)
+            }
+        }
+        b'(' => {
+            let mut fields = &ty[1..ty.len() - 1];
+            let mut builder = StructureBuilder::new();
+            while !fields.is_empty() {
+                let (field, rest) = split_complete_type(fields);
+                builder = builder.append_field(parse_value(field, args)?);
+                fields = rest;
+            }
+
+            Value::Structure(builder.build())
+        }
+        b'h' => return Err("Passing file descriptors is not supported".into()),
+        _ => return Err(format!("Unsupported type `{ty}`").into()),
+    };
+
+    Ok(value)
+}
+
+fn next_arg<'a, I>(ty: &str, args: &mut I) -> Result<&'a str, Box<dyn Error>>
+where
+    I: Iterator<Item = &'a str>,
+{
+    args.next()
+        .ok_or_else(|| format!("Missing argument for type `{ty}`").into())
+}
+
+fn parse_arg<'a, T, I>(ty: &str, args: &mut I) -> Result<T, Box<dyn Error>>
+where
+    T: FromStr,
+    T::Err: Display,
+    I: Iterator<Item = &'a str>,
+{
+    let arg = next_arg(ty, args)?;
+
+    arg.parse()
+        .map_err(|e| format!("Invalid argument `{arg}` for type `{ty}`: {e}").into())
+}
+
+/// Split the first complete type off `signature`, which must be valid.
+fn split_complete_type(signature: &str) -> (&str, &str) {
+    let bytes = signature.as_bytes();
+    let mut depth = 0;
+    let mut len = 0;
+    loop {
+        match bytes[len] {
+            b'a' => (),
+            b'(' | b'{' => depth += 1,
+            b')' | b'}' => depth -= 1,
+            _ => (),
+        }
+        len += 1;
+        if depth == 0 && bytes[len - 1] != b'a' {
+            break;
+        }
+    }
+
+    signature.split_at(len)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+
+    use zvariant::{OwnedValue, StructureBuilder};
+
+    use super::parse;
+
+    #[test]
+    fn basic() {
+        let values = parse(
+            "ybnqiuxtdsog",
+            "7 yes -1 2 -3 4 -5 6 0.5 hello /org/zbus as".split(' '),
+        )
+        .unwrap();
+        let values = values
+            .into_iter()
+            .fold(StructureBuilder::new(), StructureBuilder::append_field)
+            .build();
+        assert_eq!(
+            values.to_string(),
+            "(byte 0x07, true, int16 -1, uint16 2, -3, uint32 4, int64 -5, uint64 6, 0.5, \
+             \"hello\", objectpath \"/org/zbus\", signature \"as\")"
+        );
+
+        assert!(parse("b", ["maybe"]).is_err());
+        assert!(parse("y", ["256"]).is_err());
+        assert!(parse("o", ["not/a/path"]).is_err());
+        assert!(parse("h", ["0"]).is_err());
+        assert!(parse("ss", ["missing"]).is_err());
+        assert!(parse("s", ["too", "many"]).is_err());
+        assert!(parse("a{vs}", ["0"]).is_err());
+    }
+
+    #[test]
+    fn containers() {
+        let values = parse(
+            "asa{sv}(ia(ss))",
+            "2 a b 2 one u 1 two as 1 deux 7 1 x y".split(' '),
+        )
+        .unwrap();
+        assert_eq!(values.len(), 3);
+
+        let strings: Vec<String> = values[0].try_clone().unwrap().try_into().unwrap();
+        assert_eq!(strings, ["a", "b"]);
+
+        let map: HashMap<String, OwnedValue> = values[1].try_clone().unwrap().try_into().unwrap();
+        assert_eq!(u32::try_from(&map["one"]).unwrap(), 1);
+        assert_eq!(
+            Vec::<String>::try_from(map["two"].try_clone().unwrap()).unwrap(),
+            ["deux"]
+        );
+
+        let s: (i32, Vec<(String, String)>) = values[2].try_clone().unwrap().try_into().unwrap();
+        assert_eq!(s, (7, vec![("x".to_string(), "y".to_string())]));
+
+        // Empty containers.
+        let values = parse("asa{ss}", ["0", "0"]).unwrap();
+        assert_eq!(values[0].to_string(), "@as []");
+        assert_eq!(values[1].to_string(), "@a{ss} {}");
+
+        // Variant signatures must be a single complete type.
+        assert!(parse("v", ["ss", "a", "b"]).is_err());
+        assert!(parse("v", ["", "a"]).is_err());
+    }
+}
@@ -0,0 +1,498 @@
+#![deny(rust_2018_idioms)]
+
+use std::{collections::BTreeSet, env::args, error::Error, process::exit};
+
+use zbus::{
+    blocking::{
+        connection,
+        fdo::{DBusProxy, IntrospectableProxy, PropertiesProxy},
+        Connection, MessageIterator,
+    },
+    message::Type,
+    names::{BusName, InterfaceName, MemberName},
+    MatchRule, Message,
+};
+use zbus_xml::{ArgDirection, Node};
+use zvariant::{ObjectPath, Optional, Structure, StructureBuilder, Value};
+
+mod args;
+
+type Result<T> = std::result::Result<T, Box<dyn Error>>;
+
+fn usage() {
+    eprintln!(
+        r#"Usage:
+  zbus [--system|--session|--address <address>] <command> [<args>...]
+
+Commands:
+  list
+      List the names on the bus, along with their owners.
+  status [<service>]
+      Show the credentials of a peer, or of the bus if none is given.
+  tree <service>
+      Show the object tree of a service.
+  introspect <service> <object_path> [<interface>]
+      Show the methods, properties and signals of an object.
+  call <service> <object_path> <interface> <method> [<signature> [<args>...]]
+      Call a method and show the reply.
+  get-property <service> <object_path> <interface> <property>...
+      Show the value of properties.
+  set-property <service> <object_path> <interface> <property> <signature> <args>...
+      Set the value of a property.
+  emit <object_path> <interface> <signal> [<signature> [<args>...]]
+      Emit a signal.
+  wait [<service>] <object_path> <interface> <signal>
+      Wait for a signal and show its arguments.
+
+Arguments are given as a signature followed by the values, in the same syntax as `busctl`. For
+example: `s hello`, `as 2 hello world`, `a{{sv}} 1 volume u 42` or `(su) hello 42`.
+
+Values are shown in the GVariant text format.
+"#
+    );
+}
+
+fn main() {
+    if let Err(e) = run() {
+        eprintln!("Error: {e}");
+        exit(1);
+    }
+}
+
+fn run() -> Result<()> {
+    let args: Vec<String> = args().skip(1).collect();
+    let mut args = &args[..];
+
+    let mut builder = None;
+    while let Some(option) = args.first().filter(|arg| arg.starts_with('-')) {
+        match option.as_str() {
+            "--system" => builder = Some(connection::Builder::system()?),
+            "--session" => builder = Some(connection::Builder::session()?),
+            "--address" => {
+                let address = args.get(1).ok_or("Missing param for address")?;
+                builder = Some(connection::Builder::address(&**address)?);
+                args = &args[1..];
+            }
+            "--help" | "-h" => {
+                usage();
+                return Ok(());
+            }
+            _ => return Err(format!("Unknown option `{option}`").into()),
+        }
+        args = &args[1..];
+    }
+
+    let (command, args) = match args.split_first() {
+        Some((command, args)) => (command.as_str(), args),
+        None => {
+            usage();
+            return Ok(());
+        }
+    };
+    let builder = match builder {
+        Some(builder) => builder,
+        None => connection::Builder::session()?,
+    };
+    let conn = builder.build()?;
+
+    match command {
+        "list" => list(&conn, args),
+        "status" => status(&conn, args),
+        "tree" => tree(&conn, args),
+        "introspect" => introspect(&conn, args),
+        "call" => call(&conn, args),
+        "get-property" => get_property(&conn, args),
+        "set-property" => set_property(&conn, args),
+        "emit" => emit(&conn, args),
+        "wait" => wait(&conn, args),
+        "help" => {
+            usage();
+            Ok(())
+        }
+        _ => Err(format!("Unknown command `{command}`, see `zbus --help`").into()),
+    }
+}
+
+fn list(conn: &Connection, args: &[String]) -> Result<()> {
+    check_args("list", args, 0, Some(0))?;
+
+    let proxy = DBusProxy::new(conn)?;
+    let running: BTreeSet<_> = proxy
+        .list_names()?
+        .into_iter()
+        .map(|name| name.to_string())
+        .collect();
+    let activatable: BTreeSet<_> = proxy
+        .list_activatable_names()?
+        .into_iter()
+        .map(|name| name.to_string())
+        .collect();
+
+    let mut rows = vec![row(["NAME", "PID", "UID", "CONNECTION"])];
+    for name in running.union(&activatable) {
+        if !running.contains(name) {
+            rows.push(row([name, "-", "-", "(activatable)"]));
+            continue;
+        }
+
+        let bus_name = BusName::try_from(name.as_str())?;
+        // The name could be released in the meantime.
+        let owner = proxy
+            .get_name_owner(bus_name.clone())
+            .map_or_else(|_| "-".to_string(), |owner| owner.to_string());
+        let (pid, uid) = proxy
+            .get_connection_credentials(bus_name)
+            .map(|creds| (creds.process_id(), creds.unix_user_id()))
+            .unwrap_or_default();
+        rows.push(vec![name.clone(), or_dash(pid), or_dash(uid), owner]);
+    }
+    print_table(&rows);
+
+    Ok(())
+}
+
+fn status(conn: &Connection, args: &[String]) -> Result<()> {
+    check_args("status", args, 0, Some(1))?;
+
+    let proxy = DBusProxy::new(conn)?;
+    let name = match args.first() {
+        Some(name) => BusName::try_from(name.as_str())?,
+        None => {
+            println!("BusID={}", proxy.get_id()?);
+
+            BusName::try_from("org.freedesktop.DBus")?
+        }
+    };
+    println!("Name={name}");
+    println!("UniqueName={}", proxy.get_name_owner(name.clone())?);
+
+    let creds = proxy.get_connection_credentials(name)?;
+    println!("PID={}", or_dash(creds.process_id()));
+    println!("UID={}", or_dash(creds.unix_user_id()));
+    if let Some(gids) = creds.unix_group_ids() {
+        let gids: Vec<_> = gids.iter().map(|gid| gid.to_string()).collect();
+        println!("GIDs={}", gids.join(" "));
+    }
+    if let Some(label) = creds.linux_security_label() {
+        let label = label.strip_suffix(b"\0").unwrap_or(label);
+        println!("SecurityLabel={}", String::from_utf8_lossy(label));
+    }
+
+    Ok(())
+}
+
+fn tree(conn: &Connection, args: &[String]) -> Result<()> {
+    check_args("tree", args, 1, Some(1))?;
+
+    let service = BusName::try_from(args[0].as_str())?;
+    println!("/");
+
+    print_tree(conn, &service, &ObjectPath::try_from("/")?, "")
+}
+
+fn print_tree(
+    conn: &Connection,
+    service: &BusName<'_>,
+    path: &ObjectPath<'_>,
+    prefix: &str,
+) -> Result<()> {
+    let node = introspect_node(conn, service, path)?;
+    let children: Vec<_> = node.nodes().iter().filter_map(|node| node.name()).collect();
+    for (i, child) in children.iter().enumerate() {
+        let last = i + 1 == children.len();
+        let child_path = match path.as_str() {
+            "/" => format!("/{child}"),
+            path => format!("{path}/{child}"),
+        };
+        println!("{prefix}{}{child_path}", if last { "└─" } else { "├─" });
+
+        let child_prefix = format!("{prefix}{}", if last { "  " } else { "│ " });
+        print_tree(
+            conn,
+            service,
+            &ObjectPath::try_from(child_path)?,
+            &child_prefix,
+        )?;
+    }
+
+    Ok(())
+}
+
+fn introspect(conn: &Connection, args: &[String]) -> Result<()> {
+    check_args("introspect", args, 2, Some(3))?;
+
+    let service = BusName::try_from(args[0].as_str())?;
+    let path = ObjectPath::try_from(args[1].as_str())?;
+    let node = introspect_node(conn, &service, &path)?;
+    let properties = PropertiesProxy::builder(conn)
+        .destination(service)?
+        .path(path)?
+        .build()?;
+
+    let mut rows = vec![row(["NAME", "TYPE", "SIGNATURE", "RESULT/VALUE", "FLAGS"])];
+    for iface in node.interfaces() {
+        if args
+            .get(2)
+            .map_or(false, |name| *name != iface.name().as_str())
+        {
+            continue;
+        }
+        rows.push(row([iface.name().as_str(), "interface", "-", "-", "-"]));
+
+        for method in iface.methods() {
+            let (inputs, outputs): (Vec<_>, Vec<_>) = method
+                .args()
+                .iter()
+                .partition(|arg| arg.direction() != Some(ArgDirection::Out));
+            let inputs: String = inputs.iter().map(|arg| arg.ty().to_string()).collect();
+            let outputs: String = outputs.iter().map(|arg| arg.ty().to_string()).collect();
+            let mut flags = vec![];
+            if has_annotation(method.annotations(), "org.freedesktop.DBus.Method.NoReply") {
+                flags.push("no-reply");
+            }
+            if has_annotation(method.annotations(), "org.freedesktop.DBus.Deprecated") {
+                flags.push("deprecated");
+            }
+            rows.push(vec![
+                format!(".{}", method.name()),
+                "method".to_string(),
+                or_dash(Some(inputs).filter(|s| !s.is_empty())),
+                or_dash(Some(outputs).filter(|s| !s.is_empty())),
+                or_dash(Some(flags.join(" ")).filter(|s| !s.is_empty())),
+            ]);
+        }
+
+        // Only readable properties are returned and the call can fail altogether, e.g if a
+        // property getter fails. The values are only informative here so that's not an error.
+        let mut values = properties
+            .get_all(Optional::from(Some(iface.name())))
+            .unwrap_or_default();
+        for property in iface.properties() {
+            let value = values
+                .remove(property.name().as_str())
+                .map(|value| Value::from(value).to_string());
+            let mut flags = vec![];
+            if property.access().write() {
+                flags.push("writable");
+            }
+            if has_annotation(property.annotations(), "org.freedesktop.DBus.Deprecated") {
+                flags.push("deprecated");
+            }
+            rows.push(vec![
+                format!(".{}", property.name()),
+                "property".to_string(),
+                property.ty().to_string(),
+                or_dash(value),
+                or_dash(Some(flags.join(" ")).filter(|s| !s.is_empty())),
+            ]);
+        }
+
+        for signal in iface.signals() {
+            let signature: String = signal
+                .args()
+                .iter()
+                .map(|arg| arg.ty().to_string())
+                .collect();
+            let flags = has_annotation(signal.annotations(), "org.freedesktop.DBus.Deprecated")
+                .then_some("deprecated");
+            rows.push(vec![
+                format!(".{}", signal.name()),
+                "signal".to_string(),
+                or_dash(Some(signature).filter(|s| !s.is_empty())),
+                "-".to_string(),
+                or_dash(flags),
+            ]);
+        }
+    }
+    print_table(&rows);
+
+    Ok(())
+}
+
+fn call(conn: &Connection, args: &[String]) -> Result<()> {
+    check_args("call", args, 4, None)?;
+
+    let service = BusName::try_from(args[0].as_str())?;
+    let path = ObjectPath::try_from(args[1].as_str())?;
+    let iface = InterfaceName::try_from(args[2].as_str())?;
+    let method = MemberName::try_from(args[3].as_str())?;
+    let reply = match parse_body(&args[4..])? {
+        Some(body) => conn.call_method(Some(service), path, Some(iface), method, &body)?,
+        None => conn.call_method(Some(service), path, Some(iface), method, &())?,
+    };
+
+    print_body(&reply)
+}
+
+fn get_property(conn: &Connection, args: &[String]) -> Result<()> {
+    check_args("get-property", args, 4, None)?;
+
+    let proxy = PropertiesProxy::builder(conn)
+        .destination(args[0].as_str())?
+        .path(args[1].as_str())?
+        .build()?;
+    let iface = InterfaceName::try_from(args[2].as_str())?;
+    for property in &args[3..] {
+        let value = proxy.get(iface.clone(), property)?;
+        println!("{}", Value::from(value));
+    }
+
+    Ok(())
+}
+
+fn set_property(conn: &Connection, args: &[String]) -> Result<()> {
+    check_args("set-property", args, 5, None)?;
+
+    let proxy = PropertiesProxy::builder(conn)
+        .destination(args[0].as_str())?
+        .path(args[1].as_str())?
+        .build()?;
+    let iface = InterfaceName::try_from(args[2].as_str())?;
+    let mut values = args::parse(&args[4], args[5..].iter().map(String::as_str))?;
+    if values.len() != 1 {
+        return Err(format!(
+            "Invalid property signature `{}`, expected a single complete type",
+            args[4]
+        )
+        .into());
+    }
+
+    proxy
+        .set(iface, &args[3], &values.remove(0))
+        .map_err(Into::into)
+}
+
+fn emit(conn: &Connection, args: &[String]) -> Result<()> {
+    check_args("emit", args, 3, None)?;
+
+    let path = ObjectPath::try_from(args[0].as_str())?;
+    let iface = InterfaceName::try_from(args[1].as_str())?;
+    let signal = MemberName::try_from(args[2].as_str())?;
+    match parse_body(&args[3..])? {
+        Some(body) => conn.emit_signal(None::<BusName<'_>>, path, iface, signal, &body)?,
+        None => conn.emit_signal(None::<BusName<'_>>, path, iface, signal, &())?,
+    }
+
+    Ok(())
+}
+
+fn wait(conn: &Connection, args: &[String]) -> Result<()> {
+    check_args("wait", args, 3, Some(4))?;
+
+    let (service, args) = match args.len() {
+        4 => (Some(args[0].as_str()), &args[1..]),
+        _ => (None, args),
+    };
+    let mut rule = MatchRule::builder().msg_type(Type::Signal);
+    if let Some(service) = service {
+        rule = rule.sender(service)?;
+    }
+    let rule = rule
+        .path(args[0].as_str())?
+        .interface(args[1].as_str())?
+        .member(args[2].as_str())?
+        .build();
+
+    let msg = MessageIterator::for_match_rule(rule, conn, None)?
+        .next()
+        .ok_or("Connection closed")??;
+
+    print_body(&msg)
+}
+
+fn check_args(command: &str, args: &[String], min: usize, max: Option<usize>) -> Result<()> {
+    if args.len() < min || max.map_or(false, |max| args.len() > max) {
+        return Err(format!("Wrong number of arguments for `{command}`, see `zbus --help`").into());
+    }
+
+    Ok(())
+}
+
+fn introspect_node(
+    conn: &Connection,
+    service: &BusName<'_>,
+    path: &ObjectPath<'_>,
+) -> Result<Node<'static>> {
+    let xml = IntrospectableProxy::builder(conn)
+        .destination(service)?
+        .path(path)?
+        .build()?
+        .introspect()?;
+
+    Node::from_reader(xml.as_bytes()).map_err(Into::into)
+}
+
+/// Parse a message body given as a signature followed by the values.
+fn parse_body(args: &[String]) -> Result<Option<Structure<'static>>> {
+    let (signature, args) = match args.split_first() {
+        Some((signature, args)) => (signature, args),
+        None => return Ok(None),
+    };
+    let values = args::parse(signature, args.iter().map(String::as_str))?;
+    if values.is_empty() {
+        return Ok(None);
+    }
+
+    Ok(Some(
+        values
+            .into_iter()
+            .fold(StructureBuilder::new(), StructureBuilder::append_field)
+            .build(),
+    ))
+}
+
+/// Print the body of `msg` as a tuple.
+fn print_body(msg: &Message) -> Result<()> {
+    let body = msg.body();
+    if body
+        .signature()
+        .map_or(true, |signature| signature.is_empty())
+    {
+        println!("()");
+
+        return Ok(());
+    }
+
+    let body: Structure<'_> = body.deserialize()?;
+    println!("{body}");
+
+    Ok(())
+}
+
+fn has_annotation(annotations: &[zbus_xml::Annotation], name: &str) -> bool {
+    annotations
+        .iter()
+        .any(|annotation| annotation.name() == name && annotation.value() == "true")
+}
+
+fn or_dash<T: ToString>(value: Option<T>) -> String {
+    value.map_or_else(|| "-".to_string(), |value| value.to_string())
+}
+
+fn row<const N: usize>(cells: [&str; N]) -> Vec<String> {
+    cells.iter().map(|cell| cell.to_string()).collect()
+}
+
+fn print_table(rows: &[Vec<String>]) {
+    let columns = rows.first().map_or(0, Vec::len);
+    let widths: Vec<_> = (0..columns)
+        .map(|i| {
+            rows.iter()
+                .map(|row| row[i].chars().count())
+                .max()
+                .unwrap_or_default()
+        })
+        .collect();
+
+    for row in rows {
+        let mut line = String::new();
+        for (i, cell) in row.iter().enumerate() {
+            if i + 1 == row.len() {
+                line.push_str(cell);
+            } else {
+                line.push_str(&format!("{cell:<width$} ", width = widths[i]));
+            }
+        }
+        println!("{line}");
+    }
+}
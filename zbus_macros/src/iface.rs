@@ -4,10 +4,10 @@ use std::collections::BTreeMap;
 use syn::{
     self, parse_quote, punctuated::Punctuated, spanned::Spanned, AngleBracketedGenericArguments,
     AttributeArgs, Error, FnArg, GenericArgument, ImplItem, ItemImpl, Lit::Str, Meta,
-    Meta::NameValue, MetaList, MetaNameValue, NestedMeta, PatType, PathArguments, ReturnType,
-    Signature, Token, Type, TypePath,
+    Meta::NameValue, MetaNameValue, NestedMeta, PatType, PathArguments, ReturnType, Signature,
+    Token, Type, TypePath,
 };
-use zvariant_utils::{case, def_attrs};
+use zvariant_utils::{case, def_attrs, macros::iter_meta_lists};
 
 use crate::utils::*;
 
@@ -17,14 +17,16 @@ def_attrs! {
 
     pub TraitAttributes("trait") {
         interface str,
-        name str
+        name str,
+        local none
     };
 
     pub MethodAttributes("method") {
         name str,
         signal none,
         property none,
-        out_args [str]
+        out_args [str],
+        polkit_action str
     };
 }
 
@@ -38,19 +40,46 @@ mod arg_attrs {
             object_server none,
             connection none,
             header none,
-            signal_context none
+            signal_context none,
+            credentials none,
+            name str
         };
     }
 }
 
 use arg_attrs::ArgAttributes;
 
+mod annotation_attrs {
+    use zvariant_utils::def_attrs;
+
+    def_attrs! {
+        crate annotation;
+
+        pub AnnotationAttributes("annotation") {
+            name str,
+            value str
+        };
+    }
+}
+
+use annotation_attrs::AnnotationAttributes;
+
+const DEPRECATED_ANNOTATION: &str = "org.freedesktop.DBus.Deprecated";
+
+/// A D-Bus annotation to be added to the introspection data.
+#[derive(Debug, Clone)]
+struct Annotation {
+    name: String,
+    value: String,
+}
+
 #[derive(Debug)]
 struct Property<'a> {
     read: bool,
     write: bool,
     ty: Option<&'a Type>,
     doc_comments: TokenStream,
+    annotations: Vec<Annotation>,
 }
 
 impl<'a> Property<'a> {
@@ -60,6 +89,7 @@ impl<'a> Property<'a> {
             write: false,
             ty: None,
             doc_comments: quote!(),
+            annotations: vec![],
         }
     }
 }
@@ -90,19 +120,39 @@ pub fn expand(args: AttributeArgs, mut input: ItemImpl) -> syn::Result<TokenStre
         _ => return Err(Error::new_spanned(&input.self_ty, "Invalid type")),
     };
 
-    let iface_name =
-        {
-            let TraitAttributes { name, interface } = TraitAttributes::parse_nested_metas(&args)?;
-
-            match (name, interface) {
-                (Some(name), None) | (None, Some(name)) => name,
-                (None, None) => format!("org.freedesktop.{ty}"),
-                (Some(_), Some(_)) => return Err(syn::Error::new(
-                    input.span(),
-                    "`name` and `interface` attributes should not be specified at the same time",
-                )),
-            }
-        };
+    let (args, mut iface_annotations) = split_annotations(&args)?;
+    if take_deprecated_attr(&mut input.attrs) {
+        add_deprecated_annotation(&mut iface_annotations);
+    }
+    let TraitAttributes {
+        name,
+        interface,
+        local,
+    } = TraitAttributes::parse_nested_metas(args)?;
+    let iface_name = match (name, interface) {
+        (Some(name), None) | (None, Some(name)) => name,
+        (None, None) => format!("org.freedesktop.{ty}"),
+        (Some(_), Some(_)) => {
+            return Err(syn::Error::new(
+                input.span(),
+                "`name` and `interface` attributes should not be specified at the same time",
+            ))
+        }
+    };
+    // `LocalInterface` is the `!Send` counterpart of `Interface`.
+    let (iface_trait, dispatch_result, async_trait_args) = if local {
+        (
+            quote!(#zbus::object_server::LocalInterface),
+            quote!(#zbus::object_server::LocalDispatchResult),
+            quote!((?Send)),
+        )
+    } else {
+        (
+            quote!(#zbus::object_server::Interface),
+            quote!(#zbus::object_server::DispatchResult),
+            quote!(),
+        )
+    };
 
     for method in &mut input.items {
         let method = match method {
@@ -119,7 +169,12 @@ pub fn expand(args: AttributeArgs, mut input: ItemImpl) -> syn::Result<TokenStre
             ..
         } = &mut method.sig;
 
-        let attrs = MethodAttributes::parse(&method.attrs)?;
+        let metas = iter_meta_lists(&method.attrs, "dbus_interface")?.collect::<Vec<_>>();
+        let (metas, mut annotations) = split_annotations(&metas)?;
+        let attrs = MethodAttributes::parse_nested_metas(metas)?;
+        if method.attrs.iter().any(|a| a.path.is_ident("deprecated")) {
+            add_deprecated_annotation(&mut annotations);
+        }
         method
             .attrs
             .retain(|attr| !attr.path.is_ident("dbus_interface"));
@@ -147,6 +202,12 @@ pub fn expand(args: AttributeArgs, mut input: ItemImpl) -> syn::Result<TokenStre
         let is_signal = attrs.signal;
         let out_args = attrs.out_args.as_deref();
         assert!(!is_property || !is_signal);
+        if (is_property || is_signal) && attrs.polkit_action.is_some() {
+            return Err(Error::new_spanned(
+                &method,
+                "`polkit_action` is only supported on methods",
+            ));
+        }
 
         let has_inputs = inputs.len() > 1;
 
@@ -187,9 +248,10 @@ pub fn expand(args: AttributeArgs, mut input: ItemImpl) -> syn::Result<TokenStre
         };
 
         let mut intro_args = quote!();
-        intro_args.extend(introspect_input_args(&typed_inputs, is_signal, &cfg_attrs));
+        intro_args.extend(introspect_input_args(&typed_inputs, is_signal, &cfg_attrs)?);
         let is_result_output =
             introspect_add_output_args(&mut intro_args, output, out_args, &cfg_attrs)?;
+        intro_args.extend(introspect_annotations(&annotations));
 
         let (args_from_msg, args_names) = get_args_from_inputs(&typed_inputs, &zbus)?;
 
@@ -227,7 +289,7 @@ pub fn expand(args: AttributeArgs, mut input: ItemImpl) -> syn::Result<TokenStre
                 #signal_context.connection().emit_signal(
                     #signal_context.destination(),
                     #signal_context.path(),
-                    <#self_ty as #zbus::object_server::Interface>::name(),
+                    <#self_ty as #iface_trait>::name(),
                     #member_name,
                     &(#args_names),
                 )
@@ -242,6 +304,11 @@ pub fn expand(args: AttributeArgs, mut input: ItemImpl) -> syn::Result<TokenStre
 
             let p = p.or_insert_with(Property::new);
             p.doc_comments.extend(doc_comments);
+            for annotation in annotations {
+                if !p.annotations.iter().any(|a| a.name == annotation.name) {
+                    p.annotations.push(annotation);
+                }
+            }
             if has_inputs {
                 p.write = true;
 
@@ -340,14 +407,14 @@ pub fn expand(args: AttributeArgs, mut input: ItemImpl) -> syn::Result<TokenStre
 
                     let q = quote!(
                         #(#cfg_attrs)*
-                        #member_name => #zbus::object_server::DispatchResult::RequiresMut,
+                        #member_name => #dispatch_result::RequiresMut,
                     );
                     set_dispatch.extend(q);
                 } else {
                     let q = quote!(
                         #(#cfg_attrs)*
                         #member_name => {
-                            #zbus::object_server::DispatchResult::Async(::std::boxed::Box::pin(async move {
+                            #dispatch_result::Async(::std::boxed::Box::pin(async move {
                                 #do_set
                             }))
                         }
@@ -453,15 +520,29 @@ pub fn expand(args: AttributeArgs, mut input: ItemImpl) -> syn::Result<TokenStre
             introspect.extend(doc_comments);
             introspect.extend(introspect_method(&member_name, &intro_args));
 
+            let polkit_check = attrs.polkit_action.as_ref().map(|action| {
+                quote! {
+                    {
+                        let hdr = m.header();
+                        if let ::std::result::Result::Err(e) =
+                            #zbus::__polkit_check_authorization!(c, &hdr, #action).await
+                        {
+                            return c.reply_dbus_error(&hdr, e).await;
+                        }
+                    }
+                }
+            });
+
             let m = quote! {
                 #(#cfg_attrs)*
                 #member_name => {
                     let future = async move {
+                        #polkit_check
                         #args_from_msg
                         let reply = self.#ident(#args_names)#method_await;
                         #reply
                     };
-                    #zbus::object_server::DispatchResult::Async(::std::boxed::Box::pin(async move {
+                    #dispatch_result::Async(::std::boxed::Box::pin(async move {
                         future.await
                     }))
                 },
@@ -470,7 +551,7 @@ pub fn expand(args: AttributeArgs, mut input: ItemImpl) -> syn::Result<TokenStre
             if is_mut {
                 call_dispatch.extend(quote! {
                     #(#cfg_attrs)*
-                    #member_name => #zbus::object_server::DispatchResult::RequiresMut,
+                    #member_name => #dispatch_result::RequiresMut,
                 });
                 call_mut_dispatch.extend(m);
             } else {
@@ -480,6 +561,7 @@ pub fn expand(args: AttributeArgs, mut input: ItemImpl) -> syn::Result<TokenStre
     }
 
     introspect_properties(&mut introspect, properties)?;
+    let iface_annotations = introspect_annotations(&iface_annotations);
 
     let generics = &input.generics;
     let where_clause = &generics.where_clause;
@@ -487,14 +569,16 @@ pub fn expand(args: AttributeArgs, mut input: ItemImpl) -> syn::Result<TokenStre
     Ok(quote! {
         #input
 
+        #[allow(deprecated)]
         impl #generics #self_ty
         #where_clause
         {
             #generated_signals
         }
 
-        #[#zbus::export::async_trait::async_trait]
-        impl #generics #zbus::object_server::Interface for #self_ty
+        #[allow(deprecated)]
+        #[#zbus::export::async_trait::async_trait #async_trait_args]
+        impl #generics #iface_trait for #self_ty
         #where_clause
         {
             fn name() -> #zbus::names::InterfaceName<'static> {
@@ -530,10 +614,10 @@ pub fn expand(args: AttributeArgs, mut input: ItemImpl) -> syn::Result<TokenStre
                 property_name: &'call str,
                 value: &'call #zbus::zvariant::Value<'_>,
                 signal_context: &'call #zbus::object_server::SignalContext<'_>,
-            ) -> #zbus::object_server::DispatchResult<'call> {
+            ) -> #dispatch_result<'call> {
                 match property_name {
                     #set_dispatch
-                    _ => #zbus::object_server::DispatchResult::NotFound,
+                    _ => #dispatch_result::NotFound,
                 }
             }
 
@@ -555,10 +639,10 @@ pub fn expand(args: AttributeArgs, mut input: ItemImpl) -> syn::Result<TokenStre
                 c: &'call #zbus::Connection,
                 m: &'call #zbus::message::Message,
                 name: #zbus::names::MemberName<'call>,
-            ) -> #zbus::object_server::DispatchResult<'call> {
+            ) -> #dispatch_result<'call> {
                 match name.as_str() {
                     #call_dispatch
-                    _ => #zbus::object_server::DispatchResult::NotFound,
+                    _ => #dispatch_result::NotFound,
                 }
             }
 
@@ -568,10 +652,10 @@ pub fn expand(args: AttributeArgs, mut input: ItemImpl) -> syn::Result<TokenStre
                 c: &'call #zbus::Connection,
                 m: &'call #zbus::message::Message,
                 name: #zbus::names::MemberName<'call>,
-            ) -> #zbus::object_server::DispatchResult<'call> {
+            ) -> #dispatch_result<'call> {
                 match name.as_str() {
                     #call_mut_dispatch
-                    _ => #zbus::object_server::DispatchResult::NotFound,
+                    _ => #dispatch_result::NotFound,
                 }
             }
 
@@ -580,13 +664,14 @@ pub fn expand(args: AttributeArgs, mut input: ItemImpl) -> syn::Result<TokenStre
                     writer,
                     r#"{:indent$}<interface name="{}">"#,
                     "",
-                    <Self as #zbus::object_server::Interface>::name(),
+                    <Self as #iface_trait>::name(),
                     indent = level
                 ).unwrap();
                 {
                     use #zbus::zvariant::Type;
 
                     let level = level + 2;
+                    #iface_annotations
                     #introspect
                 }
                 ::std::writeln!(writer, r#"{:indent$}</interface>"#, "", indent = level).unwrap();
@@ -606,11 +691,12 @@ fn get_args_from_inputs(
         let mut conn_arg_decl = None;
         let mut header_arg_decl = None;
         let mut signal_context_arg_decl = None;
+        let mut credentials_arg_decl = None;
         let mut args_names = Vec::new();
         let mut tys = Vec::new();
 
         for input in inputs {
-            let attrs = ArgAttributes::parse(&input.attrs)?;
+            let (attrs, _) = parse_arg_attributes(&input.attrs)?;
 
             if attrs.object_server {
                 if server_arg_decl.is_some() {
@@ -666,6 +752,25 @@ fn get_args_from_inputs(
                         }
                     };
                 });
+            } else if attrs.credentials {
+                if credentials_arg_decl.is_some() {
+                    return Err(Error::new_spanned(
+                        input,
+                        "There can only be one `credentials` argument",
+                    ));
+                }
+
+                let credentials_arg = &input.pat;
+
+                credentials_arg_decl = Some(quote! {
+                    let #credentials_arg = match c.sender_credentials(&hdr).await {
+                        ::std::result::Result::Ok(creds) => creds,
+                        ::std::result::Result::Err(e) => {
+                            let err = <#zbus::fdo::Error as ::std::convert::From<_>>::from(e);
+                            return c.reply_dbus_error(&hdr, err).await;
+                        }
+                    };
+                });
             } else {
                 args_names.push(pat_ident(input).unwrap());
                 tys.push(&input.ty);
@@ -684,6 +789,8 @@ fn get_args_from_inputs(
 
             #signal_context_arg_decl
 
+            #credentials_arg_decl
+
             let (#(#args_names),*): (#(#tys),*) =
                 match msg_body.deserialize() {
                     ::std::result::Result::Ok(r) => r,
@@ -731,52 +838,56 @@ fn introspect_method(name: &str, args: &TokenStream) -> TokenStream {
     )
 }
 
-fn introspect_input_args<'i>(
-    inputs: &'i [PatType],
+fn introspect_input_args(
+    inputs: &[PatType],
     is_signal: bool,
-    cfg_attrs: &'i [&'i syn::Attribute],
-) -> impl Iterator<Item = TokenStream> + 'i {
-    inputs
-        .iter()
-        .filter_map(move |pat_type @ PatType { ty, attrs, .. }| {
-            let is_special_arg = attrs.iter().any(|attr| {
-                if !attr.path.is_ident("zbus") {
-                    return false;
-                }
-
-                let meta = match attr.parse_meta() {
-                    ::std::result::Result::Ok(meta) => meta,
-                    ::std::result::Result::Err(_) => return false,
-                };
-
-                let nested = match meta {
-                    Meta::List(MetaList { nested, .. }) => nested,
-                    _ => return false,
-                };
-
-                let res = nested.iter().any(|nested_meta| {
-                    matches!(
-                        nested_meta,
-                        NestedMeta::Meta(Meta::Path(path))
-                        if path.is_ident("object_server") || path.is_ident("connection") || path.is_ident("header") || path.is_ident("signal_context")
-                    )
-                });
+    cfg_attrs: &[&syn::Attribute],
+) -> syn::Result<TokenStream> {
+    let mut args = quote!();
+
+    for pat_type @ PatType { ty, attrs, .. } in inputs {
+        let (arg_attrs, annotations) = parse_arg_attributes(attrs)?;
+        if arg_attrs.object_server
+            || arg_attrs.connection
+            || arg_attrs.header
+            || arg_attrs.signal_context
+            || arg_attrs.credentials
+        {
+            continue;
+        }
 
-                res
-            });
-            if is_special_arg {
-                return None;
+        let arg_name = match arg_attrs.name {
+            Some(name) => name,
+            None => {
+                let ident = pat_ident(pat_type).unwrap();
+                quote!(#ident).to_string()
             }
-
-            let ident = pat_ident(pat_type).unwrap();
-            let arg_name = quote!(#ident).to_string();
-            let dir = if is_signal { "" } else { " direction=\"in\"" };
-            Some(quote!(
+        };
+        let dir = if is_signal { "" } else { " direction=\"in\"" };
+        if annotations.is_empty() {
+            args.extend(quote!(
                 #(#cfg_attrs)*
                 ::std::writeln!(writer, "{:indent$}<arg name=\"{}\" type=\"{}\"{}/>", "",
                          #arg_name, <#ty>::signature(), #dir, indent = level).unwrap();
-            ))
-        })
+            ));
+        } else {
+            let annotations = introspect_annotations(&annotations);
+            args.extend(quote!(
+                #(#cfg_attrs)*
+                {
+                    ::std::writeln!(writer, "{:indent$}<arg name=\"{}\" type=\"{}\"{}>", "",
+                             #arg_name, <#ty>::signature(), #dir, indent = level).unwrap();
+                    {
+                        let level = level + 2;
+                        #annotations
+                    }
+                    ::std::writeln!(writer, "{:indent$}</arg>", "", indent = level).unwrap();
+                }
+            ));
+        }
+    }
+
+    Ok(args)
 }
 
 fn introspect_output_arg(
@@ -900,19 +1011,130 @@ fn introspect_properties(
         })?;
 
         let doc_comments = prop.doc_comments;
-        introspection.extend(quote!(
-            #doc_comments
-            ::std::writeln!(
-                writer,
-                "{:indent$}<property name=\"{}\" type=\"{}\" access=\"{}\"/>",
-                "", #name, <#ty>::signature(), #access, indent = level,
-            ).unwrap();
-        ));
+        if prop.annotations.is_empty() {
+            introspection.extend(quote!(
+                #doc_comments
+                ::std::writeln!(
+                    writer,
+                    "{:indent$}<property name=\"{}\" type=\"{}\" access=\"{}\"/>",
+                    "", #name, <#ty>::signature(), #access, indent = level,
+                ).unwrap();
+            ));
+        } else {
+            let annotations = introspect_annotations(&prop.annotations);
+            introspection.extend(quote!(
+                #doc_comments
+                ::std::writeln!(
+                    writer,
+                    "{:indent$}<property name=\"{}\" type=\"{}\" access=\"{}\">",
+                    "", #name, <#ty>::signature(), #access, indent = level,
+                ).unwrap();
+                {
+                    let level = level + 2;
+                    #annotations
+                }
+                ::std::writeln!(writer, "{:indent$}</property>", "", indent = level).unwrap();
+            ));
+        }
     }
 
     Ok(())
 }
 
+// Splits the `annotation(name = "..", value = "..")` entries out of an attribute list. Unlike the
+// other attributes, these can be specified more than once so they can't go through `def_attrs`.
+fn split_annotations<'a, I>(metas: I) -> syn::Result<(Vec<&'a NestedMeta>, Vec<Annotation>)>
+where
+    I: IntoIterator<Item = &'a NestedMeta>,
+{
+    let mut others = vec![];
+    let mut annotations: Vec<Annotation> = vec![];
+
+    for nested_meta in metas {
+        match nested_meta {
+            NestedMeta::Meta(Meta::List(list)) if list.path.is_ident("annotation") => {
+                let AnnotationAttributes { name, value } =
+                    AnnotationAttributes::parse_nested_metas(&list.nested)?;
+                let name = name.ok_or_else(|| {
+                    Error::new_spanned(list, "`annotation` requires a `name` attribute")
+                })?;
+                let value = value.ok_or_else(|| {
+                    Error::new_spanned(list, "`annotation` requires a `value` attribute")
+                })?;
+                if annotations.iter().any(|a| a.name == name) {
+                    return Err(Error::new_spanned(
+                        list,
+                        format!("duplicate `{name}` annotation"),
+                    ));
+                }
+
+                annotations.push(Annotation { name, value });
+            }
+            _ => others.push(nested_meta),
+        }
+    }
+
+    Ok((others, annotations))
+}
+
+fn parse_arg_attributes(attrs: &[syn::Attribute]) -> syn::Result<(ArgAttributes, Vec<Annotation>)> {
+    let metas = iter_meta_lists(attrs, "zbus")?.collect::<Vec<_>>();
+    let (metas, annotations) = split_annotations(&metas)?;
+
+    Ok((ArgAttributes::parse_nested_metas(metas)?, annotations))
+}
+
+// `#[deprecated]` has no effect on `impl` blocks (and rustc complains about it) so we remove it.
+fn take_deprecated_attr(attrs: &mut Vec<syn::Attribute>) -> bool {
+    let len = attrs.len();
+    attrs.retain(|attr| !attr.path.is_ident("deprecated"));
+
+    attrs.len() != len
+}
+
+fn add_deprecated_annotation(annotations: &mut Vec<Annotation>) {
+    if !annotations.iter().any(|a| a.name == DEPRECATED_ANNOTATION) {
+        annotations.push(Annotation {
+            name: DEPRECATED_ANNOTATION.to_string(),
+            value: "true".to_string(),
+        });
+    }
+}
+
+fn introspect_annotations(annotations: &[Annotation]) -> TokenStream {
+    annotations
+        .iter()
+        .map(|Annotation { name, value }| {
+            let name = xml_escape(name);
+            let value = xml_escape(value);
+
+            quote!(
+                ::std::writeln!(
+                    writer,
+                    "{:indent$}<annotation name=\"{}\" value=\"{}\"/>",
+                    "", #name, #value, indent = level,
+                ).unwrap();
+            )
+        })
+        .collect()
+}
+
+fn xml_escape(s: &str) -> String {
+    let mut escaped = String::with_capacity(s.len());
+    for c in s.chars() {
+        match c {
+            '&' => escaped.push_str("&amp;"),
+            '<' => escaped.push_str("&lt;"),
+            '>' => escaped.push_str("&gt;"),
+            '"' => escaped.push_str("&quot;"),
+            '\'' => escaped.push_str("&apos;"),
+            c => escaped.push(c),
+        }
+    }
+
+    escaped
+}
+
 pub fn to_xml_docs(lines: Vec<String>) -> TokenStream {
     let mut docs = quote!();
 
@@ -226,6 +226,27 @@ pub fn dbus_proxy(attr: TokenStream, item: TokenStream) -> TokenStream {
 ///   In such case, your method must return a tuple containing
 ///   your out arguments, in the same order as passed to `out_args`.
 ///
+/// * `annotation` - add a D-Bus annotation to the introspection data of the method, property or
+///   signal, e.g `annotation(name = "org.freedesktop.DBus.Method.NoReply", value = "true")`. It can
+///   be specified multiple times. The same attribute is also accepted in the `dbus_interface`
+///   attribute of the `impl` block itself, to annotate the interface.
+///
+/// * `polkit_action` - check with polkit that the caller is authorized to perform the given action
+///   (e.g `polkit_action = "org.example.reboot"`) before calling the method. If the caller is not
+///   authorized, the method is not called and an `AccessDenied` error is returned instead, or an
+///   `InteractiveAuthorizationRequired` error if the caller could be authorized through user
+///   interaction but didn't set the `ALLOW_INTERACTIVE_AUTHORIZATION` flag on the call. Only
+///   available with the `polkit` feature of zbus. See [`zbus::polkit`] for details.
+///
+/// Methods, properties and signals marked with the `#[deprecated]` attribute (as well as the `impl`
+/// block itself) automatically get the `org.freedesktop.DBus.Deprecated` annotation.
+///
+/// Besides `name` (or its alias `interface`) and `annotation`, the `dbus_interface` attribute of
+/// the `impl` block accepts `local`, for types that are not `Send` or `Sync`. In that case, the
+/// [`LocalInterface`] trait is implemented instead of [`Interface`] and the methods don't need to
+/// return `Send` futures. The interface must then be served wrapped in a [`Local`], on a
+/// connection driven from the same thread (see [`connection::Builder::local`]).
+///
 /// The `struct_return` attribute (from zbus 1.x) is no longer supported. If you want to return a
 /// single structure from a method, declare it to return a tuple containing either a named structure
 /// or a nested tuple.
@@ -251,6 +272,11 @@ pub fn dbus_proxy(attr: TokenStream, item: TokenStream) -> TokenStream {
 ///   D-Bus method call being handled.
 /// * `signal_context` - This marks the method argument to receive a [`SignalContext`] instance,
 ///   which is needed for emitting signals the easy way.
+/// * `credentials` - This marks the method argument to receive the [`ConnectionCredentials`] of the
+///   caller. These are retrieved through [`Connection::sender_credentials`] and hence cached for as
+///   long as the caller stays connected to the bus.
+/// * `name` - override the D-Bus name of the argument (the Rust name of the argument by default).
+/// * `annotation` - add a D-Bus annotation to the argument, just like for methods.
 ///
 /// # Example
 ///
@@ -308,6 +334,13 @@ pub fn dbus_proxy(attr: TokenStream, item: TokenStream) -> TokenStream {
 ///     fn meaning_of_life(&self) -> zbus::fdo::Result<(i32, String)> {
 ///         Ok((42, String::from("Meaning of life")))
 ///     }
+///
+///     // "Ping" method, annotated as not expecting a reply and deprecated.
+///     #[dbus_interface(annotation(name = "org.freedesktop.DBus.Method.NoReply", value = "true"))]
+///     #[deprecated]
+///     fn ping(&self, #[zbus(name = "type")] r#type: &str) {
+///         println!("Ping of type {}", r#type);
+///     }
 /// }
 ///
 /// # Ok::<_, Box<dyn Error + Send + Sync>>(())
@@ -320,7 +353,13 @@ pub fn dbus_proxy(attr: TokenStream, item: TokenStream) -> TokenStream {
 /// [`Connection`]: https://docs.rs/zbus/latest/zbus/connection/struct.Connection.html
 /// [`Connection::emit_signal()`]: https://docs.rs/zbus/latest/zbus/connection/struct.Connection.html#method.emit_signal
 /// [`SignalContext`]: https://docs.rs/zbus/latest/zbus/object_server/struct.SignalContext.html
+/// [`ConnectionCredentials`]: https://docs.rs/zbus/latest/zbus/fdo/struct.ConnectionCredentials.html
+/// [`Connection::sender_credentials`]: https://docs.rs/zbus/latest/zbus/connection/struct.Connection.html#method.sender_credentials
 /// [`Interface`]: https://docs.rs/zbus/latest/zbus/object_server/trait.Interface.html
+/// [`zbus::polkit`]: https://docs.rs/zbus/latest/zbus/polkit/index.html
+/// [`LocalInterface`]: https://docs.rs/zbus/latest/zbus/object_server/trait.LocalInterface.html
+/// [`Local`]: https://docs.rs/zbus/latest/zbus/object_server/struct.Local.html
+/// [`connection::Builder::local`]: https://docs.rs/zbus/latest/zbus/connection/struct.Builder.html#method.local
 #[proc_macro_attribute]
 pub fn dbus_interface(attr: TokenStream, item: TokenStream) -> TokenStream {
     let args = parse_macro_input!(attr as AttributeArgs);
@@ -424,9 +424,8 @@ pub fn create_proxy(
         }
 
         impl<'p> #zbus::zvariant::Type for #proxy_name<'p> {
-            fn signature() -> #zbus::zvariant::Signature<'static> {
-                #zbus::zvariant::OwnedObjectPath::signature()
-            }
+            const SIGNATURE: &'static #zbus::zvariant::ParsedSignature =
+                <#zbus::zvariant::OwnedObjectPath as #zbus::zvariant::Type>::SIGNATURE;
         }
 
         impl<'p> #zbus::export::serde::ser::Serialize for #proxy_name<'p> {
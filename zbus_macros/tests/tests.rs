@@ -98,6 +98,12 @@ fn test_proxy() {
     });
 }
 
+#[test]
+fn test_polkit_action_without_feature() {
+    let t = trybuild::TestCases::new();
+    t.compile_fail("tests/ui/polkit_without_feature.rs");
+}
+
 #[test]
 fn test_derive_error() {
     #[derive(Debug, DBusError)]
@@ -255,6 +261,76 @@ fn test_interface() {
     }
 }
 
+#[test]
+fn test_interface_annotations() {
+    use zbus::object_server::Interface;
+
+    struct Annotated;
+
+    #[dbus_interface(
+        name = "org.freedesktop.zbus.Annotated",
+        annotation(name = "org.gtk.GDBus.C.Name", value = "Annotated")
+    )]
+    #[deprecated]
+    impl Annotated {
+        #[deprecated]
+        fn old_method(&self) {}
+
+        #[dbus_interface(annotation(name = "org.freedesktop.DBus.Method.NoReply", value = "true"))]
+        fn fire_and_forget(
+            &self,
+            #[zbus(name = "type", annotation(name = "org.example.Hint", value = "<kind>"))]
+            r#type: &str,
+        ) {
+            let _ = r#type;
+        }
+
+        #[dbus_interface(property)]
+        #[deprecated]
+        fn old_prop(&self) -> u32 {
+            0
+        }
+
+        #[dbus_interface(property, annotation(name = "org.example.Unit", value = "ms"))]
+        fn set_old_prop(&self, _val: u32) {}
+
+        #[dbus_interface(
+            signal,
+            annotation(name = "org.freedesktop.DBus.Deprecated", value = "false")
+        )]
+        async fn changed(
+            ctxt: &SignalContext<'_>,
+            #[zbus(name = "new_value")] value: u32,
+        ) -> zbus::Result<()>;
+    }
+
+    const EXPECTED_XML: &str = r#"<interface name="org.freedesktop.zbus.Annotated">
+  <annotation name="org.gtk.GDBus.C.Name" value="Annotated"/>
+  <annotation name="org.freedesktop.DBus.Deprecated" value="true"/>
+  <method name="OldMethod">
+    <annotation name="org.freedesktop.DBus.Deprecated" value="true"/>
+  </method>
+  <method name="FireAndForget">
+    <arg name="type" type="s" direction="in">
+      <annotation name="org.example.Hint" value="&lt;kind&gt;"/>
+    </arg>
+    <annotation name="org.freedesktop.DBus.Method.NoReply" value="true"/>
+  </method>
+  <signal name="Changed">
+    <arg name="new_value" type="u"/>
+    <annotation name="org.freedesktop.DBus.Deprecated" value="false"/>
+  </signal>
+  <property name="OldProp" type="u" access="readwrite">
+    <annotation name="org.freedesktop.DBus.Deprecated" value="true"/>
+    <annotation name="org.example.Unit" value="ms"/>
+  </property>
+</interface>
+"#;
+    let mut xml = String::new();
+    Annotated.introspect_to_writer(&mut xml, 0);
+    assert_eq!(xml, EXPECTED_XML);
+}
+
 mod signal_from_message {
     use super::*;
     use zbus::message::Message;
@@ -0,0 +1,11 @@
+use zbus_macros::dbus_interface;
+
+struct Privileged;
+
+#[dbus_interface(name = "org.zbus.Privileged")]
+impl Privileged {
+    #[dbus_interface(polkit_action = "org.zbus.Reboot")]
+    fn reboot(&self) {}
+}
+
+fn main() {}
@@ -1,13 +1,21 @@
 use snakecase::ascii::to_snakecase;
-use std::fmt::{Display, Formatter};
+use std::{
+    collections::{HashMap, HashSet},
+    fmt::{Display, Formatter, Write},
+};
 
 use zbus::names::BusName;
-use zbus_xml::{Arg, ArgDirection, Interface};
+use zbus_xml::{Annotation, Arg, ArgDirection, Interface, Property};
 use zvariant::{
-    Basic, CompleteType, ObjectPath, Signature, ARRAY_SIGNATURE_CHAR, DICT_ENTRY_SIG_END_CHAR,
+    Basic, ObjectPath, Signature, ARRAY_SIGNATURE_CHAR, DICT_ENTRY_SIG_END_CHAR,
     DICT_ENTRY_SIG_START_CHAR, STRUCT_SIG_END_CHAR, STRUCT_SIG_START_CHAR, VARIANT_SIGNATURE_CHAR,
 };
 
+const DEPRECATED_ANNOTATION: &str = "org.freedesktop.DBus.Deprecated";
+const NO_REPLY_ANNOTATION: &str = "org.freedesktop.DBus.Method.NoReply";
+const EMITS_CHANGED_SIGNAL_ANNOTATION: &str = "org.freedesktop.DBus.Property.EmitsChangedSignal";
+const QT_TYPE_NAME_ANNOTATION: &str = "org.qtproject.QtDBus.QtTypeName";
+
 pub struct GenTrait<'i> {
     pub interface: &'i Interface<'i>,
     pub service: Option<&'i BusName<'i>>,
@@ -16,10 +24,39 @@ pub struct GenTrait<'i> {
 
 impl<'i> Display for GenTrait<'i> {
     fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        let name = interface_short_name(self.interface);
+        let mut types = NamedTypes::new([
+            name.clone(),
+            format!("{name}Proxy"),
+            format!("{name}ProxyBlocking"),
+        ]);
+        // The proxy macro generates a few types for each signal.
+        for signal in self.interface.signals() {
+            let name = pascal_case(&to_identifier(&to_snakecase(signal.name().as_str())));
+            for suffix in ["", "Args", "Stream", "Iterator"] {
+                types.reserve(format!("{name}{suffix}"));
+            }
+        }
+
+        let mut body = String::new();
+        self.write_trait(&mut body, &mut types)?;
+
+        write!(f, "{types}{body}")
+    }
+}
+
+impl<'i> GenTrait<'i> {
+    fn write_trait(&self, f: &mut String, types: &mut NamedTypes) -> std::fmt::Result {
         let iface = self.interface;
-        let idx = iface.name().rfind('.').unwrap() + 1;
-        let name = &iface.name()[idx..];
+        let name = interface_short_name(iface);
+
+        let mut props = iface.properties().to_vec();
+        props.sort_by(|a, b| a.name().partial_cmp(&b.name()).unwrap());
+        register_property_types(&props, types);
 
+        if is_true(iface.annotations(), DEPRECATED_ANNOTATION) {
+            writeln!(f, "#[deprecated]")?;
+        }
         write!(f, "#[dbus_proxy(interface = \"{}\"", iface.name())?;
         if let Some(service) = self.service {
             write!(f, ", default_service = \"{service}\"")?;
@@ -36,12 +73,23 @@ impl<'i> Display for GenTrait<'i> {
         let mut methods = iface.methods().to_vec();
         methods.sort_by(|a, b| a.name().partial_cmp(&b.name()).unwrap());
         for m in &methods {
-            let (inputs, output) = inputs_output_from_args(m.args());
+            let hints = arg_hints(m.name().as_str(), m.annotations(), m.args(), false);
+            let (inputs, output) = inputs_output_from_args(m.args(), &hints, types);
             let name = to_identifier(&to_snakecase(m.name().as_str()));
+            let mut attrs = vec![];
+            if pascal_case(&name) != m.name().as_str() {
+                attrs.push(format!("name = \"{}\"", m.name()));
+            }
+            if is_true(m.annotations(), NO_REPLY_ANNOTATION) {
+                attrs.push("no_reply".to_string());
+            }
             writeln!(f)?;
             writeln!(f, "    /// {} method", m.name())?;
-            if pascal_case(&name) != m.name().as_str() {
-                writeln!(f, "    #[dbus_proxy(name = \"{}\")]", m.name())?;
+            if is_true(m.annotations(), DEPRECATED_ANNOTATION) {
+                writeln!(f, "    #[deprecated]")?;
+            }
+            if !attrs.is_empty() {
+                writeln!(f, "    #[dbus_proxy({})]", attrs.join(", "))?;
             }
             writeln!(f, "    fn {name}({inputs}){output};")?;
         }
@@ -49,10 +97,19 @@ impl<'i> Display for GenTrait<'i> {
         let mut signals = iface.signals().to_vec();
         signals.sort_by(|a, b| a.name().partial_cmp(&b.name()).unwrap());
         for signal in &signals {
-            let args = parse_signal_args(signal.args());
+            let hints = arg_hints(
+                signal.name().as_str(),
+                signal.annotations(),
+                signal.args(),
+                true,
+            );
+            let args = parse_signal_args("&self", signal.args(), &hints, types);
             let name = to_identifier(&to_snakecase(signal.name().as_str()));
             writeln!(f)?;
             writeln!(f, "    /// {} signal", signal.name())?;
+            if is_true(signal.annotations(), DEPRECATED_ANNOTATION) {
+                writeln!(f, "    #[deprecated]")?;
+            }
             if pascal_case(&name) != signal.name().as_str() {
                 writeln!(f, "    #[dbus_proxy(signal, name = \"{}\")]", signal.name())?;
             } else {
@@ -61,27 +118,45 @@ impl<'i> Display for GenTrait<'i> {
             writeln!(f, "    fn {name}({args}) -> zbus::Result<()>;",)?;
         }
 
-        let mut props = iface.properties().to_vec();
-        props.sort_by(|a, b| a.name().partial_cmp(&b.name()).unwrap());
         for p in props {
             let name = to_identifier(&to_snakecase(p.name().as_str()));
-            let fn_attribute = if pascal_case(&name) != p.name().as_str() {
-                format!("    #[dbus_proxy(property, name = \"{}\")]", p.name())
-            } else {
-                "    #[dbus_proxy(property)]".to_string()
-            };
+            // `true` is the default.
+            let emits_changed_signal = annotation(p.annotations(), EMITS_CHANGED_SIGNAL_ANNOTATION)
+                .or_else(|| annotation(iface.annotations(), EMITS_CHANGED_SIGNAL_ANNOTATION))
+                .filter(|value| *value != "true");
+            let mut attrs = vec![match emits_changed_signal {
+                Some(value) => format!("property(emits_changed_signal = \"{value}\")"),
+                None => "property".to_string(),
+            }];
+            if pascal_case(&name) != p.name().as_str() {
+                attrs.push(format!("name = \"{}\"", p.name()));
+            }
+            let mut fn_attribute = String::new();
+            if is_true(p.annotations(), DEPRECATED_ANNOTATION) {
+                fn_attribute.push_str("    #[deprecated]\n");
+            }
+            write!(fn_attribute, "    #[dbus_proxy({})]", attrs.join(", "))?;
+
+            let signature = p.ty().signature().as_str();
+            let hint = property_hint(&p);
+            let output = to_rust_type(signature, false, false, &hint, types);
 
             writeln!(f)?;
             writeln!(f, "    /// {} property", p.name())?;
             if p.access().read() {
                 writeln!(f, "{}", fn_attribute)?;
-                let output = to_rust_type(p.ty(), false, false);
                 writeln!(f, "    fn {name}(&self) -> zbus::Result<{output}>;",)?;
             }
 
             if p.access().write() {
+                // Only the named types themselves (and not references to them) convert into a
+                // `Value`.
+                let input = if types.contains(signature) {
+                    output
+                } else {
+                    to_rust_type(signature, true, true, &hint, types)
+                };
                 writeln!(f, "{}", fn_attribute)?;
-                let input = to_rust_type(p.ty(), true, true);
                 writeln!(
                     f,
                     "    fn set_{name}(&self, value: {input}) -> zbus::Result<()>;",
@@ -92,7 +167,391 @@ impl<'i> Display for GenTrait<'i> {
     }
 }
 
-fn inputs_output_from_args(args: &[Arg]) -> (String, String) {
+pub struct GenInterface<'i> {
+    pub interface: &'i Interface<'i>,
+}
+
+impl<'i> Display for GenInterface<'i> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        let mut types = NamedTypes::new([interface_short_name(self.interface)]);
+
+        let mut body = String::new();
+        self.write_impl(&mut body, &mut types)?;
+
+        write!(f, "{types}{body}")
+    }
+}
+
+impl<'i> GenInterface<'i> {
+    fn write_impl(&self, f: &mut String, types: &mut NamedTypes) -> std::fmt::Result {
+        let iface = self.interface;
+        let name = interface_short_name(iface);
+
+        let mut props = iface.properties().to_vec();
+        props.sort_by(|a, b| a.name().partial_cmp(&b.name()).unwrap());
+        register_property_types(&props, types);
+
+        let mut iface_attrs = vec![format!("name = \"{}\"", iface.name())];
+        if let Some(value) = annotation(iface.annotations(), EMITS_CHANGED_SIGNAL_ANNOTATION) {
+            iface_attrs.push(annotation_attr(EMITS_CHANGED_SIGNAL_ANNOTATION, value));
+        }
+
+        writeln!(f, "pub struct {name};")?;
+        writeln!(f)?;
+        if is_true(iface.annotations(), DEPRECATED_ANNOTATION) {
+            writeln!(f, "#[deprecated]")?;
+        }
+        writeln!(f, "#[dbus_interface({})]", iface_attrs.join(", "))?;
+        writeln!(f, "impl {name} {{")?;
+
+        let mut methods = iface.methods().to_vec();
+        methods.sort_by(|a, b| a.name().partial_cmp(&b.name()).unwrap());
+        for m in &methods {
+            let hints = arg_hints(m.name().as_str(), m.annotations(), m.args(), false);
+            let (inputs, output, out_names) =
+                interface_inputs_output_from_args(m.args(), &hints, types);
+            let name = to_identifier(&to_snakecase(m.name().as_str()));
+            let mut attrs = vec![];
+            if pascal_case(&name) != m.name().as_str() {
+                attrs.push(format!("name = \"{}\"", m.name()));
+            }
+            if let Some(out_names) = out_names {
+                attrs.push(format!("out_args({})", out_names));
+            }
+            if is_true(m.annotations(), NO_REPLY_ANNOTATION) {
+                attrs.push(annotation_attr(NO_REPLY_ANNOTATION, "true"));
+            }
+            writeln!(f)?;
+            writeln!(f, "    /// {} method", m.name())?;
+            if is_true(m.annotations(), DEPRECATED_ANNOTATION) {
+                writeln!(f, "    #[deprecated]")?;
+            }
+            if !attrs.is_empty() {
+                writeln!(f, "    #[dbus_interface({})]", attrs.join(", "))?;
+            }
+            writeln!(f, "    async fn {name}({inputs}){output} {{")?;
+            writeln!(f, "        todo!()")?;
+            writeln!(f, "    }}")?;
+        }
+
+        let mut signals = iface.signals().to_vec();
+        signals.sort_by(|a, b| a.name().partial_cmp(&b.name()).unwrap());
+        for signal in &signals {
+            let hints = arg_hints(
+                signal.name().as_str(),
+                signal.annotations(),
+                signal.args(),
+                true,
+            );
+            let args = parse_signal_args(
+                "signal_ctxt: &zbus::object_server::SignalContext<'_>",
+                signal.args(),
+                &hints,
+                types,
+            );
+            let name = to_identifier(&to_snakecase(signal.name().as_str()));
+            writeln!(f)?;
+            writeln!(f, "    /// {} signal", signal.name())?;
+            if is_true(signal.annotations(), DEPRECATED_ANNOTATION) {
+                writeln!(f, "    #[deprecated]")?;
+            }
+            if pascal_case(&name) != signal.name().as_str() {
+                writeln!(
+                    f,
+                    "    #[dbus_interface(signal, name = \"{}\")]",
+                    signal.name()
+                )?;
+            } else {
+                writeln!(f, "    #[dbus_interface(signal)]")?;
+            }
+            writeln!(f, "    async fn {name}({args}) -> zbus::Result<()>;")?;
+        }
+
+        for p in props {
+            let name = to_identifier(&to_snakecase(p.name().as_str()));
+            let mut attrs = vec!["property".to_string()];
+            if pascal_case(&name) != p.name().as_str() {
+                attrs.push(format!("name = \"{}\"", p.name()));
+            }
+            if let Some(value) = annotation(p.annotations(), EMITS_CHANGED_SIGNAL_ANNOTATION) {
+                attrs.push(annotation_attr(EMITS_CHANGED_SIGNAL_ANNOTATION, value));
+            }
+            let mut fn_attribute = String::new();
+            if is_true(p.annotations(), DEPRECATED_ANNOTATION) {
+                fn_attribute.push_str("    #[deprecated]\n");
+            }
+            write!(fn_attribute, "    #[dbus_interface({})]", attrs.join(", "))?;
+            let ty = to_rust_type(
+                p.ty().signature().as_str(),
+                false,
+                false,
+                &property_hint(&p),
+                types,
+            );
+
+            writeln!(f)?;
+            writeln!(f, "    /// {} property", p.name())?;
+            // A getter is needed even for write-only properties, since `dbus_interface` doesn't
+            // support them.
+            writeln!(f, "{}", fn_attribute)?;
+            writeln!(
+                f,
+                "    async fn {name}(&self) -> zbus::fdo::Result<{ty}> {{"
+            )?;
+            if p.access().read() {
+                writeln!(f, "        todo!()")?;
+            } else {
+                writeln!(
+                    f,
+                    "        Err(zbus::fdo::Error::NotSupported(\"{} is write-only\".into()))",
+                    p.name(),
+                )?;
+            }
+            writeln!(f, "    }}")?;
+
+            if p.access().write() {
+                writeln!(f, "{}", fn_attribute)?;
+                writeln!(
+                    f,
+                    "    async fn set_{name}(&mut self, value: {ty}) -> zbus::fdo::Result<()> {{",
+                )?;
+                writeln!(f, "        todo!()")?;
+                writeln!(f, "    }}")?;
+            }
+        }
+        writeln!(f, "}}")
+    }
+}
+
+fn interface_short_name(iface: &Interface<'_>) -> String {
+    let name = iface.name();
+    let idx = name.rfind('.').unwrap() + 1;
+
+    name[idx..].to_string()
+}
+
+/// The named types generated for the structures and the more complex dictionaries of an
+/// interface.
+struct NamedTypes {
+    // The type names, by signature.
+    names: HashMap<String, String>,
+    // The names already in use.
+    taken: HashSet<String>,
+    // The type definitions, in order.
+    defs: Vec<String>,
+}
+
+impl NamedTypes {
+    fn new<I>(reserved: I) -> Self
+    where
+        I: IntoIterator<Item = String>,
+    {
+        // The prelude types used in the generated code.
+        let prelude = [
+            "Option", "Result", "Some", "None", "Ok", "Err", "String", "Vec",
+        ];
+
+        Self {
+            names: HashMap::new(),
+            taken: prelude
+                .iter()
+                .map(|name| name.to_string())
+                .chain(reserved)
+                .collect(),
+            defs: vec![],
+        }
+    }
+
+    fn reserve(&mut self, name: String) {
+        self.taken.insert(name);
+    }
+
+    fn contains(&self, signature: &str) -> bool {
+        self.names.contains_key(signature)
+    }
+
+    /// The name of the type for `signature`, defined by `def` if it's not there yet.
+    fn get_or_insert<F>(&mut self, signature: &str, hint: &TypeHint, def: F) -> String
+    where
+        F: FnOnce(&mut Self, &str) -> String,
+    {
+        if let Some(name) = self.names.get(signature) {
+            return name.clone();
+        }
+
+        let name = self.unique_name(hint);
+        self.taken.insert(name.clone());
+        self.names.insert(signature.to_string(), name.clone());
+        // Keep the place of the definition, so it comes before the ones of the types it contains.
+        let idx = self.defs.len();
+        self.defs.push(String::new());
+        self.defs[idx] = def(self, &name);
+
+        name
+    }
+
+    fn unique_name(&self, hint: &TypeHint) -> String {
+        let mut candidates = vec![hint.name.clone()];
+        if !hint.member.is_empty() && !hint.name.starts_with(&hint.member) {
+            candidates.push(format!("{}{}", hint.member, hint.name));
+        }
+        if let Some(name) = candidates
+            .into_iter()
+            .find(|name| !self.taken.contains(name))
+        {
+            return name;
+        }
+
+        (2..)
+            .map(|n| format!("{}{n}", hint.name))
+            .find(|name| !self.taken.contains(name))
+            .unwrap()
+    }
+}
+
+impl Display for NamedTypes {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        for def in &self.defs {
+            writeln!(f, "{def}")?;
+        }
+
+        Ok(())
+    }
+}
+
+/// How to name the types generated for a signature.
+#[derive(Debug, Clone)]
+struct TypeHint {
+    name: String,
+    // The name of the member the type is used by, to disambiguate the name if needed.
+    member: String,
+    // Whether the name comes from an annotation, in which case even simple dictionaries are named.
+    explicit: bool,
+    // Whether the types need to convert from and into a `Value` (for properties).
+    value: bool,
+}
+
+impl TypeHint {
+    fn nested(&self, name: String) -> Self {
+        Self {
+            name,
+            member: String::new(),
+            explicit: false,
+            value: self.value,
+        }
+    }
+}
+
+fn arg_hints(
+    member: &str,
+    annotations: &[Annotation],
+    args: &[Arg],
+    signal: bool,
+) -> Vec<TypeHint> {
+    let member = pascal_case(&to_snakecase(member));
+    let mut n_in = 0;
+    let mut n_out = 0;
+
+    args.iter()
+        .enumerate()
+        .map(|(i, a)| {
+            // Qt annotates the members with the type names of their arguments, by direction and
+            // position. Signal arguments have been annotated both as in and out ones.
+            let keys = if signal {
+                vec![format!("Out{i}"), format!("In{i}")]
+            } else if a.direction() == Some(ArgDirection::Out) {
+                n_out += 1;
+                vec![format!("Out{}", n_out - 1)]
+            } else {
+                n_in += 1;
+                vec![format!("In{}", n_in - 1)]
+            };
+            let qt_name = annotation(a.annotations(), QT_TYPE_NAME_ANNOTATION)
+                .or_else(|| {
+                    keys.iter().find_map(|key| {
+                        annotation(annotations, &format!("{QT_TYPE_NAME_ANNOTATION}.{key}"))
+                    })
+                })
+                .and_then(qt_type_name);
+            let name = match a.name() {
+                Some(name) => pascal_case(&to_snakecase(name.replace('-', "_"))),
+                None => format!("{member}Arg{i}"),
+            };
+
+            TypeHint {
+                explicit: qt_name.is_some(),
+                name: qt_name.unwrap_or(name),
+                member: member.clone(),
+                value: false,
+            }
+        })
+        .collect()
+}
+
+fn property_hint(p: &Property<'_>) -> TypeHint {
+    let qt_name = annotation(p.annotations(), QT_TYPE_NAME_ANNOTATION).and_then(qt_type_name);
+
+    TypeHint {
+        explicit: qt_name.is_some(),
+        name: qt_name.unwrap_or_else(|| pascal_case(&to_snakecase(p.name().as_str()))),
+        member: String::new(),
+        value: true,
+    }
+}
+
+// The types of properties need more traits, so they're registered before the others.
+fn register_property_types(props: &[Property<'_>], types: &mut NamedTypes) {
+    for p in props {
+        to_rust_type(
+            p.ty().signature().as_str(),
+            false,
+            false,
+            &property_hint(p),
+            types,
+        );
+    }
+}
+
+// The name of a type in a Qt type name annotation (e.g `MyStruct` for `QList<MyStruct>`), unless
+// it's one of the Qt types (e.g `QVariantMap`), which are of no help.
+fn qt_type_name(value: &str) -> Option<String> {
+    let mut name = value.trim();
+    while let Some((_, inner)) = name.strip_suffix('>').and_then(|name| name.split_once('<')) {
+        name = inner.trim();
+    }
+    let name = name.rsplit("::").next().unwrap();
+
+    let mut chars = name.chars();
+    let first = chars.next()?;
+    let valid = (first.is_ascii_alphabetic() || first == '_')
+        && chars.all(|c| c.is_ascii_alphanumeric() || c == '_');
+    if !valid || (first == 'Q' && name[1..].starts_with(|c: char| c.is_ascii_uppercase())) {
+        return None;
+    }
+
+    Some(first.to_ascii_uppercase().to_string() + &name[1..])
+}
+
+fn annotation<'a>(annotations: &'a [Annotation], name: &str) -> Option<&'a str> {
+    annotations
+        .iter()
+        .find(|a| a.name() == name)
+        .map(|a| a.value())
+}
+
+fn is_true(annotations: &[Annotation], name: &str) -> bool {
+    annotation(annotations, name) == Some("true")
+}
+
+fn annotation_attr(name: &str, value: &str) -> String {
+    format!("annotation(name = \"{name}\", value = \"{value}\")")
+}
+
+fn inputs_output_from_args(
+    args: &[Arg],
+    hints: &[TypeHint],
+    types: &mut NamedTypes,
+) -> (String, String) {
     let mut inputs = vec!["&self".to_string()];
     let mut output = vec![];
     let mut n = 0;
@@ -101,10 +560,11 @@ fn inputs_output_from_args(args: &[Arg]) -> (String, String) {
         format!("arg_{n}")
     };
 
-    for a in args {
+    for (a, hint) in args.iter().zip(hints) {
+        let signature = a.ty().signature().as_str();
         match a.direction() {
             None | Some(ArgDirection::In) => {
-                let ty = to_rust_type(a.ty(), true, true);
+                let ty = to_rust_type(signature, true, true, hint, types);
                 let arg = if let Some(name) = a.name() {
                     to_identifier(name)
                 } else {
@@ -113,7 +573,7 @@ fn inputs_output_from_args(args: &[Arg]) -> (String, String) {
                 inputs.push(format!("{arg}: {ty}"));
             }
             Some(ArgDirection::Out) => {
-                let ty = to_rust_type(a.ty(), false, false);
+                let ty = to_rust_type(signature, false, false, hint, types);
                 output.push(ty);
             }
         }
@@ -128,16 +588,86 @@ fn inputs_output_from_args(args: &[Arg]) -> (String, String) {
     (inputs.join(", "), format!(" -> zbus::Result<{output}>"))
 }
 
-fn parse_signal_args(args: &[Arg]) -> String {
+// Unlike in proxies, the method arguments are owned (since they're deserialized from the call
+// message) and the out args names are returned, if they're all named.
+fn interface_inputs_output_from_args(
+    args: &[Arg],
+    hints: &[TypeHint],
+    types: &mut NamedTypes,
+) -> (String, String, Option<String>) {
     let mut inputs = vec!["&self".to_string()];
+    let mut output = vec![];
+    let mut out_names = vec![];
+    let mut single_struct = false;
+    let mut n = 0;
+    let mut gen_name = || {
+        n += 1;
+        format!("arg_{n}")
+    };
+
+    for (a, hint) in args.iter().zip(hints) {
+        let signature = a.ty().signature().as_str();
+        match a.direction() {
+            None | Some(ArgDirection::In) => {
+                let ty = to_rust_type(signature, false, false, hint, types);
+                let arg = match a.name() {
+                    Some(name) if to_identifier(name) != name => {
+                        format!("#[zbus(name = \"{name}\")] {}", to_identifier(name))
+                    }
+                    Some(name) => name.to_string(),
+                    None => gen_name(),
+                };
+                inputs.push(format!("{arg}: {ty}"));
+            }
+            Some(ArgDirection::Out) => {
+                single_struct = output.is_empty() && signature.starts_with(STRUCT_SIG_START_CHAR);
+                output.push(to_rust_type(signature, false, false, hint, types));
+                out_names.push(a.name());
+            }
+        }
+    }
+
+    let output = match output.len() {
+        0 => "()".to_string(),
+        // A single structure needs to be wrapped in a tuple, or it'd be taken for multiple out
+        // args.
+        1 if single_struct => format!("({},)", output[0]),
+        1 => output[0].to_string(),
+        _ => format!("({})", output.join(", ")),
+    };
+    let out_names = (out_names.len() > 1)
+        .then(|| out_names.into_iter().collect::<Option<Vec<_>>>())
+        .flatten()
+        .map(|names| {
+            names
+                .iter()
+                .map(|name| format!("\"{name}\""))
+                .collect::<Vec<_>>()
+                .join(", ")
+        });
+
+    (
+        inputs.join(", "),
+        format!(" -> zbus::fdo::Result<{output}>"),
+        out_names,
+    )
+}
+
+fn parse_signal_args(
+    receiver: &str,
+    args: &[Arg],
+    hints: &[TypeHint],
+    types: &mut NamedTypes,
+) -> String {
+    let mut inputs = vec![receiver.to_string()];
     let mut n = 0;
     let mut gen_name = || {
         n += 1;
         format!("arg_{n}")
     };
 
-    for a in args {
-        let ty = to_rust_type(a.ty(), true, false);
+    for (a, hint) in args.iter().zip(hints) {
+        let ty = to_rust_type(a.ty().signature().as_str(), true, false, hint, types);
         let arg = if let Some(name) = a.name() {
             to_identifier(name)
         } else {
@@ -149,107 +679,176 @@ fn parse_signal_args(args: &[Arg]) -> String {
     inputs.join(", ")
 }
 
-fn to_rust_type(ty: &CompleteType, input: bool, as_ref: bool) -> String {
-    // can't haz recursive closure, yet
-    fn iter_to_rust_type(
-        it: &mut std::iter::Peekable<std::slice::Iter<'_, u8>>,
-        input: bool,
-        as_ref: bool,
-    ) -> String {
-        let c = it.next().unwrap();
-        match *c as char {
-            u8::SIGNATURE_CHAR => "u8".into(),
-            bool::SIGNATURE_CHAR => "bool".into(),
-            i16::SIGNATURE_CHAR => "i16".into(),
-            u16::SIGNATURE_CHAR => "u16".into(),
-            i32::SIGNATURE_CHAR => "i32".into(),
-            u32::SIGNATURE_CHAR => "u32".into(),
-            i64::SIGNATURE_CHAR => "i64".into(),
-            u64::SIGNATURE_CHAR => "u64".into(),
-            f64::SIGNATURE_CHAR => "f64".into(),
-            // xmlgen accepts 'h' on Windows, only for code generation
-            'h' => (if input {
-                "zbus::zvariant::Fd<'_>"
+// Structures are mapped to named structs and dictionaries of collections to named type aliases,
+// both using owned types. The other types map to the standard ones.
+fn to_rust_type(
+    signature: &str,
+    input: bool,
+    as_ref: bool,
+    hint: &TypeHint,
+    types: &mut NamedTypes,
+) -> String {
+    let named = |name: String| {
+        if input && as_ref {
+            format!("&{name}")
+        } else {
+            name
+        }
+    };
+
+    match signature.as_bytes()[0] as char {
+        u8::SIGNATURE_CHAR => "u8".into(),
+        bool::SIGNATURE_CHAR => "bool".into(),
+        i16::SIGNATURE_CHAR => "i16".into(),
+        u16::SIGNATURE_CHAR => "u16".into(),
+        i32::SIGNATURE_CHAR => "i32".into(),
+        u32::SIGNATURE_CHAR => "u32".into(),
+        i64::SIGNATURE_CHAR => "i64".into(),
+        u64::SIGNATURE_CHAR => "u64".into(),
+        f64::SIGNATURE_CHAR => "f64".into(),
+        // xmlgen accepts 'h' on Windows, only for code generation
+        'h' => (if input {
+            "zbus::zvariant::Fd<'_>"
+        } else {
+            "zbus::zvariant::OwnedFd"
+        })
+        .into(),
+        <&str>::SIGNATURE_CHAR => (if input || as_ref { "&str" } else { "String" }).into(),
+        ObjectPath::SIGNATURE_CHAR => (if input {
+            if as_ref {
+                "&zbus::zvariant::ObjectPath<'_>"
             } else {
-                "zbus::zvariant::OwnedFd"
-            })
-            .into(),
-            <&str>::SIGNATURE_CHAR => (if input || as_ref { "&str" } else { "String" }).into(),
-            ObjectPath::SIGNATURE_CHAR => (if input {
-                if as_ref {
-                    "&zbus::zvariant::ObjectPath<'_>"
-                } else {
-                    "zbus::zvariant::ObjectPath<'_>"
-                }
+                "zbus::zvariant::ObjectPath<'_>"
+            }
+        } else {
+            "zbus::zvariant::OwnedObjectPath"
+        })
+        .into(),
+        Signature::SIGNATURE_CHAR => (if input {
+            if as_ref {
+                "&zbus::zvariant::Signature<'_>"
             } else {
-                "zbus::zvariant::OwnedObjectPath"
-            })
-            .into(),
-            Signature::SIGNATURE_CHAR => (if input {
-                if as_ref {
-                    "&zbus::zvariant::Signature<'_>"
-                } else {
-                    "zbus::zvariant::Signature<'_>"
+                "zbus::zvariant::Signature<'_>"
+            }
+        } else {
+            "zbus::zvariant::OwnedSignature"
+        })
+        .into(),
+        ARRAY_SIGNATURE_CHAR => {
+            let element = &signature[1..];
+            let entry = match element.strip_prefix(DICT_ENTRY_SIG_START_CHAR) {
+                Some(entry) => entry.strip_suffix(DICT_ENTRY_SIG_END_CHAR).unwrap(),
+                None => {
+                    let ty = to_rust_type(element, input, false, hint, types);
+
+                    return if input {
+                        format!("&[{ty}]")
+                    } else {
+                        format!("{}Vec<{}>", if as_ref { "&" } else { "" }, ty)
+                    };
                 }
+            };
+            let (key, value) = split_complete_type(entry);
+
+            if hint.explicit || value.starts_with(ARRAY_SIGNATURE_CHAR) {
+                let name = types.get_or_insert(signature, hint, |types, name| {
+                    let key = to_rust_type(key, false, false, hint, types);
+                    let value_hint = hint.nested(format!("{name}Value"));
+                    let value = to_rust_type(value, false, false, &value_hint, types);
+
+                    format!(
+                        "/// `{signature}` dictionary\n\
+                         pub type {name} = std::collections::HashMap<{key}, {value}>;\n"
+                    )
+                });
+
+                named(name)
             } else {
-                "zbus::zvariant::OwnedSignature"
-            })
-            .into(),
-            VARIANT_SIGNATURE_CHAR => (if input {
-                if as_ref {
-                    "&zbus::zvariant::Value<'_>"
-                } else {
-                    "zbus::zvariant::Value<'_>"
+                format!(
+                    "std::collections::HashMap<{}, {}>",
+                    to_rust_type(key, input, false, hint, types),
+                    to_rust_type(value, input, false, hint, types),
+                )
+            }
+        }
+        STRUCT_SIG_START_CHAR => {
+            let name = types.get_or_insert(signature, hint, |types, name| {
+                let mut fields = vec![];
+                let mut rest = signature
+                    .strip_prefix(STRUCT_SIG_START_CHAR)
+                    .and_then(|s| s.strip_suffix(STRUCT_SIG_END_CHAR))
+                    .unwrap();
+                while !rest.is_empty() {
+                    let (field, next) = split_complete_type(rest);
+                    let field_hint = hint.nested(format!("{name}Field{}", fields.len()));
+                    fields.push(to_rust_type(field, false, false, &field_hint, types));
+                    rest = next;
                 }
-            } else {
-                "zbus::zvariant::OwnedValue"
-            })
-            .into(),
-            ARRAY_SIGNATURE_CHAR => {
-                let c = it.peek().unwrap();
-                match **c as char {
-                    '{' => format!(
-                        "std::collections::HashMap<{}>",
-                        iter_to_rust_type(it, input, false)
-                    ),
-                    _ => {
-                        let ty = iter_to_rust_type(it, input, false);
-                        if input {
-                            format!("&[{ty}]")
-                        } else {
-                            format!("{}Vec<{}>", if as_ref { "&" } else { "" }, ty)
-                        }
-                    }
+
+                // Values (including those used for maybe types) and file descriptors can't be
+                // cloned.
+                let mut derives = vec!["Debug"];
+                if !signature.contains([VARIANT_SIGNATURE_CHAR, 'h', 'm']) {
+                    derives.push("Clone");
                 }
-            }
-            c @ STRUCT_SIG_START_CHAR | c @ DICT_ENTRY_SIG_START_CHAR => {
-                let dict = c == '{';
-                let mut vec = vec![];
-                loop {
-                    let c = it.peek().unwrap();
-                    match **c as char {
-                        STRUCT_SIG_END_CHAR | DICT_ENTRY_SIG_END_CHAR => {
-                            // consume the closing character
-                            it.next().unwrap();
-                            break;
-                        }
-                        _ => vec.push(iter_to_rust_type(it, input, false)),
-                    }
+                derives.extend([
+                    "PartialEq",
+                    "serde::Serialize",
+                    "serde::Deserialize",
+                    "zbus::zvariant::Type",
+                ]);
+                if hint.value {
+                    derives.extend(["zbus::zvariant::Value", "zbus::zvariant::OwnedValue"]);
                 }
-                if dict {
-                    vec.join(", ")
-                } else if vec.len() > 1 {
-                    format!("{}({})", if as_ref { "&" } else { "" }, vec.join(", "))
-                } else {
-                    vec[0].to_string()
+
+                let mut def = format!(
+                    "/// `{signature}` structure\n#[derive({})]\npub struct {name} {{\n",
+                    derives.join(", "),
+                );
+                for (i, field) in fields.iter().enumerate() {
+                    def.push_str(&format!("    pub field_{i}: {field},\n"));
                 }
+                def.push_str("}\n");
+
+                def
+            });
+
+            named(name)
+        }
+        // Variants, as well as the types that can't be expressed more precisely (i-e GVariant's
+        // maybe types), are handled as a generic value.
+        _ => (if input {
+            if as_ref {
+                "&zbus::zvariant::Value<'_>"
+            } else {
+                "zbus::zvariant::Value<'_>"
             }
-            _ => unimplemented!(),
+        } else {
+            "zbus::zvariant::OwnedValue"
+        })
+        .into(),
+    }
+}
+
+/// Split the first complete type off `signature`, which must be valid.
+fn split_complete_type(signature: &str) -> (&str, &str) {
+    let bytes = signature.as_bytes();
+    let mut depth = 0;
+    let mut len = 0;
+    loop {
+        match bytes[len] {
+            b'(' | b'{' => depth += 1,
+            b')' | b'}' => depth -= 1,
+            _ => (),
+        }
+        len += 1;
+        // Arrays and GVariant's maybe types are followed by their element type.
+        if depth == 0 && !matches!(bytes[len - 1], b'a' | b'm') {
+            break;
         }
     }
 
-    let mut it = ty.signature().as_bytes().iter().peekable();
-    iter_to_rust_type(&mut it, input, as_ref)
+    signature.split_at(len)
 }
 
 static KWORDS: &[&str] = &[
@@ -284,3 +883,45 @@ pub fn pascal_case(s: &str) -> String {
     }
     pascal
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn unsupported_types() {
+        let hint = TypeHint {
+            name: "Foo".into(),
+            member: String::new(),
+            explicit: false,
+            value: false,
+        };
+        let mut types = NamedTypes::new([]);
+        let mut rust_type =
+            |signature, input, as_ref| to_rust_type(signature, input, as_ref, &hint, &mut types);
+
+        // GVariant's maybe types fall back to a generic value.
+        assert_eq!(rust_type("mi", false, false), "zbus::zvariant::OwnedValue");
+        assert_eq!(rust_type("mi", true, false), "zbus::zvariant::Value<'_>");
+        assert_eq!(rust_type("mi", true, true), "&zbus::zvariant::Value<'_>");
+        assert_eq!(
+            rust_type("amas", false, false),
+            "Vec<zbus::zvariant::OwnedValue>"
+        );
+        assert_eq!(
+            rust_type("a{sms}", false, false),
+            "std::collections::HashMap<String, zbus::zvariant::OwnedValue>"
+        );
+        assert_eq!(rust_type("(mbs)", false, false), "Foo");
+        assert_eq!(
+            types.to_string(),
+            "/// `(mbs)` structure\n\
+             #[derive(Debug, PartialEq, serde::Serialize, serde::Deserialize, \
+             zbus::zvariant::Type)]\n\
+             pub struct Foo {\n    \
+             pub field_0: zbus::zvariant::OwnedValue,\n    \
+             pub field_1: String,\n\
+             }\n\n"
+        );
+    }
+}
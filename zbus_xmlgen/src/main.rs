@@ -16,15 +16,18 @@ use zbus::{
 };
 use zbus_xml::{Interface, Node};
 
-use zbus_xmlgen::GenTrait;
+use zbus_xmlgen::{GenInterface, GenTrait};
 use zvariant::ObjectPath;
 
 fn usage() {
     eprintln!(
         r#"Usage:
-  zbus-xmlgen <interface.xml>
-  zbus-xmlgen --system|--session <service> <object_path>
-  zbus-xmlgen --address <address> <service> <object_path>
+  zbus-xmlgen [--server] <interface.xml>
+  zbus-xmlgen [--server] --system|--session <service> <object_path>
+  zbus-xmlgen [--server] --address <address> <service> <object_path>
+
+By default, client proxies are generated. With `--server`, `dbus_interface` skeletons are
+generated instead, to implement the interfaces.
 "#
     );
 }
@@ -32,6 +35,16 @@ fn usage() {
 fn main() -> Result<(), Box<dyn Error>> {
     let input_src;
 
+    let mut args: Vec<String> = args().collect();
+    let server = match args.iter().position(|arg| arg == "--server") {
+        Some(i) => {
+            args.remove(i);
+            true
+        }
+        None => false,
+    };
+    let arg = |n: usize| args.get(n).cloned();
+
     let proxy = |conn: Connection, service, path| -> IntrospectableProxy<'_> {
         IntrospectableProxy::builder(&conn)
             .destination(service)
@@ -42,21 +55,15 @@ fn main() -> Result<(), Box<dyn Error>> {
             .unwrap()
     };
 
-    let (node, service, path) = match args().nth(1) {
+    let (node, service, path) = match arg(1) {
         Some(bus) if bus == "--system" || bus == "--session" => {
             let connection = if bus == "--system" {
                 Connection::system()?
             } else {
                 Connection::session()?
             };
-            let service: BusName<'_> = args()
-                .nth(2)
-                .expect("Missing param for service")
-                .try_into()?;
-            let path: ObjectPath<'_> = args()
-                .nth(3)
-                .expect("Missing param for object path")
-                .try_into()?;
+            let service: BusName<'_> = arg(2).expect("Missing param for service").try_into()?;
+            let path: ObjectPath<'_> = arg(3).expect("Missing param for object path").try_into()?;
 
             input_src = format!(
                 "Interface '{}' from service '{}' on {} bus",
@@ -73,15 +80,9 @@ fn main() -> Result<(), Box<dyn Error>> {
             )
         }
         Some(address) if address == "--address" => {
-            let address = args().nth(2).expect("Missing param for address path");
-            let service: BusName<'_> = args()
-                .nth(3)
-                .expect("Missing param for service")
-                .try_into()?;
-            let path: ObjectPath<'_> = args()
-                .nth(4)
-                .expect("Missing param for object path")
-                .try_into()?;
+            let address = arg(2).expect("Missing param for address path");
+            let service: BusName<'_> = arg(3).expect("Missing param for service").try_into()?;
+            let path: ObjectPath<'_> = arg(4).expect("Missing param for object path").try_into()?;
 
             let connection = connection::Builder::address(&*address)?.build()?;
 
@@ -113,7 +114,11 @@ fn main() -> Result<(), Box<dyn Error>> {
         }
     };
 
-    let mut process = match Command::new("rustfmt").stdin(Stdio::piped()).spawn() {
+    let mut process = match Command::new("rustfmt")
+        .args(["--edition", "2021"])
+        .stdin(Stdio::piped())
+        .spawn()
+    {
         Err(why) => panic!("couldn't spawn rustfmt: {}", why),
         Ok(process) => process,
     };
@@ -124,17 +129,22 @@ fn main() -> Result<(), Box<dyn Error>> {
         .iter()
         .partition(|&i| i.name().starts_with(fdo_iface_prefix));
 
+    let (kind, kinds) = if server {
+        ("skeleton", "skeletons")
+    } else {
+        ("proxy", "proxies")
+    };
     if let Some((first_iface, following_ifaces)) = needed_ifaces.split_first() {
         if following_ifaces.is_empty() {
             writeln!(
                 rustfmt_stdin,
-                "//! # DBus interface proxy for: `{}`",
+                "//! # DBus interface {kind} for: `{}`",
                 first_iface.name()
             )?;
         } else {
             write!(
                 rustfmt_stdin,
-                "//! # DBus interface proxies for: `{}`",
+                "//! # DBus interface {kinds} for: `{}`",
                 first_iface.name()
             )?;
             for iface in following_ifaces {
@@ -144,6 +154,11 @@ fn main() -> Result<(), Box<dyn Error>> {
         }
     }
 
+    let (section, section_page) = if server {
+        ("Writing a server interface", "server")
+    } else {
+        ("Writing a client proxy", "client")
+    };
     write!(
         rustfmt_stdin,
         "//!
@@ -153,7 +168,7 @@ fn main() -> Result<(), Box<dyn Error>> {
          //! You may prefer to adapt it, instead of using it verbatim.
          //!
          //! More information can be found in the
-         //! [Writing a client proxy](https://dbus2.github.io/zbus/client.html)
+         //! [{section}](https://dbus2.github.io/zbus/{section_page}.html)
          //! section of the zbus documentation.
          //!
         ",
@@ -161,7 +176,24 @@ fn main() -> Result<(), Box<dyn Error>> {
         env!("CARGO_PKG_VERSION"),
         input_src,
     )?;
-    if !fdo_standard_ifaces.is_empty() {
+    if !fdo_standard_ifaces.is_empty() && server {
+        write!(rustfmt_stdin,
+            "//! This DBus object implements
+             //! [standard DBus interfaces](https://dbus.freedesktop.org/doc/dbus-specification.html),
+             //! (`org.freedesktop.DBus.*`), which are provided by the zbus `ObjectServer` itself:
+             //!
+            ")?;
+        for iface in &fdo_standard_ifaces {
+            writeln!(rustfmt_stdin, "//! * `{}`", iface.name())?;
+        }
+        write!(
+            rustfmt_stdin,
+            "//!
+             //! …consequently `{}` did not generate code for the above interfaces.
+            ",
+            env!("CARGO_BIN_NAME")
+        )?;
+    } else if !fdo_standard_ifaces.is_empty() {
         write!(rustfmt_stdin,
             "//! This DBus object implements
              //! [standard DBus interfaces](https://dbus.freedesktop.org/doc/dbus-specification.html),
@@ -184,17 +216,26 @@ fn main() -> Result<(), Box<dyn Error>> {
     write!(
         rustfmt_stdin,
         "
-        use zbus::dbus_proxy;
-        "
+        use zbus::{};
+        ",
+        if server {
+            "dbus_interface"
+        } else {
+            "dbus_proxy"
+        }
     )?;
     for iface in &needed_ifaces {
         writeln!(rustfmt_stdin)?;
-        let gen = GenTrait {
-            interface: iface,
-            service: service.as_ref(),
-            path: path.as_ref(),
-        }
-        .to_string();
+        let gen = if server {
+            GenInterface { interface: iface }.to_string()
+        } else {
+            GenTrait {
+                interface: iface,
+                service: service.as_ref(),
+                path: path.as_ref(),
+            }
+            .to_string()
+        };
         rustfmt_stdin.write_all(gen.as_bytes())?;
     }
     process.wait()?;
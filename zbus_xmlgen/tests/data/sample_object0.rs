@@ -1,17 +1,55 @@
+/// `(aiia{ss}iaiiasib)` structure
+#[derive(Debug, Clone, PartialEq, serde::Serialize, serde::Deserialize, zbus::zvariant::Type)]
+pub struct Rule {
+    pub field_0: Vec<i32>,
+    pub field_1: i32,
+    pub field_2: std::collections::HashMap<String, String>,
+    pub field_3: i32,
+    pub field_4: Vec<i32>,
+    pub field_5: i32,
+    pub field_6: Vec<String>,
+    pub field_7: i32,
+    pub field_8: bool,
+}
+
+/// `(so)` structure
+#[derive(Debug, Clone, PartialEq, serde::Serialize, serde::Deserialize, zbus::zvariant::Type)]
+pub struct BarplexSigArg1 {
+    pub field_0: String,
+    pub field_1: zbus::zvariant::OwnedObjectPath,
+}
+
+/// `(iiu)` structure
+#[derive(Debug, Clone, PartialEq, serde::Serialize, serde::Deserialize, zbus::zvariant::Type)]
+pub struct Bar {
+    pub field_0: i32,
+    pub field_1: i32,
+    pub field_2: u32,
+}
+
+/// `(iiav)` structure
+#[derive(Debug, PartialEq, serde::Serialize, serde::Deserialize, zbus::zvariant::Type)]
+pub struct MogrifyMeBar {
+    pub field_0: i32,
+    pub field_1: i32,
+    pub field_2: Vec<zbus::zvariant::OwnedValue>,
+}
+
 #[dbus_proxy(interface = "com.example.SampleInterface0", assume_defaults = true)]
 trait SampleInterface0 {
 
     /// BarplexSig method
-    fn barplex_sig(&self, rule: &(&[i32], i32, std::collections::HashMap<&str, &str>, i32, &[i32], i32, &[&str], i32, bool)) -> zbus::Result<Vec<(String, zbus::zvariant::OwnedObjectPath)>>;
+    fn barplex_sig(&self, rule: &Rule) -> zbus::Result<Vec<BarplexSigArg1>>;
 
     /// Bazify method
-    fn bazify(&self, bar: &(i32, i32, u32)) -> zbus::Result<zbus::zvariant::OwnedValue>;
+    fn bazify(&self, bar: &Bar) -> zbus::Result<zbus::zvariant::OwnedValue>;
 
     /// Frobate method
+    #[deprecated]
     fn frobate(&self, foz: i32, foo: i32) -> zbus::Result<(String, std::collections::HashMap<u32, String>)>;
 
     /// MogrifyMe method
-    fn mogrify_me(&self, bar: &(i32, i32, &[zbus::zvariant::Value<'_>])) -> zbus::Result<()>;
+    fn mogrify_me(&self, bar: &MogrifyMeBar) -> zbus::Result<()>;
 
     /// Changed signal
     #[dbus_proxy(signal)]
@@ -0,0 +1,82 @@
+/// `(aiia{ss}iaiiasib)` structure
+#[derive(Debug, Clone, PartialEq, serde::Serialize, serde::Deserialize, zbus::zvariant::Type)]
+pub struct Rule {
+    pub field_0: Vec<i32>,
+    pub field_1: i32,
+    pub field_2: std::collections::HashMap<String, String>,
+    pub field_3: i32,
+    pub field_4: Vec<i32>,
+    pub field_5: i32,
+    pub field_6: Vec<String>,
+    pub field_7: i32,
+    pub field_8: bool,
+}
+
+/// `(so)` structure
+#[derive(Debug, Clone, PartialEq, serde::Serialize, serde::Deserialize, zbus::zvariant::Type)]
+pub struct BarplexSigArg1 {
+    pub field_0: String,
+    pub field_1: zbus::zvariant::OwnedObjectPath,
+}
+
+/// `(iiu)` structure
+#[derive(Debug, Clone, PartialEq, serde::Serialize, serde::Deserialize, zbus::zvariant::Type)]
+pub struct Bar {
+    pub field_0: i32,
+    pub field_1: i32,
+    pub field_2: u32,
+}
+
+/// `(iiav)` structure
+#[derive(Debug, PartialEq, serde::Serialize, serde::Deserialize, zbus::zvariant::Type)]
+pub struct MogrifyMeBar {
+    pub field_0: i32,
+    pub field_1: i32,
+    pub field_2: Vec<zbus::zvariant::OwnedValue>,
+}
+
+pub struct SampleInterface0;
+
+#[dbus_interface(name = "com.example.SampleInterface0")]
+impl SampleInterface0 {
+
+    /// BarplexSig method
+    async fn barplex_sig(&self, rule: Rule) -> zbus::fdo::Result<Vec<BarplexSigArg1>> {
+        todo!()
+    }
+
+    /// Bazify method
+    async fn bazify(&self, bar: Bar) -> zbus::fdo::Result<zbus::zvariant::OwnedValue> {
+        todo!()
+    }
+
+    /// Frobate method
+    #[deprecated]
+    #[dbus_interface(out_args("bar", "baz"))]
+    async fn frobate(&self, foz: i32, foo: i32) -> zbus::fdo::Result<(String, std::collections::HashMap<u32, String>)> {
+        todo!()
+    }
+
+    /// MogrifyMe method
+    async fn mogrify_me(&self, bar: MogrifyMeBar) -> zbus::fdo::Result<()> {
+        todo!()
+    }
+
+    /// Changed signal
+    #[dbus_interface(signal)]
+    async fn changed(signal_ctxt: &zbus::object_server::SignalContext<'_>, new_value: bool) -> zbus::Result<()>;
+
+    /// Changed2 signal
+    #[dbus_interface(signal)]
+    async fn changed2(signal_ctxt: &zbus::object_server::SignalContext<'_>, new_value: bool, new_value2: bool) -> zbus::Result<()>;
+
+    /// Bar property
+    #[dbus_interface(property)]
+    async fn bar(&self) -> zbus::fdo::Result<u8> {
+        todo!()
+    }
+    #[dbus_interface(property)]
+    async fn set_bar(&mut self, value: u8) -> zbus::fdo::Result<()> {
+        todo!()
+    }
+}
@@ -0,0 +1,87 @@
+/// `(qay)` structure
+#[derive(Debug, Clone, PartialEq, serde::Serialize, serde::Deserialize, zbus::zvariant::Type, zbus::zvariant::Value, zbus::zvariant::OwnedValue)]
+pub struct Address {
+    pub field_0: u16,
+    pub field_1: Vec<u8>,
+}
+
+/// `a{sa{st}}` dictionary
+pub type Statistics = std::collections::HashMap<String, std::collections::HashMap<String, u64>>;
+
+/// `(ssuo)` structure
+#[derive(Debug, Clone, PartialEq, serde::Serialize, serde::Deserialize, zbus::zvariant::Type)]
+pub struct Peer {
+    pub field_0: String,
+    pub field_1: String,
+    pub field_2: u32,
+    pub field_3: zbus::zvariant::OwnedObjectPath,
+}
+
+/// `a{sv}` dictionary
+pub type ConnectOptions = std::collections::HashMap<String, zbus::zvariant::OwnedValue>;
+
+/// `(s(uu)as)` structure
+#[derive(Debug, Clone, PartialEq, serde::Serialize, serde::Deserialize, zbus::zvariant::Type)]
+pub struct Route {
+    pub field_0: String,
+    pub field_1: RouteField1,
+    pub field_2: Vec<String>,
+}
+
+/// `(uu)` structure
+#[derive(Debug, Clone, PartialEq, serde::Serialize, serde::Deserialize, zbus::zvariant::Type)]
+pub struct RouteField1 {
+    pub field_0: u32,
+    pub field_1: u32,
+}
+
+/// `a{oa{sa{sv}}}` dictionary
+pub type Devices = std::collections::HashMap<zbus::zvariant::OwnedObjectPath, DevicesValue>;
+
+/// `a{sa{sv}}` dictionary
+pub type DevicesValue = std::collections::HashMap<String, std::collections::HashMap<String, zbus::zvariant::OwnedValue>>;
+
+#[dbus_proxy(interface = "com.example.SampleInterface1", assume_defaults = true)]
+trait SampleInterface1 {
+
+    /// Connect method
+    fn connect(&self, peer: &Peer, options: &ConnectOptions) -> zbus::Result<Route>;
+
+    /// GetManagedDevices method
+    fn get_managed_devices(&self) -> zbus::Result<Devices>;
+
+    /// ListPeers method
+    fn list_peers(&self, filter: std::collections::HashMap<&str, zbus::zvariant::Value<'_>>) -> zbus::Result<Vec<Peer>>;
+
+    /// Ping method
+    #[dbus_proxy(no_reply)]
+    fn ping(&self, peer: &Peer) -> zbus::Result<()>;
+
+    /// Reset method
+    #[deprecated]
+    fn reset(&self) -> zbus::Result<()>;
+
+    /// PeerAdded signal
+    #[dbus_proxy(signal)]
+    fn peer_added(&self, peer: Peer) -> zbus::Result<()>;
+
+    /// RouteChanged signal
+    #[dbus_proxy(signal)]
+    fn route_changed(&self, arg_1: Route) -> zbus::Result<()>;
+
+    /// Address property
+    #[dbus_proxy(property(emits_changed_signal = "const"))]
+    fn address(&self) -> zbus::Result<Address>;
+
+    /// Statistics property
+    #[dbus_proxy(property(emits_changed_signal = "invalidates"))]
+    fn statistics(&self) -> zbus::Result<Statistics>;
+
+    /// Timeout property
+    #[deprecated]
+    #[dbus_proxy(property)]
+    fn timeout(&self) -> zbus::Result<u32>;
+    #[deprecated]
+    #[dbus_proxy(property)]
+    fn set_timeout(&self, value: u32) -> zbus::Result<()>;
+}
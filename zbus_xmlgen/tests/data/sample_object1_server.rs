@@ -0,0 +1,107 @@
+/// `(qay)` structure
+#[derive(Debug, Clone, PartialEq, serde::Serialize, serde::Deserialize, zbus::zvariant::Type, zbus::zvariant::Value, zbus::zvariant::OwnedValue)]
+pub struct Address {
+    pub field_0: u16,
+    pub field_1: Vec<u8>,
+}
+
+/// `a{sa{st}}` dictionary
+pub type Statistics = std::collections::HashMap<String, std::collections::HashMap<String, u64>>;
+
+/// `(ssuo)` structure
+#[derive(Debug, Clone, PartialEq, serde::Serialize, serde::Deserialize, zbus::zvariant::Type)]
+pub struct Peer {
+    pub field_0: String,
+    pub field_1: String,
+    pub field_2: u32,
+    pub field_3: zbus::zvariant::OwnedObjectPath,
+}
+
+/// `a{sv}` dictionary
+pub type ConnectOptions = std::collections::HashMap<String, zbus::zvariant::OwnedValue>;
+
+/// `(s(uu)as)` structure
+#[derive(Debug, Clone, PartialEq, serde::Serialize, serde::Deserialize, zbus::zvariant::Type)]
+pub struct Route {
+    pub field_0: String,
+    pub field_1: RouteField1,
+    pub field_2: Vec<String>,
+}
+
+/// `(uu)` structure
+#[derive(Debug, Clone, PartialEq, serde::Serialize, serde::Deserialize, zbus::zvariant::Type)]
+pub struct RouteField1 {
+    pub field_0: u32,
+    pub field_1: u32,
+}
+
+/// `a{oa{sa{sv}}}` dictionary
+pub type Devices = std::collections::HashMap<zbus::zvariant::OwnedObjectPath, DevicesValue>;
+
+/// `a{sa{sv}}` dictionary
+pub type DevicesValue = std::collections::HashMap<String, std::collections::HashMap<String, zbus::zvariant::OwnedValue>>;
+
+pub struct SampleInterface1;
+
+#[dbus_interface(name = "com.example.SampleInterface1", annotation(name = "org.freedesktop.DBus.Property.EmitsChangedSignal", value = "invalidates"))]
+impl SampleInterface1 {
+
+    /// Connect method
+    async fn connect(&self, peer: Peer, options: ConnectOptions) -> zbus::fdo::Result<(Route,)> {
+        todo!()
+    }
+
+    /// GetManagedDevices method
+    async fn get_managed_devices(&self) -> zbus::fdo::Result<Devices> {
+        todo!()
+    }
+
+    /// ListPeers method
+    async fn list_peers(&self, filter: std::collections::HashMap<String, zbus::zvariant::OwnedValue>) -> zbus::fdo::Result<Vec<Peer>> {
+        todo!()
+    }
+
+    /// Ping method
+    #[dbus_interface(annotation(name = "org.freedesktop.DBus.Method.NoReply", value = "true"))]
+    async fn ping(&self, peer: Peer) -> zbus::fdo::Result<()> {
+        todo!()
+    }
+
+    /// Reset method
+    #[deprecated]
+    async fn reset(&self) -> zbus::fdo::Result<()> {
+        todo!()
+    }
+
+    /// PeerAdded signal
+    #[dbus_interface(signal)]
+    async fn peer_added(signal_ctxt: &zbus::object_server::SignalContext<'_>, peer: Peer) -> zbus::Result<()>;
+
+    /// RouteChanged signal
+    #[dbus_interface(signal)]
+    async fn route_changed(signal_ctxt: &zbus::object_server::SignalContext<'_>, arg_1: Route) -> zbus::Result<()>;
+
+    /// Address property
+    #[dbus_interface(property, annotation(name = "org.freedesktop.DBus.Property.EmitsChangedSignal", value = "const"))]
+    async fn address(&self) -> zbus::fdo::Result<Address> {
+        todo!()
+    }
+
+    /// Statistics property
+    #[dbus_interface(property)]
+    async fn statistics(&self) -> zbus::fdo::Result<Statistics> {
+        todo!()
+    }
+
+    /// Timeout property
+    #[deprecated]
+    #[dbus_interface(property, annotation(name = "org.freedesktop.DBus.Property.EmitsChangedSignal", value = "true"))]
+    async fn timeout(&self) -> zbus::fdo::Result<u32> {
+        todo!()
+    }
+    #[deprecated]
+    #[dbus_interface(property, annotation(name = "org.freedesktop.DBus.Property.EmitsChangedSignal", value = "true"))]
+    async fn set_timeout(&mut self, value: u32) -> zbus::fdo::Result<()> {
+        todo!()
+    }
+}
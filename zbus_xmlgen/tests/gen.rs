@@ -2,21 +2,16 @@ use pretty_assertions::assert_eq;
 use std::{env, error::Error, io::Write, path::Path, result::Result};
 
 use zbus_xml::Node;
-use zbus_xmlgen::GenTrait;
+use zbus_xmlgen::{GenInterface, GenTrait};
 
 macro_rules! gen_diff {
-    ($infile:literal, $outfile:literal) => {{
+    ($infile:literal, $outfile:literal, $gen:expr) => {{
         let input = include_str!(concat!("data/", $infile));
         let expected = include_str!(concat!("data/", $outfile));
         #[cfg(windows)]
         let expected = expected.replace("\r\n", "\n");
         let node = Node::from_reader(input.as_bytes())?;
-        let gen = GenTrait {
-            interface: &node.interfaces()[0],
-            path: None,
-            service: None,
-        }
-        .to_string();
+        let gen = $gen(&node.interfaces()[0]);
 
         if env::var("TEST_OVERWRITE").is_ok() {
             let path = Path::new(env!("CARGO_MANIFEST_DIR"))
@@ -39,5 +34,57 @@ macro_rules! gen_diff {
 
 #[test]
 fn sample_object0() -> Result<(), Box<dyn Error>> {
-    gen_diff!("sample_object0.xml", "sample_object0.rs")
+    gen_diff!("sample_object0.xml", "sample_object0.rs", |interface| {
+        GenTrait {
+            interface,
+            path: None,
+            service: None,
+        }
+        .to_string()
+    })
+}
+
+#[test]
+fn sample_object0_server() -> Result<(), Box<dyn Error>> {
+    gen_diff!(
+        "sample_object0.xml",
+        "sample_object0_server.rs",
+        |interface| GenInterface { interface }.to_string()
+    )
+}
+
+#[test]
+fn sample_object1() -> Result<(), Box<dyn Error>> {
+    gen_diff!("sample_object1.xml", "sample_object1.rs", |interface| {
+        GenTrait {
+            interface,
+            path: None,
+            service: None,
+        }
+        .to_string()
+    })
+}
+
+#[test]
+fn sample_object1_server() -> Result<(), Box<dyn Error>> {
+    gen_diff!(
+        "sample_object1.xml",
+        "sample_object1_server.rs",
+        |interface| GenInterface { interface }.to_string()
+    )
+}
+
+// Ensure the generated server skeletons build, as `zbus-xmlgen --server` writes them out.
+#[allow(dead_code, deprecated, unused_variables, clippy::disallowed_names)]
+mod sample_object0_server {
+    use zbus::dbus_interface;
+
+    include!("data/sample_object0_server.rs");
+}
+
+#[allow(dead_code, deprecated, unused_variables, clippy::disallowed_names)]
+mod sample_object1_server {
+    use zbus::dbus_interface;
+
+    include!("data/sample_object1_server.rs");
 }
@@ -7,6 +7,7 @@ use static_assertions::assert_impl_all;
 use std::fmt::{Display, Write};
 
 use crate::{
+    text::write_string_literal,
     value::{value_display_fmt, SignatureSeed},
     DynamicDeserialize, DynamicType, Error, Result, Signature, Type, Value,
 };
@@ -145,7 +146,8 @@ pub(crate) fn array_display_fmt(
     f: &mut std::fmt::Formatter<'_>,
     type_annotate: bool,
 ) -> std::fmt::Result {
-    // Print as string if it is a bytestring (i.e., first nul character is the last byte)
+    // Print as string if it is a bytestring (i.e., first nul character is the last byte) of
+    // UTF-8 text.
     if let [leading @ .., Value::U8(b'\0')] = array.as_ref() {
         if !leading.contains(&Value::U8(b'\0')) {
             let bytes = leading
@@ -156,10 +158,9 @@ pub(crate) fn array_display_fmt(
                 })
                 .collect::<Vec<_>>();
 
-            let string = String::from_utf8_lossy(&bytes);
-            write!(f, "b{:?}", string.as_ref())?;
-
-            return Ok(());
+            if let Ok(string) = std::str::from_utf8(&bytes) {
+                return write_string_literal(f, string, true);
+            }
         }
     }
 
@@ -7,6 +7,7 @@ use std::{marker::PhantomData, str};
 use std::os::fd::AsFd;
 
 use crate::{
+    container_depths::ContainerDepths,
     de::{DeserializerCommon, ValueParseStage},
     serialized::{Context, Format},
     signature_parser::SignatureParser,
@@ -43,6 +44,11 @@ where
         S::Error: Into<Error>,
     {
         assert_eq!(ctxt.format(), Format::DBus);
+        #[cfg(unix)]
+        let num_fds = fds.map_or(0, |fds| fds.len());
+        #[cfg(not(unix))]
+        let num_fds = 0;
+        ctxt.limits().check_data(bytes.len(), num_fds)?;
 
         let signature = signature.try_into().map_err(Into::into)?;
         let sig_parser = SignatureParser::new(signature);
@@ -55,7 +61,7 @@ where
             #[cfg(not(unix))]
             fds: PhantomData,
             pos: 0,
-            container_depths: Default::default(),
+            container_depths: ContainerDepths::new(ctxt.limits()),
             b: PhantomData,
         }))
     }
@@ -418,6 +424,7 @@ where
         de.0.container_depths = de.0.container_depths.inc_array()?;
 
         let len = B::read_u32(de.0.next_slice(4)?) as usize;
+        de.0.ctxt.limits().check_array_size(len)?;
         let element_signature = de.0.sig_parser.next_signature()?;
         let element_alignment = alignment_for_signature(&element_signature, Format::DBus)?;
         let mut element_signature_len = element_signature.len();
@@ -445,7 +452,8 @@ where
     where
         T: DeserializeSeed<'de>,
     {
-        let ctxt = Context::new_dbus(self.de.0.ctxt.position() + self.de.0.pos);
+        let ctxt = Context::new_dbus(self.de.0.ctxt.position() + self.de.0.pos)
+            .with_limits(self.de.0.ctxt.limits());
 
         let mut de = Deserializer::<B, F>(DeserializerCommon {
             ctxt,
@@ -641,7 +649,8 @@ where
                 let signature = Signature::try_from(slice)?;
                 let sig_parser = SignatureParser::new(signature);
 
-                let ctxt = Context::new(Format::DBus, self.de.0.ctxt.position() + value_start);
+                let ctxt = Context::new(Format::DBus, self.de.0.ctxt.position() + value_start)
+                    .with_limits(self.de.0.ctxt.limits());
                 let mut de = Deserializer::<B, F>(DeserializerCommon {
                     ctxt,
                     sig_parser,
@@ -9,15 +9,12 @@ use std::{
 
 use crate::{
     container_depths::ContainerDepths,
+    ser::NextSignature,
     serialized::{Context, Format},
-    signature_parser::SignatureParser,
     utils::*,
-    Basic, Error, ObjectPath, Result, Signature,
+    Basic, Error, ParsedSignature, Result, Signature,
 };
 
-#[cfg(unix)]
-use crate::Fd;
-
 /// Our D-Bus serialization implementation.
 pub(crate) struct Serializer<'ser, 'sig, B, W>(
     pub(crate) crate::SerializerCommon<'ser, 'sig, B, W>,
@@ -33,31 +30,25 @@ where
     /// Create a D-Bus Serializer struct instance.
     ///
     /// On Windows, there is no `fds` argument.
-    pub fn new<'w: 'ser, 'f: 'ser, S>(
-        signature: S,
+    pub fn new<'w: 'ser, 'f: 'ser>(
+        signature: &'sig ParsedSignature,
         writer: &'w mut W,
         #[cfg(unix)] fds: &'f mut crate::ser::FdList,
         ctxt: Context<B>,
-    ) -> Result<Self>
-    where
-        S: TryInto<Signature<'sig>>,
-        S::Error: Into<Error>,
-    {
+    ) -> Self {
         assert_eq!(ctxt.format(), Format::DBus);
 
-        let signature = signature.try_into().map_err(Into::into)?;
-        let sig_parser = SignatureParser::new(signature);
-        Ok(Self(crate::SerializerCommon {
+        Self(crate::SerializerCommon {
             ctxt,
-            sig_parser,
+            signature: NextSignature::Type(signature),
             writer,
             #[cfg(unix)]
             fds,
             bytes_written: 0,
             value_sign: None,
-            container_depths: Default::default(),
+            container_depths: ContainerDepths::new(ctxt.limits()),
             b: PhantomData,
-        }))
+        })
     }
 }
 
@@ -96,10 +87,9 @@ where
     serialize_basic!(serialize_i64(i64) write_i64);
 
     fn serialize_i32(self, v: i32) -> Result<()> {
-        match self.0.sig_parser.next_char()? {
+        match self.0.signature {
             #[cfg(unix)]
-            Fd::SIGNATURE_CHAR => {
-                self.0.sig_parser.skip_char()?;
+            NextSignature::Type(ParsedSignature::Fd) => {
                 self.0.add_padding(u32::alignment(Format::DBus))?;
                 let idx = self.0.add_fd(v)?;
                 self.0
@@ -140,19 +130,19 @@ where
                 &"D-Bus string type must not contain interior null bytes",
             ));
         }
-        let c = self.0.sig_parser.next_char()?;
-        if c == VARIANT_SIGNATURE_CHAR {
+        let signature = self.0.signature;
+        if let NextSignature::Type(ParsedSignature::Variant) = signature {
             self.0.value_sign = Some(signature_string!(v));
         }
 
-        match c {
-            ObjectPath::SIGNATURE_CHAR | <&str>::SIGNATURE_CHAR => {
+        match signature {
+            NextSignature::Type(ParsedSignature::ObjectPath | ParsedSignature::Str) => {
                 self.0.add_padding(<&str>::alignment(Format::DBus))?;
                 self.0
                     .write_u32::<B>(usize_to_u32(v.len()))
                     .map_err(|e| Error::InputOutput(e.into()))?;
             }
-            Signature::SIGNATURE_CHAR | VARIANT_SIGNATURE_CHAR => {
+            NextSignature::Type(ParsedSignature::Signature | ParsedSignature::Variant) => {
                 self.0
                     .write_u8(usize_to_u8(v.len()))
                     .map_err(|e| Error::InputOutput(e.into()))?;
@@ -162,17 +152,16 @@ where
                     "`{}`, `{}`, `{}` or `{}`",
                     <&str>::SIGNATURE_STR,
                     Signature::SIGNATURE_STR,
-                    ObjectPath::SIGNATURE_STR,
+                    crate::ObjectPath::SIGNATURE_STR,
                     VARIANT_SIGNATURE_CHAR,
                 );
                 return Err(serde::de::Error::invalid_type(
-                    serde::de::Unexpected::Char(c),
+                    serde::de::Unexpected::Char(signature.first_char()?),
                     &expected.as_str(),
                 ));
             }
         }
 
-        self.0.sig_parser.skip_char()?;
         self.0
             .write_all(v.as_bytes())
             .map_err(|e| Error::InputOutput(e.into()))?;
@@ -236,7 +225,7 @@ where
         variant_index: u32,
         variant: &'static str,
     ) -> Result<()> {
-        if self.0.sig_parser.next_char()? == <&str>::SIGNATURE_CHAR {
+        if let NextSignature::Type(ParsedSignature::Str) = self.0.signature {
             variant.serialize(self)
         } else {
             variant_index.serialize(self)
@@ -263,15 +252,12 @@ where
         T: ?Sized + Serialize,
     {
         self.0.prep_serialize_enum_variant(variant_index)?;
-        value.serialize(&mut *self)?;
-        // Skip the `)`.
-        self.0.sig_parser.skip_char()?;
 
-        Ok(())
+        value.serialize(self)
     }
 
     fn serialize_seq(self, _len: Option<usize>) -> Result<Self::SerializeSeq> {
-        self.0.sig_parser.skip_char()?;
+        let element_signature = self.0.signature.element()?;
         self.0.add_padding(ARRAY_ALIGNMENT_DBUS)?;
         // Length in bytes (unfortunately not the same as len passed to us here) which we
         // initially set to 0.
@@ -279,9 +265,7 @@ where
             .write_u32::<B>(0_u32)
             .map_err(|e| Error::InputOutput(e.into()))?;
 
-        let element_signature = self.0.sig_parser.next_signature()?;
-        let element_signature_len = element_signature.len();
-        let element_alignment = alignment_for_signature(&element_signature, self.0.ctxt.format())?;
+        let element_alignment = element_signature.alignment(Format::DBus);
 
         // D-Bus expects us to add padding for the first element even when there is no first
         // element (i-e empty array) so we add padding already.
@@ -292,8 +276,8 @@ where
         Ok(SeqSerializer {
             ser: self,
             start,
+            element_signature,
             element_alignment,
-            element_signature_len,
             first_padding,
         })
     }
@@ -319,7 +303,7 @@ where
     ) -> Result<Self::SerializeTupleVariant> {
         self.0.prep_serialize_enum_variant(variant_index)?;
 
-        StructSerializer::enum_variant(self).map(StructSeqSerializer::Struct)
+        StructSerializer::structure(self).map(StructSeqSerializer::Struct)
     }
 
     fn serialize_map(self, len: Option<usize>) -> Result<Self::SerializeMap> {
@@ -331,11 +315,13 @@ where
             return StructSerializer::unit(self).map(StructSeqSerializer::Struct);
         }
 
-        match self.0.sig_parser.next_char()? {
-            VARIANT_SIGNATURE_CHAR => {
+        match self.0.signature {
+            NextSignature::Type(ParsedSignature::Variant) => {
                 StructSerializer::variant(self).map(StructSeqSerializer::Struct)
             }
-            ARRAY_SIGNATURE_CHAR => self.serialize_seq(Some(len)).map(StructSeqSerializer::Seq),
+            NextSignature::Type(ParsedSignature::Array(_) | ParsedSignature::Dict { .. }) => {
+                self.serialize_seq(Some(len)).map(StructSeqSerializer::Seq)
+            }
             _ => StructSerializer::structure(self).map(StructSeqSerializer::Struct),
         }
     }
@@ -349,7 +335,7 @@ where
     ) -> Result<Self::SerializeStructVariant> {
         self.0.prep_serialize_enum_variant(variant_index)?;
 
-        StructSerializer::enum_variant(self).map(StructSeqSerializer::Struct)
+        StructSerializer::structure(self).map(StructSeqSerializer::Struct)
     }
 
     fn is_human_readable(&self) -> bool {
@@ -361,10 +347,10 @@ where
 pub struct SeqSerializer<'ser, 'sig, 'b, B, W> {
     ser: &'b mut Serializer<'ser, 'sig, B, W>,
     start: usize,
+    // signature of element
+    element_signature: NextSignature<'sig>,
     // alignment of element
     element_alignment: usize,
-    // size of element signature
-    element_signature_len: usize,
     // First element's padding
     first_padding: usize,
 }
@@ -375,11 +361,6 @@ where
     W: Write + Seek,
 {
     pub(self) fn end_seq(self) -> Result<()> {
-        self.ser
-            .0
-            .sig_parser
-            .skip_chars(self.element_signature_len)?;
-
         // Set size of array in bytes
         let array_len = self.ser.0.bytes_written - self.start;
         let len = usize_to_u32(array_len);
@@ -418,15 +399,9 @@ where
     where
         T: ?Sized + Serialize,
     {
-        // We want to keep parsing the same signature repeatedly for each element so we use a
-        // disposable clone.
-        let sig_parser = self.ser.0.sig_parser.clone();
-        self.ser.0.sig_parser = sig_parser.clone();
+        self.ser.0.signature = self.element_signature;
 
-        value.serialize(&mut *self.ser)?;
-        self.ser.0.sig_parser = sig_parser;
-
-        Ok(())
+        value.serialize(&mut *self.ser)
     }
 
     fn end(self) -> Result<()> {
@@ -437,8 +412,10 @@ where
 #[doc(hidden)]
 pub struct StructSerializer<'ser, 'sig, 'b, B, W> {
     ser: &'b mut Serializer<'ser, 'sig, B, W>,
-    // The number of `)` in the signature to skip at the end.
-    end_parens: u8,
+    // The signature of the structure.
+    signature: NextSignature<'sig>,
+    // The index of the next field.
+    field_index: usize,
     // The original container depths. We restore to that at the end.
     container_depths: ContainerDepths,
 }
@@ -450,38 +427,42 @@ where
 {
     fn variant(ser: &'b mut Serializer<'ser, 'sig, B, W>) -> Result<Self> {
         ser.0.add_padding(VARIANT_ALIGNMENT_DBUS)?;
+        let signature = ser.0.signature;
         let container_depths = ser.0.container_depths;
         ser.0.container_depths = ser.0.container_depths.inc_variant()?;
 
         Ok(Self {
             ser,
-            end_parens: 0,
+            signature,
+            field_index: 0,
             container_depths,
         })
     }
 
     fn structure(ser: &'b mut Serializer<'ser, 'sig, B, W>) -> Result<Self> {
-        let c = ser.0.sig_parser.next_char()?;
-        if c != STRUCT_SIG_START_CHAR && c != DICT_ENTRY_SIG_START_CHAR {
+        let signature = ser.0.signature;
+        if !matches!(
+            signature,
+            NextSignature::Type(ParsedSignature::Structure(_)) | NextSignature::DictEntry { .. }
+        ) {
             let expected = format!("`{STRUCT_SIG_START_STR}` or `{DICT_ENTRY_SIG_START_STR}`",);
 
             return Err(serde::de::Error::invalid_type(
-                serde::de::Unexpected::Char(c),
+                serde::de::Unexpected::Char(signature.first_char()?),
                 &expected.as_str(),
             ));
         }
 
-        let signature = ser.0.sig_parser.next_signature()?;
-        let alignment = alignment_for_signature(&signature, Format::DBus)?;
+        let alignment = signature.alignment(Format::DBus);
         ser.0.add_padding(alignment)?;
 
-        ser.0.sig_parser.skip_char()?;
         let container_depths = ser.0.container_depths;
         ser.0.container_depths = ser.0.container_depths.inc_structure()?;
 
         Ok(Self {
             ser,
-            end_parens: 1,
+            signature,
+            field_index: 0,
             container_depths,
         })
     }
@@ -490,21 +471,16 @@ where
         // serialize as a `0u8`
         serde::Serializer::serialize_u8(&mut *ser, 0)?;
 
+        let signature = ser.0.signature;
         let container_depths = ser.0.container_depths;
         Ok(Self {
             ser,
-            end_parens: 0,
+            signature,
+            field_index: 0,
             container_depths,
         })
     }
 
-    fn enum_variant(ser: &'b mut Serializer<'ser, 'sig, B, W>) -> Result<Self> {
-        let mut ser = Self::structure(ser)?;
-        ser.end_parens += 1;
-
-        Ok(ser)
-    }
-
     fn serialize_struct_element<T>(&mut self, name: Option<&'static str>, value: &T) -> Result<()>
     where
         T: ?Sized + Serialize,
@@ -520,11 +496,11 @@ where
                     .take()
                     .expect("Incorrect Value encoding");
 
-                let sig_parser = SignatureParser::new(signature);
+                let signature = ParsedSignature::try_from(&signature)?;
                 let bytes_written = self.ser.0.bytes_written;
                 let mut ser = Serializer(crate::SerializerCommon::<B, W> {
                     ctxt: self.ser.0.ctxt,
-                    sig_parser,
+                    signature: NextSignature::Type(&signature),
                     writer: self.ser.0.writer,
                     #[cfg(unix)]
                     fds: self.ser.0.fds,
@@ -538,14 +514,20 @@ where
 
                 Ok(())
             }
-            _ => value.serialize(&mut *self.ser),
+            _ => {
+                self.ser.0.signature = match self.signature {
+                    // The signature of a variant is serialized as its first field.
+                    NextSignature::Type(ParsedSignature::Variant) => self.signature,
+                    _ => NextSignature::Type(self.signature.field(self.field_index)?),
+                };
+                self.field_index += 1;
+
+                value.serialize(&mut *self.ser)
+            }
         }
     }
 
     fn end_struct(self) -> Result<()> {
-        if self.end_parens > 0 {
-            self.ser.0.sig_parser.skip_chars(self.end_parens as usize)?;
-        }
         // Restore the original container depths.
         self.ser.0.container_depths = self.container_depths;
 
@@ -626,38 +608,18 @@ where
         T: ?Sized + Serialize,
     {
         self.ser.0.add_padding(self.element_alignment)?;
+        self.ser.0.signature = NextSignature::Type(self.element_signature.field(0)?);
 
-        // We want to keep parsing the same signature repeatedly for each key so we use a
-        // disposable clone.
-        let sig_parser = self.ser.0.sig_parser.clone();
-        self.ser.0.sig_parser = sig_parser.clone();
-
-        // skip `{`
-        self.ser.0.sig_parser.skip_char()?;
-
-        key.serialize(&mut *self.ser)?;
-        self.ser.0.sig_parser = sig_parser;
-
-        Ok(())
+        key.serialize(&mut *self.ser)
     }
 
     fn serialize_value<T>(&mut self, value: &T) -> Result<()>
     where
         T: ?Sized + Serialize,
     {
-        // We want to keep parsing the same signature repeatedly for each key so we use a
-        // disposable clone.
-        let sig_parser = self.ser.0.sig_parser.clone();
-        self.ser.0.sig_parser = sig_parser.clone();
+        self.ser.0.signature = NextSignature::Type(self.element_signature.field(1)?);
 
-        // skip `{` and key char
-        self.ser.0.sig_parser.skip_chars(2)?;
-
-        value.serialize(&mut *self.ser)?;
-        // Restore the original parser
-        self.ser.0.sig_parser = sig_parser;
-
-        Ok(())
+        value.serialize(&mut *self.ser)
     }
 
     fn end(self) -> Result<()> {
@@ -104,6 +104,10 @@ impl<'k, 'v> Dict<'k, 'v> {
         &self.signature
     }
 
+    pub(crate) fn iter(&self) -> impl Iterator<Item = (&Value<'k>, &Value<'v>)> {
+        self.map.iter()
+    }
+
     pub(crate) fn try_to_owned(&self) -> crate::Result<Dict<'static, 'static>> {
         Ok(Dict {
             key_signature: self.key_signature.to_owned(),
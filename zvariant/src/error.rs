@@ -32,6 +32,30 @@ impl fmt::Display for MaxDepthExceeded {
     }
 }
 
+/// Enum representing the max size exceeded error.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MaxSizeExceeded {
+    /// The maximum allowed size of an array in encoding was exceeded.
+    Array,
+    /// The maximum allowed size of the encoded data was exceeded.
+    Message,
+    /// The maximum allowed number of file descriptors was exceeded.
+    Fds,
+}
+
+impl fmt::Display for MaxSizeExceeded {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Array => write!(
+                f,
+                "Maximum allowed size for arrays in encoding was exceeded"
+            ),
+            Self::Message => write!(f, "Maximum allowed size for encoded data was exceeded"),
+            Self::Fds => write!(f, "Maximum allowed number of file descriptors was exceeded"),
+        }
+    }
+}
+
 /// Error type used by zvariant API.
 #[derive(Debug)]
 #[non_exhaustive]
@@ -60,6 +84,11 @@ pub enum Error {
     OutOfBounds,
     /// The maximum allowed depth for containers in encoding was exceeded.
     MaxDepthExceeded(MaxDepthExceeded),
+    /// The maximum allowed size for arrays, data or file descriptors was exceeded.
+    MaxSizeExceeded(MaxSizeExceeded),
+    /// Invalid text representation of a value, at the byte offset in the first argument. Details
+    /// on the problem are in the second argument.
+    InvalidText(usize, String),
 }
 
 assert_impl_all!(Error: Send, Sync, Unpin);
@@ -74,6 +103,10 @@ impl PartialEq for Error {
             (Error::PaddingNot0(p), Error::PaddingNot0(other)) => p == other,
             (Error::UnknownFd, Error::UnknownFd) => true,
             (Error::MaxDepthExceeded(max1), Error::MaxDepthExceeded(max2)) => max1 == max2,
+            (Error::MaxSizeExceeded(max1), Error::MaxSizeExceeded(max2)) => max1 == max2,
+            (Error::InvalidText(offset1, msg1), Error::InvalidText(offset2, msg2)) => {
+                offset1 == offset2 && msg1 == msg2
+            }
             (_, _) => false,
         }
     }
@@ -115,6 +148,8 @@ impl fmt::Display for Error {
                 "Out of bounds range specified",
             ),
             Error::MaxDepthExceeded(max) => write!(f, "{max}"),
+            Error::MaxSizeExceeded(max) => write!(f, "{max}"),
+            Error::InvalidText(offset, msg) => write!(f, "Invalid text at offset {offset}: {msg}"),
         }
     }
 }
@@ -137,6 +172,8 @@ impl Clone for Error {
             }
             Error::OutOfBounds => Error::OutOfBounds,
             Error::MaxDepthExceeded(max) => Error::MaxDepthExceeded(*max),
+            Error::MaxSizeExceeded(max) => Error::MaxSizeExceeded(*max),
+            Error::InvalidText(offset, msg) => Error::InvalidText(*offset, msg.clone()),
         }
     }
 }
@@ -173,5 +210,11 @@ impl From<io::Error> for Error {
     }
 }
 
+impl From<crate::parsed_signature::Error> for Error {
+    fn from(val: crate::parsed_signature::Error) -> Self {
+        Error::Message(val.to_string())
+    }
+}
+
 /// Alias for a `Result` with the error type `zvariant::Error`.
 pub type Result<T> = result::Result<T, Error>;
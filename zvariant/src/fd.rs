@@ -2,7 +2,7 @@ use serde::{de::Error, Deserialize, Deserializer, Serialize, Serializer};
 use static_assertions::assert_impl_all;
 use std::os::fd::{self, AsFd, AsRawFd, BorrowedFd, RawFd};
 
-use crate::{serialized::Format, Basic, Signature, Type};
+use crate::{serialized::Format, Basic, ParsedSignature, Type};
 
 /// A file-descriptor type wrapper.
 ///
@@ -109,21 +109,24 @@ macro_rules! fd_impl {
         }
 
         impl Type for $i {
-            fn signature() -> Signature<'static> {
-                Signature::from_static_str_unchecked(Self::SIGNATURE_STR)
-            }
+            const SIGNATURE: &'static ParsedSignature = &ParsedSignature::Fd;
         }
     };
 }
 
 fd_impl!(Fd<'_>);
 
+/// The name of the newtype `Fd` is serialized as, wrapping the raw file descriptor.
+///
+/// This allows the `Value` serializer to tell a file descriptor apart from an arbitrary `i32`.
+pub(crate) const FD_NEWTYPE_NAME: &str = "$zvariant::private::Fd";
+
 impl Serialize for Fd<'_> {
     fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
     where
         S: Serializer,
     {
-        serializer.serialize_i32(self.as_raw_fd())
+        serializer.serialize_newtype_struct(FD_NEWTYPE_NAME, &self.as_raw_fd())
     }
 }
 
@@ -7,6 +7,7 @@ use std::{ffi::CStr, marker::PhantomData, str};
 use std::os::fd::AsFd;
 
 use crate::{
+    container_depths::ContainerDepths,
     de::{DeserializerCommon, ValueParseStage},
     framing_offset_size::FramingOffsetSize,
     framing_offsets::FramingOffsets,
@@ -40,6 +41,11 @@ where
         S::Error: Into<Error>,
     {
         assert_eq!(ctxt.format(), Format::GVariant);
+        #[cfg(unix)]
+        let num_fds = fds.map_or(0, |fds| fds.len());
+        #[cfg(not(unix))]
+        let num_fds = 0;
+        ctxt.limits().check_data(bytes.len(), num_fds)?;
 
         let signature = signature.try_into().map_err(Into::into)?;
         let sig_parser = SignatureParser::new(signature);
@@ -52,7 +58,7 @@ where
             #[cfg(not(unix))]
             fds: PhantomData,
             pos: 0,
-            container_depths: Default::default(),
+            container_depths: ContainerDepths::new(ctxt.limits()),
             b: PhantomData,
         }))
     }
@@ -64,7 +70,8 @@ macro_rules! deserialize_basic {
         where
             V: Visitor<'de>,
         {
-            let ctxt = Context::new_dbus(self.0.ctxt.position() + self.0.pos);
+            let ctxt = Context::new_dbus(self.0.ctxt.position() + self.0.pos)
+                .with_limits(self.0.ctxt.limits());
 
             let mut dbus_de = crate::dbus::Deserializer::<B, F>(DeserializerCommon::<B, F> {
                 ctxt,
@@ -219,7 +226,8 @@ where
 
             visitor.visit_none()
         } else {
-            let ctxt = Context::new(self.0.ctxt.format(), self.0.ctxt.position() + self.0.pos);
+            let ctxt = Context::new(self.0.ctxt.format(), self.0.ctxt.position() + self.0.pos)
+                .with_limits(self.0.ctxt.limits());
             let end = if fixed_sized_child {
                 self.0.bytes.len()
             } else {
@@ -475,6 +483,7 @@ where
         } else {
             (None, 0, None)
         };
+        de.0.ctxt.limits().check_array_size(len)?;
         let start = de.0.pos;
 
         if de.0.sig_parser.next_char()? == DICT_ENTRY_SIG_START_CHAR {
@@ -542,7 +551,8 @@ where
         let ctxt = Context::new(
             self.de.0.ctxt.format(),
             self.de.0.ctxt.position() + self.de.0.pos,
-        );
+        )
+        .with_limits(self.de.0.ctxt.limits());
         let end = self.element_end(true)?;
 
         let mut de = Deserializer::<B, F>(DeserializerCommon {
@@ -597,7 +607,8 @@ where
         let ctxt = Context::new(
             self.de.0.ctxt.format(),
             self.de.0.ctxt.position() + self.de.0.pos,
-        );
+        )
+        .with_limits(self.de.0.ctxt.limits());
         let element_end = self.element_end(false)?;
 
         let key_end = match self.key_offset_size {
@@ -643,7 +654,8 @@ where
         let ctxt = Context::new(
             self.de.0.ctxt.format(),
             self.de.0.ctxt.position() + self.de.0.pos,
-        );
+        )
+        .with_limits(self.de.0.ctxt.limits());
         let element_end = self.element_end(true)?;
         let value_end = match self.key_offset_size {
             Some(key_offset_size) => element_end - key_offset_size as usize,
@@ -706,7 +718,8 @@ where
         let ctxt = Context::new(
             self.de.0.ctxt.format(),
             self.de.0.ctxt.position() + self.de.0.pos,
-        );
+        )
+        .with_limits(self.de.0.ctxt.limits());
         let element_signature = self.de.0.sig_parser.next_signature()?;
         let fixed_sized_element = crate::utils::is_fixed_sized_signature(&element_signature)?;
         let element_end = if !fixed_sized_element {
@@ -864,7 +877,8 @@ where
                 let ctxt = Context::new(
                     self.de.0.ctxt.format(),
                     self.de.0.ctxt.position() + self.value_start,
-                );
+                )
+                .with_limits(self.de.0.ctxt.limits());
                 let mut de = Deserializer::<B, F>(DeserializerCommon {
                     ctxt,
                     sig_parser,
@@ -2,3 +2,6 @@ mod de;
 pub use de::*;
 mod ser;
 pub use ser::*;
+mod normal_form;
+mod view;
+pub use view::*;
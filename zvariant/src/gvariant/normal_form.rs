@@ -0,0 +1,366 @@
+use byteorder::ByteOrder;
+
+use super::view::{align, alignment, dict_entry_signature, fixed_size, View};
+use crate::{framing_offset_size::FramingOffsetSize, ObjectPath, ParsedSignature};
+
+// The normal form of the value viewed by `view`.
+//
+// The parts of the value that are invalid are replaced by the default value of their type, as
+// the GVariant specification requires:
+//
+// * fixed-sized values of the wrong size are zero-filled,
+// * booleans other than 0 are true,
+// * invalid strings, object paths and signatures are empty (or `/` for object paths),
+// * children with invalid framing offsets are the default value of their type,
+// * arrays with invalid framing offsets or sizes are empty,
+// * maybes of fixed-sized values with the wrong size are `Nothing` and
+// * variants without a valid signature, or nested deeper than `MAX_DEPTH`, contain the unit type.
+//
+// Padding is always written as 0 bytes and the framing offsets are recomputed, so this also
+// removes any excess bytes between the children.
+pub(super) fn normalize<B: ByteOrder>(view: &View<'_, '_, '_, B>) -> Vec<u8> {
+    normalize_nested(view, 0)
+}
+
+// The maximum nesting depth of containers, the same as GLib's. Variants can be nested without any
+// bound otherwise, since each comes with its own signature.
+const MAX_DEPTH: usize = 128;
+
+fn normalize_nested<B: ByteOrder>(view: &View<'_, '_, '_, B>, depth: usize) -> Vec<u8> {
+    let signature = view.signature();
+    let signature = &*signature;
+    let bytes = view.data().bytes();
+    if let Some(size) = fixed_size(signature) {
+        if bytes.len() != size {
+            return default(signature);
+        }
+    }
+
+    match signature {
+        ParsedSignature::Unit => vec![0],
+        ParsedSignature::Bool => vec![u8::from(bytes[0] != 0)],
+        ParsedSignature::U8
+        | ParsedSignature::I16
+        | ParsedSignature::U16
+        | ParsedSignature::I32
+        | ParsedSignature::U32
+        | ParsedSignature::I64
+        | ParsedSignature::U64
+        | ParsedSignature::F64
+        | ParsedSignature::Fd => bytes.to_vec(),
+        ParsedSignature::Str | ParsedSignature::Signature | ParsedSignature::ObjectPath => {
+            if is_valid_string(bytes, signature) {
+                bytes.to_vec()
+            } else {
+                default(signature)
+            }
+        }
+        ParsedSignature::Variant if depth >= MAX_DEPTH => default(signature),
+        ParsedSignature::Variant => match view.variant() {
+            Ok(child) => {
+                let mut bytes = normalize_nested(&child, depth + 1);
+                bytes.push(0);
+                match &*child.signature() {
+                    ParsedSignature::Unit => bytes.extend_from_slice(b"()"),
+                    signature => bytes.extend_from_slice(signature.to_string().as_bytes()),
+                }
+
+                bytes
+            }
+            Err(_) => default(signature),
+        },
+        ParsedSignature::Array(element) => normalize_array(view, element, depth),
+        ParsedSignature::Dict { key, value } => {
+            normalize_array(view, &dict_entry_signature(key, value), depth)
+        }
+        ParsedSignature::Structure(fields) => {
+            let children = fields
+                .iter()
+                .enumerate()
+                .map(|(i, field)| match view.child(i) {
+                    Ok(child) => normalize_nested(&child, depth + 1),
+                    Err(_) => default(field),
+                })
+                .collect();
+
+            write_structure(signature, children)
+        }
+        ParsedSignature::Maybe(child) => match view.maybe() {
+            Ok(Some(just)) => {
+                let mut bytes = normalize_nested(&just, depth + 1);
+                // Variable-sized values are followed by a 0 byte.
+                if fixed_size(child).is_none() {
+                    bytes.push(0);
+                }
+
+                bytes
+            }
+            Ok(None) | Err(_) => vec![],
+        },
+    }
+}
+
+fn normalize_array<B: ByteOrder>(
+    view: &View<'_, '_, '_, B>,
+    element: &ParsedSignature,
+    depth: usize,
+) -> Vec<u8> {
+    let len = match view.len() {
+        Ok(len) => len,
+        Err(_) => return vec![],
+    };
+    let elements = (0..len)
+        .map(|i| match view.child(i) {
+            Ok(child) => normalize_nested(&child, depth + 1),
+            Err(_) => default(element),
+        })
+        .collect();
+
+    write_array(element, elements)
+}
+
+// The normal form of the default value of `signature`.
+fn default(signature: &ParsedSignature) -> Vec<u8> {
+    if let Some(size) = fixed_size(signature) {
+        return vec![0; size];
+    }
+
+    match signature {
+        ParsedSignature::Str | ParsedSignature::Signature => vec![0],
+        ParsedSignature::ObjectPath => b"/\0".to_vec(),
+        // The unit type, a 0 byte, followed by the separator and its signature.
+        ParsedSignature::Variant => b"\0\0()".to_vec(),
+        ParsedSignature::Array(_) | ParsedSignature::Dict { .. } | ParsedSignature::Maybe(_) => {
+            vec![]
+        }
+        ParsedSignature::Structure(fields) => {
+            write_structure(signature, fields.iter().map(default).collect())
+        }
+        _ => unreachable!("fixed-sized types are handled above"),
+    }
+}
+
+fn is_valid_string(bytes: &[u8], signature: &ParsedSignature) -> bool {
+    let s = match bytes.split_last() {
+        Some((0, s)) if !s.contains(&0) => s,
+        _ => return false,
+    };
+    let s = match std::str::from_utf8(s) {
+        Ok(s) => s,
+        Err(_) => return false,
+    };
+
+    match signature {
+        ParsedSignature::ObjectPath => ObjectPath::try_from(s).is_ok(),
+        ParsedSignature::Signature => ParsedSignature::from_bytes(s.as_bytes()).is_ok(),
+        _ => true,
+    }
+}
+
+// Concatenate the encoded `elements` of an array, followed by their framing offsets if they are
+// variable-sized.
+fn write_array(element: &ParsedSignature, elements: Vec<Vec<u8>>) -> Vec<u8> {
+    if fixed_size(element).is_some() {
+        return elements.concat();
+    }
+
+    let alignment = alignment(element);
+    let mut bytes = vec![];
+    let mut offsets = Vec::with_capacity(elements.len());
+    for element in elements {
+        bytes.resize(align(bytes.len(), alignment), 0);
+        bytes.extend_from_slice(&element);
+        offsets.push(bytes.len());
+    }
+    write_offsets(&mut bytes, &offsets);
+
+    bytes
+}
+
+// Concatenate the encoded `fields` of a structure of type `signature`, followed by the framing
+// offsets of its variable-sized fields, except the last one.
+fn write_structure(signature: &ParsedSignature, fields: Vec<Vec<u8>>) -> Vec<u8> {
+    let signatures = match signature {
+        ParsedSignature::Structure(signatures) => signatures,
+        _ => unreachable!("not a structure signature"),
+    };
+    let num_fields = fields.len();
+    let mut bytes = vec![];
+    let mut offsets = vec![];
+    for (i, (signature, field)) in signatures.iter().zip(fields).enumerate() {
+        bytes.resize(align(bytes.len(), alignment(signature)), 0);
+        bytes.extend_from_slice(&field);
+        if i != num_fields - 1 && fixed_size(signature).is_none() {
+            offsets.push(bytes.len());
+        }
+    }
+
+    match fixed_size(signature) {
+        // Fixed-sized structures are padded to their alignment, and empty ones are a 0 byte.
+        Some(size) => bytes.resize(size, 0),
+        // The offsets are stored in reverse order.
+        None => {
+            offsets.reverse();
+            write_offsets(&mut bytes, &offsets);
+        }
+    }
+
+    bytes
+}
+
+fn write_offsets(bytes: &mut Vec<u8>, offsets: &[usize]) {
+    let offset_size = FramingOffsetSize::for_bare_container(bytes.len(), offsets.len());
+    for offset in offsets {
+        offset_size
+            .write_offset(bytes, *offset)
+            .expect("writing to a `Vec` can't fail");
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use byteorder::LE;
+
+    use crate::{
+        gvariant::View,
+        serialized::{Context, Data},
+        to_bytes, ObjectPath, OwnedValue, ParsedSignature, Type, Value,
+    };
+
+    fn view(bytes: &[u8], signature: &str) -> View<'static, 'static, 'static, LE> {
+        let data = Data::new(bytes.to_vec(), Context::<LE>::new_gvariant(0));
+
+        View::new(data, signature.parse().unwrap()).unwrap()
+    }
+
+    // Check that `bytes` is not in normal form, that it's normalized to `normal` and that
+    // `normal` is in normal form.
+    fn check_non_normal(bytes: &[u8], signature: &str, normal: &[u8]) {
+        let v = view(bytes, signature);
+        assert!(!v.is_normal_form());
+        let normalized = v.to_normal_form();
+        assert_eq!(normalized.data().bytes(), normal);
+        assert!(normalized.is_normal_form());
+    }
+
+    #[test]
+    fn normal() {
+        fn check<T>(value: &T)
+        where
+            T: serde::Serialize + Type,
+        {
+            let encoded = to_bytes(Context::<LE>::new_gvariant(0), value).unwrap();
+            let view = View::new(encoded.clone(), T::SIGNATURE.clone()).unwrap();
+            assert!(view.is_normal_form(), "{}", T::SIGNATURE);
+            assert_eq!(view.to_normal_form().data().bytes(), encoded.bytes());
+        }
+
+        check(&42u64);
+        check(&"hello");
+        check(&ObjectPath::try_from("/a/b").unwrap());
+        check(&(1u8, 2u32));
+        check(&("a", 1u8, vec!["b", "c"], 2u64, "d"));
+        check(&vec![(1u16, 2u16), (3, 4)]);
+        // Booleans are a single byte.
+        assert!(view(&[1], "b").is_normal_form());
+        assert!(view(&[1, 2], "(by)").is_normal_form());
+        // Fixed-sized structures are padded to their alignment.
+        assert!(view(&[1, 0, 0, 0, 2, 0, 0, 0, 3, 0, 0, 0], "(yuy)").is_normal_form());
+        check(&vec![vec![1u64], vec![], vec![2, 3]]);
+        check(&(0..300).map(|i| i.to_string()).collect::<Vec<_>>());
+        check(&(Some(1u32), None::<u32>, Some("a"), None::<String>));
+        check(&Value::from((42u32, "hi")));
+        check(&std::collections::HashMap::from([("a", Value::from(1u8))]));
+    }
+
+    #[test]
+    fn fixed_sized() {
+        // Booleans other than 0 and 1 are true.
+        check_non_normal(&[2], "b", &[1]);
+        check_non_normal(&[1, 7], "(yb)", &[1, 1]);
+        // Values of the wrong size are zero-filled.
+        check_non_normal(&[1, 2, 3], "u", &[0; 4]);
+        assert_eq!(
+            view(&[1, 2, 3], "u").deserialize_lenient::<u32>().unwrap(),
+            0
+        );
+        check_non_normal(&[], "(yu)", &[0; 8]);
+        // Non-zero padding.
+        check_non_normal(&[1, 9, 9, 9, 2, 0, 0, 0], "(yu)", &[1, 0, 0, 0, 2, 0, 0, 0]);
+        // The unit type is a 0 byte.
+        check_non_normal(&[1], "", &[0]);
+    }
+
+    #[test]
+    fn strings() {
+        // Missing nul terminator.
+        check_non_normal(b"abc", "s", b"\0");
+        // Embedded nul.
+        check_non_normal(b"a\0c\0", "s", b"\0");
+        // Invalid UTF-8.
+        check_non_normal(b"\xff\0", "s", b"\0");
+        assert_eq!(
+            view(b"\xff\0", "s")
+                .deserialize_lenient::<String>()
+                .unwrap(),
+            ""
+        );
+        check_non_normal(b"a/b\0", "o", b"/\0");
+        check_non_normal(b"a{\0", "g", b"\0");
+        assert!(view(b"a{sv}\0", "g").is_normal_form());
+    }
+
+    #[test]
+    fn containers() {
+        // Fixed-sized elements not filling the whole array.
+        check_non_normal(&[1, 2, 3], "au", &[]);
+        // Last framing offset out of bounds.
+        check_non_normal(b"a\0\xff", "as", &[]);
+        // The second element ends before it starts, making the third one start within it.
+        check_non_normal(b"a\0b\0c\0\x02\x01\x06", "as", b"a\0\0\0\x02\x03\x04");
+        assert_eq!(
+            view(b"a\0b\0c\0\x02\x01\x06", "as")
+                .deserialize_lenient::<Vec<String>>()
+                .unwrap(),
+            ["a", "", ""],
+        );
+        // The first element ends past the framing offsets.
+        check_non_normal(b"a\0b\0\x05\x04", "as", b"\0\0\x01\x02");
+        // The framing offset of the first field of a structure makes it empty.
+        check_non_normal(b"ab\0", "(ss)", b"\0\0\x01");
+        // Maybe of a fixed-sized value with the wrong size.
+        check_non_normal(&[1, 2], "mu", &[]);
+        // Non-zero byte after a variable-sized value in a maybe.
+        check_non_normal(b"a\0\x01", "ms", b"a\0\0");
+    }
+
+    #[test]
+    fn variants() {
+        // Missing separator.
+        check_non_normal(&[1, 2, 3], "v", b"\0\0()");
+        // Invalid signature.
+        check_non_normal(b"\x01\0z", "v", b"\0\0()");
+        // More than one complete type.
+        check_non_normal(b"\x01\x02\0yy", "v", b"\0\0()");
+        // Invalid content.
+        check_non_normal(b"\x01\x02\0u", "v", b"\0\0\0\0\0u");
+        let v = view(b"\0\0()", "v");
+        assert!(v.is_normal_form());
+        assert_eq!(*v.variant().unwrap().signature(), ParsedSignature::Unit);
+        let value = view(b"\x07\0y", "v")
+            .deserialize_lenient::<OwnedValue>()
+            .unwrap();
+        assert_eq!(value, OwnedValue::from(7u8));
+    }
+
+    #[test]
+    fn nesting_depth() {
+        // Variants in variants, as deep as GLib allows.
+        let nested = |depth| [&b"\0\0()"[..], &b"\0v".repeat(depth)].concat();
+        assert!(view(&nested(128), "v").is_normal_form());
+
+        // The deeper ones are replaced with the unit type.
+        check_non_normal(&nested(129), "v", &nested(128));
+        check_non_normal(&nested(1_000_000), "v", &nested(128));
+    }
+}
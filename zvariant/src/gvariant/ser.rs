@@ -10,10 +10,10 @@ use crate::{
     container_depths::ContainerDepths,
     framing_offset_size::FramingOffsetSize,
     framing_offsets::FramingOffsets,
+    ser::NextSignature,
     serialized::{Context, Format},
-    signature_parser::SignatureParser,
     utils::*,
-    Basic, Error, Result, Signature,
+    Error, ParsedSignature, Result, Signature,
 };
 
 /// Our serialization implementation.
@@ -31,31 +31,25 @@ where
     /// Create a GVariant Serializer struct instance.
     ///
     /// On Windows, the method doesn't have `fds` argument.
-    pub fn new<'w: 'ser, 'f: 'ser, S>(
-        signature: S,
+    pub fn new<'w: 'ser, 'f: 'ser>(
+        signature: &'sig ParsedSignature,
         writer: &'w mut W,
         #[cfg(unix)] fds: &'f mut crate::ser::FdList,
         ctxt: Context<B>,
-    ) -> Result<Self>
-    where
-        S: TryInto<Signature<'sig>>,
-        S::Error: Into<Error>,
-    {
+    ) -> Self {
         assert_eq!(ctxt.format(), Format::GVariant);
 
-        let signature = signature.try_into().map_err(Into::into)?;
-        let sig_parser = SignatureParser::new(signature);
-        Ok(Self(crate::SerializerCommon {
+        Self(crate::SerializerCommon {
             ctxt,
-            sig_parser,
+            signature: NextSignature::Type(signature),
             writer,
             #[cfg(unix)]
             fds,
             bytes_written: 0,
             value_sign: None,
-            container_depths: Default::default(),
+            container_depths: ContainerDepths::new(ctxt.limits()),
             b: PhantomData,
-        }))
+        })
     }
 
     #[cfg(not(feature = "option-as-array"))]
@@ -63,31 +57,25 @@ where
     where
         T: ?Sized + Serialize,
     {
-        let signature = self.0.sig_parser.next_signature()?;
-        let alignment = alignment_for_signature(&signature, self.0.ctxt.format())?;
-        let child_sig_parser = self.0.sig_parser.slice(1..);
-        let child_signature = child_sig_parser.next_signature()?;
-        let child_sig_len = child_signature.len();
-        let fixed_sized_child = crate::utils::is_fixed_sized_signature(&child_signature)?;
-
-        self.0.sig_parser.skip_char()?;
-
-        self.0.add_padding(alignment)?;
-
-        match value {
-            Some(value) => {
-                self.0.container_depths = self.0.container_depths.inc_maybe()?;
-                value.serialize(&mut *self)?;
-                self.0.container_depths = self.0.container_depths.dec_maybe();
-
-                if !fixed_sized_child {
-                    self.0
-                        .write_all(&b"\0"[..])
-                        .map_err(|e| Error::InputOutput(e.into()))?;
-                }
-            }
-            None => {
-                self.0.sig_parser.skip_chars(child_sig_len)?;
+        let signature = self.0.signature;
+        let child_signature = match signature {
+            NextSignature::Type(ParsedSignature::Maybe(child)) => child,
+            _ => return Err(signature.mismatch(&format!("`{MAYBE_SIGNATURE_CHAR}`"))),
+        };
+        let fixed_sized_child = child_signature.is_fixed_sized();
+
+        self.0.add_padding(signature.alignment(Format::GVariant))?;
+
+        if let Some(value) = value {
+            self.0.signature = NextSignature::Type(child_signature);
+            self.0.container_depths = self.0.container_depths.inc_maybe()?;
+            value.serialize(&mut *self)?;
+            self.0.container_depths = self.0.container_depths.dec_maybe();
+
+            if !fixed_sized_child {
+                self.0
+                    .write_all(&b"\0"[..])
+                    .map_err(|e| Error::InputOutput(e.into()))?;
             }
         }
 
@@ -98,11 +86,11 @@ where
 macro_rules! serialize_basic {
     ($method:ident, $type:ty) => {
         fn $method(self, v: $type) -> Result<()> {
-            let ctxt = Context::new_dbus(self.0.ctxt.position());
+            let ctxt = Context::new_dbus(self.0.ctxt.position()).with_limits(self.0.ctxt.limits());
             let bytes_written = self.0.bytes_written;
             let mut dbus_ser = crate::dbus::Serializer(crate::SerializerCommon::<B, W> {
                 ctxt,
-                sig_parser: self.0.sig_parser.clone(),
+                signature: self.0.signature,
                 writer: &mut self.0.writer,
                 #[cfg(unix)]
                 fds: self.0.fds,
@@ -115,7 +103,6 @@ macro_rules! serialize_basic {
             dbus_ser.$method(v)?;
 
             self.0.bytes_written = dbus_ser.0.bytes_written;
-            self.0.sig_parser = dbus_ser.0.sig_parser;
 
             Ok(())
         }
@@ -173,8 +160,7 @@ where
             ));
         }
 
-        let c = self.0.sig_parser.next_char()?;
-        if c == VARIANT_SIGNATURE_CHAR {
+        if let NextSignature::Type(ParsedSignature::Variant) = self.0.signature {
             self.0.value_sign = Some(signature_string!(v));
 
             // signature is serialized after the value in GVariant
@@ -183,7 +169,6 @@ where
 
         // Strings in GVariant format require no alignment.
 
-        self.0.sig_parser.skip_char()?;
         self.0
             .write_all(v.as_bytes())
             .map_err(|e| Error::InputOutput(e.into()))?;
@@ -245,7 +230,7 @@ where
         variant_index: u32,
         variant: &'static str,
     ) -> Result<()> {
-        if self.0.sig_parser.next_char()? == <&str>::SIGNATURE_CHAR {
+        if let NextSignature::Type(ParsedSignature::Str) = self.0.signature {
             variant.serialize(self)
         } else {
             variant_index.serialize(self)
@@ -277,19 +262,15 @@ where
     }
 
     fn serialize_seq(self, _len: Option<usize>) -> Result<Self::SerializeSeq> {
-        self.0.sig_parser.skip_char()?;
-        let element_signature = self.0.sig_parser.next_signature()?;
-        let element_signature_len = element_signature.len();
-        let element_alignment = alignment_for_signature(&element_signature, self.0.ctxt.format())?;
+        let element_signature = self.0.signature.element()?;
+        let element_alignment = element_signature.alignment(Format::GVariant);
 
-        let fixed_sized_child = crate::utils::is_fixed_sized_signature(&element_signature)?;
+        let fixed_sized_child = element_signature.is_fixed_sized();
         let offsets = (!fixed_sized_child).then(FramingOffsets::new);
 
-        let key_start = if self.0.sig_parser.next_char()? == DICT_ENTRY_SIG_START_CHAR {
-            let key_signature = Signature::from_str_unchecked(&element_signature[1..2]);
-            (!crate::utils::is_fixed_sized_signature(&key_signature)?).then_some(0)
-        } else {
-            None
+        let key_start = match element_signature {
+            NextSignature::DictEntry { key, .. } => (!key.is_fixed_sized()).then_some(0),
+            NextSignature::Type(_) => None,
         };
         self.0.add_padding(element_alignment)?;
         self.0.container_depths = self.0.container_depths.inc_array()?;
@@ -299,8 +280,8 @@ where
         Ok(SeqSerializer {
             ser: self,
             start,
+            element_signature,
             element_alignment,
-            element_signature_len,
             offsets,
             key_start,
         })
@@ -327,7 +308,7 @@ where
     ) -> Result<Self::SerializeTupleVariant> {
         self.0.prep_serialize_enum_variant(variant_index)?;
 
-        StructSerializer::enum_variant(self).map(StructSeqSerializer::Struct)
+        StructSerializer::structure(self).map(StructSeqSerializer::Struct)
     }
 
     fn serialize_map(self, len: Option<usize>) -> Result<Self::SerializeMap> {
@@ -339,11 +320,13 @@ where
             return StructSerializer::unit(self).map(StructSeqSerializer::Struct);
         }
 
-        match self.0.sig_parser.next_char()? {
-            VARIANT_SIGNATURE_CHAR => {
+        match self.0.signature {
+            NextSignature::Type(ParsedSignature::Variant) => {
                 StructSerializer::variant(self).map(StructSeqSerializer::Struct)
             }
-            ARRAY_SIGNATURE_CHAR => self.serialize_seq(Some(len)).map(StructSeqSerializer::Seq),
+            NextSignature::Type(ParsedSignature::Array(_) | ParsedSignature::Dict { .. }) => {
+                self.serialize_seq(Some(len)).map(StructSeqSerializer::Seq)
+            }
             _ => StructSerializer::structure(self).map(StructSeqSerializer::Struct),
         }
     }
@@ -357,7 +340,7 @@ where
     ) -> Result<Self::SerializeStructVariant> {
         self.0.prep_serialize_enum_variant(variant_index)?;
 
-        StructSerializer::enum_variant(self).map(StructSeqSerializer::Struct)
+        StructSerializer::structure(self).map(StructSeqSerializer::Struct)
     }
 
     fn is_human_readable(&self) -> bool {
@@ -369,10 +352,10 @@ where
 pub struct SeqSerializer<'ser, 'sig, 'b, B, W> {
     ser: &'b mut Serializer<'ser, 'sig, B, W>,
     start: usize,
+    // signature of element
+    element_signature: NextSignature<'sig>,
     // alignment of element
     element_alignment: usize,
-    // size of element signature
-    element_signature_len: usize,
     // All offsets
     offsets: Option<FramingOffsets>,
     // start of last dict-entry key written
@@ -385,10 +368,6 @@ where
     W: Write + Seek,
 {
     pub(self) fn end_seq(self) -> Result<()> {
-        self.ser
-            .0
-            .sig_parser
-            .skip_chars(self.element_signature_len)?;
         self.ser.0.container_depths = self.ser.0.container_depths.dec_array();
 
         let offsets = match self.offsets {
@@ -419,13 +398,8 @@ where
     where
         T: ?Sized + Serialize,
     {
-        // We want to keep parsing the same signature repeatedly for each element so we use a
-        // disposable clone.
-        let sig_parser = self.ser.0.sig_parser.clone();
-        self.ser.0.sig_parser = sig_parser.clone();
-
+        self.ser.0.signature = self.element_signature;
         value.serialize(&mut *self.ser)?;
-        self.ser.0.sig_parser = sig_parser;
 
         if let Some(ref mut offsets) = self.offsets {
             let offset = self.ser.0.bytes_written - self.start;
@@ -445,8 +419,10 @@ where
 pub struct StructSerializer<'ser, 'sig, 'b, B, W> {
     ser: &'b mut Serializer<'ser, 'sig, B, W>,
     start: usize,
-    // The number of `)` in the signature to skip at the end.
-    end_parens: u8,
+    // The signature of the structure.
+    signature: NextSignature<'sig>,
+    // The index of the next field.
+    field_index: usize,
     // All offsets
     offsets: Option<FramingOffsets>,
     // The original container depths. We restore to that at the end.
@@ -460,53 +436,47 @@ where
 {
     fn variant(ser: &'b mut Serializer<'ser, 'sig, B, W>) -> Result<Self> {
         ser.0.add_padding(VARIANT_ALIGNMENT_GVARIANT)?;
-        let offsets = if ser.0.sig_parser.next_char()? == STRUCT_SIG_START_CHAR {
-            Some(FramingOffsets::new())
-        } else {
-            None
-        };
+        let signature = ser.0.signature;
         let start = ser.0.bytes_written;
         let container_depths = ser.0.container_depths;
         ser.0.container_depths = ser.0.container_depths.inc_variant()?;
 
         Ok(Self {
             ser,
-            end_parens: 0,
-            offsets,
+            signature,
+            field_index: 0,
+            offsets: None,
             start,
             container_depths,
         })
     }
 
     fn structure(ser: &'b mut Serializer<'ser, 'sig, B, W>) -> Result<Self> {
-        let c = ser.0.sig_parser.next_char()?;
-        if c != STRUCT_SIG_START_CHAR && c != DICT_ENTRY_SIG_START_CHAR {
-            let expected = format!("`{STRUCT_SIG_START_STR}` or `{DICT_ENTRY_SIG_START_STR}`",);
+        let signature = ser.0.signature;
+        let offsets = match signature {
+            NextSignature::Type(ParsedSignature::Structure(_)) => Some(FramingOffsets::new()),
+            NextSignature::DictEntry { .. } => None,
+            _ => {
+                let expected = format!("`{STRUCT_SIG_START_STR}` or `{DICT_ENTRY_SIG_START_STR}`",);
 
-            return Err(serde::de::Error::invalid_type(
-                serde::de::Unexpected::Char(c),
-                &expected.as_str(),
-            ));
-        }
+                return Err(serde::de::Error::invalid_type(
+                    serde::de::Unexpected::Char(signature.first_char()?),
+                    &expected.as_str(),
+                ));
+            }
+        };
 
-        let signature = ser.0.sig_parser.next_signature()?;
-        let alignment = alignment_for_signature(&signature, Format::GVariant)?;
+        let alignment = signature.alignment(Format::GVariant);
         ser.0.add_padding(alignment)?;
 
-        ser.0.sig_parser.skip_char()?;
-
-        let offsets = if c == STRUCT_SIG_START_CHAR {
-            Some(FramingOffsets::new())
-        } else {
-            None
-        };
         let start = ser.0.bytes_written;
         let container_depths = ser.0.container_depths;
         ser.0.container_depths = ser.0.container_depths.inc_structure()?;
 
         Ok(Self {
             ser,
-            end_parens: 1,
+            signature,
+            field_index: 0,
             offsets,
             start,
             container_depths,
@@ -517,24 +487,19 @@ where
         // serialize as a `0u8`
         serde::Serializer::serialize_u8(&mut *ser, 0)?;
 
+        let signature = ser.0.signature;
         let start = ser.0.bytes_written;
         let container_depths = ser.0.container_depths;
         Ok(Self {
             ser,
-            end_parens: 0,
+            signature,
+            field_index: 0,
             offsets: None,
             start,
             container_depths,
         })
     }
 
-    fn enum_variant(ser: &'b mut Serializer<'ser, 'sig, B, W>) -> Result<Self> {
-        let mut ser = Self::structure(ser)?;
-        ser.end_parens += 1;
-
-        Ok(ser)
-    }
-
     fn serialize_struct_element<T>(&mut self, name: Option<&'static str>, value: &T) -> Result<()>
     where
         T: ?Sized + Serialize,
@@ -550,11 +515,11 @@ where
                     .take()
                     .expect("Incorrect Value encoding");
 
-                let sig_parser = SignatureParser::new(signature.clone());
+                let parsed = ParsedSignature::try_from(&signature)?;
                 let bytes_written = self.ser.0.bytes_written;
                 let mut ser = Serializer(crate::SerializerCommon::<B, W> {
                     ctxt: self.ser.0.ctxt,
-                    sig_parser,
+                    signature: NextSignature::Type(&parsed),
                     writer: self.ser.0.writer,
                     #[cfg(unix)]
                     fds: self.ser.0.fds,
@@ -578,10 +543,15 @@ where
                 Ok(())
             }
             _ => {
-                let element_signature = self.ser.0.sig_parser.next_signature()?;
-                let fixed_sized_element =
-                    crate::utils::is_fixed_sized_signature(&element_signature)?;
-
+                let element_signature = match self.signature {
+                    // The signature of a variant is serialized as its first field.
+                    NextSignature::Type(ParsedSignature::Variant) => self.signature,
+                    _ => NextSignature::Type(self.signature.field(self.field_index)?),
+                };
+                self.field_index += 1;
+                let fixed_sized_element = element_signature.is_fixed_sized();
+
+                self.ser.0.signature = element_signature;
                 value.serialize(&mut *self.ser)?;
 
                 if let Some(ref mut offsets) = self.offsets {
@@ -596,9 +566,6 @@ where
     }
 
     fn end_struct(self) -> Result<()> {
-        if self.end_parens > 0 {
-            self.ser.0.sig_parser.skip_chars(self.end_parens as usize)?;
-        }
         // Restore the original container depths.
         self.ser.0.container_depths = self.container_depths;
 
@@ -700,18 +667,9 @@ where
             self.key_start.replace(self.ser.0.bytes_written);
         }
 
-        // We want to keep parsing the same signature repeatedly for each key so we use a
-        // disposable clone.
-        let sig_parser = self.ser.0.sig_parser.clone();
-        self.ser.0.sig_parser = sig_parser.clone();
-
-        // skip `{`
-        self.ser.0.sig_parser.skip_char()?;
+        self.ser.0.signature = NextSignature::Type(self.element_signature.field(0)?);
 
-        key.serialize(&mut *self.ser)?;
-        self.ser.0.sig_parser = sig_parser;
-
-        Ok(())
+        key.serialize(&mut *self.ser)
     }
 
     fn serialize_value<T>(&mut self, value: &T) -> Result<()>
@@ -721,17 +679,8 @@ where
         // For non-fixed-sized keys, we must add the key offset after the value
         let key_offset = self.key_start.map(|start| self.ser.0.bytes_written - start);
 
-        // We want to keep parsing the same signature repeatedly for each key so we use a
-        // disposable clone.
-        let sig_parser = self.ser.0.sig_parser.clone();
-        self.ser.0.sig_parser = sig_parser.clone();
-
-        // skip `{` and key char
-        self.ser.0.sig_parser.skip_chars(2)?;
-
+        self.ser.0.signature = NextSignature::Type(self.element_signature.field(1)?);
         value.serialize(&mut *self.ser)?;
-        // Restore the original parser
-        self.ser.0.sig_parser = sig_parser;
 
         if let Some(key_offset) = key_offset {
             let entry_size = self.ser.0.bytes_written - self.key_start.unwrap_or(0);
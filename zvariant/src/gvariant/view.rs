@@ -0,0 +1,794 @@
+use std::{borrow::Cow, ops::Range};
+
+use byteorder::ByteOrder;
+use serde::{de::DeserializeOwned, Deserialize, Serialize};
+use static_assertions::assert_impl_all;
+
+use super::normal_form::normalize;
+use crate::{
+    framing_offset_size::FramingOffsetSize,
+    parsed_signature::Fields,
+    serialized::{Context, Data, Format},
+    to_bytes,
+    utils::subslice,
+    DynamicType, Error, ParsedSignature, Result, Signature,
+};
+
+/// A lazily-evaluated view into GVariant-encoded data.
+///
+/// Unlike the [`Deserializer`], which decodes the whole value eagerly, a `View` only decodes
+/// what is asked of it. Elements of arrays and dictionaries, fields of structures, the contents
+/// of variants and maybes are all returned as views of their own, using the framing offsets of
+/// the containers to locate them, borrowing the signature of their parent. Array elements are
+/// located in constant time, regardless of the size of the array. The leaves can then be
+/// deserialized into the specific type through [`View::deserialize`], without copying the
+/// underlying bytes where the type allows.
+///
+/// # Examples
+///
+/// ```
+/// use std::collections::HashMap;
+///
+/// use byteorder::LE;
+/// use zvariant::{gvariant::View, serialized::Context, to_bytes, Type, Value};
+///
+/// let ctxt = Context::<LE>::new_gvariant(0);
+/// let commit = (
+///     "Initial commit",
+///     vec![(1u32, "a"), (2, "b"), (3, "c")],
+///     HashMap::from([("version", Value::from(2u16))]),
+/// );
+/// let encoded = to_bytes(ctxt, &commit).unwrap();
+/// let signature = <(&str, Vec<(u32, &str)>, HashMap<&str, Value<'_>>)>::SIGNATURE;
+///
+/// let view = View::new(encoded, signature.clone()).unwrap();
+/// assert_eq!(view.len().unwrap(), 3);
+/// assert_eq!(view.child(0).unwrap().deserialize::<&str>().unwrap(), "Initial commit");
+///
+/// let entries = view.child(1).unwrap();
+/// assert_eq!(entries.len().unwrap(), 3);
+/// let entry = entries.child(2).unwrap();
+/// assert_eq!(entry.deserialize::<(u32, &str)>().unwrap(), (3, "c"));
+/// assert_eq!(entry.child(1).unwrap().deserialize::<&str>().unwrap(), "c");
+///
+/// let metadata = view.child(2).unwrap();
+/// let version = metadata.lookup("version").unwrap().unwrap();
+/// assert_eq!(version.signature().to_string(), "v");
+/// assert_eq!(version.variant().unwrap().deserialize::<u16>().unwrap(), 2);
+/// assert!(metadata.lookup("branch").unwrap().is_none());
+/// ```
+///
+/// [`Deserializer`]: struct.Deserializer.html
+#[derive(Debug, Clone)]
+pub struct View<'bytes, 'fds, 'sig, B: ByteOrder> {
+    data: Data<'bytes, 'fds, B>,
+    signature: ViewSignature<'sig>,
+}
+
+assert_impl_all!(View<'_, '_, '_, byteorder::NativeEndian>: Send, Sync, Unpin);
+
+// The signature of a viewed value. Children borrow the signature of their parent so walking a
+// value doesn't clone (or build) any signature.
+#[derive(Debug, Clone)]
+enum ViewSignature<'sig> {
+    Owned(ParsedSignature),
+    Borrowed(&'sig ParsedSignature),
+    // A dictionary entry, i.e. a structure of the key and the value.
+    Entry {
+        key: &'sig ParsedSignature,
+        value: &'sig ParsedSignature,
+    },
+}
+
+impl ViewSignature<'_> {
+    fn borrow(&self) -> ViewSignature<'_> {
+        match self {
+            Self::Owned(signature) => ViewSignature::Borrowed(signature),
+            Self::Borrowed(signature) => ViewSignature::Borrowed(signature),
+            Self::Entry { key, value } => ViewSignature::Entry { key, value },
+        }
+    }
+}
+
+impl<'bytes, 'fds, 'sig, B: ByteOrder> View<'bytes, 'fds, 'sig, B> {
+    /// Create a view of `data`, which contains a GVariant-encoded value of type `signature`.
+    ///
+    /// Nothing is decoded at this point so errors in the data are only reported when the affected
+    /// part of the data is accessed.
+    pub fn new(data: Data<'bytes, 'fds, B>, signature: ParsedSignature) -> Result<Self> {
+        if data.context().format() != Format::GVariant {
+            return Err(Error::IncompatibleFormat(
+                Signature::from(&signature),
+                data.context().format(),
+            ));
+        }
+
+        Ok(Self {
+            data,
+            signature: ViewSignature::Owned(signature),
+        })
+    }
+
+    /// The signature of the viewed value.
+    ///
+    /// The signature of dictionary entries is built on demand, hence the [`Cow`].
+    pub fn signature(&self) -> Cow<'_, ParsedSignature> {
+        match &self.signature {
+            ViewSignature::Owned(signature) => Cow::Borrowed(signature),
+            ViewSignature::Borrowed(signature) => Cow::Borrowed(signature),
+            ViewSignature::Entry { key, value } => Cow::Owned(dict_entry_signature(key, value)),
+        }
+    }
+
+    /// The encoded data of the viewed value.
+    pub fn data(&self) -> &Data<'bytes, 'fds, B> {
+        &self.data
+    }
+
+    /// The number of children of the viewed value.
+    ///
+    /// This is the number of elements for arrays and dictionaries, the number of fields for
+    /// structures and 0 or 1 for maybes. Other types do not have children.
+    pub fn len(&self) -> Result<usize> {
+        let bytes = self.data.bytes();
+
+        let signature = match &self.signature {
+            ViewSignature::Owned(signature) => signature,
+            ViewSignature::Borrowed(signature) => *signature,
+            ViewSignature::Entry { .. } => return Ok(2),
+        };
+        match signature {
+            ParsedSignature::Unit => Ok(0),
+            ParsedSignature::Array(child) => array_len(bytes, fixed_size(child)),
+            ParsedSignature::Dict { key, value } => {
+                array_len(bytes, structure_fixed_size([&**key, &**value].into_iter()))
+            }
+            ParsedSignature::Structure(fields) => Ok(fields.len()),
+            ParsedSignature::Maybe(_) => Ok(usize::from(!bytes.is_empty())),
+            _ => Err(self.signature_mismatch("a container signature")),
+        }
+    }
+
+    /// If the viewed value has no children.
+    ///
+    /// See [`View::len`] for details.
+    pub fn is_empty(&self) -> Result<bool> {
+        self.len().map(|len| len == 0)
+    }
+
+    /// The child of the viewed value at `index`.
+    ///
+    /// See [`View::len`] for what the children are. Entries of dictionaries are viewed as
+    /// structures of the key and the value.
+    pub fn child(&self, index: usize) -> Result<View<'bytes, 'fds, '_, B>> {
+        let bytes = self.data.bytes();
+
+        let signature = match &self.signature {
+            ViewSignature::Owned(signature) => signature,
+            ViewSignature::Borrowed(signature) => *signature,
+            ViewSignature::Entry { key, value } => {
+                let fields = [*key, *value];
+                let signature = fields.get(index).ok_or(Error::OutOfBounds)?;
+
+                return Ok(View {
+                    data: self
+                        .data
+                        .slice(structure_child(bytes, fields.into_iter(), index)?),
+                    signature: ViewSignature::Borrowed(signature),
+                });
+            }
+        };
+        let (range, signature) = match signature {
+            ParsedSignature::Array(child) => (
+                array_child(bytes, fixed_size(child), alignment(child), index)?,
+                ViewSignature::Borrowed(child),
+            ),
+            ParsedSignature::Dict { key, value } => {
+                let fields = [&**key, &**value].into_iter();
+                let range = array_child(
+                    bytes,
+                    structure_fixed_size(fields.clone()),
+                    structure_alignment(fields),
+                    index,
+                )?;
+
+                (range, ViewSignature::Entry { key, value })
+            }
+            ParsedSignature::Structure(fields) => {
+                let signature = fields.get(index).ok_or(Error::OutOfBounds)?;
+
+                (
+                    structure_child(bytes, fields_iter(fields), index)?,
+                    ViewSignature::Borrowed(signature),
+                )
+            }
+            ParsedSignature::Maybe(child) if index == 0 => {
+                let range = self.maybe_range(child)?.ok_or(Error::OutOfBounds)?;
+
+                (range, ViewSignature::Borrowed(child))
+            }
+            ParsedSignature::Unit | ParsedSignature::Maybe(_) => return Err(Error::OutOfBounds),
+            _ => return Err(self.signature_mismatch("a container signature")),
+        };
+
+        Ok(View {
+            data: self.data.slice(range),
+            signature,
+        })
+    }
+
+    /// Iterate over the children of the viewed value.
+    ///
+    /// See [`View::len`] for what the children are.
+    pub fn children(&self) -> Result<impl Iterator<Item = Result<View<'bytes, 'fds, '_, B>>> + '_> {
+        let len = self.len()?;
+
+        Ok((0..len).map(|i| self.child(i)))
+    }
+
+    /// The value contained in the viewed variant.
+    pub fn variant(&self) -> Result<View<'bytes, 'fds, 'static, B>> {
+        if !matches!(self.parsed_signature(), Some(ParsedSignature::Variant)) {
+            return Err(self.signature_mismatch("a variant signature"));
+        }
+
+        // The value is followed by a 0 byte and the signature of the value.
+        let bytes = self.data.bytes();
+        let separator = bytes
+            .iter()
+            .rposition(|b| *b == 0)
+            .ok_or(Error::MissingFramingOffset)?;
+        let signature = match &bytes[separator + 1..] {
+            b"()" => ParsedSignature::Unit,
+            signature => {
+                let parsed = ParsedSignature::from_bytes(signature)?;
+                // Several complete types are parsed as a structure, while the empty signature is
+                // parsed as the unit type.
+                if signature.is_empty() || parsed.string_len() != signature.len() {
+                    return Err(Error::Message(format!(
+                        "`{}` is not a single complete type",
+                        String::from_utf8_lossy(signature),
+                    )));
+                }
+
+                parsed
+            }
+        };
+
+        Ok(View {
+            data: self.data.slice(..separator),
+            signature: ViewSignature::Owned(signature),
+        })
+    }
+
+    /// The value contained in the viewed maybe, if any.
+    pub fn maybe(&self) -> Result<Option<View<'bytes, 'fds, '_, B>>> {
+        match self.parsed_signature() {
+            Some(ParsedSignature::Maybe(child)) => Ok(self.maybe_range(child)?.map(|range| View {
+                data: self.data.slice(range),
+                signature: ViewSignature::Borrowed(child),
+            })),
+            _ => Err(self.signature_mismatch("a maybe signature")),
+        }
+    }
+
+    /// The value for `key` in the viewed dictionary, if any.
+    ///
+    /// Since GVariant dictionaries are not sorted, this is a linear search through the entries.
+    /// The keys are compared in their encoded form, so they are not decoded.
+    pub fn lookup<K>(&self, key: &K) -> Result<Option<View<'bytes, 'fds, '_, B>>>
+    where
+        K: ?Sized + Serialize + DynamicType,
+    {
+        let fields = match self.parsed_signature() {
+            Some(ParsedSignature::Dict { key, value }) => [&**key, &**value],
+            _ => return Err(self.signature_mismatch("a dictionary signature")),
+        };
+        let ctxt = Context::<B>::new_gvariant(0);
+        let key = to_bytes(ctxt, key)?;
+
+        for entry in self.children()? {
+            let entry = entry?.data;
+            let bytes = entry.bytes();
+            let key_range = structure_child(bytes, fields.into_iter(), 0)?;
+            if bytes[key_range] == *key.bytes() {
+                return Ok(Some(View {
+                    data: entry.slice(structure_child(bytes, fields.into_iter(), 1)?),
+                    signature: ViewSignature::Borrowed(fields[1]),
+                }));
+            }
+        }
+
+        Ok(None)
+    }
+
+    /// If the viewed value is in normal form.
+    ///
+    /// The GVariant specification allows only one encoding for each value, its normal form. Data
+    /// from untrusted sources may deviate from it, e.g. through non-zero padding, framing offsets
+    /// that are out of bounds or booleans other than 0 and 1. Such data is either rejected by the
+    /// [`Deserializer`] or may be decoded differently by other GVariant implementations.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use byteorder::LE;
+    /// use zvariant::{
+    ///     gvariant::View,
+    ///     serialized::{Context, Data},
+    /// };
+    ///
+    /// // An array of strings with its framing offset pointing past the end.
+    /// let data = Data::new(&b"hi\0\x09"[..], Context::<LE>::new_gvariant(0));
+    /// let view = View::new(data, "as".parse().unwrap()).unwrap();
+    /// assert!(!view.is_normal_form());
+    ///
+    /// // Such an array is empty.
+    /// assert!(view.to_normal_form().data().is_empty());
+    /// let strings: Vec<String> = view.deserialize_lenient().unwrap();
+    /// assert!(strings.is_empty());
+    /// ```
+    ///
+    /// [`Deserializer`]: struct.Deserializer.html
+    pub fn is_normal_form(&self) -> bool {
+        normalize(self) == self.data.bytes()
+    }
+
+    /// A view of the normal form of the viewed value.
+    ///
+    /// The invalid parts of the value are replaced by the default value of their type (zero,
+    /// empty, `/` for object paths or a unit variant), as the GVariant specification requires.
+    /// Since any data is accepted, this never fails. Data that is already in normal form is left
+    /// as is.
+    pub fn to_normal_form(&self) -> View<'static, '_, '_, B> {
+        let bytes = normalize(self);
+        let ctxt = self.data.context();
+        #[cfg(unix)]
+        let data = Data::new_borrowed_fds(bytes, ctxt, self.data.fds());
+        #[cfg(not(unix))]
+        let data = Data::new(bytes, ctxt);
+
+        View {
+            data,
+            signature: self.signature.borrow(),
+        }
+    }
+
+    /// Deserialize the viewed value as `T`, leniently.
+    ///
+    /// Unlike [`View::deserialize`], this doesn't fail on data that is not in normal form but
+    /// decodes the value as described in [`View::to_normal_form`]. This still fails if `T` doesn't
+    /// match the signature of the viewed value.
+    pub fn deserialize_lenient<T>(&self) -> Result<T>
+    where
+        T: DeserializeOwned,
+    {
+        self.to_normal_form().deserialize()
+    }
+
+    /// Deserialize the viewed value as `T`.
+    pub fn deserialize<'d, T>(&'d self) -> Result<T>
+    where
+        T: Deserialize<'d>,
+    {
+        // The deserializer needs the signature as a string, which only has to be built for
+        // containers.
+        let signature = match self.parsed_signature().and_then(|s| s.as_static_str()) {
+            Some(signature) => Signature::from_static_str_unchecked(signature),
+            None => Signature::from(&*self.signature()),
+        };
+
+        self.data
+            .deserialize_for_signature(signature)
+            .map(|(value, _)| value)
+    }
+
+    // The signature of the viewed value, unless it's a dictionary entry.
+    fn parsed_signature(&self) -> Option<&ParsedSignature> {
+        match &self.signature {
+            ViewSignature::Owned(signature) => Some(signature),
+            ViewSignature::Borrowed(signature) => Some(signature),
+            ViewSignature::Entry { .. } => None,
+        }
+    }
+
+    fn maybe_range(&self, child: &ParsedSignature) -> Result<Option<Range<usize>>> {
+        let len = self.data.len();
+
+        match fixed_size(child) {
+            _ if len == 0 => Ok(None),
+            Some(size) if size == len => Ok(Some(0..len)),
+            Some(size) => Err(serde::de::Error::invalid_length(
+                len,
+                &format!("0 or {size}").as_str(),
+            )),
+            // Variable-sized values are followed by a 0 byte.
+            None => Ok(Some(0..len - 1)),
+        }
+    }
+
+    fn signature_mismatch(&self, expected: &str) -> Error {
+        Error::SignatureMismatch(Signature::from(&*self.signature()), expected.to_string())
+    }
+}
+
+pub(super) fn dict_entry_signature(
+    key: &ParsedSignature,
+    value: &ParsedSignature,
+) -> ParsedSignature {
+    ParsedSignature::structure([key.clone(), value.clone()])
+}
+
+// The number of elements in the encoded array `bytes`, whose elements are of `element_size` if
+// they are fixed-sized.
+fn array_len(bytes: &[u8], element_size: Option<usize>) -> Result<usize> {
+    if bytes.is_empty() {
+        return Ok(0);
+    }
+
+    match element_size {
+        Some(size) if bytes.len() % size == 0 => Ok(bytes.len() / size),
+        Some(size) => Err(serde::de::Error::invalid_length(
+            bytes.len(),
+            &format!("a multiple of {size}").as_str(),
+        )),
+        None => {
+            let (offsets_start, offset_size) = array_offsets(bytes)?;
+
+            Ok((bytes.len() - offsets_start) / offset_size as usize)
+        }
+    }
+}
+
+// The range of the element at `index` in the encoded array `bytes`, given the size (if fixed) and
+// alignment of the elements.
+fn array_child(
+    bytes: &[u8],
+    element_size: Option<usize>,
+    element_alignment: usize,
+    index: usize,
+) -> Result<Range<usize>> {
+    if index >= array_len(bytes, element_size)? {
+        return Err(Error::OutOfBounds);
+    }
+
+    if let Some(size) = element_size {
+        return Ok(index * size..(index + 1) * size);
+    }
+
+    // Each framing offset gives the end of the respective element.
+    let (offsets_start, offset_size) = array_offsets(bytes)?;
+    let read_offset = |i: usize| {
+        let start = offsets_start + i * offset_size as usize;
+        let offset = subslice(bytes, start..start + offset_size as usize)?;
+
+        Ok::<_, Error>(offset_size.read_last_offset_from_buffer(offset))
+    };
+    let start = match index {
+        0 => 0,
+        _ => align(read_offset(index - 1)?, element_alignment),
+    };
+    let end = read_offset(index)?;
+    check_range(start, end, offsets_start)?;
+
+    Ok(start..end)
+}
+
+// The start of the framing offsets of the encoded array `bytes` of variable-sized elements, and
+// the size of each offset.
+fn array_offsets(bytes: &[u8]) -> Result<(usize, FramingOffsetSize)> {
+    let offset_size = FramingOffsetSize::for_encoded_container(bytes.len());
+    // The last offset is the end of the last element, hence the start of the offsets.
+    let offsets_start = offset_size.read_last_offset_from_buffer(bytes);
+    if offsets_start > bytes.len() || (bytes.len() - offsets_start) % offset_size as usize != 0 {
+        return Err(Error::MissingFramingOffset);
+    }
+
+    Ok((offsets_start, offset_size))
+}
+
+// The range of the field at `index` in the encoded structure `bytes`, of `fields` types.
+fn structure_child<'s, F>(bytes: &[u8], fields: F, index: usize) -> Result<Range<usize>>
+where
+    F: Iterator<Item = &'s ParsedSignature> + Clone,
+{
+    let offset_size = FramingOffsetSize::for_encoded_container(bytes.len());
+    let num_fields = fields.clone().count();
+    // The framing offsets give the end of each variable-sized field, except the last field. They
+    // are stored in reverse order at the end of the structure.
+    let total_offsets = fields
+        .clone()
+        .take(num_fields.saturating_sub(1))
+        .filter(|field| fixed_size(field).is_none())
+        .count();
+    let offsets_start = bytes
+        .len()
+        .checked_sub(total_offsets * offset_size as usize)
+        .ok_or(Error::MissingFramingOffset)?;
+    let mut num_offsets = 0;
+    let mut start = 0;
+    for (i, field) in fields.enumerate() {
+        start = align(start, alignment(field));
+        let end = match fixed_size(field) {
+            Some(size) => start + size,
+            None if i == num_fields - 1 => offsets_start,
+            None => {
+                num_offsets += 1;
+                let offset_end = bytes.len() - (num_offsets - 1) * offset_size as usize;
+                let offset_start = offset_end - offset_size as usize;
+
+                offset_size.read_last_offset_from_buffer(&bytes[offset_start..offset_end])
+            }
+        };
+        check_range(start, end, offsets_start)?;
+        if i == index {
+            return Ok(start..end);
+        }
+
+        start = end;
+    }
+
+    Err(Error::OutOfBounds)
+}
+
+fn check_range(start: usize, end: usize, limit: usize) -> Result<()> {
+    if start > end || end > limit {
+        return Err(Error::OutOfBounds);
+    }
+
+    Ok(())
+}
+
+pub(super) fn align(offset: usize, alignment: usize) -> usize {
+    (offset + alignment - 1) & !(alignment - 1)
+}
+
+// The alignment of `signature` in the GVariant format.
+pub(super) fn alignment(signature: &ParsedSignature) -> usize {
+    match signature {
+        ParsedSignature::Unit
+        | ParsedSignature::U8
+        | ParsedSignature::Bool
+        | ParsedSignature::Str
+        | ParsedSignature::Signature
+        | ParsedSignature::ObjectPath => 1,
+        ParsedSignature::I16 | ParsedSignature::U16 => 2,
+        ParsedSignature::I32 | ParsedSignature::U32 | ParsedSignature::Fd => 4,
+        ParsedSignature::I64
+        | ParsedSignature::U64
+        | ParsedSignature::F64
+        | ParsedSignature::Variant => 8,
+        ParsedSignature::Array(child) | ParsedSignature::Maybe(child) => alignment(child),
+        ParsedSignature::Dict { key, value } => alignment(key).max(alignment(value)),
+        ParsedSignature::Structure(fields) => structure_alignment(fields.iter()),
+    }
+}
+
+// The alignment of a structure of `fields` in the GVariant format.
+fn structure_alignment<'s>(fields: impl Iterator<Item = &'s ParsedSignature>) -> usize {
+    fields.map(alignment).max().unwrap_or(1)
+}
+
+// The size of `signature` in the GVariant format, if it's fixed.
+pub(super) fn fixed_size(signature: &ParsedSignature) -> Option<usize> {
+    match signature {
+        // The unit type is encoded as a single 0 byte.
+        ParsedSignature::Unit | ParsedSignature::U8 | ParsedSignature::Bool => Some(1),
+        ParsedSignature::I16 | ParsedSignature::U16 => Some(2),
+        ParsedSignature::I32 | ParsedSignature::U32 | ParsedSignature::Fd => Some(4),
+        ParsedSignature::I64 | ParsedSignature::U64 | ParsedSignature::F64 => Some(8),
+        ParsedSignature::Structure(fields) => structure_fixed_size(fields_iter(fields)),
+        ParsedSignature::Str
+        | ParsedSignature::Signature
+        | ParsedSignature::ObjectPath
+        | ParsedSignature::Variant
+        | ParsedSignature::Array(_)
+        | ParsedSignature::Dict { .. }
+        | ParsedSignature::Maybe(_) => None,
+    }
+}
+
+// The size of a structure of `fields` in the GVariant format, if it's fixed.
+fn structure_fixed_size<'s, F>(fields: F) -> Option<usize>
+where
+    F: Iterator<Item = &'s ParsedSignature> + Clone,
+{
+    let mut size = 0;
+    for field in fields.clone() {
+        size = align(size, alignment(field)) + fixed_size(field)?;
+    }
+
+    Some(align(size, structure_alignment(fields)).max(1))
+}
+
+// Unlike `Fields::iter`, the returned iterator is `Clone`.
+fn fields_iter(fields: &Fields) -> impl Iterator<Item = &ParsedSignature> + Clone {
+    (0..fields.len()).filter_map(move |i| fields.get(i))
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{borrow::Cow, collections::HashMap};
+
+    use byteorder::LE;
+
+    use super::View;
+    use crate::{serialized::Context, to_bytes, Error, OwnedValue, ParsedSignature, Type, Value};
+
+    fn view<T>(value: &T) -> View<'static, 'static, 'static, LE>
+    where
+        T: serde::Serialize + Type,
+    {
+        let encoded = to_bytes(Context::<LE>::new_gvariant(0), value).unwrap();
+
+        View::new(encoded, T::SIGNATURE.clone()).unwrap()
+    }
+
+    #[test]
+    fn arrays() {
+        let fixed = view(&vec![(1u8, 2u32), (3, 4), (5, 6)]);
+        assert_eq!(fixed.len().unwrap(), 3);
+        assert_eq!(fixed.child(1).unwrap().data().len(), 8);
+        assert_eq!(
+            fixed.child(2).unwrap().deserialize::<(u8, u32)>().unwrap(),
+            (5, 6)
+        );
+        assert!(matches!(fixed.child(3), Err(Error::OutOfBounds)));
+
+        let strings = ["", "a", "hello", "world"];
+        let variable = view(&strings.to_vec());
+        let children: Vec<String> = variable
+            .children()
+            .unwrap()
+            .map(|child| child.unwrap().deserialize().unwrap())
+            .collect();
+        assert_eq!(children, strings);
+        assert_eq!(
+            variable.child(2).unwrap().deserialize::<&str>().unwrap(),
+            "hello"
+        );
+
+        let nested = view(&vec![vec![1u64], vec![], vec![2, 3]]);
+        assert_eq!(nested.len().unwrap(), 3);
+        assert!(nested.child(1).unwrap().is_empty().unwrap());
+        let last = nested.child(2).unwrap();
+        assert_eq!(last.len().unwrap(), 2);
+        assert_eq!(last.child(1).unwrap().deserialize::<u64>().unwrap(), 3);
+
+        let empty = view(&Vec::<String>::new());
+        assert!(empty.is_empty().unwrap());
+
+        // Large enough for 2-byte framing offsets.
+        let large: Vec<String> = (0..1000).map(|i| i.to_string()).collect();
+        let large_view = view(&large);
+        assert_eq!(large_view.len().unwrap(), 1000);
+        assert_eq!(
+            large_view
+                .child(789)
+                .unwrap()
+                .deserialize::<&str>()
+                .unwrap(),
+            "789"
+        );
+    }
+
+    #[test]
+    fn borrowed_signatures() {
+        // Unlike the signatures of types, parsed signatures are allocated so children must borrow
+        // them rather than cloning them.
+        let encoded = to_bytes(Context::<LE>::new_gvariant(0), &vec![("a", vec!["b"])]).unwrap();
+        let array = View::new(encoded, "a(sas)".parse().unwrap()).unwrap();
+        let signature = array.signature();
+        let element = match &*signature {
+            ParsedSignature::Array(element) => &**element,
+            _ => unreachable!(),
+        };
+        let child = array.child(0).unwrap();
+        assert!(matches!(child.signature(), Cow::Borrowed(s) if std::ptr::eq(s, element)));
+        let strings = child.child(1).unwrap();
+        assert!(matches!(strings.signature(), Cow::Borrowed(_)));
+        assert_eq!(
+            strings.child(0).unwrap().deserialize::<&str>().unwrap(),
+            "b"
+        );
+
+        // The signature of dictionary entries is only built when asked for.
+        let dict = view(&HashMap::from([("a", 1u8)]));
+        let entry = dict.child(0).unwrap();
+        assert!(matches!(entry.signature(), Cow::Owned(s) if s == "(sy)"));
+        assert!(matches!(
+            entry.child(1).unwrap().signature(),
+            Cow::Borrowed(_)
+        ));
+    }
+
+    #[test]
+    fn structures() {
+        let s = ("hello", 42u8, vec!["a", "b"], 7u64, "world");
+        let structure = view(&s);
+        assert_eq!(structure.len().unwrap(), 5);
+        assert_eq!(
+            structure.child(0).unwrap().deserialize::<&str>().unwrap(),
+            "hello"
+        );
+        assert_eq!(structure.child(1).unwrap().deserialize::<u8>().unwrap(), 42);
+        let array = structure.child(2).unwrap();
+        assert_eq!(array.child(1).unwrap().deserialize::<&str>().unwrap(), "b");
+        assert_eq!(structure.child(3).unwrap().deserialize::<u64>().unwrap(), 7);
+        assert_eq!(
+            structure.child(4).unwrap().deserialize::<&str>().unwrap(),
+            "world"
+        );
+        assert!(matches!(structure.child(5), Err(Error::OutOfBounds)));
+        assert_eq!(
+            structure
+                .deserialize::<(&str, u8, Vec<&str>, u64, &str)>()
+                .unwrap(),
+            s
+        );
+
+        assert!(matches!(
+            structure.child(0).unwrap().child(0),
+            Err(Error::SignatureMismatch(_, _))
+        ));
+    }
+
+    #[test]
+    fn variants_and_maybes() {
+        let value = view(&Value::from((42u32, "hi")));
+        let inner = value.variant().unwrap();
+        assert_eq!(inner.signature().to_string(), "(us)");
+        assert_eq!(inner.child(1).unwrap().deserialize::<&str>().unwrap(), "hi");
+
+        let maybes = view(&(Some(42u32), None::<u32>, Some("hi"), None::<String>));
+        let some = maybes.child(0).unwrap();
+        assert_eq!(some.len().unwrap(), 1);
+        assert_eq!(
+            some.maybe().unwrap().unwrap().deserialize::<u32>().unwrap(),
+            42
+        );
+        assert!(maybes.child(1).unwrap().maybe().unwrap().is_none());
+        let maybe = maybes.child(2).unwrap();
+        let some = maybe.maybe().unwrap().unwrap();
+        assert_eq!(some.deserialize::<&str>().unwrap(), "hi");
+        assert!(maybes.child(3).unwrap().maybe().unwrap().is_none());
+    }
+
+    #[test]
+    fn dicts() {
+        let dict: HashMap<String, OwnedValue> = HashMap::from([
+            ("one".to_string(), OwnedValue::from(1u8)),
+            ("two".to_string(), Value::from("2").try_into().unwrap()),
+        ]);
+        let view = view(&dict);
+        assert_eq!(view.len().unwrap(), 2);
+        let entry = view.child(0).unwrap();
+        assert_eq!(entry.signature().to_string(), "(sv)");
+
+        let one = view.lookup("one").unwrap().unwrap();
+        assert_eq!(one.variant().unwrap().deserialize::<u8>().unwrap(), 1);
+        let two = view.lookup("two").unwrap().unwrap();
+        assert_eq!(two.variant().unwrap().deserialize::<&str>().unwrap(), "2");
+        assert!(view.lookup("three").unwrap().is_none());
+    }
+
+    #[test]
+    fn invalid() {
+        let encoded = to_bytes(Context::<LE>::new_dbus(0), &42u32).unwrap();
+        assert!(matches!(
+            View::new(encoded, ParsedSignature::U32),
+            Err(Error::IncompatibleFormat(_, _))
+        ));
+
+        // Not a multiple of the element size.
+        let encoded = to_bytes(Context::<LE>::new_gvariant(0), &[1u8, 2, 3][..]).unwrap();
+        let view = View::new(encoded, "au".parse().unwrap()).unwrap();
+        view.len().unwrap_err();
+
+        // Framing offset pointing past the end.
+        let encoded =
+            crate::serialized::Data::new(&[b'a', 0, 0xff][..], Context::<LE>::new_gvariant(0));
+        let view = View::new(encoded, "as".parse().unwrap()).unwrap();
+        assert!(matches!(view.len(), Err(Error::MissingFramingOffset)));
+    }
+}
@@ -0,0 +1,261 @@
+//! Reading and writing of GVDB files.
+//!
+//! GVDB is the on-disk hash table format GLib uses for dconf databases and GResource bundles.
+//! Each table maps string keys to GVariant-encoded values, lists of other items or nested
+//! tables. [`File`] provides lookups in an existing file, without decoding anything other than
+//! the requested values, while [`HashTableBuilder`] and [`to_bytes`] create new files.
+//!
+//! # Examples
+//!
+//! ```
+//! use byteorder::LE;
+//! use zvariant::{
+//!     gvdb::{to_bytes, File, HashTableBuilder},
+//!     Value,
+//! };
+//!
+//! let mut settings = HashTableBuilder::new();
+//! settings.insert("/org/example/enabled", &true).unwrap();
+//! settings.insert("/org/example/name", "example").unwrap();
+//! let mut nested = HashTableBuilder::new();
+//! nested.insert("count", &42u32).unwrap();
+//! settings.insert_table("nested", nested);
+//!
+//! let bytes = to_bytes::<LE>(&settings).unwrap();
+//!
+//! let file = File::new(&bytes).unwrap();
+//! let root = file.root().unwrap();
+//! assert_eq!(root.get("/org/example/enabled").unwrap(), Some(Value::Bool(true)));
+//! assert_eq!(root.get("/org/example/name").unwrap(), Some(Value::from("example")));
+//! assert_eq!(root.get("/org/example/other").unwrap(), None);
+//! let nested = root.get_table("nested").unwrap().unwrap();
+//! assert_eq!(nested.get("count").unwrap(), Some(Value::U32(42)));
+//! ```
+
+mod reader;
+pub use reader::*;
+mod writer;
+pub use writer::*;
+
+// The "GVariant" magic, as two 32-bit words in the byte order of the values in the file.
+const SIGNATURE: [u32; 2] = [0x7261_5647, 0x746e_6169];
+const HEADER_SIZE: usize = 24;
+const HASH_HEADER_SIZE: usize = 8;
+const HASH_ITEM_SIZE: usize = 24;
+// The `parent` of items without a parent.
+const NO_PARENT: u32 = u32::MAX;
+
+const ITEM_VALUE: u8 = b'v';
+const ITEM_HASH_TABLE: u8 = b'H';
+const ITEM_LIST: u8 = b'L';
+
+// The hash function used by GVDB for keys (DJB's, treating bytes as signed).
+fn hash(key: &[u8]) -> u32 {
+    key.iter().fold(5381u32, |hash, b| {
+        hash.wrapping_mul(33).wrapping_add(*b as i8 as u32)
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use byteorder::{ByteOrder, BE, LE};
+
+    use super::{hash, to_bytes, File, HashTableBuilder, NO_PARENT};
+    use crate::{Error, Value};
+
+    #[test]
+    fn djb_hash() {
+        assert_eq!(hash(b""), 5381);
+        assert_eq!(hash(b"a"), 5381 * 33 + 97);
+        // Non-ASCII bytes are treated as signed.
+        assert_eq!(hash(&[0xff]), (5381u32 * 33).wrapping_sub(1));
+    }
+
+    fn round_trip<B: ByteOrder>() {
+        let mut root = HashTableBuilder::new();
+        for i in 0..100u32 {
+            root.insert(format!("/key/{i}"), &i).unwrap();
+        }
+        root.insert("string", "hello").unwrap();
+        root.insert("tuple", &(1u8, "two", vec![3.0f64])).unwrap();
+        root.insert_value("variant", Value::Value(Box::new(Value::I16(7))));
+        let mut nested = HashTableBuilder::new();
+        nested.insert("inner", &true).unwrap();
+        root.insert_table("nested", nested);
+        root.insert_table("empty", HashTableBuilder::new());
+
+        let bytes = to_bytes::<B>(&root).unwrap();
+        let file = File::new(bytes).unwrap();
+        let table = file.root().unwrap();
+        assert_eq!(table.len(), 105);
+        for i in 0..100u32 {
+            assert_eq!(
+                table.get(&format!("/key/{i}")).unwrap(),
+                Some(Value::U32(i))
+            );
+        }
+        assert_eq!(table.get("string").unwrap(), Some(Value::from("hello")));
+        assert_eq!(
+            table.get("tuple").unwrap(),
+            Some(Value::from((1u8, "two", vec![3.0f64])))
+        );
+        assert_eq!(
+            table.get("variant").unwrap(),
+            Some(Value::Value(Box::new(Value::I16(7))))
+        );
+        assert_eq!(table.get("missing").unwrap(), None);
+        // Tables are not values and vice versa.
+        assert_eq!(table.get("nested").unwrap(), None);
+        assert!(table.get_table("string").unwrap().is_none());
+
+        let nested = table.get_table("nested").unwrap().unwrap();
+        assert_eq!(nested.keys().unwrap(), ["inner"]);
+        assert_eq!(nested.get("inner").unwrap(), Some(Value::Bool(true)));
+        let empty = table.get_table("empty").unwrap().unwrap();
+        assert!(empty.is_empty());
+        assert_eq!(empty.get("inner").unwrap(), None);
+
+        let mut keys = table.keys().unwrap();
+        keys.sort();
+        assert_eq!(keys.len(), 105);
+        assert_eq!(keys[0], "/key/0");
+        assert_eq!(keys[104], "variant");
+    }
+
+    #[test]
+    fn round_trip_le() {
+        round_trip::<LE>();
+    }
+
+    #[test]
+    fn round_trip_be() {
+        round_trip::<BE>();
+    }
+
+    #[test]
+    fn signature() {
+        let bytes = to_bytes::<LE>(&HashTableBuilder::new()).unwrap();
+        assert_eq!(&bytes[..8], b"GVariant");
+        let bytes = to_bytes::<BE>(&HashTableBuilder::new()).unwrap();
+        assert_eq!(&bytes[..8], b"raVGtnai");
+        // The hash tables themselves are always little-endian.
+        assert_eq!(bytes[16..24], [24, 0, 0, 0, 32, 0, 0, 0]);
+
+        assert!(matches!(
+            File::new(&b"GVariant"[..]),
+            Err(Error::OutOfBounds)
+        ));
+        let mut bytes = bytes;
+        bytes[0] = b'X';
+        assert!(matches!(File::new(bytes), Err(Error::Message(_))));
+    }
+
+    // A file like the ones created by `glib-compile-resources`, where items have parents and
+    // directories are lists of their children.
+    fn hierarchical_file() -> Vec<u8> {
+        let value = crate::to_bytes(
+            crate::serialized::Context::<LE>::new_gvariant(0),
+            &Value::U32(42),
+        )
+        .unwrap();
+        // (key, parent, type)
+        let items: [(&str, u32, u8); 4] = [
+            ("/", NO_PARENT, b'L'),
+            ("a/", 0, b'L'),
+            ("b", 1, b'v'),
+            ("c", 1, b'v'),
+        ];
+        let n_items = items.len();
+        let table_start = 24;
+        let table_size = 8 + 4 + 24 * n_items;
+        let mut bytes = vec![0; table_start + table_size];
+        bytes[..8].copy_from_slice(b"GVariant");
+        LE::write_u32(&mut bytes[16..], table_start as u32);
+        LE::write_u32(&mut bytes[20..], (table_start + table_size) as u32);
+        // All items in one bucket.
+        LE::write_u32(&mut bytes[table_start + 4..], 1);
+
+        let full_keys = ["/", "/a/", "/a/b", "/a/c"];
+        for (i, (key, parent, ty)) in items.iter().enumerate() {
+            let key_start = bytes.len();
+            bytes.extend_from_slice(key.as_bytes());
+            let (start, end) = match ty {
+                b'L' => {
+                    let children: &[u32] = if i == 0 { &[1] } else { &[2, 3] };
+                    while bytes.len() % 4 != 0 {
+                        bytes.push(0);
+                    }
+                    let start = bytes.len();
+                    for child in children {
+                        bytes.extend_from_slice(&child.to_le_bytes());
+                    }
+
+                    (start, bytes.len())
+                }
+                _ => {
+                    while bytes.len() % 8 != 0 {
+                        bytes.push(0);
+                    }
+                    let start = bytes.len();
+                    bytes.extend_from_slice(&value);
+
+                    (start, bytes.len())
+                }
+            };
+
+            let item = table_start + 12 + 24 * i;
+            let item = &mut bytes[item..item + 24];
+            LE::write_u32(&mut item[0..], hash(full_keys[i].as_bytes()));
+            LE::write_u32(&mut item[4..], *parent);
+            LE::write_u32(&mut item[8..], key_start as u32);
+            LE::write_u16(&mut item[12..], key.len() as u16);
+            item[14] = *ty;
+            LE::write_u32(&mut item[16..], start as u32);
+            LE::write_u32(&mut item[20..], end as u32);
+        }
+
+        bytes
+    }
+
+    #[test]
+    fn parents_and_lists() {
+        let bytes = hierarchical_file();
+        let file = File::new(&bytes).unwrap();
+        let root = file.root().unwrap();
+
+        assert_eq!(root.get("/a/b").unwrap(), Some(Value::U32(42)));
+        assert_eq!(root.get("/a/c").unwrap(), Some(Value::U32(42)));
+        // Only the full key matches.
+        assert_eq!(root.get("b").unwrap(), None);
+        assert_eq!(root.get("a/b").unwrap(), None);
+        assert_eq!(root.get("/b/b").unwrap(), None);
+
+        assert_eq!(root.list("/").unwrap().unwrap(), ["a/"]);
+        assert_eq!(root.list("/a/").unwrap().unwrap(), ["b", "c"]);
+        assert!(root.list("/a/b").unwrap().is_none());
+
+        let mut keys = root.keys().unwrap();
+        keys.sort();
+        assert_eq!(keys, ["/", "/a/", "/a/b", "/a/c"]);
+    }
+
+    #[test]
+    fn invalid() {
+        let mut bytes = hierarchical_file();
+        // Make `/a/` its own parent.
+        LE::write_u32(&mut bytes[24 + 12 + 24 + 4..], 1);
+        let file = File::new(&bytes).unwrap();
+        let root = file.root().unwrap();
+        assert_eq!(root.get("/a/b").unwrap(), None);
+        root.keys().unwrap_err();
+
+        let mut bytes = hierarchical_file();
+        // Point the value of `/a/b` past the end of the file.
+        let len = bytes.len() as u32;
+        LE::write_u32(&mut bytes[24 + 12 + 48 + 20..], len + 8);
+        let file = File::new(&bytes).unwrap();
+        let root = file.root().unwrap();
+        assert!(matches!(root.get("/a/b"), Err(Error::OutOfBounds)));
+        assert_eq!(root.get("/a/c").unwrap(), Some(Value::U32(42)));
+    }
+}
@@ -0,0 +1,339 @@
+use std::borrow::Cow;
+
+use byteorder::{ByteOrder, BE, LE};
+use static_assertions::assert_impl_all;
+
+use super::{
+    hash, HASH_HEADER_SIZE, HASH_ITEM_SIZE, HEADER_SIZE, ITEM_HASH_TABLE, ITEM_LIST, ITEM_VALUE,
+    NO_PARENT, SIGNATURE,
+};
+use crate::{
+    serialized::{Context, Data},
+    utils::subslice,
+    Error, Result, Value,
+};
+
+/// A GVDB file.
+///
+/// The file is only validated lazily, as its tables and values are accessed.
+///
+/// See the [module documentation](index.html) for an example.
+#[derive(Debug, Clone)]
+pub struct File<'bytes> {
+    bytes: Cow<'bytes, [u8]>,
+    // If the values in the file are big-endian.
+    big_endian: bool,
+}
+
+assert_impl_all!(File<'_>: Send, Sync, Unpin);
+
+impl<'bytes> File<'bytes> {
+    /// Create a `File` for the contents of a GVDB file.
+    pub fn new<T>(bytes: T) -> Result<Self>
+    where
+        T: Into<Cow<'bytes, [u8]>>,
+    {
+        let bytes = bytes.into();
+        let header = subslice(&bytes, ..HEADER_SIZE)?;
+        let signature = [LE::read_u32(&header[0..]), LE::read_u32(&header[4..])];
+        let big_endian = if signature == SIGNATURE {
+            false
+        } else if signature == SIGNATURE.map(u32::swap_bytes) {
+            true
+        } else {
+            return Err(invalid("unknown file signature"));
+        };
+        let version = LE::read_u32(&header[8..]);
+        if version != 0 {
+            return Err(invalid(&format!("unsupported version {version}")));
+        }
+
+        Ok(Self { bytes, big_endian })
+    }
+
+    /// The root hash table of the file.
+    pub fn root(&self) -> Result<HashTable<'_>> {
+        let (start, end) = read_pointer(&self.bytes[16..HEADER_SIZE]);
+
+        HashTable::new(&self.bytes, self.big_endian, start, end)
+    }
+}
+
+/// A hash table in a GVDB [`File`].
+#[derive(Debug, Clone, Copy)]
+pub struct HashTable<'f> {
+    file: &'f [u8],
+    big_endian: bool,
+    bloom_shift: u32,
+    bloom_words: &'f [u8],
+    buckets: &'f [u8],
+    items: &'f [u8],
+}
+
+assert_impl_all!(HashTable<'_>: Send, Sync, Unpin);
+
+impl<'f> HashTable<'f> {
+    fn new(file: &'f [u8], big_endian: bool, start: usize, end: usize) -> Result<Self> {
+        if start % 4 != 0 {
+            return Err(invalid("unaligned hash table"));
+        }
+        let table = subslice(file, start..end)?;
+        let header = subslice(table, ..HASH_HEADER_SIZE)?;
+        // The top 5 bits are the shift of the bloom filter, the rest the number of its words.
+        let bloom = LE::read_u32(&header[0..]);
+        let bloom_shift = bloom >> 27;
+        let n_bloom_words = (bloom & ((1 << 27) - 1)) as usize;
+        let n_buckets = LE::read_u32(&header[4..]) as usize;
+
+        let buckets_start = HASH_HEADER_SIZE + n_bloom_words * 4;
+        let items_start = buckets_start + n_buckets * 4;
+        let bloom_words = subslice(table, HASH_HEADER_SIZE..buckets_start)?;
+        let buckets = subslice(table, buckets_start..items_start)?;
+        let items = subslice(table, items_start..)?;
+        if items.len() % HASH_ITEM_SIZE != 0 {
+            return Err(invalid("hash table size not a multiple of the item size"));
+        }
+
+        Ok(Self {
+            file,
+            big_endian,
+            bloom_shift,
+            bloom_words,
+            buckets,
+            items,
+        })
+    }
+
+    /// The number of items in the table.
+    ///
+    /// Note that this includes the items that are not values, like the lists of their children
+    /// GResource and dconf files have for each directory.
+    pub fn len(&self) -> usize {
+        self.items.len() / HASH_ITEM_SIZE
+    }
+
+    /// If the table has no items.
+    pub fn is_empty(&self) -> bool {
+        self.items.is_empty()
+    }
+
+    /// The value for `key`, if any.
+    pub fn get(&self, key: &str) -> Result<Option<Value<'static>>> {
+        let item = match self.lookup(key, ITEM_VALUE) {
+            Some(item) => item,
+            None => return Ok(None),
+        };
+        let bytes = self.pointer(item.pointer())?;
+        // Values are stored in variants.
+        let value = if self.big_endian {
+            Data::new(bytes, Context::<BE>::new_gvariant(0))
+                .deserialize::<Value<'_>>()?
+                .0
+                .try_to_owned()?
+        } else {
+            Data::new(bytes, Context::<LE>::new_gvariant(0))
+                .deserialize::<Value<'_>>()?
+                .0
+                .try_to_owned()?
+        };
+
+        Ok(Some(value.into()))
+    }
+
+    /// The nested hash table for `key`, if any.
+    pub fn get_table(&self, key: &str) -> Result<Option<HashTable<'f>>> {
+        match self.lookup(key, ITEM_HASH_TABLE) {
+            Some(item) => {
+                let (start, end) = item.pointer();
+
+                Self::new(self.file, self.big_endian, start, end).map(Some)
+            }
+            None => Ok(None),
+        }
+    }
+
+    /// The names of the children of the list item for `key`, if any.
+    ///
+    /// The names are relative to `key`. GResource and dconf files contain such an item for each
+    /// directory.
+    pub fn list(&self, key: &str) -> Result<Option<Vec<&'f str>>> {
+        let item = match self.lookup(key, ITEM_LIST) {
+            Some(item) => item,
+            None => return Ok(None),
+        };
+        let children = self.pointer(item.pointer())?;
+        if children.len() % 4 != 0 {
+            return Err(invalid("list size not a multiple of 4"));
+        }
+
+        children
+            .chunks_exact(4)
+            .map(|child| {
+                let child = self.item(LE::read_u32(child)).ok_or(Error::OutOfBounds)?;
+
+                std::str::from_utf8(self.key(&child)?).map_err(Error::Utf8)
+            })
+            .collect::<Result<_>>()
+            .map(Some)
+    }
+
+    /// The keys of all the items in the table.
+    pub fn keys(&self) -> Result<Vec<String>> {
+        let mut keys: Vec<Option<Vec<u8>>> = vec![None; self.len()];
+
+        for i in 0..self.len() {
+            // The items up the parent chain, whose keys are not yet known.
+            let mut chain = vec![];
+            let mut index = i as u32;
+            let mut prefix = loop {
+                if let Some(key) = &keys[index as usize] {
+                    break key.clone();
+                }
+                if chain.len() == self.len() {
+                    return Err(invalid("loop in item parents"));
+                }
+                chain.push(index);
+                let item = self.item(index).ok_or(Error::OutOfBounds)?;
+                match item.parent() {
+                    NO_PARENT => break vec![],
+                    parent if parent as usize >= self.len() => return Err(Error::OutOfBounds),
+                    parent => index = parent,
+                }
+            };
+            for index in chain.into_iter().rev() {
+                let item = self.item(index).ok_or(Error::OutOfBounds)?;
+                prefix.extend_from_slice(self.key(&item)?);
+                keys[index as usize] = Some(prefix.clone());
+            }
+        }
+
+        keys.into_iter()
+            .map(|key| String::from_utf8(key.unwrap_or_default()).map_err(|e| e.utf8_error()))
+            .collect::<std::result::Result<_, _>>()
+            .map_err(Error::Utf8)
+    }
+
+    fn item(&self, index: u32) -> Option<Item<'f>> {
+        let start = (index as usize).checked_mul(HASH_ITEM_SIZE)?;
+
+        self.items
+            .get(start..start + HASH_ITEM_SIZE)
+            .map(|bytes| Item { bytes })
+    }
+
+    fn key(&self, item: &Item<'f>) -> Result<&'f [u8]> {
+        let start = item.key_start();
+
+        subslice(self.file, start..start + item.key_size())
+    }
+
+    fn pointer(&self, (start, end): (usize, usize)) -> Result<&'f [u8]> {
+        subslice(self.file, start..end)
+    }
+
+    fn lookup(&self, key: &str, item_type: u8) -> Option<Item<'f>> {
+        let n_buckets = self.buckets.len() / 4;
+        let n_items = self.len();
+        if n_buckets == 0 || n_items == 0 {
+            return None;
+        }
+
+        let key = key.as_bytes();
+        let hash = hash(key);
+        if !self.bloom_filter(hash) {
+            return None;
+        }
+
+        let bucket = hash as usize % n_buckets;
+        let first = LE::read_u32(&self.buckets[bucket * 4..]) as usize;
+        let last = if bucket == n_buckets - 1 {
+            n_items
+        } else {
+            (LE::read_u32(&self.buckets[(bucket + 1) * 4..]) as usize).min(n_items)
+        };
+
+        (first..last)
+            .filter_map(|i| self.item(i as u32))
+            .find(|item| {
+                item.hash() == hash && item.item_type() == item_type && self.check_name(item, key)
+            })
+    }
+
+    fn bloom_filter(&self, hash: u32) -> bool {
+        let n_bloom_words = self.bloom_words.len() / 4;
+        if n_bloom_words == 0 {
+            return true;
+        }
+
+        let word = (hash / 32) as usize % n_bloom_words;
+        let mask = (1 << (hash & 31)) | (1 << ((hash >> self.bloom_shift) & 31));
+
+        LE::read_u32(&self.bloom_words[word * 4..]) & mask == mask
+    }
+
+    // If the full key of `item`, its own key prefixed by those of its parents, is `key`.
+    fn check_name(&self, item: &Item<'f>, mut key: &[u8]) -> bool {
+        let mut item = *item;
+
+        loop {
+            let this_key = match self.key(&item) {
+                Ok(this_key) if key.ends_with(this_key) => this_key,
+                _ => return false,
+            };
+            key = &key[..key.len() - this_key.len()];
+
+            match item.parent() {
+                NO_PARENT => return key.is_empty(),
+                // An empty key would make no progress, and possibly loop forever.
+                _ if this_key.is_empty() => return false,
+                parent => match self.item(parent) {
+                    Some(parent) => item = parent,
+                    None => return false,
+                },
+            }
+        }
+    }
+}
+
+// An item in a hash table.
+#[derive(Debug, Clone, Copy)]
+struct Item<'f> {
+    bytes: &'f [u8],
+}
+
+impl Item<'_> {
+    fn hash(&self) -> u32 {
+        LE::read_u32(&self.bytes[0..])
+    }
+
+    fn parent(&self) -> u32 {
+        LE::read_u32(&self.bytes[4..])
+    }
+
+    fn key_start(&self) -> usize {
+        LE::read_u32(&self.bytes[8..]) as usize
+    }
+
+    fn key_size(&self) -> usize {
+        LE::read_u16(&self.bytes[12..]) as usize
+    }
+
+    fn item_type(&self) -> u8 {
+        self.bytes[14]
+    }
+
+    fn pointer(&self) -> (usize, usize) {
+        read_pointer(&self.bytes[16..])
+    }
+}
+
+fn read_pointer(bytes: &[u8]) -> (usize, usize) {
+    (
+        LE::read_u32(&bytes[0..]) as usize,
+        LE::read_u32(&bytes[4..]) as usize,
+    )
+}
+
+fn invalid(reason: &str) -> Error {
+    Error::Message(format!("invalid GVDB file: {reason}"))
+}
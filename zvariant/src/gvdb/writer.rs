@@ -0,0 +1,174 @@
+use std::collections::BTreeMap;
+
+use byteorder::{ByteOrder, LE};
+use serde::Serialize;
+use static_assertions::assert_impl_all;
+
+use super::{
+    hash, HASH_HEADER_SIZE, HASH_ITEM_SIZE, HEADER_SIZE, ITEM_HASH_TABLE, ITEM_VALUE, NO_PARENT,
+    SIGNATURE,
+};
+use crate::{
+    serialized::Context, to_value, utils::usize_to_u32, DynamicType, Error, Result, Value,
+};
+
+/// A builder for a hash table of a GVDB file.
+///
+/// Use [`to_bytes`] to create a GVDB file with the table as its root. See the
+/// [module documentation](index.html) for an example.
+#[derive(Debug, Default)]
+pub struct HashTableBuilder {
+    items: BTreeMap<String, Item>,
+}
+
+assert_impl_all!(HashTableBuilder: Send, Sync, Unpin);
+
+#[derive(Debug)]
+enum Item {
+    Value(Value<'static>),
+    HashTable(HashTableBuilder),
+}
+
+impl HashTableBuilder {
+    /// Create an empty table.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Insert `value` for `key`, replacing any previous item for `key`.
+    pub fn insert<K, T>(&mut self, key: K, value: &T) -> Result<()>
+    where
+        K: Into<String>,
+        T: ?Sized + Serialize + DynamicType,
+    {
+        let value = to_value(value)?;
+        self.insert_value(key, value);
+
+        Ok(())
+    }
+
+    /// Insert `value` for `key`, replacing any previous item for `key`.
+    pub fn insert_value<K>(&mut self, key: K, value: Value<'static>)
+    where
+        K: Into<String>,
+    {
+        self.items.insert(key.into(), Item::Value(value));
+    }
+
+    /// Insert a nested `table` for `key`, replacing any previous item for `key`.
+    pub fn insert_table<K>(&mut self, key: K, table: HashTableBuilder)
+    where
+        K: Into<String>,
+    {
+        self.items.insert(key.into(), Item::HashTable(table));
+    }
+
+    /// The number of items in the table.
+    pub fn len(&self) -> usize {
+        self.items.len()
+    }
+
+    /// If the table has no items.
+    pub fn is_empty(&self) -> bool {
+        self.items.is_empty()
+    }
+}
+
+/// Create a GVDB file with `root` as its root hash table.
+///
+/// The values are encoded in the byte order `B`.
+pub fn to_bytes<B>(root: &HashTableBuilder) -> Result<Vec<u8>>
+where
+    B: ByteOrder,
+{
+    let mut bytes = vec![0; HEADER_SIZE];
+    B::write_u32(&mut bytes[0..], SIGNATURE[0]);
+    B::write_u32(&mut bytes[4..], SIGNATURE[1]);
+    // The version and options are 0.
+    let pointer = write_table::<B>(&mut bytes, root)?;
+    bytes[16..HEADER_SIZE].copy_from_slice(&pointer);
+
+    Ok(bytes)
+}
+
+// Append `table` and everything it refers to, to `bytes`, returning the pointer to the table.
+fn write_table<B>(bytes: &mut Vec<u8>, table: &HashTableBuilder) -> Result<[u8; 8]>
+where
+    B: ByteOrder,
+{
+    let n_items = table.items.len();
+    // Like GLib, we use as many buckets as items and no bloom filter.
+    let n_buckets = n_items;
+    let mut items: Vec<_> = table
+        .items
+        .iter()
+        .map(|(key, item)| (hash(key.as_bytes()), key, item))
+        .collect();
+    items.sort_by_key(|(hash, _, _)| *hash as usize % n_buckets);
+
+    let size = HASH_HEADER_SIZE + n_buckets * 4 + n_items * HASH_ITEM_SIZE;
+    let (start, pointer) = allocate(bytes, 4, size)?;
+    LE::write_u32(&mut bytes[start + 4..], usize_to_u32(n_buckets));
+
+    let buckets_start = start + HASH_HEADER_SIZE;
+    let items_start = buckets_start + n_buckets * 4;
+    let mut bucket = 0;
+    for (i, (hash, key, item)) in items.into_iter().enumerate() {
+        // Each bucket points to its first item.
+        while bucket <= hash as usize % n_buckets {
+            LE::write_u32(&mut bytes[buckets_start + bucket * 4..], usize_to_u32(i));
+            bucket += 1;
+        }
+
+        let key_size = u16::try_from(key.len())
+            .map_err(|_| Error::Message(format!("GVDB key too long: {key}")))?;
+        let (key_start, _) = allocate(bytes, 1, key.len())?;
+        bytes[key_start..key_start + key.len()].copy_from_slice(key.as_bytes());
+        let (item_type, value_pointer) = match item {
+            Item::Value(value) => {
+                let ctxt = Context::<B>::new_gvariant(0);
+                // Values are stored in variants.
+                let encoded = crate::to_bytes(ctxt, value)?;
+                let (value_start, value_pointer) = allocate(bytes, 8, encoded.len())?;
+                bytes[value_start..value_start + encoded.len()].copy_from_slice(&encoded);
+
+                (ITEM_VALUE, value_pointer)
+            }
+            Item::HashTable(table) => (ITEM_HASH_TABLE, write_table::<B>(bytes, table)?),
+        };
+
+        let item = &mut bytes[items_start + i * HASH_ITEM_SIZE..][..HASH_ITEM_SIZE];
+        LE::write_u32(&mut item[0..], hash);
+        LE::write_u32(&mut item[4..], NO_PARENT);
+        LE::write_u32(&mut item[8..], usize_to_u32(key_start));
+        LE::write_u16(&mut item[12..], key_size);
+        item[14] = item_type;
+        item[16..].copy_from_slice(&value_pointer);
+    }
+    // Trailing empty buckets point past the last item.
+    while bucket < n_buckets {
+        LE::write_u32(
+            &mut bytes[buckets_start + bucket * 4..],
+            usize_to_u32(n_items),
+        );
+        bucket += 1;
+    }
+
+    Ok(pointer)
+}
+
+// Append `size` 0 bytes to `bytes`, at an offset aligned to `alignment`, returning the offset and
+// the encoded pointer to the new bytes.
+fn allocate(bytes: &mut Vec<u8>, alignment: usize, size: usize) -> Result<(usize, [u8; 8])> {
+    let start = (bytes.len() + alignment - 1) & !(alignment - 1);
+    let end = start + size;
+    let pointer_end = u32::try_from(end)
+        .map_err(|_| Error::Message("GVDB file larger than 4 GiB".to_string()))?;
+    bytes.resize(end, 0);
+
+    let mut pointer = [0; 8];
+    LE::write_u32(&mut pointer[0..], start as u32);
+    LE::write_u32(&mut pointer[4..], pointer_end);
+
+    Ok((start, pointer))
+}
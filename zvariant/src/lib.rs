@@ -38,6 +38,11 @@ mod fd;
 #[cfg(unix)]
 pub use fd::*;
 
+#[cfg(all(target_os = "linux", feature = "memfd"))]
+mod sealed_bytes;
+#[cfg(all(target_os = "linux", feature = "memfd"))]
+pub use sealed_bytes::*;
+
 mod object_path;
 pub use crate::object_path::*;
 
@@ -49,9 +54,12 @@ mod de;
 pub mod dbus;
 #[cfg(feature = "gvariant")]
 pub mod gvariant;
+#[cfg(feature = "gvdb")]
+pub mod gvdb;
 
 mod signature;
 pub use crate::signature::*;
+pub use zvariant_utils::signature::{self as parsed_signature, ParsedSignature};
 
 mod complete_type;
 pub use complete_type::*;
@@ -76,9 +84,18 @@ pub use value::*;
 mod serialize_value;
 pub use serialize_value::*;
 
+mod serialize_iter;
+pub use serialize_iter::*;
+
 mod deserialize_value;
 pub use deserialize_value::*;
 
+mod value_ser;
+pub use value_ser::*;
+
+mod value_de;
+pub use value_de::*;
+
 mod error;
 pub use error::*;
 
@@ -101,7 +118,11 @@ mod signature_parser;
 
 mod container_depths;
 
-pub use zvariant_derive::{DeserializeDict, OwnedValue, SerializeDict, Type, Value};
+mod text;
+
+pub use zvariant_derive::{
+    DeserializeDict, DeserializeEnum, OwnedValue, SerializeDict, SerializeEnum, Type, Value,
+};
 
 // Required for the macros to function within this crate.
 extern crate self as zvariant;
@@ -134,6 +155,8 @@ mod tests {
 
     #[cfg(unix)]
     use crate::Fd;
+    #[cfg(all(target_os = "linux", feature = "memfd"))]
+    use crate::SealedBytes;
     use crate::{
         serialized::{Context, Format},
         Array, Basic, DeserializeDict, DeserializeValue, Dict, Error, ObjectPath, Result,
@@ -392,6 +415,47 @@ mod tests {
         fd_value_test!(LE, GVariant, Fd::from(fd), 4, 4, 6);
     }
 
+    #[cfg(all(target_os = "linux", feature = "memfd"))]
+    #[test]
+    fn sealed_bytes() {
+        use std::os::fd::AsFd;
+
+        let bytes: Vec<u8> = (0..=255).cycle().take(1024 * 1024).collect();
+        let sealed = SealedBytes::new(&bytes).unwrap();
+        assert_eq!(sealed.as_bytes(), &bytes[..]);
+        assert_eq!(SealedBytes::signature(), "h");
+
+        let ctxt = Context::<LE>::new_dbus(0);
+        let encoded = to_bytes(ctxt, &(&sealed, 7u8)).unwrap();
+        assert_eq!(encoded.len(), 5);
+        assert_eq!(encoded.fds().len(), 1);
+        let (decoded, byte): (SealedBytes, u8) = encoded.deserialize().unwrap().0;
+        assert_eq!(decoded, sealed);
+        assert_eq!(byte, 7);
+
+        let empty = SealedBytes::new(&[]).unwrap();
+        assert!(empty.is_empty());
+        assert_eq!(empty.try_clone().unwrap(), empty);
+
+        // Through a variant in a dict.
+        let mut dict = HashMap::new();
+        dict.insert("frame", Value::from(sealed.try_clone().unwrap()));
+        let encoded = to_bytes(ctxt, &dict).unwrap();
+        assert_eq!(encoded.fds().len(), 1);
+        let decoded: HashMap<&str, crate::OwnedValue> = encoded.deserialize().unwrap().0;
+        let frame = SealedBytes::try_from(&*decoded["frame"]).unwrap();
+        assert_eq!(frame, sealed);
+        let frame = SealedBytes::try_from(decoded["frame"].try_clone().unwrap()).unwrap();
+        assert_eq!(frame, sealed);
+        SealedBytes::try_from(Value::from(7u8)).unwrap_err();
+
+        // Only sealed memfds are accepted.
+        let file = std::fs::File::open("/dev/null").unwrap();
+        let fd = file.as_fd().try_clone_to_owned().unwrap();
+        SealedBytes::from_fd(fd).unwrap_err();
+        SealedBytes::try_from(Value::from(Fd::from(&file))).unwrap_err();
+    }
+
     #[test]
     fn u16_value() {
         let encoded = basic_type_test!(BE, DBus, 0xABBA_u16, 2, u16, 2, U16, 6);
@@ -1270,6 +1334,102 @@ mod tests {
         );
     }
 
+    #[test]
+    fn dict_field_attributes() {
+        use std::time::Duration;
+
+        use crate::OwnedValue;
+
+        mod secs {
+            use serde::{Deserialize, Deserializer, Serialize, Serializer};
+            use std::time::Duration;
+
+            pub fn serialize<S: Serializer>(d: &Duration, s: S) -> Result<S::Ok, S::Error> {
+                d.as_secs().serialize(s)
+            }
+
+            pub fn deserialize<'de, D: Deserializer<'de>>(d: D) -> Result<Duration, D::Error> {
+                u64::deserialize(d).map(Duration::from_secs)
+            }
+        }
+
+        #[derive(SerializeDict, DeserializeDict, Type, PartialEq, Debug)]
+        #[zvariant(signature = "dict")]
+        struct Options {
+            #[zvariant(default)]
+            modal: bool,
+            #[zvariant(default, skip_serializing_if = "Vec::is_empty")]
+            choices: Vec<String>,
+            #[zvariant(with = "secs", signature = "t")]
+            timeout: Duration,
+            #[zvariant(with = "secs", signature = "t")]
+            delay: Option<Duration>,
+            #[zvariant(signature = "o")]
+            parent: String,
+            #[zvariant(flatten)]
+            rest: HashMap<String, OwnedValue>,
+        }
+
+        let ctxt = Context::<LE>::new_dbus(0);
+        let options = Options {
+            modal: true,
+            choices: vec![],
+            timeout: Duration::from_secs(30),
+            delay: Some(Duration::from_secs(2)),
+            parent: "/org/example".to_string(),
+            rest: HashMap::from([(
+                "handle_token".to_string(),
+                Value::new("t1").try_into().unwrap(),
+            )]),
+        };
+        let encoded = to_bytes(ctxt, &options).unwrap();
+        let decoded: HashMap<&str, Value<'_>> = encoded.deserialize().unwrap().0;
+        assert_eq!(decoded.len(), 5);
+        assert_eq!(decoded["modal"], Value::Bool(true));
+        assert!(!decoded.contains_key("choices"));
+        assert_eq!(decoded["timeout"], Value::U64(30));
+        assert_eq!(decoded["delay"], Value::U64(2));
+        assert_eq!(
+            decoded["parent"],
+            Value::ObjectPath(ObjectPath::from_static_str_unchecked("/org/example"))
+        );
+        assert_eq!(decoded["handle_token"], Value::new("t1"));
+        let decoded: Options = encoded.deserialize().unwrap().0;
+        assert_eq!(decoded, options);
+
+        // Missing `default` fields get their default values.
+        let mut dict = HashMap::new();
+        dict.insert("timeout", Value::U64(5));
+        dict.insert(
+            "parent",
+            Value::ObjectPath(ObjectPath::from_static_str_unchecked("/")),
+        );
+        dict.insert("extra", Value::U8(1));
+        let encoded = to_bytes(ctxt, &dict).unwrap();
+        let decoded: Options = encoded.deserialize().unwrap().0;
+        assert!(!decoded.modal);
+        assert!(decoded.choices.is_empty());
+        assert_eq!(decoded.timeout, Duration::from_secs(5));
+        assert_eq!(decoded.delay, None);
+        assert_eq!(decoded.parent, "/");
+        assert_eq!(decoded.rest.len(), 1);
+        assert_eq!(decoded.rest["extra"], Value::U8(1).try_into().unwrap());
+
+        // The struct's generics are available to fields with custom signatures.
+        #[derive(SerializeDict, DeserializeDict, Type, PartialEq, Debug)]
+        #[zvariant(signature = "dict")]
+        struct Generic<T: Type + Serialize + serde::de::DeserializeOwned + 'static> {
+            #[zvariant(signature = "(s)")]
+            value: (T,),
+        }
+        let generic = Generic {
+            value: ("hi".to_string(),),
+        };
+        let encoded = to_bytes(ctxt, &generic).unwrap();
+        let decoded: Generic<String> = encoded.deserialize().unwrap().0;
+        assert_eq!(decoded, generic);
+    }
+
     #[test]
     fn dict_compare() {
         // the order in which a dict has been constructed must not play a role
@@ -1443,6 +1603,124 @@ mod tests {
         }
     }
 
+    #[test]
+    fn tagged_enums() {
+        use crate::{to_value, DeserializeEnum, OwnedObjectPath, OwnedValue, SerializeEnum};
+
+        #[derive(
+            Debug, PartialEq, Clone, SerializeEnum, DeserializeEnum, Type, Value, OwnedValue,
+        )]
+        #[zvariant(signature = "(sv)", rename_all = "snake_case")]
+        enum Named {
+            Unit,
+            NewType(String),
+            Tuple(u8, String),
+            Struct {
+                y: u8,
+                path: OwnedObjectPath,
+            },
+            #[zvariant(rename = "renamed")]
+            Other(Vec<u32>),
+        }
+
+        #[derive(
+            Debug, PartialEq, Clone, SerializeEnum, DeserializeEnum, Type, Value, OwnedValue,
+        )]
+        #[zvariant(signature = "(uv)")]
+        enum Indexed {
+            Unit,
+            NewType(u64),
+            Struct { b: bool, s: String },
+        }
+
+        assert_eq!(Named::signature(), "(sv)");
+        assert_eq!(Indexed::signature(), "(uv)");
+
+        let named = [
+            (Named::Unit, "unit", "y"),
+            (Named::NewType("hi".into()), "new_type", "s"),
+            (Named::Tuple(42, "hi".into()), "tuple", "(ys)"),
+            (
+                Named::Struct {
+                    y: 42,
+                    path: OwnedObjectPath::try_from("/hi").unwrap(),
+                },
+                "struct",
+                "(yo)",
+            ),
+            (Named::Other(vec![1, 2]), "renamed", "au"),
+        ];
+        let ctxts = [
+            Context::<LE>::new_dbus(0),
+            #[cfg(feature = "gvariant")]
+            Context::<LE>::new_gvariant(0),
+        ];
+        for ctxt in ctxts {
+            for (e, tag, signature) in &named {
+                let encoded = to_bytes(ctxt, e).unwrap();
+                let (decoded_tag, value): (String, Value<'_>) = encoded.deserialize().unwrap().0;
+                assert_eq!(decoded_tag, *tag);
+                assert_eq!(value.value_signature(), *signature);
+                let decoded: Named = encoded.deserialize().unwrap().0;
+                assert_eq!(&decoded, e);
+            }
+
+            // Unit variants are encoded as a structure without fields.
+            let encoded = to_bytes(ctxt, &Named::Unit).unwrap();
+            let expected: &[u8] = match ctxt.format() {
+                Format::DBus => b"\x04\0\0\0unit\0\x01y\0\0",
+                #[cfg(feature = "gvariant")]
+                Format::GVariant => b"unit\0\0\0\0\0\0y\x05",
+            };
+            assert_eq!(encoded.bytes(), expected);
+            assert_eq!(encoded.deserialize::<Named>().unwrap().0, Named::Unit);
+            let encoded = to_bytes(ctxt, &Indexed::Unit).unwrap();
+            let expected: &[u8] = match ctxt.format() {
+                Format::DBus => b"\0\0\0\0\x01y\0\0",
+                #[cfg(feature = "gvariant")]
+                Format::GVariant => b"\0\0\0\0\0\0\0\0\0\0y",
+            };
+            assert_eq!(encoded.bytes(), expected);
+            assert_eq!(encoded.deserialize::<Indexed>().unwrap().0, Indexed::Unit);
+
+            let e = Indexed::Struct {
+                b: true,
+                s: "hi".into(),
+            };
+            let encoded = to_bytes(ctxt, &e).unwrap();
+            let (index, value): (u32, Value<'_>) = encoded.deserialize().unwrap().0;
+            assert_eq!(index, 2);
+            assert_eq!(value.value_signature(), "(bs)");
+            let decoded: Indexed = encoded.deserialize().unwrap().0;
+            assert_eq!(decoded, e);
+
+            // Unknown tags are rejected.
+            let encoded = to_bytes(ctxt, &(3u32, Value::from(0u8))).unwrap();
+            encoded.deserialize::<Indexed>().unwrap_err();
+            let encoded = to_bytes(ctxt, &("unknown", Value::from(0u8))).unwrap();
+            encoded.deserialize::<Named>().unwrap_err();
+        }
+
+        // `Value` conversions use the same encoding as the serde implementations.
+        for (e, _, _) in named {
+            let value = Value::from(e.clone());
+            assert_eq!(value, to_value(&e).unwrap());
+            assert_eq!(Named::try_from(value).unwrap(), e);
+            let value = OwnedValue::try_from(e.clone()).unwrap();
+            assert_eq!(Named::try_from(value).unwrap(), e);
+        }
+        for e in [Indexed::Unit, Indexed::NewType(42)] {
+            let value = Value::from(e.clone());
+            assert_eq!(value, to_value(&e).unwrap());
+            assert_eq!(Indexed::try_from(value).unwrap(), e);
+        }
+        let value = Value::from(Named::NewType("hi".into()));
+        assert!(matches!(
+            Indexed::try_from(value),
+            Err(Error::IncorrectType)
+        ));
+    }
+
     #[test]
     fn derive() {
         use serde::{Deserialize, Serialize};
@@ -2088,4 +2366,98 @@ mod tests {
         // * Test deserializers.
         // * Test gvariant format.
     }
+    #[test]
+    fn custom_limits() {
+        check_custom_limits(Format::DBus);
+        #[cfg(feature = "gvariant")]
+        check_custom_limits(Format::GVariant);
+    }
+
+    fn check_custom_limits(format: Format) {
+        use crate::{serialized::Limits, MaxSizeExceeded};
+
+        fn check<T>(ctxt: Context<LE>, limits: Limits, value: &T, error: Error)
+        where
+            T: Serialize + Type + for<'d> Deserialize<'d> + std::fmt::Debug,
+        {
+            let encoded = to_bytes(ctxt, value).unwrap();
+            let data = Data::new(encoded.bytes(), ctxt.with_limits(limits));
+            assert_eq!(data.deserialize::<T>().unwrap_err(), error);
+        }
+
+        let ctxt = Context::<LE>::new(format, 0);
+        let limits = Limits::default()
+            .set_max_struct_depth(2)
+            .set_max_array_depth(2)
+            .set_max_container_depth(3)
+            .set_max_array_size(16)
+            .set_max_message_size(32)
+            .set_max_fds(1);
+
+        // Within the limits.
+        let value = (vec![vec![1u32, 2]], (8u8,));
+        let encoded = to_bytes(ctxt.with_limits(limits), &value).unwrap();
+        assert_eq!(encoded.context().limits(), limits);
+        let decoded: (Vec<Vec<u32>>, (u8,)) = encoded.deserialize().unwrap().0;
+        assert_eq!(decoded, value);
+
+        let depth = |max| Error::MaxDepthExceeded(max);
+        check(
+            ctxt,
+            limits,
+            &vec![vec![vec![0u8]]],
+            depth(MaxDepthExceeded::Array),
+        );
+        check(
+            ctxt,
+            limits,
+            &(((0u8,),),),
+            depth(MaxDepthExceeded::Structure),
+        );
+        check(
+            ctxt,
+            limits,
+            &((vec![vec![0u8]],),),
+            depth(MaxDepthExceeded::Container),
+        );
+        // The depth limits also apply to serialization.
+        assert_eq!(
+            to_bytes(ctxt.with_limits(limits), &(((0u8,),),)).unwrap_err(),
+            depth(MaxDepthExceeded::Structure),
+        );
+
+        let size = |max| Error::MaxSizeExceeded(max);
+        check(ctxt, limits, &vec![0u32; 5], size(MaxSizeExceeded::Array));
+        check(
+            ctxt,
+            limits,
+            &"a".repeat(40),
+            size(MaxSizeExceeded::Message),
+        );
+        // Nested arrays are checked too.
+        check(
+            ctxt,
+            limits,
+            &(vec![vec![0u32; 5]],),
+            size(MaxSizeExceeded::Array),
+        );
+
+        #[cfg(unix)]
+        {
+            use std::os::fd::AsFd;
+
+            let stdout = std::io::stdout();
+            let fd = Fd::from(stdout.as_fd());
+            let encoded = to_bytes(ctxt, &(&fd, &fd)).unwrap();
+            let data = Data::new_borrowed_fds(
+                encoded.bytes(),
+                ctxt.with_limits(limits),
+                encoded.fds().iter().map(AsFd::as_fd),
+            );
+            assert_eq!(
+                data.deserialize::<(Fd<'_>, Fd<'_>)>().unwrap_err(),
+                size(MaxSizeExceeded::Fds)
+            );
+        }
+    }
 }
@@ -76,9 +76,7 @@ impl<T> Type for Optional<T>
 where
     T: Type,
 {
-    fn signature() -> crate::Signature<'static> {
-        T::signature()
-    }
+    const SIGNATURE: &'static crate::ParsedSignature = T::SIGNATURE;
 }
 
 impl<T> Serialize for Optional<T>
@@ -269,6 +269,15 @@ impl std::ops::Deref for OwnedValue {
     }
 }
 
+impl std::str::FromStr for OwnedValue {
+    type Err = crate::Error;
+
+    /// Parse `s` in the GVariant text format, inferring the types that aren't annotated.
+    fn from_str(s: &str) -> crate::Result<Self> {
+        crate::text::parse(s, None).map(OwnedValue)
+    }
+}
+
 impl<'de> Deserialize<'de> for OwnedValue {
     fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
     where
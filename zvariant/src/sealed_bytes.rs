@@ -0,0 +1,317 @@
+use serde::{de::Error as _, Deserialize, Deserializer, Serialize, Serializer};
+use static_assertions::assert_impl_all;
+use std::{
+    ffi::CStr,
+    fmt, io,
+    ops::Deref,
+    os::fd::{self, AsFd, AsRawFd, BorrowedFd, FromRawFd},
+    ptr, slice,
+};
+
+use crate::{Error, Fd, OwnedValue, ParsedSignature, Type, Value};
+
+// The seals a memfd must have for its contents to never change under our feet.
+const REQUIRED_SEALS: libc::c_int = libc::F_SEAL_SHRINK | libc::F_SEAL_GROW | libc::F_SEAL_WRITE;
+
+/// Immutable bytes, kept in a sealed memfd.
+///
+/// This is an efficient alternative to `Vec<u8>` (i-e `ay`) for large payloads, such as image
+/// frames or logs. Instead of the bytes being copied into the message and through the socket, only
+/// a file descriptor to the memory holding them is passed. Hence this type is encoded as a file
+/// descriptor (i-e `h`) and requires a transport that supports passing file descriptors.
+///
+/// The memfd is sealed against any modification before being sent, so the receiving side can
+/// safely map it (read-only) and access the bytes without any copying. A deserialized
+/// `SealedBytes` is only accepted if the file descriptor refers to a memfd with such seals.
+///
+/// It converts to and from [`Value::Fd`], so it can also be passed in variants and dictionaries
+/// (e.g `a{sv}`).
+///
+/// Note that `Vec<u8>` and other `ay` payloads can't be offloaded to a memfd behind the scenes: the
+/// signature of the message body is part of the D-Bus API, and a peer expecting `ay` would reject
+/// (or misinterpret) an `h`. So APIs have to explicitly use this type.
+///
+/// Requires Linux and the `memfd` feature.
+///
+/// # Example
+///
+/// ```
+/// use byteorder::LE;
+/// use zvariant::{serialized::Context, to_bytes, SealedBytes};
+///
+/// let frame = vec![42u8; 4 * 1024 * 1024];
+/// let sealed = SealedBytes::new(&frame).unwrap();
+///
+/// let ctxt = Context::<LE>::new_dbus(0);
+/// let encoded = to_bytes(ctxt, &sealed).unwrap();
+/// // Only the index of the file descriptor is in the encoded data.
+/// assert_eq!(encoded.len(), 4);
+/// assert_eq!(encoded.fds().len(), 1);
+///
+/// let decoded: SealedBytes = encoded.deserialize().unwrap().0;
+/// assert_eq!(&*decoded, &frame[..]);
+/// ```
+pub struct SealedBytes {
+    // Declared before `fd` so that it's unmapped before the memfd is closed.
+    map: Mapping,
+    fd: fd::OwnedFd,
+}
+
+assert_impl_all!(SealedBytes: Send, Sync, Unpin);
+
+impl SealedBytes {
+    /// Copy `bytes` into a new sealed memfd.
+    pub fn new(bytes: &[u8]) -> crate::Result<Self> {
+        let name = CStr::from_bytes_with_nul(b"zvariant-sealed-bytes\0").unwrap();
+        // SAFETY: `name` is a valid C string.
+        let fd = unsafe {
+            libc::memfd_create(name.as_ptr(), libc::MFD_CLOEXEC | libc::MFD_ALLOW_SEALING)
+        };
+        if fd < 0 {
+            return Err(io::Error::last_os_error().into());
+        }
+        // SAFETY: We just created `fd` and nothing else owns it.
+        let fd = unsafe { fd::OwnedFd::from_raw_fd(fd) };
+
+        let mut file = std::fs::File::from(fd);
+        io::Write::write_all(&mut file, bytes)?;
+        let fd = fd::OwnedFd::from(file);
+        // SAFETY: `F_ADD_SEALS` takes an integer argument.
+        let ret = unsafe {
+            libc::fcntl(
+                fd.as_raw_fd(),
+                libc::F_ADD_SEALS,
+                REQUIRED_SEALS | libc::F_SEAL_SEAL,
+            )
+        };
+        if ret < 0 {
+            return Err(io::Error::last_os_error().into());
+        }
+
+        Self::from_sealed_fd(fd, bytes.len())
+    }
+
+    /// Map the bytes in the memfd `fd`.
+    ///
+    /// Fails if `fd` isn't a memfd that is sealed against writing, shrinking and growing.
+    pub fn from_fd(fd: fd::OwnedFd) -> crate::Result<Self> {
+        // SAFETY: `F_GET_SEALS` takes no argument.
+        let seals = unsafe { libc::fcntl(fd.as_raw_fd(), libc::F_GET_SEALS) };
+        if seals < 0 {
+            return Err(io::Error::last_os_error().into());
+        }
+        if seals & REQUIRED_SEALS != REQUIRED_SEALS {
+            return Err(Error::Message(
+                "file descriptor is not a sealed memfd".to_string(),
+            ));
+        }
+        let len = std::fs::File::from(fd.try_clone()?).metadata()?.len();
+        let len = usize::try_from(len).map_err(|_| Error::OutOfBounds)?;
+
+        Self::from_sealed_fd(fd, len)
+    }
+
+    fn from_sealed_fd(fd: fd::OwnedFd, len: usize) -> crate::Result<Self> {
+        Ok(Self {
+            map: Mapping::new(fd.as_fd(), len)?,
+            fd,
+        })
+    }
+
+    /// The bytes.
+    pub fn as_bytes(&self) -> &[u8] {
+        self.map.as_bytes()
+    }
+
+    /// Try to clone `self`.
+    ///
+    /// The memory holding the bytes is shared between the clones.
+    pub fn try_clone(&self) -> crate::Result<Self> {
+        Self::from_sealed_fd(self.fd.try_clone()?, self.len())
+    }
+}
+
+impl Deref for SealedBytes {
+    type Target = [u8];
+
+    fn deref(&self) -> &[u8] {
+        self.as_bytes()
+    }
+}
+
+impl AsRef<[u8]> for SealedBytes {
+    fn as_ref(&self) -> &[u8] {
+        self.as_bytes()
+    }
+}
+
+impl AsFd for SealedBytes {
+    fn as_fd(&self) -> BorrowedFd<'_> {
+        self.fd.as_fd()
+    }
+}
+
+impl AsRawFd for SealedBytes {
+    fn as_raw_fd(&self) -> fd::RawFd {
+        self.fd.as_raw_fd()
+    }
+}
+
+impl TryFrom<&[u8]> for SealedBytes {
+    type Error = Error;
+
+    fn try_from(bytes: &[u8]) -> crate::Result<Self> {
+        Self::new(bytes)
+    }
+}
+
+impl TryFrom<fd::OwnedFd> for SealedBytes {
+    type Error = Error;
+
+    fn try_from(fd: fd::OwnedFd) -> crate::Result<Self> {
+        Self::from_fd(fd)
+    }
+}
+
+impl From<SealedBytes> for fd::OwnedFd {
+    fn from(bytes: SealedBytes) -> Self {
+        bytes.fd
+    }
+}
+
+impl From<SealedBytes> for Value<'static> {
+    fn from(bytes: SealedBytes) -> Self {
+        Value::Fd(Fd::from(fd::OwnedFd::from(bytes)))
+    }
+}
+
+impl TryFrom<Value<'_>> for SealedBytes {
+    type Error = Error;
+
+    fn try_from(value: Value<'_>) -> crate::Result<Self> {
+        match value {
+            Value::Fd(Fd::Owned(fd)) => Self::from_fd(fd),
+            value => Self::try_from(&value),
+        }
+    }
+}
+
+impl TryFrom<&Value<'_>> for SealedBytes {
+    type Error = Error;
+
+    fn try_from(value: &Value<'_>) -> crate::Result<Self> {
+        if let Value::Fd(fd) = value {
+            Self::from_fd(fd.as_fd().try_clone_to_owned()?)
+        } else {
+            Err(Error::IncorrectType)
+        }
+    }
+}
+
+impl TryFrom<OwnedValue> for SealedBytes {
+    type Error = Error;
+
+    fn try_from(value: OwnedValue) -> crate::Result<Self> {
+        Self::try_from(Value::from(value))
+    }
+}
+
+impl fmt::Debug for SealedBytes {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("SealedBytes")
+            .field("fd", &self.fd)
+            .field("len", &self.len())
+            .finish()
+    }
+}
+
+impl PartialEq for SealedBytes {
+    fn eq(&self, other: &Self) -> bool {
+        self.as_bytes() == other.as_bytes()
+    }
+}
+
+impl Eq for SealedBytes {}
+
+impl Type for SealedBytes {
+    const SIGNATURE: &'static ParsedSignature = Fd::SIGNATURE;
+}
+
+impl Serialize for SealedBytes {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        Fd::from(&self.fd).serialize(serializer)
+    }
+}
+
+impl<'de> Deserialize<'de> for SealedBytes {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        let fd = Fd::deserialize(deserializer)?;
+        let fd = fd.as_fd().try_clone_to_owned().map_err(D::Error::custom)?;
+
+        Self::from_fd(fd).map_err(D::Error::custom)
+    }
+}
+
+/// A read-only shared mapping of a file.
+struct Mapping {
+    ptr: *mut libc::c_void,
+    len: usize,
+}
+
+// SAFETY: The mapping is read-only and of a sealed memfd, so it's never modified.
+unsafe impl Send for Mapping {}
+unsafe impl Sync for Mapping {}
+
+impl Mapping {
+    fn new(fd: BorrowedFd<'_>, len: usize) -> io::Result<Self> {
+        if len == 0 {
+            // Empty mappings are not allowed.
+            return Ok(Self {
+                ptr: ptr::null_mut(),
+                len,
+            });
+        }
+
+        // SAFETY: We map the whole file read-only, so the mapping doesn't alias any Rust memory.
+        let ptr = unsafe {
+            libc::mmap(
+                ptr::null_mut(),
+                len,
+                libc::PROT_READ,
+                libc::MAP_SHARED,
+                fd.as_raw_fd(),
+                0,
+            )
+        };
+        if ptr == libc::MAP_FAILED {
+            return Err(io::Error::last_os_error());
+        }
+
+        Ok(Self { ptr, len })
+    }
+
+    fn as_bytes(&self) -> &[u8] {
+        if self.len == 0 {
+            return &[];
+        }
+
+        // SAFETY: The memory is mapped for as long as `self` lives and since the memfd is sealed,
+        // the file can't shrink (which would make accessing the memory fault) or be modified.
+        unsafe { slice::from_raw_parts(self.ptr.cast(), self.len) }
+    }
+}
+
+impl Drop for Mapping {
+    fn drop(&mut self) {
+        if self.len != 0 {
+            // SAFETY: The memory was mapped by us and no references to it outlive `self`.
+            unsafe { libc::munmap(self.ptr, self.len) };
+        }
+    }
+}
@@ -14,9 +14,8 @@ use crate::{
     container_depths::ContainerDepths,
     dbus::Serializer as DBusSerializer,
     serialized::{Context, Data, Format, Size, Written},
-    signature_parser::SignatureParser,
     utils::*,
-    Basic, DynamicType, Error, Result, Signature,
+    Basic, DynamicType, Error, ParsedSignature, Result, Signature,
 };
 
 struct NullWriteSeek;
@@ -57,31 +56,31 @@ where
     T: Serialize + DynamicType,
 {
     let mut null = NullWriteSeek;
-    let signature = value.dynamic_signature();
+    let signature = value.dynamic_parsed_signature()?;
     #[cfg(unix)]
     let mut fds = FdList::Number(0);
 
     let len = match ctxt.format() {
         Format::DBus => {
             let mut ser = DBusSerializer::<B, NullWriteSeek>::new(
-                signature,
+                &signature,
                 &mut null,
                 #[cfg(unix)]
                 &mut fds,
                 ctxt,
-            )?;
+            );
             value.serialize(&mut ser)?;
             ser.0.bytes_written
         }
         #[cfg(feature = "gvariant")]
         Format::GVariant => {
             let mut ser = GVSerializer::<B, NullWriteSeek>::new(
-                signature,
+                &signature,
                 &mut null,
                 #[cfg(unix)]
                 &mut fds,
                 ctxt,
-            )?;
+            );
             value.serialize(&mut ser)?;
             ser.0.bytes_written
         }
@@ -134,9 +133,9 @@ where
     W: Write + Seek,
     T: Serialize + DynamicType,
 {
-    let signature = value.dynamic_signature();
+    let signature = value.dynamic_parsed_signature()?;
 
-    to_writer_for_signature(writer, ctxt, &signature, value)
+    to_writer_for_parsed_signature(writer, ctxt, &signature, value)
 }
 
 /// Serialize `T` as a byte vector.
@@ -147,7 +146,9 @@ where
     B: byteorder::ByteOrder,
     T: Serialize + DynamicType,
 {
-    to_bytes_for_signature(ctxt, value.dynamic_signature(), value)
+    let signature = value.dynamic_parsed_signature()?;
+
+    to_bytes_for_parsed_signature(ctxt, &signature, value)
 }
 
 /// Serialize `T` that has the given signature, to the given `writer`.
@@ -178,6 +179,23 @@ where
     S: TryInto<Signature<'s>>,
     S::Error: Into<Error>,
     T: Serialize,
+{
+    let signature = signature.try_into().map_err(Into::into)?;
+    let signature = ParsedSignature::try_from(&signature)?;
+
+    to_writer_for_parsed_signature(writer, ctxt, &signature, value)
+}
+
+unsafe fn to_writer_for_parsed_signature<B, W, T: ?Sized>(
+    writer: &mut W,
+    ctxt: Context<B>,
+    signature: &ParsedSignature,
+    value: &T,
+) -> Result<Written<B>>
+where
+    B: byteorder::ByteOrder,
+    W: Write + Seek,
+    T: Serialize,
 {
     #[cfg(unix)]
     let mut fds = FdList::Fds(vec![]);
@@ -190,7 +208,7 @@ where
                 #[cfg(unix)]
                 &mut fds,
                 ctxt,
-            )?;
+            );
             value.serialize(&mut ser)?;
             ser.0.bytes_written
         }
@@ -202,7 +220,7 @@ where
                 #[cfg(unix)]
                 &mut fds,
                 ctxt,
-            )?;
+            );
             value.serialize(&mut ser)?;
             ser.0.bytes_written
         }
@@ -236,11 +254,26 @@ where
     S: TryInto<Signature<'s>>,
     S::Error: Into<Error>,
     T: Serialize,
+{
+    let signature = signature.try_into().map_err(Into::into)?;
+    let signature = ParsedSignature::try_from(&signature)?;
+
+    to_bytes_for_parsed_signature(ctxt, &signature, value)
+}
+
+fn to_bytes_for_parsed_signature<B, T: ?Sized>(
+    ctxt: Context<B>,
+    signature: &ParsedSignature,
+    value: &T,
+) -> Result<Data<'static, 'static, B>>
+where
+    B: byteorder::ByteOrder,
+    T: Serialize,
 {
     let mut cursor = std::io::Cursor::new(vec![]);
     // SAFETY: We put the bytes and FDs in the `Data` to ensure that the data and FDs are only
     // dropped together.
-    let ret = unsafe { to_writer_for_signature(&mut cursor, ctxt, signature, value) }?;
+    let ret = unsafe { to_writer_for_parsed_signature(&mut cursor, ctxt, signature, value) }?;
     #[cfg(unix)]
     let encoded = Data::new_fds(cursor.into_inner(), ctxt, ret.into_fds());
     #[cfg(not(unix))]
@@ -260,7 +293,7 @@ pub(crate) struct SerializerCommon<'ser, 'sig, B, W> {
     #[cfg(unix)]
     pub(crate) fds: &'ser mut FdList,
 
-    pub(crate) sig_parser: SignatureParser<'sig>,
+    pub(crate) signature: NextSignature<'sig>,
 
     pub(crate) value_sign: Option<Signature<'static>>,
 
@@ -269,6 +302,89 @@ pub(crate) struct SerializerCommon<'ser, 'sig, B, W> {
     pub(crate) b: PhantomData<B>,
 }
 
+/// The signature of the value to be serialized next.
+#[derive(Debug, Clone, Copy)]
+pub(crate) enum NextSignature<'sig> {
+    /// A complete type.
+    Type(&'sig ParsedSignature),
+    /// A dict-entry, i.e. an element of a dictionary.
+    DictEntry {
+        key: &'sig ParsedSignature,
+        value: &'sig ParsedSignature,
+    },
+}
+
+impl<'sig> NextSignature<'sig> {
+    /// The first character of the signature, mostly useful for error messages.
+    pub(crate) fn first_char(self) -> Result<char> {
+        let signature = match self {
+            Self::Type(signature) => signature,
+            Self::DictEntry { .. } => return Ok(DICT_ENTRY_SIG_START_CHAR),
+        };
+
+        let c = match signature {
+            ParsedSignature::Unit => return Err(Error::OutOfBounds),
+            ParsedSignature::Array(_) | ParsedSignature::Dict { .. } => ARRAY_SIGNATURE_CHAR,
+            ParsedSignature::Structure(_) => STRUCT_SIG_START_CHAR,
+            // `MAYBE_SIGNATURE_CHAR` is only defined with the `gvariant` feature.
+            ParsedSignature::Maybe(_) => 'm',
+            basic => basic
+                .as_static_str()
+                .and_then(|s| s.chars().next())
+                .expect("basic signature without a character"),
+        };
+
+        Ok(c)
+    }
+
+    /// The signature of the elements, if this is an array or a dictionary.
+    pub(crate) fn element(self) -> Result<Self> {
+        match self {
+            Self::Type(ParsedSignature::Array(child)) => Ok(Self::Type(child)),
+            Self::Type(ParsedSignature::Dict { key, value }) => Ok(Self::DictEntry { key, value }),
+            _ => Err(self.mismatch("an array or a dictionary")),
+        }
+    }
+
+    /// The signature of the field at `index`, if this is a structure or a dict-entry.
+    pub(crate) fn field(self, index: usize) -> Result<&'sig ParsedSignature> {
+        let field = match self {
+            Self::Type(ParsedSignature::Structure(fields)) => fields.get(index),
+            Self::DictEntry { key, .. } if index == 0 => Some(key),
+            Self::DictEntry { value, .. } if index == 1 => Some(value),
+            _ => None,
+        };
+
+        field.ok_or_else(|| self.mismatch(&format!("a structure with field {index}")))
+    }
+
+    pub(crate) fn alignment(self, format: Format) -> usize {
+        match self {
+            Self::Type(signature) => alignment_for_parsed_signature(signature, format),
+            Self::DictEntry { key, value } => alignment_for_dict_entry(key, value, format),
+        }
+    }
+
+    #[cfg(feature = "gvariant")]
+    pub(crate) fn is_fixed_sized(self) -> bool {
+        match self {
+            Self::Type(signature) => signature.is_fixed_sized(),
+            Self::DictEntry { key, value } => key.is_fixed_sized() && value.is_fixed_sized(),
+        }
+    }
+
+    pub(crate) fn mismatch(self, expected: &str) -> Error {
+        let signature = match self {
+            Self::Type(signature) => Signature::from(signature),
+            Self::DictEntry { key, value } => {
+                Signature::from_string_unchecked(format!("{{{key}{value}}}"))
+            }
+        };
+
+        Error::SignatureMismatch(signature, format!("expected {expected}"))
+    }
+}
+
 #[cfg(unix)]
 pub(crate) enum FdList {
     Fds(Vec<OwnedFd>),
@@ -323,7 +439,6 @@ where
     where
         T: Basic,
     {
-        self.sig_parser.skip_char()?;
         self.add_padding(T::alignment(self.ctxt.format()))?;
 
         Ok(())
@@ -331,26 +446,28 @@ where
 
     /// This starts the enum serialization.
     ///
-    /// It's up to the caller to do the rest: serialize the variant payload and skip the `).
+    /// It's up to the caller to do the rest, i.e. serialize the variant payload, for which the
+    /// signature is set.
     pub(crate) fn prep_serialize_enum_variant(&mut self, variant_index: u32) -> Result<()> {
         // Encode enum variants as a struct with first field as variant index
-        let signature = self.sig_parser.next_signature()?;
-        if self.sig_parser.next_char()? != STRUCT_SIG_START_CHAR {
-            return Err(Error::SignatureMismatch(
-                signature.to_owned(),
-                format!("expected `{STRUCT_SIG_START_CHAR}`"),
-            ));
+        if !matches!(
+            self.signature,
+            NextSignature::Type(ParsedSignature::Structure(_))
+        ) {
+            return Err(self
+                .signature
+                .mismatch(&format!("`{STRUCT_SIG_START_CHAR}`")));
         }
 
-        let alignment = alignment_for_signature(&signature, self.ctxt.format())?;
+        let alignment = self.signature.alignment(self.ctxt.format());
         self.add_padding(alignment)?;
 
         // Now serialize the veriant index.
         self.write_u32::<B>(variant_index)
             .map_err(|e| Error::InputOutput(e.into()))?;
 
-        // Skip the `(`, `u`.
-        self.sig_parser.skip_chars(2)?;
+        // The payload is the second field.
+        self.signature = NextSignature::Type(self.signature.field(1)?);
 
         Ok(())
     }
@@ -0,0 +1,96 @@
+use serde::ser::{Serialize, Serializer};
+use static_assertions::assert_impl_all;
+
+use crate::{ParsedSignature, Type};
+
+/// A wrapper to serialize the items of an iterator as an array.
+///
+/// The items are serialized as the iterator produces them, so unlike collecting them into a `Vec`
+/// first, only one item needs to be in memory at a time. The size of the array, which the D-Bus
+/// format encodes before the elements, is filled in once all the elements are written.
+///
+/// Since serialization only borrows the wrapper, the iterator is cloned each time it's serialized.
+///
+/// ```
+/// # use zvariant::{to_bytes, serialized::Context, SerializeIter};
+/// #
+/// # let ctxt = Context::<byteorder::LE>::new_dbus(0);
+/// let entries = (0..1000u32).map(|i| (i, i.to_string(), i64::from(i)));
+/// let encoded = to_bytes(ctxt, &SerializeIter(entries)).unwrap();
+///
+/// let decoded: Vec<(u32, String, i64)> = encoded.deserialize().unwrap().0;
+/// assert_eq!(decoded.len(), 1000);
+/// assert_eq!(decoded[999], (999, String::from("999"), 999));
+/// ```
+///
+/// Use [`SerializeDictIter`] to serialize pairs of keys and values as a dictionary.
+#[derive(Debug, Clone)]
+pub struct SerializeIter<I>(pub I);
+
+assert_impl_all!(SerializeIter<std::ops::Range<u32>>: Send, Sync, Unpin);
+
+impl<I> Serialize for SerializeIter<I>
+where
+    I: IntoIterator + Clone,
+    I::Item: Serialize,
+{
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        serializer.collect_seq(self.0.clone())
+    }
+}
+
+impl<I> Type for SerializeIter<I>
+where
+    I: IntoIterator,
+    I::Item: Type,
+{
+    const SIGNATURE: &'static ParsedSignature =
+        &ParsedSignature::static_array(<I::Item as Type>::SIGNATURE);
+}
+
+/// A wrapper to serialize the key-value pairs of an iterator as a dictionary.
+///
+/// This is the dictionary counterpart of [`SerializeIter`].
+///
+/// ```
+/// # use std::collections::HashMap;
+/// # use zvariant::{to_bytes, serialized::Context, SerializeDictIter};
+/// #
+/// # let ctxt = Context::<byteorder::LE>::new_dbus(0);
+/// let entries = (0..100u32).map(|i| (i.to_string(), i));
+/// let encoded = to_bytes(ctxt, &SerializeDictIter(entries)).unwrap();
+///
+/// let decoded: HashMap<String, u32> = encoded.deserialize().unwrap().0;
+/// assert_eq!(decoded["42"], 42);
+/// ```
+#[derive(Debug, Clone)]
+pub struct SerializeDictIter<I>(pub I);
+
+assert_impl_all!(SerializeDictIter<std::vec::IntoIter<(u8, u8)>>: Send, Sync, Unpin);
+
+impl<I, K, V> Serialize for SerializeDictIter<I>
+where
+    I: IntoIterator<Item = (K, V)> + Clone,
+    K: Serialize,
+    V: Serialize,
+{
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        serializer.collect_map(self.0.clone())
+    }
+}
+
+impl<I, K, V> Type for SerializeDictIter<I>
+where
+    I: IntoIterator<Item = (K, V)>,
+    K: Type,
+    V: Type,
+{
+    const SIGNATURE: &'static ParsedSignature =
+        &ParsedSignature::static_dict(K::SIGNATURE, V::SIGNATURE);
+}
@@ -0,0 +1,313 @@
+use std::{iter::FusedIterator, marker::PhantomData};
+
+use byteorder::ByteOrder;
+use serde::Deserialize;
+
+#[cfg(feature = "gvariant")]
+use crate::{framing_offset_size::FramingOffsetSize, utils::is_fixed_sized_signature};
+use crate::{
+    serialized::{Data, Format},
+    utils::{alignment_for_signature, padding_for_n_bytes, subslice, ARRAY_ALIGNMENT_DBUS},
+    Error, Result, Signature,
+};
+
+/// An iterator over the elements of a serialized array.
+///
+/// Each element is only deserialized when the iterator gets to it, so unlike deserializing the
+/// whole array into a `Vec`, only one element needs to be in memory at a time.
+///
+/// Created by [`Data::array_iter`]. Once an element fails to deserialize, the iterator ends.
+#[derive(Debug)]
+pub struct ArrayIter<'d, 'bytes, 'fds, B: ByteOrder, T> {
+    data: &'d Data<'bytes, 'fds, B>,
+    element_signature: Signature<'static>,
+    element_alignment: usize,
+    // Where the next element starts, before its padding.
+    pos: usize,
+    // Where the elements end.
+    end: usize,
+    // The framing offsets of GVariant arrays of variable-sized elements.
+    #[cfg(feature = "gvariant")]
+    offsets: Option<FramingOffsets>,
+    done: bool,
+    phantom: PhantomData<fn() -> T>,
+}
+
+#[cfg(feature = "gvariant")]
+#[derive(Debug)]
+struct FramingOffsets {
+    size: FramingOffsetSize,
+    // Where the array starts, which the offsets are relative to.
+    start: usize,
+    // Where the offset of the next element is.
+    next: usize,
+}
+
+impl<'d, 'bytes, 'fds, B, T> ArrayIter<'d, 'bytes, 'fds, B, T>
+where
+    B: ByteOrder,
+    T: Deserialize<'d>,
+{
+    pub(super) fn new(
+        data: &'d Data<'bytes, 'fds, B>,
+        element_signature: Signature<'static>,
+    ) -> Result<Self> {
+        let format = data.context().format();
+        let element_alignment = alignment_for_signature(&element_signature, format)?;
+        let mut iter = Self {
+            data,
+            element_signature,
+            element_alignment,
+            pos: 0,
+            end: data.len(),
+            #[cfg(feature = "gvariant")]
+            offsets: None,
+            done: false,
+            phantom: PhantomData,
+        };
+
+        match format {
+            Format::DBus => {
+                iter.skip_padding(ARRAY_ALIGNMENT_DBUS)?;
+                let len = B::read_u32(subslice(data.bytes(), iter.pos..iter.pos + 4)?) as usize;
+                iter.pos += 4;
+                // The padding of the first element is there even if the array is empty.
+                iter.skip_padding(element_alignment)?;
+                iter.end = iter.pos + len;
+                if iter.end > data.len() {
+                    return Err(serde::de::Error::invalid_length(
+                        data.len(),
+                        &format!(">= {}", iter.end).as_str(),
+                    ));
+                }
+            }
+            #[cfg(feature = "gvariant")]
+            Format::GVariant => {
+                iter.skip_padding(element_alignment)?;
+                if !is_fixed_sized_signature(&iter.element_signature)? {
+                    // The last offset tells us the start of offsets.
+                    let container = &data.bytes()[iter.pos..];
+                    let size = FramingOffsetSize::for_encoded_container(container.len());
+                    let offsets_start = size.read_last_offset_from_buffer(container);
+                    if offsets_start > container.len()
+                        || (container.len() - offsets_start) % size as usize != 0
+                    {
+                        return Err(Error::MissingFramingOffset);
+                    }
+
+                    iter.end = iter.pos + offsets_start;
+                    iter.offsets = Some(FramingOffsets {
+                        size,
+                        start: iter.pos,
+                        next: iter.end,
+                    });
+                }
+            }
+        }
+
+        Ok(iter)
+    }
+
+    fn next_element(&mut self) -> Result<Option<T>> {
+        #[cfg(feature = "gvariant")]
+        if let Some(offsets) = &mut self.offsets {
+            if offsets.next == self.data.len() {
+                return Ok(None);
+            }
+            let offset_end = offsets.next + offsets.size as usize;
+            let offset = &self.data.bytes()[offsets.next..offset_end];
+            let end = offsets.start + offsets.size.read_last_offset_from_buffer(offset);
+            offsets.next = offset_end;
+
+            self.skip_padding(self.element_alignment)?;
+            if self.pos > end || end > self.end {
+                return Err(Error::OutOfBounds);
+            }
+            let (element, _) = self
+                .data
+                .deserialize_range_for_signature(self.pos..end, self.element_signature.clone())?;
+            self.pos = end;
+
+            return Ok(Some(element));
+        }
+
+        if self.pos == self.end {
+            return Ok(None);
+        }
+        self.skip_padding(self.element_alignment)?;
+        let (element, size) = self
+            .data
+            .deserialize_range_for_signature(self.pos..self.end, self.element_signature.clone())?;
+        self.pos += size;
+
+        Ok(Some(element))
+    }
+
+    fn skip_padding(&mut self, alignment: usize) -> Result<()> {
+        let padding = padding_for_n_bytes(self.data.context().position() + self.pos, alignment);
+        for byte in subslice(self.data.bytes(), self.pos..self.pos + padding)? {
+            if *byte != 0 {
+                return Err(Error::PaddingNot0(*byte));
+            }
+        }
+        self.pos += padding;
+
+        Ok(())
+    }
+}
+
+impl<'d, 'bytes, 'fds, B, T> Iterator for ArrayIter<'d, 'bytes, 'fds, B, T>
+where
+    B: ByteOrder,
+    T: Deserialize<'d>,
+{
+    type Item = Result<T>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.done {
+            return None;
+        }
+
+        let element = self.next_element();
+        if !matches!(element, Ok(Some(_))) {
+            self.done = true;
+        }
+
+        element.transpose()
+    }
+}
+
+impl<'d, 'bytes, 'fds, B, T> FusedIterator for ArrayIter<'d, 'bytes, 'fds, B, T>
+where
+    B: ByteOrder,
+    T: Deserialize<'d>,
+{
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+
+    use byteorder::{ByteOrder, BE, LE};
+
+    use crate::{
+        serialized::{Context, Data, Format},
+        to_bytes, Error, SerializeDictIter, SerializeIter, Value,
+    };
+
+    fn check_format<B: ByteOrder>(format: Format) {
+        let ctxt = Context::<B>::new(format, 0);
+
+        // Variable-sized elements, large enough for multi-byte GVariant framing offsets.
+        let entries = (0..10_000u32).map(|i| (i.to_string(), i.to_string(), i64::from(i)));
+        let encoded = to_bytes(ctxt, &SerializeIter(entries.clone())).unwrap();
+        assert_eq!(
+            encoded.bytes(),
+            to_bytes(ctxt, &entries.clone().collect::<Vec<_>>())
+                .unwrap()
+                .bytes()
+        );
+        let mut count = 0;
+        for (i, entry) in encoded
+            .array_iter::<(&str, &str, i64)>()
+            .unwrap()
+            .enumerate()
+        {
+            let (a, b, x) = entry.unwrap();
+            assert_eq!(a, i.to_string());
+            assert_eq!(b, a);
+            assert_eq!(x, i as i64);
+            count += 1;
+        }
+        assert_eq!(count, 10_000);
+
+        // Fixed-sized elements.
+        let encoded =
+            to_bytes(ctxt, &SerializeIter((0..100u16).map(|i| (i, u64::from(i))))).unwrap();
+        let decoded: Vec<(u16, u64)> = encoded
+            .array_iter()
+            .unwrap()
+            .collect::<crate::Result<_>>()
+            .unwrap();
+        assert_eq!(
+            decoded,
+            (0..100).map(|i| (i, u64::from(i))).collect::<Vec<_>>()
+        );
+
+        // Nested arrays and variants.
+        let nested = vec![
+            vec![Value::from(1u8)],
+            vec![],
+            vec![Value::from("a"), Value::from(2u64)],
+        ];
+        let encoded = to_bytes(ctxt, &nested).unwrap();
+        let decoded: Vec<Vec<Value<'_>>> = encoded
+            .array_iter()
+            .unwrap()
+            .collect::<crate::Result<_>>()
+            .unwrap();
+        assert_eq!(decoded, nested);
+
+        // Empty arrays.
+        let encoded = to_bytes(ctxt, &SerializeIter(Vec::<String>::new())).unwrap();
+        assert!(encoded.array_iter::<&str>().unwrap().next().is_none());
+        let encoded = to_bytes(ctxt, &Vec::<u64>::new()).unwrap();
+        assert!(encoded.array_iter::<u64>().unwrap().next().is_none());
+
+        // Dictionaries.
+        let dict = HashMap::from([("one", Value::from(1u8)), ("two", Value::from("2"))]);
+        let encoded = to_bytes(ctxt, &dict).unwrap();
+        let decoded: HashMap<&str, Value<'_>> = encoded
+            .array_iter()
+            .unwrap()
+            .collect::<crate::Result<_>>()
+            .unwrap();
+        assert_eq!(decoded, dict);
+        let encoded_iter = to_bytes(ctxt, &SerializeDictIter(dict.iter())).unwrap();
+        assert_eq!(encoded_iter.bytes(), encoded.bytes());
+
+        // Not at the start of the data.
+        let encoded = to_bytes(Context::<B>::new(format, 3), &vec![(1u8, 2u64), (3, 4)]).unwrap();
+        let data = Data::new(encoded.bytes(), Context::<B>::new(format, 3));
+        let decoded: Vec<(u8, u64)> = data
+            .array_iter()
+            .unwrap()
+            .collect::<crate::Result<_>>()
+            .unwrap();
+        assert_eq!(decoded, [(1, 2), (3, 4)]);
+    }
+
+    #[test]
+    fn dbus() {
+        check_format::<LE>(Format::DBus);
+        check_format::<BE>(Format::DBus);
+    }
+
+    #[cfg(feature = "gvariant")]
+    #[test]
+    fn gvariant() {
+        check_format::<LE>(Format::GVariant);
+        check_format::<BE>(Format::GVariant);
+    }
+
+    #[test]
+    fn invalid() {
+        let ctxt = Context::<LE>::new_dbus(0);
+        let encoded = to_bytes(ctxt, &vec!["a", "b"]).unwrap();
+        // Iteration stops at the first error.
+        let mut bytes = encoded.bytes().to_vec();
+        bytes[8] = 0xff;
+        let data = Data::new(bytes, ctxt);
+        let mut iter = data.array_iter::<&str>().unwrap();
+        assert!(matches!(iter.next(), Some(Err(Error::Utf8(_)))));
+        assert!(iter.next().is_none());
+
+        // Array size past the end.
+        let mut bytes = encoded.bytes().to_vec();
+        bytes[0] = 0xff;
+        let data = Data::new(bytes, ctxt);
+        data.array_iter::<&str>().unwrap_err();
+        let data = Data::new(&[1, 0][..], ctxt);
+        assert!(matches!(data.array_iter::<u8>(), Err(Error::OutOfBounds)));
+    }
+}
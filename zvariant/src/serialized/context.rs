@@ -2,7 +2,7 @@ use std::marker::PhantomData;
 
 use static_assertions::assert_impl_all;
 
-use crate::serialized::Format;
+use crate::serialized::{Format, Limits};
 
 /// The encoding context to use with the [serialization and deserialization] API.
 ///
@@ -37,6 +37,7 @@ use crate::serialized::Format;
 pub struct Context<B> {
     format: Format,
     position: usize,
+    limits: Limits,
 
     b: PhantomData<B>,
 }
@@ -52,6 +53,7 @@ where
         Self {
             format,
             position,
+            limits: Limits::default(),
             b: PhantomData,
         }
     }
@@ -80,4 +82,19 @@ where
     pub fn position(self) -> usize {
         self.position
     }
+
+    /// Set the [`Limits`] to enforce.
+    ///
+    /// All limits are enforced when deserializing, while only the depth limits apply to
+    /// serialization.
+    pub fn with_limits(mut self, limits: Limits) -> Self {
+        self.limits = limits;
+
+        self
+    }
+
+    /// The [`Limits`] to enforce.
+    pub fn limits(self) -> Limits {
+        self.limits
+    }
 }
@@ -11,7 +11,7 @@ use serde::{de::DeserializeSeed, Deserialize};
 
 use crate::{
     de::Deserializer,
-    serialized::{Context, Format},
+    serialized::{ArrayIter, Context, Format},
     DynamicDeserialize, DynamicType, Error, Result, Signature, Type,
 };
 
@@ -105,7 +105,8 @@ impl<'bytes, 'fds, B: ByteOrder> Data<'bytes, 'fds, B> {
         );
         assert!(end <= len, "range end out of bounds: {end:?} > {len:?}");
 
-        let context = Context::new(self.context.format(), self.context.position() + start);
+        let context = Context::new(self.context.format(), self.context.position() + start)
+            .with_limits(self.context.limits());
         let range = Range {
             start: self.range.start + start,
             end: self.range.start + end,
@@ -217,6 +218,24 @@ impl<'bytes, 'fds, B: ByteOrder> Data<'bytes, 'fds, B> {
     {
         let signature = signature.try_into().map_err(Into::into)?;
 
+        self.deserialize_range_for_signature(0..self.len(), signature)
+    }
+
+    // Deserialize `T` from the `range` of `self` with the given signature.
+    //
+    // Unlike deserializing from a `slice` of `self`, the result can borrow from `self`.
+    pub(super) fn deserialize_range_for_signature<'d, T>(
+        &'d self,
+        range: Range<usize>,
+        signature: Signature<'d>,
+    ) -> Result<(T, usize)>
+    where
+        T: Deserialize<'d>,
+    {
+        let bytes = crate::utils::subslice(self.bytes(), range.clone())?;
+        let ctxt = Context::<B>::new(self.context.format(), self.context.position() + range.start)
+            .with_limits(self.context.limits());
+
         #[cfg(unix)]
         let fds = &self.inner.fds;
         let mut de = match self.context.format() {
@@ -224,31 +243,22 @@ impl<'bytes, 'fds, B: ByteOrder> Data<'bytes, 'fds, B> {
             Format::GVariant => {
                 #[cfg(unix)]
                 {
-                    crate::gvariant::Deserializer::new(
-                        self.bytes(),
-                        Some(fds),
-                        signature,
-                        self.context,
-                    )
+                    crate::gvariant::Deserializer::new(bytes, Some(fds), signature, ctxt)
                 }
                 #[cfg(not(unix))]
                 {
-                    crate::gvariant::Deserializer::<_, ()>::new(
-                        self.bytes(),
-                        signature,
-                        self.context,
-                    )
+                    crate::gvariant::Deserializer::<_, ()>::new(bytes, signature, ctxt)
                 }
             }
             .map(Deserializer::GVariant)?,
             Format::DBus => {
                 #[cfg(unix)]
                 {
-                    crate::dbus::Deserializer::new(self.bytes(), Some(fds), signature, self.context)
+                    crate::dbus::Deserializer::new(bytes, Some(fds), signature, ctxt)
                 }
                 #[cfg(not(unix))]
                 {
-                    crate::dbus::Deserializer::<_, ()>::new(self.bytes(), signature, self.context)
+                    crate::dbus::Deserializer::<_, ()>::new(bytes, signature, ctxt)
                 }
             }
             .map(Deserializer::DBus)?,
@@ -333,6 +343,78 @@ impl<'bytes, 'fds, B: ByteOrder> Data<'bytes, 'fds, B> {
             Deserializer::DBus(de) => (t, de.0.pos),
         })
     }
+
+    /// Iterate over the elements of the array in `self`, deserializing them as `T`.
+    ///
+    /// The elements are deserialized one at a time, as the iterator gets to them, so this is
+    /// useful for large arrays that would take a lot of memory to deserialize all at once. For
+    /// dictionaries, use a tuple of the key and value types as `T` to iterate over the entries.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use std::collections::HashMap;
+    ///
+    /// use zvariant::{serialized::Context, to_bytes};
+    ///
+    /// let ctxt = Context::<byteorder::LE>::new_dbus(0);
+    /// let entries: Vec<_> = (0..1000u32).map(|i| (i.to_string(), i64::from(i))).collect();
+    /// let encoded = to_bytes(ctxt, &entries).unwrap();
+    ///
+    /// let mut sum = 0;
+    /// for entry in encoded.array_iter::<(&str, i64)>().unwrap() {
+    ///     let (name, value) = entry.unwrap();
+    ///     assert_eq!(name, value.to_string());
+    ///     sum += value;
+    /// }
+    /// assert_eq!(sum, 499500);
+    ///
+    /// let dict = HashMap::from([("one", 1u8)]);
+    /// let encoded = to_bytes(ctxt, &dict).unwrap();
+    /// let mut entries = encoded.array_iter::<(&str, u8)>().unwrap();
+    /// assert_eq!(entries.next().unwrap().unwrap(), ("one", 1));
+    /// assert!(entries.next().is_none());
+    /// ```
+    pub fn array_iter<'d, T>(&'d self) -> Result<ArrayIter<'d, 'bytes, 'fds, B, T>>
+    where
+        T: Deserialize<'d> + Type,
+    {
+        ArrayIter::new(self, T::signature())
+    }
+
+    /// Take the underlying buffer out of `self`, e.g to reuse it.
+    ///
+    /// This succeeds only if the bytes are owned and `self` is the last reference to them. Note
+    /// that the whole buffer is returned, even if `self` is a slice of it. The file descriptors, if
+    /// any, are dropped.
+    ///
+    /// On failure, `self` is returned as is.
+    pub fn try_into_vec(self) -> std::result::Result<Vec<u8>, Self> {
+        let Data {
+            inner,
+            context,
+            range,
+        } = self;
+        let inner = match Arc::try_unwrap(inner) {
+            Ok(inner) => inner,
+            Err(inner) => {
+                return Err(Data {
+                    inner,
+                    context,
+                    range,
+                })
+            }
+        };
+
+        match inner.bytes {
+            Cow::Owned(bytes) => Ok(bytes),
+            bytes @ Cow::Borrowed(_) => Err(Data {
+                inner: Arc::new(Inner { bytes, ..inner }),
+                context,
+                range,
+            }),
+        }
+    }
 }
 
 impl<'bytes, B: ByteOrder> Data<'bytes, 'static, B> {
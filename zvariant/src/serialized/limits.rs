@@ -0,0 +1,175 @@
+use static_assertions::assert_impl_all;
+
+use crate::{Error, MaxSizeExceeded, Result};
+
+/// Limits on the encoded data to deserialize.
+///
+/// When deserializing data from an untrusted source, the default limits might be too lenient (or
+/// too strict) for your needs. Set the limits of your choice on the [`Context`] used for
+/// deserialization through [`Context::with_limits`].
+///
+/// The default values are the limits imposed by the [D-Bus specification], which we also use for
+/// the GVariant format:
+///
+/// * structures and arrays can be nested 32 levels deep and containers in general, 64 levels deep.
+/// * arrays can be at most 64 MiB in size.
+/// * the encoded data can be at most 128 MiB in size.
+///
+/// Additionally, at most 16 file descriptors can accompany the encoded data by default, which is
+/// also the default of the reference D-Bus message bus implementation.
+///
+/// # Examples
+///
+/// ```
+/// use zvariant::{
+///     serialized::{Context, Data, Limits},
+///     to_bytes, Error, MaxSizeExceeded,
+/// };
+///
+/// let ctxt = Context::<byteorder::LE>::new_dbus(0);
+/// let encoded = to_bytes(ctxt, &vec![0u8; 1024]).unwrap();
+///
+/// let limits = Limits::default().set_max_array_size(512);
+/// let data = Data::new(encoded.bytes(), ctxt.with_limits(limits));
+/// assert_eq!(
+///     data.deserialize::<Vec<u8>>().unwrap_err(),
+///     Error::MaxSizeExceeded(MaxSizeExceeded::Array),
+/// );
+/// ```
+///
+/// [`Context`]: crate::serialized::Context
+/// [`Context::with_limits`]: crate::serialized::Context::with_limits
+/// [D-Bus specification]: https://dbus.freedesktop.org/doc/dbus-specification.html#message-protocol-marshaling
+#[derive(Debug, PartialEq, Eq, Copy, Clone)]
+pub struct Limits {
+    max_struct_depth: u8,
+    max_array_depth: u8,
+    max_container_depth: u8,
+    max_array_size: usize,
+    max_message_size: usize,
+    max_fds: usize,
+}
+
+assert_impl_all!(Limits: Send, Sync, Unpin);
+
+// We take the limits from the D-Bus specification for gvariant as well.
+//
+// The GVariant specification removed all the limits, from the D-Bus specification but that turned
+// out to be a [mistake]. Although glib went for a higher limit (128) but we'll stick to the D-Bus
+// limits and expand if/when needed.
+//
+// [mistake]: https://gitlab.gnome.org/GNOME/glib/-/commit/7c4e6e9fbe473de0401c778c6b0c4aad27d5145a
+const MAX_STRUCT_DEPTH: u8 = 32;
+const MAX_ARRAY_DEPTH: u8 = 32;
+const MAX_CONTAINER_DEPTH: u8 = 64;
+const MAX_ARRAY_SIZE: usize = 1 << 26;
+const MAX_MESSAGE_SIZE: usize = 1 << 27;
+const MAX_FDS: usize = 16;
+
+impl Default for Limits {
+    fn default() -> Self {
+        Self {
+            max_struct_depth: MAX_STRUCT_DEPTH,
+            max_array_depth: MAX_ARRAY_DEPTH,
+            max_container_depth: MAX_CONTAINER_DEPTH,
+            max_array_size: MAX_ARRAY_SIZE,
+            max_message_size: MAX_MESSAGE_SIZE,
+            max_fds: MAX_FDS,
+        }
+    }
+}
+
+impl Limits {
+    /// The maximum nesting depth of structures.
+    pub fn max_struct_depth(self) -> u8 {
+        self.max_struct_depth
+    }
+
+    /// Set the maximum nesting depth of structures.
+    pub fn set_max_struct_depth(mut self, depth: u8) -> Self {
+        self.max_struct_depth = depth;
+
+        self
+    }
+
+    /// The maximum nesting depth of arrays.
+    pub fn max_array_depth(self) -> u8 {
+        self.max_array_depth
+    }
+
+    /// Set the maximum nesting depth of arrays.
+    pub fn set_max_array_depth(mut self, depth: u8) -> Self {
+        self.max_array_depth = depth;
+
+        self
+    }
+
+    /// The maximum nesting depth of all containers combined.
+    pub fn max_container_depth(self) -> u8 {
+        self.max_container_depth
+    }
+
+    /// Set the maximum nesting depth of all containers combined.
+    pub fn set_max_container_depth(mut self, depth: u8) -> Self {
+        self.max_container_depth = depth;
+
+        self
+    }
+
+    /// The maximum size of an array, in bytes.
+    pub fn max_array_size(self) -> usize {
+        self.max_array_size
+    }
+
+    /// Set the maximum size of an array, in bytes.
+    pub fn set_max_array_size(mut self, size: usize) -> Self {
+        self.max_array_size = size;
+
+        self
+    }
+
+    /// The maximum size of the encoded data, in bytes.
+    pub fn max_message_size(self) -> usize {
+        self.max_message_size
+    }
+
+    /// Set the maximum size of the encoded data, in bytes.
+    pub fn set_max_message_size(mut self, size: usize) -> Self {
+        self.max_message_size = size;
+
+        self
+    }
+
+    /// The maximum number of file descriptors accompanying the encoded data.
+    pub fn max_fds(self) -> usize {
+        self.max_fds
+    }
+
+    /// Set the maximum number of file descriptors accompanying the encoded data.
+    pub fn set_max_fds(mut self, max: usize) -> Self {
+        self.max_fds = max;
+
+        self
+    }
+
+    // Check the size of the encoded data and the number of file descriptors against the limits.
+    pub(crate) fn check_data(self, len: usize, num_fds: usize) -> Result<()> {
+        if len > self.max_message_size {
+            return Err(Error::MaxSizeExceeded(MaxSizeExceeded::Message));
+        }
+
+        if num_fds > self.max_fds {
+            return Err(Error::MaxSizeExceeded(MaxSizeExceeded::Fds));
+        }
+
+        Ok(())
+    }
+
+    pub(crate) fn check_array_size(self, size: usize) -> Result<()> {
+        if size > self.max_array_size {
+            return Err(Error::MaxSizeExceeded(MaxSizeExceeded::Array));
+        }
+
+        Ok(())
+    }
+}
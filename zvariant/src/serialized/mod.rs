@@ -1,5 +1,7 @@
 mod data;
 pub use data::Data;
+mod array_iter;
+pub use array_iter::ArrayIter;
 mod size;
 pub use size::Size;
 mod written;
@@ -8,3 +10,5 @@ mod format;
 pub use format::Format;
 mod context;
 pub use context::Context;
+mod limits;
+pub use limits::Limits;
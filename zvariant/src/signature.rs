@@ -15,7 +15,10 @@ use std::{
     sync::Arc,
 };
 
-use crate::{serialized::Format, signature_parser::SignatureParser, Basic, Error, Result, Type};
+use crate::{
+    serialized::Format, signature_parser::SignatureParser, Basic, Error, ParsedSignature, Result,
+    Type,
+};
 
 // A data type similar to Cow and [`bytes::Bytes`] but unlike the former won't allow us to only keep
 // the owned bytes in Arc and latter doesn't have a notion of borrowed data and would require API
@@ -358,8 +361,20 @@ impl<'a> Basic for Signature<'a> {
 }
 
 impl<'a> Type for Signature<'a> {
-    fn signature() -> Signature<'static> {
-        Signature::from_static_str_unchecked(Self::SIGNATURE_STR)
+    const SIGNATURE: &'static ParsedSignature = &ParsedSignature::Signature;
+}
+
+impl From<&ParsedSignature> for Signature<'static> {
+    fn from(parsed: &ParsedSignature) -> Self {
+        Signature::from_string_unchecked(parsed.to_string())
+    }
+}
+
+impl TryFrom<&Signature<'_>> for ParsedSignature {
+    type Error = Error;
+
+    fn try_from(signature: &Signature<'_>) -> Result<Self> {
+        ParsedSignature::from_bytes(signature.as_bytes()).map_err(Into::into)
     }
 }
 
@@ -475,6 +490,27 @@ impl<'a> PartialEq<&str> for Signature<'a> {
     }
 }
 
+/// Evaluate equality with a parsed signature, ignoring outer parentheses if needed.
+impl<'a> PartialEq<ParsedSignature> for Signature<'a> {
+    fn eq(&self, other: &ParsedSignature) -> bool {
+        let mut signature = without_outer_parentheses(self);
+        match other {
+            ParsedSignature::Structure(fields) => {
+                for field in fields.iter() {
+                    let len = field.string_len();
+                    match signature.get(..len) {
+                        Some(s) if *field == *s => signature = &signature[len..],
+                        _ => return false,
+                    }
+                }
+
+                signature.is_empty()
+            }
+            _ => *other == *signature,
+        }
+    }
+}
+
 // According to the docs, `Eq` derive should only be used on structs if all its fields are
 // are `Eq`. Hence the manual implementation.
 impl Eq for Signature<'_> {}
@@ -580,7 +616,8 @@ impl std::fmt::Display for OwnedSignature {
 #[cfg(test)]
 mod tests {
     use super::{Bytes, Signature};
-    use std::sync::Arc;
+    use crate::{parsed_signature::Error, ParsedSignature, Type};
+    use std::{collections::HashMap, sync::Arc};
 
     #[test]
     fn bytes_equality() {
@@ -636,4 +673,105 @@ mod tests {
         let sig_b = Signature::from_str_unchecked("(so)u");
         assert_ne!(sig_a, sig_b);
     }
+
+    #[test]
+    fn parsed_signature() {
+        for s in [
+            "",
+            "y",
+            "h",
+            "v",
+            "as",
+            "a{sv}",
+            "a{oa{sa{sv}}}",
+            "(yv)",
+            "a(ua(yt))",
+            "mas",
+            "(y(bn)a{qi})",
+        ] {
+            let parsed: ParsedSignature = s.parse().unwrap();
+            assert_eq!(parsed.to_string(), s);
+            assert_eq!(parsed.string_len(), s.len());
+            assert_eq!(parsed, *s);
+            assert_eq!(Signature::from(&parsed), s);
+        }
+
+        // Multiple complete types are treated as a structure.
+        let parsed: ParsedSignature = "su".parse().unwrap();
+        assert_eq!(parsed, *"(su)");
+        assert_eq!(Signature::from_str_unchecked("su"), parsed);
+        assert_eq!(Signature::from_str_unchecked("(su)"), parsed);
+        assert_ne!(Signature::from_str_unchecked("(s)u"), parsed);
+        assert_ne!(Signature::from_str_unchecked("sus"), parsed);
+
+        let parsed: ParsedSignature = "a{sv}".parse().unwrap();
+        assert_eq!(
+            parsed,
+            ParsedSignature::dict(ParsedSignature::Str, ParsedSignature::Variant)
+        );
+        assert_eq!(
+            parsed,
+            ParsedSignature::static_dict(&ParsedSignature::Str, &ParsedSignature::Variant)
+        );
+        assert!(!parsed.is_basic());
+        assert!(!parsed.is_fixed_sized());
+        assert!(
+            ParsedSignature::structure([ParsedSignature::U8, ParsedSignature::F64])
+                .is_fixed_sized()
+        );
+
+        assert_eq!(ParsedSignature::from_bytes(b"a"), Err(Error::UnexpectedEnd));
+        assert_eq!(
+            ParsedSignature::from_bytes(b"(s"),
+            Err(Error::UnexpectedEnd)
+        );
+        assert_eq!(
+            ParsedSignature::from_bytes(b"s)"),
+            Err(Error::UnexpectedChar(1, ')'))
+        );
+        assert_eq!(
+            ParsedSignature::from_bytes(b"a()"),
+            Err(Error::EmptyStructure(1))
+        );
+        assert_eq!(
+            ParsedSignature::from_bytes(b"a{vs}"),
+            Err(Error::NonBasicKey(2))
+        );
+        assert_eq!(
+            ParsedSignature::from_bytes(b"a{sss}"),
+            Err(Error::UnexpectedChar(4, 's'))
+        );
+        assert_eq!(
+            ParsedSignature::from_bytes(b"{sv}"),
+            Err(Error::UnexpectedChar(0, '{'))
+        );
+        assert_eq!(
+            ParsedSignature::from_bytes(&[b'y'; 256]),
+            Err(Error::TooLong(256))
+        );
+    }
+
+    #[test]
+    fn const_type_signatures() {
+        const SIGNATURE: &ParsedSignature = <Vec<(u32, String)>>::SIGNATURE;
+        match SIGNATURE {
+            ParsedSignature::Array(child) => match &**child {
+                ParsedSignature::Structure(fields) => {
+                    assert_eq!(fields.len(), 2);
+                    assert_eq!(fields.get(0), Some(&ParsedSignature::U32));
+                    assert_eq!(fields.get(1), Some(&ParsedSignature::Str));
+                }
+                _ => panic!("expected a structure"),
+            },
+            _ => panic!("expected an array"),
+        }
+        assert_eq!(<Vec<(u32, String)>>::signature(), "a(us)");
+        // Served from the cache the second time around.
+        assert_eq!(<Vec<(u32, String)>>::signature(), "a(us)");
+
+        assert_eq!(<HashMap<&str, (u8, [i64; 2])>>::SIGNATURE, &"a{s(y(xx))}");
+        assert_eq!(<HashMap<&str, (u8, [i64; 2])>>::signature(), "a{s(y(xx))}");
+        assert_eq!(<()>::signature(), "");
+        assert_eq!(bool::signature(), "b");
+    }
 }
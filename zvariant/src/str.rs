@@ -7,7 +7,7 @@ use std::{
     sync::Arc,
 };
 
-use crate::{serialized::Format, Basic, Signature, Type};
+use crate::{serialized::Format, Basic, ParsedSignature, Type};
 
 /// A string wrapper.
 ///
@@ -131,9 +131,7 @@ impl<'a> Basic for Str<'a> {
 }
 
 impl<'a> Type for Str<'a> {
-    fn signature() -> Signature<'static> {
-        Signature::from_static_str_unchecked(Self::SIGNATURE_STR)
-    }
+    const SIGNATURE: &'static ParsedSignature = &ParsedSignature::Str;
 }
 
 impl<'a> From<&'a str> for Str<'a> {
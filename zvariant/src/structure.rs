@@ -8,7 +8,7 @@ use std::fmt::{Display, Write};
 
 use crate::{
     signature_parser::SignatureParser, value::SignatureSeed, value_display_fmt, DynamicDeserialize,
-    DynamicType, OwnedValue, Signature, Value,
+    DynamicType, OwnedValue, ParsedSignature, Signature, Type, Value,
 };
 
 /// Use this to efficiently build a [`Structure`].
@@ -313,6 +313,19 @@ impl<'a> Serialize for Structure<'a> {
     }
 }
 
+/// A structure without any fields.
+///
+/// Used by the `SerializeEnum` and `DeserializeEnum` derives as the content of unit variants. Since
+/// a signature can't express an empty structure, it's encoded as a `0u8`, like any other structure
+/// without fields.
+#[doc(hidden)]
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+pub struct EmptyStructure {}
+
+impl Type for EmptyStructure {
+    const SIGNATURE: &'static ParsedSignature = &ParsedSignature::U8;
+}
+
 macro_rules! tuple_impls {
     ($($len:expr => ($($n:tt $name:ident)+))+) => {
         $(
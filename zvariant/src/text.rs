@@ -0,0 +1,926 @@
+//! The GVariant text format, as used by `g_variant_print`, `g_variant_parse` and the tools based on
+//! them (e.g `gdbus call`).
+//!
+//! The printing side is the `Display` implementation of [`Value`] and the containers, which use the
+//! helper here for the string literals.
+
+use std::{
+    fmt::{self, Write},
+    iter::Peekable,
+    str::CharIndices,
+};
+
+#[cfg(feature = "gvariant")]
+use crate::Maybe;
+use crate::{Array, Dict, Error, ObjectPath, Result, Signature, Str, StructureBuilder, Value};
+
+// The maximum nesting depth of values, the same as GLib's.
+const MAX_DEPTH: usize = 128;
+
+/// Write `s` as a double-quoted string literal, escaped the GVariant way.
+///
+/// In byte strings, the non-printable characters are escaped as octal bytes since the unicode
+/// escapes aren't supported there.
+pub(crate) fn write_string_literal<W>(f: &mut W, s: &str, bytestring: bool) -> fmt::Result
+where
+    W: Write,
+{
+    if bytestring {
+        f.write_char('b')?;
+    }
+    f.write_char('"')?;
+    for c in s.chars() {
+        match c {
+            '"' => f.write_str("\\\"")?,
+            '\'' => f.write_char('\'')?,
+            '\u{7}' => f.write_str("\\a")?,
+            '\u{8}' => f.write_str("\\b")?,
+            '\u{b}' => f.write_str("\\v")?,
+            '\u{c}' => f.write_str("\\f")?,
+            // These escapes are the same in Rust and GVariant.
+            '\\' | '\n' | '\r' | '\t' => write!(f, "{}", c.escape_debug())?,
+            c if c.escape_debug().next() != Some('\\') => f.write_char(c)?,
+            c if bytestring => {
+                for b in c.encode_utf8(&mut [0; 4]).bytes() {
+                    write!(f, "\\{b:03o}")?;
+                }
+            }
+            c if (c as u32) <= 0xffff => write!(f, "\\u{:04x}", c as u32)?,
+            c => write!(f, "\\U{:08x}", c as u32)?,
+        }
+    }
+
+    f.write_char('"')
+}
+
+/// Parse `text` as a value of type `signature`, or of the type inferred from the text itself.
+pub(crate) fn parse(text: &str, signature: Option<&Signature<'_>>) -> Result<Value<'static>> {
+    let mut parser = Parser {
+        text,
+        pos: 0,
+        depth: 0,
+    };
+    let node = parser.value()?;
+    parser.skip_whitespace();
+    if parser.pos < text.len() {
+        return Err(parser.error("unexpected trailing characters"));
+    }
+
+    let signature = match signature {
+        Some(signature) => signature.to_string(),
+        None => node.infer_signature()?,
+    };
+
+    node.build(&signature)
+}
+
+struct Parser<'t> {
+    text: &'t str,
+    pos: usize,
+    // The nesting depth of the value being parsed.
+    depth: usize,
+}
+
+impl<'t> Parser<'t> {
+    fn error(&self, msg: impl Into<String>) -> Error {
+        Error::InvalidText(self.pos, msg.into())
+    }
+
+    fn rest(&self) -> &'t str {
+        &self.text[self.pos..]
+    }
+
+    fn peek(&self) -> Option<char> {
+        self.rest().chars().next()
+    }
+
+    fn skip_whitespace(&mut self) {
+        let rest = self.rest();
+        self.pos += rest.len() - rest.trim_start().len();
+    }
+
+    fn eat(&mut self, c: char) -> bool {
+        self.skip_whitespace();
+        if self.peek() == Some(c) {
+            self.pos += c.len_utf8();
+
+            true
+        } else {
+            false
+        }
+    }
+
+    fn expect(&mut self, c: char) -> Result<()> {
+        if self.eat(c) {
+            Ok(())
+        } else {
+            Err(self.error(format!("expected `{c}`")))
+        }
+    }
+
+    fn take_while<F>(&mut self, f: F) -> &'t str
+    where
+        F: Fn(char) -> bool,
+    {
+        let rest = self.rest();
+        let len = rest.find(|c| !f(c)).unwrap_or(rest.len());
+        self.pos += len;
+
+        &rest[..len]
+    }
+
+    fn value(&mut self) -> Result<Node> {
+        self.skip_whitespace();
+        if self.depth == MAX_DEPTH {
+            return Err(self.error("maximum nesting depth exceeded"));
+        }
+
+        self.depth += 1;
+        let node = self.unnested_value();
+        self.depth -= 1;
+
+        node
+    }
+
+    fn unnested_value(&mut self) -> Result<Node> {
+        let offset = self.pos;
+        let kind = match self.peek() {
+            Some('@') => {
+                self.pos += 1;
+                let signature = self.type_annotation()?;
+
+                Kind::Typed(signature, Box::new(self.value()?))
+            }
+            Some('[') => {
+                self.pos += 1;
+
+                Kind::Array(self.list(']')?)
+            }
+            Some('(') => {
+                self.pos += 1;
+                if self.eat(')') {
+                    Kind::Tuple(vec![])
+                } else {
+                    let first = self.value()?;
+                    if !self.eat(',') {
+                        // Just parentheses around a value.
+                        self.expect(')')?;
+
+                        return Ok(first);
+                    }
+                    // The list may be empty, for `(value,)`.
+                    let mut fields = vec![first];
+                    fields.extend(self.list(')')?);
+
+                    Kind::Tuple(fields)
+                }
+            }
+            Some('{') => {
+                self.pos += 1;
+
+                self.dict()?
+            }
+            Some('<') => {
+                self.pos += 1;
+                let value = self.value()?;
+                self.expect('>')?;
+
+                Kind::Variant(Box::new(value))
+            }
+            Some('\'' | '"') => {
+                let bytes = self.string(false)?;
+                let s = String::from_utf8(bytes).map_err(|_| self.error("invalid UTF-8"))?;
+                if s.contains('\0') {
+                    return Err(Error::InvalidText(offset, "nul character in string".into()));
+                }
+
+                Kind::Str(s)
+            }
+            Some('b') if matches!(self.rest()[1..].chars().next(), Some('\'' | '"')) => {
+                self.pos += 1;
+                // Byte strings are nul-terminated.
+                let mut bytes = self.string(true)?;
+                bytes.push(0);
+
+                Kind::Bytes(bytes)
+            }
+            Some(c) if c.is_ascii_digit() || matches!(c, '-' | '+' | '.') => {
+                let number = self.take_while(|c| c.is_ascii_alphanumeric() || "+-.".contains(c));
+
+                Kind::Number(number.to_string())
+            }
+            Some(c) if c.is_ascii_alphabetic() => self.keyword()?,
+            Some(c) => return Err(self.error(format!("unexpected `{c}`"))),
+            None => return Err(self.error("expected a value")),
+        };
+
+        Ok(Node { offset, kind })
+    }
+
+    fn list(&mut self, close: char) -> Result<Vec<Node>> {
+        let mut items = vec![];
+        if self.eat(close) {
+            return Ok(items);
+        }
+
+        loop {
+            items.push(self.value()?);
+            if self.eat(close) {
+                return Ok(items);
+            }
+            self.expect(',')?;
+        }
+    }
+
+    fn dict(&mut self) -> Result<Kind> {
+        let mut entries = vec![];
+        if self.eat('}') {
+            return Ok(Kind::Dict(entries));
+        }
+
+        loop {
+            let key = self.value()?;
+            if self.eat(',') {
+                return Err(self.error("dictionary entries are only supported in dictionaries"));
+            }
+            self.expect(':')?;
+            let value = self.value()?;
+            entries.push((key, value));
+
+            if self.eat('}') {
+                return Ok(Kind::Dict(entries));
+            }
+            self.expect(',')?;
+        }
+    }
+
+    fn keyword(&mut self) -> Result<Kind> {
+        let start = self.pos;
+        let word = self.take_while(|c| c.is_ascii_alphanumeric() || c == '_');
+        let signature = match word {
+            "true" => return Ok(Kind::Bool(true)),
+            "false" => return Ok(Kind::Bool(false)),
+            "inf" | "nan" => return Ok(Kind::Number(word.to_string())),
+            "nothing" => return Ok(Kind::Nothing),
+            "just" => return Ok(Kind::Just(Box::new(self.value()?))),
+            "boolean" => "b",
+            "byte" => "y",
+            "int16" => "n",
+            "uint16" => "q",
+            "int32" => "i",
+            "uint32" => "u",
+            "handle" => "h",
+            "int64" => "x",
+            "uint64" => "t",
+            "double" => "d",
+            "string" => "s",
+            "objectpath" => "o",
+            "signature" => "g",
+            _ => {
+                return Err(Error::InvalidText(
+                    start,
+                    format!("unknown keyword `{word}`"),
+                ))
+            }
+        };
+
+        Ok(Kind::Typed(signature.into(), Box::new(self.value()?)))
+    }
+
+    // The single complete type after a `@`.
+    fn type_annotation(&mut self) -> Result<String> {
+        let rest = self.rest();
+        let signature = complete_type_len(rest)
+            .map(|len| &rest[..len])
+            .filter(|signature| {
+                Signature::try_from(*signature).is_ok() && dict_keys_are_basic(signature)
+            })
+            .ok_or_else(|| self.error("invalid type annotation"))?;
+        self.pos += signature.len();
+
+        Ok(signature.to_string())
+    }
+
+    // The string (or byte string) literal at the current position, quotes included.
+    fn string(&mut self, bytestring: bool) -> Result<Vec<u8>> {
+        let start = self.pos;
+        let rest = self.rest();
+        let mut chars = rest.char_indices().peekable();
+        let (_, quote) = chars.next().unwrap();
+        let mut bytes = vec![];
+        let unterminated = || Error::InvalidText(start, "unterminated string".into());
+
+        loop {
+            let (i, c) = chars.next().ok_or_else(unterminated)?;
+            match c {
+                c if c == quote => {
+                    self.pos += i + 1;
+
+                    return Ok(bytes);
+                }
+                '\\' => {
+                    let (i, e) = chars.next().ok_or_else(unterminated)?;
+                    let invalid = || Error::InvalidText(start + i - 1, "invalid escape".into());
+                    let c = match e {
+                        'a' => '\u{7}',
+                        'b' => '\u{8}',
+                        'f' => '\u{c}',
+                        'n' => '\n',
+                        'r' => '\r',
+                        't' => '\t',
+                        'v' => '\u{b}',
+                        '0'..='7' if bytestring => {
+                            let mut byte = e.to_digit(8).unwrap();
+                            for _ in 0..2 {
+                                match chars.peek().and_then(|(_, c)| c.to_digit(8)) {
+                                    Some(digit) => {
+                                        byte = byte * 8 + digit;
+                                        chars.next();
+                                    }
+                                    None => break,
+                                }
+                            }
+                            bytes.push(u8::try_from(byte).map_err(|_| invalid())?);
+
+                            continue;
+                        }
+                        'u' | 'U' => {
+                            parse_unicode_escape(&mut chars, e == 'U').ok_or_else(invalid)?
+                        }
+                        '\\' | '\'' | '"' => e,
+                        _ => return Err(invalid()),
+                    };
+                    bytes.extend_from_slice(c.encode_utf8(&mut [0; 4]).as_bytes());
+                }
+                c => bytes.extend_from_slice(c.encode_utf8(&mut [0; 4]).as_bytes()),
+            }
+        }
+    }
+}
+
+// The character of a `\uXXXX`, `\UXXXXXXXX` or (Rust style) `\u{X}` escape, after the `u`.
+fn parse_unicode_escape(chars: &mut Peekable<CharIndices<'_>>, long: bool) -> Option<char> {
+    let braced = !long && chars.peek().map(|(_, c)| *c) == Some('{');
+    if braced {
+        chars.next();
+    }
+
+    let max_digits = if braced {
+        6
+    } else if long {
+        8
+    } else {
+        4
+    };
+    let mut code = 0;
+    let mut digits = 0;
+    while digits < max_digits {
+        match chars.peek().and_then(|(_, c)| c.to_digit(16)) {
+            Some(digit) => {
+                code = code * 16 + digit;
+                digits += 1;
+                chars.next();
+            }
+            None => break,
+        }
+    }
+    let complete = if braced {
+        digits > 0 && chars.next().map(|(_, c)| c) == Some('}')
+    } else {
+        digits == max_digits
+    };
+
+    complete.then(|| char::from_u32(code)).flatten()
+}
+
+// The length of the complete type at the start of `s`, if any.
+fn complete_type_len(s: &str) -> Option<usize> {
+    let bytes = s.as_bytes();
+    let mut len = bytes
+        .iter()
+        .take_while(|b| matches!(b, b'a' | b'm'))
+        .count();
+    match bytes.get(len)? {
+        b'(' | b'{' => {
+            let mut depth = 0;
+            loop {
+                match bytes.get(len)? {
+                    b'(' | b'{' => depth += 1,
+                    b')' | b'}' => depth -= 1,
+                    _ => (),
+                }
+                len += 1;
+                if depth == 0 {
+                    return Some(len);
+                }
+            }
+        }
+        _ => Some(len + 1),
+    }
+}
+
+fn dict_keys_are_basic(signature: &str) -> bool {
+    signature
+        .match_indices('{')
+        .all(|(i, _)| signature[i + 1..].starts_with(|c| "ybnqiuxtdsogh".contains(c)))
+}
+
+/// Split the first complete type off `signature`, which must be valid.
+fn split_complete_type(signature: &str) -> (&str, &str) {
+    signature.split_at(complete_type_len(signature).unwrap())
+}
+
+// The placeholders for the types of number (without decimal point or exponent) and string
+// literals in the inferred signatures, since they can be of any of the numeric and string types,
+// respectively.
+const ANY_NUMBER: char = 'N';
+const ANY_STRING: char = 'S';
+
+fn concrete_signature(pattern: &str) -> String {
+    pattern.replace(ANY_NUMBER, "i").replace(ANY_STRING, "s")
+}
+
+// The unified pattern of `a` and `b`, if they're compatible.
+fn unify_patterns(a: &str, b: &str) -> Option<String> {
+    if a.len() != b.len() {
+        return None;
+    }
+
+    a.chars()
+        .zip(b.chars())
+        .map(|(a, b)| match (a, b) {
+            _ if a == b => Some(a),
+            (ANY_NUMBER, c) | (c, ANY_NUMBER) if "ynqiuxtd".contains(c) => Some(c),
+            (ANY_STRING, c) | (c, ANY_STRING) if "sog".contains(c) => Some(c),
+            _ => None,
+        })
+        .collect()
+}
+
+// The unified pattern of `nodes`, which must all be of the same type.
+fn unify<'n, I>(nodes: I) -> Result<Option<String>>
+where
+    I: Iterator<Item = &'n Node>,
+{
+    let mut unified: Option<String> = None;
+    for node in nodes {
+        let pattern = match node.infer()? {
+            Some(pattern) => pattern,
+            None => continue,
+        };
+        unified = Some(match unified {
+            Some(unified) => unify_patterns(&unified, &pattern).ok_or_else(|| {
+                node.error(format!(
+                    "type `{}` doesn't match the type `{}` of the previous elements",
+                    concrete_signature(&pattern),
+                    concrete_signature(&unified),
+                ))
+            })?,
+            None => pattern,
+        });
+    }
+
+    Ok(unified)
+}
+
+fn is_float(number: &str) -> bool {
+    let number = number.trim_start_matches(['-', '+']);
+
+    number == "inf"
+        || number == "nan"
+        || (!number.starts_with("0x")
+            && !number.starts_with("0X")
+            && number.contains(['.', 'e', 'E']))
+}
+
+fn integer<T>(number: &str, offset: usize, signature: &str) -> Result<T>
+where
+    T: TryFrom<i128>,
+{
+    parse_integer(number).ok_or_else(|| {
+        Error::InvalidText(
+            offset,
+            format!("invalid number `{number}` for type `{signature}`"),
+        )
+    })
+}
+
+fn parse_integer<T>(number: &str) -> Option<T>
+where
+    T: TryFrom<i128>,
+{
+    let (negative, digits) = match number.strip_prefix('-') {
+        Some(digits) => (true, digits),
+        None => (false, number.strip_prefix('+').unwrap_or(number)),
+    };
+    let value = match digits
+        .strip_prefix("0x")
+        .or_else(|| digits.strip_prefix("0X"))
+    {
+        Some(hex) if hex.bytes().all(|b| b.is_ascii_hexdigit()) => {
+            i128::from_str_radix(hex, 16).ok()?
+        }
+        None if digits.bytes().all(|b| b.is_ascii_digit()) => digits.parse().ok()?,
+        _ => return None,
+    };
+
+    T::try_from(if negative { -value } else { value }).ok()
+}
+
+struct Node {
+    // Where the value starts in the text, for the errors.
+    offset: usize,
+    kind: Kind,
+}
+
+enum Kind {
+    Bool(bool),
+    Number(String),
+    Str(String),
+    Bytes(Vec<u8>),
+    Array(Vec<Node>),
+    Dict(Vec<(Node, Node)>),
+    Tuple(Vec<Node>),
+    Variant(Box<Node>),
+    Nothing,
+    Just(Box<Node>),
+    Typed(String, Box<Node>),
+}
+
+impl Node {
+    fn error(&self, msg: impl Into<String>) -> Error {
+        Error::InvalidText(self.offset, msg.into())
+    }
+
+    fn infer_signature(&self) -> Result<String> {
+        self.infer()?
+            .map(|pattern| concrete_signature(&pattern))
+            .ok_or_else(|| self.error("unable to infer the type of the value"))
+    }
+
+    // The pattern of the signature of the value, if it can be inferred.
+    fn infer(&self) -> Result<Option<String>> {
+        let pattern = match &self.kind {
+            Kind::Bool(_) => "b".into(),
+            Kind::Number(number) if is_float(number) => "d".into(),
+            Kind::Number(_) => ANY_NUMBER.into(),
+            Kind::Str(_) => ANY_STRING.into(),
+            Kind::Bytes(_) => "ay".into(),
+            Kind::Variant(_) => "v".into(),
+            Kind::Typed(signature, _) => signature.clone(),
+            Kind::Nothing => return Ok(None),
+            Kind::Just(value) => match value.infer()? {
+                Some(pattern) => format!("m{pattern}"),
+                None => return Ok(None),
+            },
+            Kind::Array(elements) => match unify(elements.iter())? {
+                Some(pattern) => format!("a{pattern}"),
+                None => return Ok(None),
+            },
+            Kind::Dict(entries) => {
+                let key = unify(entries.iter().map(|(key, _)| key))?;
+                let value = unify(entries.iter().map(|(_, value)| value))?;
+                match (key, value) {
+                    (Some(key), Some(value)) => format!("a{{{key}{value}}}"),
+                    _ => return Ok(None),
+                }
+            }
+            Kind::Tuple(fields) => {
+                let mut pattern = String::from("(");
+                for field in fields {
+                    match field.infer()? {
+                        Some(field) => pattern.push_str(&field),
+                        None => return Ok(None),
+                    }
+                }
+                pattern.push(')');
+
+                pattern
+            }
+        };
+
+        Ok(Some(pattern))
+    }
+
+    // Build the value, as one of type `signature`.
+    //
+    // The containers are built by separate methods, to keep the frame of this recursive method
+    // small.
+    fn build(self, signature: &str) -> Result<Value<'static>> {
+        let offset = self.offset;
+        let error = |msg: String| Error::InvalidText(offset, msg);
+        let sig = |s: &str| Signature::try_from(s.to_string());
+
+        let value = match (signature.as_bytes()[0], self.kind) {
+            (_, Kind::Typed(annotated, value)) if annotated == signature => {
+                return value.build(signature)
+            }
+            #[cfg(feature = "gvariant")]
+            (b'm', kind) => return Self::build_maybe(offset, kind, signature),
+            (_, Kind::Typed(annotated, _)) => {
+                return Err(error(format!(
+                    "type `{annotated}` doesn't match the expected type `{signature}`"
+                )))
+            }
+            (b'v', Kind::Variant(value)) => return Self::build_variant(*value),
+            (b'b', Kind::Bool(b)) => Value::Bool(b),
+            (b'y', Kind::Number(n)) => Value::U8(integer(&n, offset, signature)?),
+            (b'n', Kind::Number(n)) => Value::I16(integer(&n, offset, signature)?),
+            (b'q', Kind::Number(n)) => Value::U16(integer(&n, offset, signature)?),
+            (b'i', Kind::Number(n)) => Value::I32(integer(&n, offset, signature)?),
+            (b'u', Kind::Number(n)) => Value::U32(integer(&n, offset, signature)?),
+            (b'x', Kind::Number(n)) => Value::I64(integer(&n, offset, signature)?),
+            (b't', Kind::Number(n)) => Value::U64(integer(&n, offset, signature)?),
+            (b'd', Kind::Number(n)) => Value::F64(
+                n.parse()
+                    .map_err(|_| error(format!("invalid floating point number `{n}`")))?,
+            ),
+            (b's', Kind::Str(s)) => Value::Str(Str::from(s)),
+            (b'o', Kind::Str(s)) => Value::ObjectPath(
+                ObjectPath::try_from(s).map_err(|e| error(format!("invalid object path: {e}")))?,
+            ),
+            (b'g', Kind::Str(s)) => {
+                if !dict_keys_are_basic(&s) {
+                    return Err(error("invalid signature: non-basic dictionary key".into()));
+                }
+
+                Value::Signature(sig(&s).map_err(|e| error(format!("invalid signature: {e}")))?)
+            }
+            (b'a', Kind::Bytes(bytes)) if signature == "ay" => {
+                let mut array = Array::new(sig("y")?);
+                for byte in bytes {
+                    array.append(Value::U8(byte))?;
+                }
+
+                Value::Array(array)
+            }
+            (b'a', Kind::Array(elements)) if !signature.starts_with("a{") => {
+                return Self::build_array(elements, signature)
+            }
+            // `[]` is an empty array of dictionary entries, so an empty dictionary too.
+            (b'a', Kind::Array(elements)) if elements.is_empty() => {
+                return Self::build_dict(vec![], signature)
+            }
+            (b'a', Kind::Dict(entries)) if signature.starts_with("a{") => {
+                return Self::build_dict(entries, signature)
+            }
+            (b'(', Kind::Tuple(fields)) => return Self::build_tuple(offset, fields, signature),
+            (b'h', _) => return Err(error("file descriptors are not supported".into())),
+            _ => return Err(mismatch(offset, signature)),
+        };
+
+        Ok(value)
+    }
+
+    #[cfg(feature = "gvariant")]
+    fn build_maybe(offset: usize, kind: Kind, signature: &str) -> Result<Value<'static>> {
+        let child = &signature[1..];
+        let maybe = match kind {
+            Kind::Nothing => Maybe::nothing(Signature::try_from(child.to_string())?),
+            Kind::Just(value) => Maybe::just(value.build(child)?),
+            kind => Maybe::just(Node { offset, kind }.build(child)?),
+        };
+
+        Ok(Value::Maybe(maybe))
+    }
+
+    fn build_variant(value: Node) -> Result<Value<'static>> {
+        let child = value.infer_signature()?;
+
+        Ok(Value::Value(Box::new(value.build(&child)?)))
+    }
+
+    fn build_array(elements: Vec<Node>, signature: &str) -> Result<Value<'static>> {
+        let element = &signature[1..];
+        let mut array = Array::new(Signature::try_from(element.to_string())?);
+        for e in elements {
+            array.append(e.build(element)?)?;
+        }
+
+        Ok(Value::Array(array))
+    }
+
+    fn build_dict(entries: Vec<(Node, Node)>, signature: &str) -> Result<Value<'static>> {
+        let (key, value) = split_complete_type(&signature[2..signature.len() - 1]);
+        let mut dict = Dict::new(
+            Signature::try_from(key.to_string())?,
+            Signature::try_from(value.to_string())?,
+        );
+        for (k, v) in entries {
+            dict.append(k.build(key)?, v.build(value)?)?;
+        }
+
+        Ok(Value::Dict(dict))
+    }
+
+    fn build_tuple(offset: usize, fields: Vec<Node>, signature: &str) -> Result<Value<'static>> {
+        let mut rest = &signature[1..signature.len() - 1];
+        let mut builder = StructureBuilder::new();
+        for field in fields {
+            if rest.is_empty() {
+                return Err(mismatch(offset, signature));
+            }
+            let (field_signature, next) = split_complete_type(rest);
+            builder = builder.append_field(field.build(field_signature)?);
+            rest = next;
+        }
+        if !rest.is_empty() {
+            return Err(mismatch(offset, signature));
+        }
+
+        Ok(Value::Structure(builder.build()))
+    }
+}
+
+fn mismatch(offset: usize, signature: &str) -> Error {
+    Error::InvalidText(offset, format!("expected a value of type `{signature}`"))
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+
+    use crate::{Error, ObjectPath, OwnedValue, Signature, Structure, Value};
+
+    fn parse(text: &str) -> Value<'static> {
+        text.parse().unwrap()
+    }
+
+    fn round_trip(value: Value<'_>) {
+        let text = value.to_string();
+        assert_eq!(parse(&text), value, "{text}");
+    }
+
+    #[test]
+    fn basic() {
+        assert_eq!(parse("true"), Value::Bool(true));
+        assert_eq!(parse(" false "), Value::Bool(false));
+        assert_eq!(parse("42"), Value::I32(42));
+        assert_eq!(parse("-0x2a"), Value::I32(-42));
+        assert_eq!(parse("byte 0xff"), Value::U8(255));
+        assert_eq!(parse("uint64 18446744073709551615"), Value::U64(u64::MAX));
+        assert_eq!(parse("int64 -9223372036854775808"), Value::I64(i64::MIN));
+        assert_eq!(parse("1."), Value::F64(1.));
+        assert_eq!(parse("-1.5e3"), Value::F64(-1500.));
+        assert_eq!(parse("double 3"), Value::F64(3.));
+        assert_eq!(parse("inf"), Value::F64(f64::INFINITY));
+        assert_eq!(parse("'hello'"), Value::from("hello"));
+        assert_eq!(parse(r#""it's""#), Value::from("it's"));
+        assert_eq!(
+            parse(r#"'\a\b\t\n\v\f\r\\\'\"é\U0001f600\u{7f}'"#),
+            Value::from("\x07\x08\t\n\x0b\x0c\r\\'\"é😀\x7f")
+        );
+        assert_eq!(
+            parse("objectpath '/org/zbus'"),
+            Value::from(ObjectPath::try_from("/org/zbus").unwrap())
+        );
+        assert_eq!(
+            parse("signature 'a{sv}'"),
+            Value::from(Signature::try_from("a{sv}").unwrap())
+        );
+        assert_eq!(parse("b'hi\\001'"), Value::from(b"hi\x01\0".to_vec()));
+        assert_eq!(parse("(5)"), Value::I32(5));
+        assert_eq!(
+            parse("<<5>>"),
+            Value::Value(Box::new(Value::Value(Box::new(Value::I32(5)))))
+        );
+
+        round_trip(Value::F64(f64::MIN_POSITIVE));
+        round_trip(Value::F64(-0.1));
+        round_trip(Value::from("\x07\x08\t\n\x0b\x0c\r\\'\"é😀\x7f\u{d8000}"));
+        round_trip(Value::from(b"\x01\xc2\x85\n\0".to_vec()));
+        round_trip(Value::from(vec![0xffu8, 0]));
+        assert!(parse("nan").downcast_ref::<f64>().unwrap().is_nan());
+        assert_eq!(Value::F64(f64::NAN).to_string(), "nan");
+    }
+
+    #[test]
+    fn containers() {
+        assert_eq!(parse("[1, 2, 3]"), Value::new(vec![1, 2, 3]));
+        assert_eq!(parse("[int64 1, 2]"), Value::new(vec![1_i64, 2]));
+        assert_eq!(parse("[1, 2.5]"), Value::new(vec![1., 2.5]));
+        assert_eq!(parse("@as []"), Value::new(Vec::<String>::new()));
+        assert_eq!(
+            parse("[@as [], ['a']]"),
+            Value::new(vec![vec![], vec!["a"]])
+        );
+        assert_eq!(
+            parse("[objectpath '/', '/a']"),
+            Value::new(vec![
+                ObjectPath::try_from("/").unwrap(),
+                ObjectPath::try_from("/a").unwrap()
+            ])
+        );
+        assert_eq!(parse("()"), Value::new(Structure::default()));
+        assert_eq!(parse("(true,)"), Value::new((true,)));
+        assert_eq!(
+            parse("('a', uint32 1, <'b'>)"),
+            Value::new(("a", 1_u32, Value::new("b")))
+        );
+
+        let map: HashMap<String, OwnedValue> =
+            parse("{'a': <int32 5>, 'b': <@as []>}").try_into().unwrap();
+        assert_eq!(i32::try_from(&map["a"]).unwrap(), 5);
+        assert!(Vec::<String>::try_from(map["b"].try_clone().unwrap())
+            .unwrap()
+            .is_empty());
+        assert_eq!(
+            parse("@a{sv} {}"),
+            Value::new(HashMap::<String, Value<'_>>::new())
+        );
+        assert_eq!(
+            parse("@a{sv} []"),
+            Value::new(HashMap::<String, Value<'_>>::new())
+        );
+
+        round_trip(Value::new((
+            vec![(1_u16, vec!["a", "b"])]
+                .into_iter()
+                .collect::<HashMap<_, _>>(),
+            vec![Signature::try_from("").unwrap()],
+            (Value::new(0_u8), Value::new((51_u32, Value::new(-1_i16)))),
+            vec![vec![0_i64], vec![]],
+        )));
+    }
+
+    #[test]
+    fn with_signature() {
+        let signature = Signature::try_from("(yqa{oas}d)").unwrap();
+        let value =
+            Value::parse_with_signature("(1, 2, {'/a': ['x'], '/b': []}, 3)", &signature).unwrap();
+        assert_eq!(value.value_signature(), signature);
+        round_trip(value);
+
+        let signature = Signature::try_from("u").unwrap();
+        assert!(Value::parse_with_signature("-1", &signature).is_err());
+        assert!(Value::parse_with_signature("int32 1", &signature).is_err());
+        assert!(Value::parse_with_signature("'1'", &signature).is_err());
+        assert_eq!(
+            Value::parse_with_signature("@u 1", &signature).unwrap(),
+            Value::U32(1)
+        );
+        let signature = Signature::try_from("(ii)").unwrap();
+        assert!(Value::parse_with_signature("(1,)", &signature).is_err());
+        assert!(Value::parse_with_signature("(1, 2, 3)", &signature).is_err());
+    }
+
+    #[cfg(all(feature = "gvariant", not(feature = "option-as-array")))]
+    #[test]
+    fn maybe() {
+        assert_eq!(parse("@mn 5"), Value::new(Some(5_i16)));
+        assert_eq!(parse("@mn nothing"), Value::new(None::<i16>));
+        assert_eq!(parse("@mmn just nothing"), Value::new(Some(None::<i16>)));
+        assert_eq!(parse("[just 1, nothing]"), Value::new(vec![Some(1), None]));
+        round_trip(Value::new((Some(Some(0_u64)), None::<Option<bool>>)));
+    }
+
+    #[test]
+    fn errors() {
+        let err = |text: &str| match text.parse::<Value<'_>>() {
+            Err(Error::InvalidText(offset, _)) => offset,
+            r => panic!("unexpected result for `{text}`: {r:?}"),
+        };
+
+        assert_eq!(err(""), 0);
+        assert_eq!(err("[1, 'a']"), 4);
+        assert_eq!(err("[]"), 0);
+        assert_eq!(err("nothing"), 0);
+        assert_eq!(err("[1 2]"), 3);
+        assert_eq!(err("'abc"), 0);
+        assert_eq!(err("'\\x'"), 1);
+        assert_eq!(err("'\\u12'"), 1);
+        assert_eq!(err("byte 256"), 5);
+        assert_eq!(err("objectpath 'a'"), 11);
+        assert_eq!(err("signature 'a{vs}'"), 10);
+        assert_eq!(err("@a{vs} {}"), 1);
+        assert_eq!(err("@ai"), 3);
+        assert_eq!(err("{1, 2}"), 3);
+        assert_eq!(err("handle 0"), 7);
+        assert_eq!(err("maybe"), 0);
+        assert_eq!(err("5 6"), 2);
+        assert_eq!(err("'\\u0000'"), 0);
+    }
+
+    #[test]
+    fn nesting_depth() {
+        let err = |text: &str| match text.parse::<Value<'_>>() {
+            Err(Error::InvalidText(offset, msg)) => (offset, msg),
+            res => panic!("unexpected result for `{text}`: {res:?}"),
+        };
+        let depth_exceeded = (128, String::from("maximum nesting depth exceeded"));
+        for open in ["[", "(", "<"] {
+            assert_eq!(err(&open.repeat(200_000)), depth_exceeded);
+        }
+        for open in ["{1:", "just ", "@av <"] {
+            assert_eq!(err(&open.repeat(200_000)).1, depth_exceeded.1);
+        }
+
+        // Within the limit.
+        let text = format!("{}1{}", "<".repeat(127), ">".repeat(127));
+        let mut value = Value::from(1_i32);
+        for _ in 0..127 {
+            value = Value::Value(Box::new(value));
+        }
+        assert_eq!(parse(&text), value);
+    }
+}
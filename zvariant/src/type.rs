@@ -1,4 +1,4 @@
-use crate::{utils::*, Signature};
+use crate::{parsed_signature::SignatureBuf, utils::*, ParsedSignature, Signature};
 use serde::de::{Deserialize, DeserializeSeed};
 use std::{
     marker::PhantomData,
@@ -23,6 +23,29 @@ use std::{
 /// [DynamicType] trait instead, which is otherwise automatically implemented if you implement this
 /// trait.
 ///
+/// # Implementing manually
+///
+/// The only required item is [`Type::SIGNATURE`]. In earlier versions, it was [`Type::signature`]
+/// instead, which is now provided based on the former. Hence existing implementations need to
+/// provide their signature in parsed form, which is easiest by composing it from other types:
+///
+/// ```
+/// use serde::{Deserialize, Serialize};
+/// use zvariant::{ParsedSignature, Type};
+///
+/// #[derive(Deserialize, Serialize)]
+/// struct Point {
+///     x: i32,
+///     y: i32,
+/// }
+///
+/// impl Type for Point {
+///     const SIGNATURE: &'static ParsedSignature = <(i32, i32)>::SIGNATURE;
+/// }
+///
+/// assert_eq!(Point::signature(), "(ii)");
+/// ```
+///
 /// [D-Bus type system]: https://dbus.freedesktop.org/doc/dbus-specification.html#type-system
 /// [serialization and deserialization]: index.html#functions
 /// [`Serialize`]: https://docs.serde.rs/serde/trait.Serialize.html
@@ -32,8 +55,32 @@ use std::{
 /// [`HashMap`]: https://doc.rust-lang.org/std/collections/struct.HashMap.html
 /// [zvariant_derive]: https://docs.rs/zvariant_derive/latest/zvariant_derive/
 pub trait Type {
+    /// The signature for the implementing type, in parsed form.
+    ///
+    /// Being a constant, the signature of container types is composed at compile time from the
+    /// signatures of their contained types, rather than built and parsed at runtime.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use zvariant::{ParsedSignature, Type};
+    ///
+    /// const SIGNATURE: &ParsedSignature = <Vec<(u32, String)>>::SIGNATURE;
+    /// match SIGNATURE {
+    ///     ParsedSignature::Array(child) => assert_eq!(**child, *"(us)"),
+    ///     _ => unreachable!(),
+    /// }
+    /// ```
+    const SIGNATURE: &'static ParsedSignature;
+
+    /// The string form of [`Type::SIGNATURE`], built at compile time.
+    #[doc(hidden)]
+    const SIGNATURE_BUF: &'static SignatureBuf = &SignatureBuf::new(Self::SIGNATURE);
+
     /// Get the signature for the implementing type.
     ///
+    /// This is [`Type::SIGNATURE`] in string form. The string is built at compile time.
+    ///
     /// # Example
     ///
     /// ```
@@ -46,7 +93,10 @@ pub trait Type {
     /// assert_eq!(<(u32, &str, &[u64])>::signature(), "(usat)");
     /// assert_eq!(<HashMap<u8, &str>>::signature(), "a{ys}");
     /// ```
-    fn signature() -> Signature<'static>;
+    #[inline]
+    fn signature() -> Signature<'static> {
+        Signature::from_static_str_unchecked(Self::SIGNATURE_BUF.as_str())
+    }
 }
 
 /// Types with dynamic signatures.
@@ -59,6 +109,14 @@ pub trait DynamicType {
     ///
     /// See [Type::signature] for details.
     fn dynamic_signature(&self) -> Signature<'_>;
+
+    /// Get the signature for the implementing type, in parsed form.
+    ///
+    /// By default, this parses the result of [DynamicType::dynamic_signature]. For types
+    /// implementing [Type], this is [Type::SIGNATURE] and hence doesn't involve any parsing.
+    fn dynamic_parsed_signature(&self) -> zvariant::Result<Cow<'_, ParsedSignature>> {
+        ParsedSignature::try_from(&self.dynamic_signature()).map(Cow::Owned)
+    }
 }
 
 /// Types that deserialize based on dynamic signatures.
@@ -84,15 +142,17 @@ where
     fn dynamic_signature(&self) -> Signature<'_> {
         <T as Type>::signature()
     }
+
+    fn dynamic_parsed_signature(&self) -> zvariant::Result<Cow<'_, ParsedSignature>> {
+        Ok(Cow::Borrowed(T::SIGNATURE))
+    }
 }
 
 impl<T> Type for PhantomData<T>
 where
     T: Type + ?Sized,
 {
-    fn signature() -> Signature<'static> {
-        T::signature()
-    }
+    const SIGNATURE: &'static ParsedSignature = T::SIGNATURE;
 }
 
 impl<'de, T> DynamicDeserialize<'de> for T
@@ -146,10 +206,8 @@ macro_rules! array_type {
         where
             T: Type,
         {
-            #[inline]
-            fn signature() -> Signature<'static> {
-                Signature::from_string_unchecked(format!("a{}", T::signature()))
-            }
+            const SIGNATURE: &'static ParsedSignature =
+                &ParsedSignature::static_array(T::SIGNATURE);
         }
     };
 }
@@ -162,10 +220,7 @@ where
     T: Type + Eq + Hash,
     S: BuildHasher,
 {
-    #[inline]
-    fn signature() -> Signature<'static> {
-        <[T]>::signature()
-    }
+    const SIGNATURE: &'static ParsedSignature = <[T]>::SIGNATURE;
 }
 
 #[cfg(feature = "arrayvec")]
@@ -173,26 +228,17 @@ impl<T, const CAP: usize> Type for arrayvec::ArrayVec<T, CAP>
 where
     T: Type,
 {
-    #[inline]
-    fn signature() -> Signature<'static> {
-        <[T]>::signature()
-    }
+    const SIGNATURE: &'static ParsedSignature = <[T]>::SIGNATURE;
 }
 
 #[cfg(feature = "arrayvec")]
 impl<const CAP: usize> Type for arrayvec::ArrayString<CAP> {
-    #[inline]
-    fn signature() -> Signature<'static> {
-        <&str>::signature()
-    }
+    const SIGNATURE: &'static ParsedSignature = <&str>::SIGNATURE;
 }
 
 // Empty type deserves empty signature
 impl Type for () {
-    #[inline]
-    fn signature() -> Signature<'static> {
-        Signature::from_static_str_unchecked("")
-    }
+    const SIGNATURE: &'static ParsedSignature = &ParsedSignature::Unit;
 }
 
 macro_rules! deref_impl {
@@ -201,10 +247,7 @@ macro_rules! deref_impl {
         <$($desc:tt)+
     ) => {
         impl <$($desc)+ {
-            #[inline]
-            fn signature() -> Signature<'static> {
-                <$type>::signature()
-            }
+            const SIGNATURE: &'static ParsedSignature = <$type>::SIGNATURE;
         }
     };
 }
@@ -223,10 +266,7 @@ impl<T> Type for Option<T>
 where
     T: Type,
 {
-    #[inline]
-    fn signature() -> Signature<'static> {
-        Signature::from_string_unchecked(format!("m{}", T::signature()))
-    }
+    const SIGNATURE: &'static ParsedSignature = &ParsedSignature::static_maybe(T::SIGNATURE);
 }
 
 #[cfg(feature = "option-as-array")]
@@ -234,10 +274,7 @@ impl<T> Type for Option<T>
 where
     T: Type,
 {
-    #[inline]
-    fn signature() -> Signature<'static> {
-        Signature::from_string_unchecked(format!("a{}", T::signature()))
-    }
+    const SIGNATURE: &'static ParsedSignature = &ParsedSignature::static_array(T::SIGNATURE);
 }
 
 ////////////////////////////////////////////////////////////////////////////////
@@ -249,16 +286,8 @@ macro_rules! tuple_impls {
             where
                 $($name: Type,)+
             {
-                fn signature() -> Signature<'static> {
-                    let mut sig = String::with_capacity(255);
-                    sig.push(STRUCT_SIG_START_CHAR);
-                    $(
-                        sig.push_str($name::signature().as_str());
-                    )+
-                    sig.push(STRUCT_SIG_END_CHAR);
-
-                    Signature::from_string_unchecked(sig)
-                }
+                const SIGNATURE: &'static ParsedSignature =
+                    &ParsedSignature::static_structure(&[$($name::SIGNATURE,)+]);
             }
         )+
     }
@@ -292,17 +321,8 @@ impl<T, const N: usize> Type for [T; N]
 where
     T: Type,
 {
-    #[allow(clippy::reversed_empty_ranges)]
-    fn signature() -> Signature<'static> {
-        let mut sig = String::with_capacity(255);
-        sig.push(STRUCT_SIG_START_CHAR);
-        for _ in 0..N {
-            sig.push_str(T::signature().as_str());
-        }
-        sig.push(STRUCT_SIG_END_CHAR);
-
-        Signature::from_string_unchecked(sig)
-    }
+    const SIGNATURE: &'static ParsedSignature =
+        &ParsedSignature::static_structure(&[T::SIGNATURE; N]);
 }
 
 ////////////////////////////////////////////////////////////////////////////////
@@ -322,10 +342,8 @@ macro_rules! map_impl {
             V: Type,
             $($typaram: $bound,)*
         {
-            #[inline]
-            fn signature() -> Signature<'static> {
-                Signature::from_string_unchecked(format!("a{{{}{}}}", K::signature(), V::signature()))
-            }
+            const SIGNATURE: &'static ParsedSignature =
+                &ParsedSignature::static_dict(K::SIGNATURE, V::SIGNATURE);
         }
     }
 }
@@ -334,42 +352,28 @@ map_impl!(BTreeMap<K: Ord, V>);
 map_impl!(HashMap<K: Eq + Hash, V, H: BuildHasher>);
 
 impl Type for Duration {
-    fn signature() -> Signature<'static> {
-        <(u64, u32)>::signature()
-    }
+    const SIGNATURE: &'static ParsedSignature = <(u64, u32)>::SIGNATURE;
 }
 
 impl Type for SystemTime {
-    #[inline]
-    fn signature() -> Signature<'static> {
-        <(
-            // seconds
-            u64,
-            // nano
-            u32,
-        )>::signature()
-    }
+    const SIGNATURE: &'static ParsedSignature = <(
+        // seconds
+        u64,
+        // nano
+        u32,
+    )>::SIGNATURE;
 }
 
 impl Type for Ipv4Addr {
-    #[inline]
-    fn signature() -> Signature<'static> {
-        <[u8; 4]>::signature()
-    }
+    const SIGNATURE: &'static ParsedSignature = <[u8; 4]>::SIGNATURE;
 }
 
 impl Type for Ipv6Addr {
-    #[inline]
-    fn signature() -> Signature<'static> {
-        <[u8; 16]>::signature()
-    }
+    const SIGNATURE: &'static ParsedSignature = <[u8; 16]>::SIGNATURE;
 }
 
 impl Type for IpAddr {
-    #[inline]
-    fn signature() -> Signature<'static> {
-        <(u32, &[u8])>::signature()
-    }
+    const SIGNATURE: &'static ParsedSignature = <(u32, &[u8])>::SIGNATURE;
 }
 
 // BitFlags
@@ -378,33 +382,26 @@ impl<F> Type for enumflags2::BitFlags<F>
 where
     F: Type + enumflags2::BitFlag,
 {
-    #[inline]
-    fn signature() -> Signature<'static> {
-        F::signature()
-    }
+    const SIGNATURE: &'static ParsedSignature = F::SIGNATURE;
 }
 
 #[cfg(feature = "serde_bytes")]
 impl Type for serde_bytes::Bytes {
-    fn signature() -> Signature<'static> {
-        Signature::from_static_str_unchecked("ay")
-    }
+    const SIGNATURE: &'static ParsedSignature =
+        &ParsedSignature::static_array(&ParsedSignature::U8);
 }
 
 #[cfg(feature = "serde_bytes")]
 impl Type for serde_bytes::ByteBuf {
-    fn signature() -> Signature<'static> {
-        Signature::from_static_str_unchecked("ay")
-    }
+    const SIGNATURE: &'static ParsedSignature =
+        &ParsedSignature::static_array(&ParsedSignature::U8);
 }
 
 #[allow(unused)]
 macro_rules! static_str_type {
     ($ty:ty) => {
         impl Type for $ty {
-            fn signature() -> Signature<'static> {
-                <&str>::signature()
-            }
+            const SIGNATURE: &'static ParsedSignature = <&str>::SIGNATURE;
         }
     };
 }
@@ -414,9 +411,8 @@ static_str_type!(PathBuf);
 
 #[cfg(feature = "uuid")]
 impl Type for uuid::Uuid {
-    fn signature() -> Signature<'static> {
-        Signature::from_static_str_unchecked("ay")
-    }
+    const SIGNATURE: &'static ParsedSignature =
+        &ParsedSignature::static_array(&ParsedSignature::U8);
 }
 
 #[cfg(feature = "url")]
@@ -426,122 +422,104 @@ static_str_type!(url::Url);
 // https://github.com/time-rs/time/blob/f9398b9598757508ca3815694f23203843e0011b/src/serde/mod.rs#L110
 #[cfg(feature = "time")]
 impl Type for time::Date {
-    fn signature() -> Signature<'static> {
-        // Serialized as a (year, ordinal) tuple:
-        // https://github.com/time-rs/time/blob/f9398b9598757508ca3815694f23203843e0011b/src/serde/mod.rs#L92
-        <(i32, u16)>::signature()
-    }
+    // Serialized as a (year, ordinal) tuple:
+    // https://github.com/time-rs/time/blob/f9398b9598757508ca3815694f23203843e0011b/src/serde/mod.rs#L92
+    const SIGNATURE: &'static ParsedSignature = <(i32, u16)>::SIGNATURE;
 }
 
 #[cfg(feature = "time")]
 impl Type for time::Duration {
-    fn signature() -> Signature<'static> {
-        // Serialized as a (whole seconds, nanoseconds) tuple:
-        // https://github.com/time-rs/time/blob/f9398b9598757508ca3815694f23203843e0011b/src/serde/mod.rs#L119
-        <(i64, i32)>::signature()
-    }
+    // Serialized as a (whole seconds, nanoseconds) tuple:
+    // https://github.com/time-rs/time/blob/f9398b9598757508ca3815694f23203843e0011b/src/serde/mod.rs#L119
+    const SIGNATURE: &'static ParsedSignature = <(i64, i32)>::SIGNATURE;
 }
 
 #[cfg(feature = "time")]
 impl Type for time::OffsetDateTime {
-    fn signature() -> Signature<'static> {
-        // Serialized as a tuple:
-        // https://github.com/time-rs/time/blob/f9398b9598757508ca3815694f23203843e0011b/src/serde/mod.rs#L155
-        <(
-            // year
-            i32,
-            // ordinal
-            u16,
-            // hour
-            u8,
-            // minute
-            u8,
-            // second
-            u8,
-            // nanosecond
-            u32,
-            // offset.whole_hours
-            i8,
-            // offset.minutes_past_hour
-            i8,
-            // offset.seconds_past_minute
-            i8,
-        )>::signature()
-    }
+    // Serialized as a tuple:
+    // https://github.com/time-rs/time/blob/f9398b9598757508ca3815694f23203843e0011b/src/serde/mod.rs#L155
+    const SIGNATURE: &'static ParsedSignature = <(
+        // year
+        i32,
+        // ordinal
+        u16,
+        // hour
+        u8,
+        // minute
+        u8,
+        // second
+        u8,
+        // nanosecond
+        u32,
+        // offset.whole_hours
+        i8,
+        // offset.minutes_past_hour
+        i8,
+        // offset.seconds_past_minute
+        i8,
+    )>::SIGNATURE;
 }
 
 #[cfg(feature = "time")]
 impl Type for time::PrimitiveDateTime {
-    fn signature() -> Signature<'static> {
-        // Serialized as a tuple:
-        // https://github.com/time-rs/time/blob/f9398b9598757508ca3815694f23203843e0011b/src/serde/mod.rs#L200
-        <(
-            // year
-            i32,
-            // ordinal
-            u16,
-            // hour
-            u8,
-            // minute
-            u8,
-            // second
-            u8,
-            // nanosecond
-            u32,
-        )>::signature()
-    }
+    // Serialized as a tuple:
+    // https://github.com/time-rs/time/blob/f9398b9598757508ca3815694f23203843e0011b/src/serde/mod.rs#L200
+    const SIGNATURE: &'static ParsedSignature = <(
+        // year
+        i32,
+        // ordinal
+        u16,
+        // hour
+        u8,
+        // minute
+        u8,
+        // second
+        u8,
+        // nanosecond
+        u32,
+    )>::SIGNATURE;
 }
 
 #[cfg(feature = "time")]
 impl Type for time::Time {
-    fn signature() -> Signature<'static> {
-        // Serialized as a tuple:
-        // https://github.com/time-rs/time/blob/f9398b9598757508ca3815694f23203843e0011b/src/serde/mod.rs#L246
-        <(
-            // hour
-            u8,
-            // minute
-            u8,
-            // second
-            u8,
-            // nanosecond
-            u32,
-        )>::signature()
-    }
+    // Serialized as a tuple:
+    // https://github.com/time-rs/time/blob/f9398b9598757508ca3815694f23203843e0011b/src/serde/mod.rs#L246
+    const SIGNATURE: &'static ParsedSignature = <(
+        // hour
+        u8,
+        // minute
+        u8,
+        // second
+        u8,
+        // nanosecond
+        u32,
+    )>::SIGNATURE;
 }
 
 #[cfg(feature = "time")]
 impl Type for time::UtcOffset {
-    fn signature() -> Signature<'static> {
-        // Serialized as a (whole hours, minutes past hour, seconds past minute) tuple:
-        // https://github.com/time-rs/time/blob/f9398b9598757508ca3815694f23203843e0011b/src/serde/mod.rs#L282
-        <(i8, i8, i8)>::signature()
-    }
+    // Serialized as a (whole hours, minutes past hour, seconds past minute) tuple:
+    // https://github.com/time-rs/time/blob/f9398b9598757508ca3815694f23203843e0011b/src/serde/mod.rs#L282
+    const SIGNATURE: &'static ParsedSignature = <(i8, i8, i8)>::SIGNATURE;
 }
 
 #[cfg(feature = "time")]
 impl Type for time::Weekday {
-    fn signature() -> Signature<'static> {
-        // Serialized as number from Monday:
-        // https://github.com/time-rs/time/blob/f9398b9598757508ca3815694f23203843e0011b/src/serde/mod.rs#L312
-        u8::signature()
-    }
+    // Serialized as number from Monday:
+    // https://github.com/time-rs/time/blob/f9398b9598757508ca3815694f23203843e0011b/src/serde/mod.rs#L312
+    const SIGNATURE: &'static ParsedSignature = u8::SIGNATURE;
 }
 
 #[cfg(feature = "time")]
 impl Type for time::Month {
-    fn signature() -> Signature<'static> {
-        // Serialized as month number:
-        // https://github.com/time-rs/time/blob/f9398b9598757508ca3815694f23203843e0011b/src/serde/mod.rs#L337
-        u8::signature()
-    }
+    // Serialized as month number:
+    // https://github.com/time-rs/time/blob/f9398b9598757508ca3815694f23203843e0011b/src/serde/mod.rs#L337
+    const SIGNATURE: &'static ParsedSignature = u8::SIGNATURE;
 }
 
 #[cfg(feature = "chrono")]
 impl<Tz: chrono::TimeZone> Type for chrono::DateTime<Tz> {
-    fn signature() -> Signature<'static> {
-        <&str>::signature()
-    }
+    const SIGNATURE: &'static ParsedSignature = <&str>::SIGNATURE;
 }
 
 #[cfg(feature = "chrono")]
@@ -2,7 +2,10 @@ use std::slice::SliceIndex;
 
 #[cfg(feature = "gvariant")]
 use crate::signature_parser::SignatureParser;
-use crate::{serialized::Format, Basic, Error, ObjectPath, Result, Signature};
+use crate::{
+    parsed_signature::Fields, serialized::Format, Basic, Error, ObjectPath, ParsedSignature,
+    Result, Signature,
+};
 
 #[cfg(unix)]
 use crate::Fd;
@@ -154,6 +157,75 @@ pub(crate) fn is_fixed_sized_signature<'a>(signature: &'a Signature<'a>) -> Resu
     }
 }
 
+// The counterpart of `alignment_for_signature` for parsed signatures.
+pub(crate) fn alignment_for_parsed_signature(signature: &ParsedSignature, format: Format) -> usize {
+    match signature {
+        ParsedSignature::Unit | ParsedSignature::U8 => u8::alignment(format),
+        ParsedSignature::Bool => bool::alignment(format),
+        ParsedSignature::I16 => i16::alignment(format),
+        ParsedSignature::U16 => u16::alignment(format),
+        ParsedSignature::I32 => i32::alignment(format),
+        ParsedSignature::U32 | ParsedSignature::Fd => u32::alignment(format),
+        ParsedSignature::I64 => i64::alignment(format),
+        ParsedSignature::U64 => u64::alignment(format),
+        ParsedSignature::F64 => f64::alignment(format),
+        ParsedSignature::Str => <&str>::alignment(format),
+        ParsedSignature::ObjectPath => ObjectPath::alignment(format),
+        ParsedSignature::Signature => Signature::alignment(format),
+        ParsedSignature::Variant => match format {
+            Format::DBus => VARIANT_ALIGNMENT_DBUS,
+            #[cfg(feature = "gvariant")]
+            Format::GVariant => VARIANT_ALIGNMENT_GVARIANT,
+        },
+        ParsedSignature::Array(child) => {
+            alignment_for_parsed_child(child, format, ARRAY_ALIGNMENT_DBUS)
+        }
+        ParsedSignature::Maybe(child) => alignment_for_parsed_child(child, format, 1),
+        // In the D-Bus format, dictionaries are aligned like any other array.
+        ParsedSignature::Dict { .. } if format == Format::DBus => ARRAY_ALIGNMENT_DBUS,
+        ParsedSignature::Dict { key, value } => alignment_for_dict_entry(key, value, format),
+        ParsedSignature::Structure(fields) => alignment_for_parsed_struct(fields, format),
+    }
+}
+
+fn alignment_for_parsed_struct(#[allow(unused)] fields: &Fields, format: Format) -> usize {
+    match format {
+        Format::DBus => STRUCT_ALIGNMENT_DBUS,
+        #[cfg(feature = "gvariant")]
+        Format::GVariant => fields
+            .iter()
+            .map(|field| alignment_for_parsed_signature(field, format))
+            .max()
+            .unwrap_or(1),
+    }
+}
+
+// The alignment of a dict-entry with the given key and value signatures.
+pub(crate) fn alignment_for_dict_entry(
+    #[allow(unused)] key: &ParsedSignature,
+    #[allow(unused)] value: &ParsedSignature,
+    format: Format,
+) -> usize {
+    match format {
+        Format::DBus => DICT_ENTRY_ALIGNMENT_DBUS,
+        #[cfg(feature = "gvariant")]
+        Format::GVariant => alignment_for_parsed_signature(key, format)
+            .max(alignment_for_parsed_signature(value, format)),
+    }
+}
+
+fn alignment_for_parsed_child(
+    #[allow(unused)] child: &ParsedSignature,
+    format: Format,
+    dbus_align: usize,
+) -> usize {
+    match format {
+        Format::DBus => dbus_align,
+        #[cfg(feature = "gvariant")]
+        Format::GVariant => alignment_for_parsed_signature(child, format),
+    }
+}
+
 // Given an &str, create an owned (String-based) Signature w/ appropriate capacity
 macro_rules! signature_string {
     ($signature:expr) => {{
@@ -4,7 +4,7 @@ use core::{
     hash::{Hash, Hasher},
     marker::PhantomData,
     mem::discriminant,
-    str,
+    str::{self, FromStr},
 };
 
 use serde::{
@@ -17,9 +17,13 @@ use serde::{
 use static_assertions::assert_impl_all;
 
 use crate::{
-    array_display_fmt, dict_display_fmt, signature_parser::SignatureParser, structure_display_fmt,
-    utils::*, Array, Basic, Dict, DynamicType, ObjectPath, OwnedValue, Signature, Str, Structure,
-    StructureBuilder, Type,
+    array_display_fmt, dict_display_fmt,
+    signature_parser::SignatureParser,
+    structure_display_fmt,
+    text::{self, write_string_literal},
+    utils::*,
+    Array, Basic, Dict, DynamicType, ObjectPath, OwnedValue, ParsedSignature, Signature, Str,
+    Structure, StructureBuilder, Type,
 };
 #[cfg(feature = "gvariant")]
 use crate::{maybe_display_fmt, Maybe};
@@ -74,7 +78,28 @@ use crate::Fd;
 /// );
 /// ```
 ///
+/// # Text format
+///
+/// `Value` is printed (through its [`Display`] implementation) in, and parsed (through its
+/// [`FromStr`] implementation) from the [GVariant text format], as used by `g_variant_print`,
+/// `gdbus` and other tools:
+///
+/// ```
+/// use zvariant::{Signature, Value};
+///
+/// let v: Value = "{'a': <int32 5>, 'b': <@as []>}".parse().unwrap();
+/// assert_eq!(v.value_signature(), "a{sv}");
+/// assert_eq!(Value::new(vec![1_u16, 2]).to_string(), "[uint16 1, 2]");
+///
+/// // Without type annotations, the types are inferred from the text, unless they are given.
+/// let signature = Signature::try_from("(uqs)").unwrap();
+/// let v = Value::parse_with_signature("(1, 2, 'three')", &signature).unwrap();
+/// assert_eq!(v, Value::new((1_u32, 2_u16, "three")));
+/// ```
+///
 /// [D-Bus specification]: https://dbus.freedesktop.org/doc/dbus-specification.html#container-types
+/// [`FromStr`]: std::str::FromStr
+/// [GVariant text format]: https://docs.gtk.org/glib/gvariant-text-format.html
 #[derive(Debug, PartialEq, PartialOrd)]
 pub enum Value<'a> {
     // Simple types
@@ -449,6 +474,16 @@ impl<'a> Value<'a> {
         }
         .map_err(Into::into)
     }
+
+    /// Parse `text`, in the GVariant text format, as a value of type `signature`.
+    ///
+    /// Unlike with the [`FromStr`] implementation, the type annotations are only needed in the
+    /// variants here.
+    ///
+    /// [`FromStr`]: std::str::FromStr
+    pub fn parse_with_signature(text: &str, signature: &Signature<'_>) -> crate::Result<Self> {
+        text::parse(text, Some(signature))
+    }
 }
 
 impl Display for Value<'_> {
@@ -457,6 +492,15 @@ impl Display for Value<'_> {
     }
 }
 
+impl FromStr for Value<'_> {
+    type Err = crate::Error;
+
+    /// Parse `s` in the GVariant text format, inferring the types that aren't annotated.
+    fn from_str(s: &str) -> crate::Result<Self> {
+        text::parse(s, None)
+    }
+}
+
 /// Implemented based on https://gitlab.gnome.org/GNOME/glib/-/blob/e1d47f0b0d0893ac9171e24cc7bf635495376546/glib/gvariant.c#L2213
 pub(crate) fn value_display_fmt(
     value: &Value<'_>,
@@ -508,27 +552,27 @@ pub(crate) fn value_display_fmt(
             write!(f, "{}", num)
         }
         Value::F64(num) => {
-            if num.fract() == 0. {
+            if num.is_nan() {
+                f.write_str("nan")
+            } else if num.fract() == 0. {
                 // Add a dot to make it clear that this is a float
                 write!(f, "{}.", num)
             } else {
                 write!(f, "{}", num)
             }
         }
-        Value::Str(string) => {
-            write!(f, "{:?}", string.as_str())
-        }
+        Value::Str(string) => write_string_literal(f, string.as_str(), false),
         Value::Signature(val) => {
             if type_annotate {
                 f.write_str("signature ")?;
             }
-            write!(f, "{:?}", val.as_str())
+            write_string_literal(f, val.as_str(), false)
         }
         Value::ObjectPath(val) => {
             if type_annotate {
                 f.write_str("objectpath ")?;
             }
-            write!(f, "{:?}", val.as_str())
+            write_string_literal(f, val.as_str(), false)
         }
         Value::Value(child) => {
             f.write_char('<')?;
@@ -927,9 +971,7 @@ where
 }
 
 impl<'a> Type for Value<'a> {
-    fn signature() -> Signature<'static> {
-        Signature::from_static_str_unchecked(VARIANT_SIGNATURE_STR)
-    }
+    const SIGNATURE: &'static ParsedSignature = &ParsedSignature::Variant;
 }
 
 impl<'a> TryFrom<&Value<'a>> for Value<'a> {
@@ -980,7 +1022,7 @@ mod tests {
                 char::from_u32(0xD8000).unwrap().to_string().as_str()
             ])
             .to_string(),
-            r#"["\u{7}\u{8}\t\n\u{b}\u{c}\r", "\u{7f}", "\u{d8000}"]"#
+            r#"["\a\b\t\n\v\f\r", "\u007f", "\U000d8000"]"#
         );
 
         assert_eq!(
@@ -0,0 +1,764 @@
+use serde::de::{
+    self,
+    value::{MapDeserializer, SeqDeserializer},
+    Deserialize, DeserializeSeed, Deserializer, EnumAccess, IntoDeserializer, SeqAccess,
+    VariantAccess, Visitor,
+};
+
+#[cfg(unix)]
+use std::os::fd::AsRawFd;
+
+use crate::{Basic, Error, ObjectPath, Result, Signature, Type, Value};
+
+macro_rules! deserialize_basic {
+    ($method:ident, $visit:ident, $variant:ident, $type:ty) => {
+        fn $method<V>(self, visitor: V) -> Result<V::Value>
+        where
+            V: Visitor<'de>,
+        {
+            match self {
+                Value::$variant(v) => visitor.$visit(*v),
+                _ => Err(signature_mismatch(
+                    self,
+                    &format!("`{}`", <$type>::SIGNATURE_STR),
+                )),
+            }
+        }
+    };
+}
+
+/// Deserialize `T` from a [`Value`], after checking that their signatures match.
+///
+/// Unlike `T::deserialize(&value)`, this also catches mismatching types of the elements of empty
+/// arrays and dictionaries, since there are no elements to check them against.
+///
+/// # Examples
+///
+/// ```
+/// use zvariant::{from_value, Value};
+///
+/// let value = Value::new(Vec::<u32>::new());
+/// assert_eq!(from_value::<Vec<u32>>(&value).unwrap(), Vec::<u32>::new());
+/// assert!(from_value::<Vec<String>>(&value).is_err());
+/// ```
+pub fn from_value<'de, T>(value: &'de Value<'de>) -> Result<T>
+where
+    T: Deserialize<'de> + Type,
+{
+    let signature = value.value_signature();
+    if signature != T::signature() {
+        return Err(Error::SignatureMismatch(
+            signature.to_owned(),
+            format!("expected `{}`", T::signature()),
+        ));
+    }
+
+    T::deserialize(value)
+}
+
+/// Deserialize `T` directly from a [`Value`], without encoding it to bytes first.
+///
+/// The value must match the signature of `T` exactly: integers are not converted to other integer
+/// types and variants (`Value::Value`) are not unwrapped, except when `T` is itself a [`Value`]
+/// (or e.g a [`DeserializeValue`]). Borrowed types, like `&str`, borrow from the value.
+///
+/// Since [`OwnedValue`] dereferences to `Value`, `T::deserialize(&*owned_value)` works as well.
+///
+/// The types of the elements of empty arrays and dictionaries can't be checked this way. Use
+/// [`from_value`] to check the signature of the whole value first.
+///
+/// # Examples
+///
+/// ```
+/// use std::collections::HashMap;
+/// use serde::Deserialize;
+/// use zvariant::{DeserializeDict, OwnedValue, Type, Value};
+///
+/// #[derive(Debug, PartialEq, DeserializeDict, Type)]
+/// #[zvariant(signature = "a{sv}")]
+/// struct Props {
+///     name: String,
+///     count: u32,
+/// }
+///
+/// let mut map = HashMap::<&str, Value<'_>>::new();
+/// map.insert("name", "zbus".into());
+/// map.insert("count", 3_u32.into());
+/// let value = OwnedValue::try_from(Value::from(map)).unwrap();
+///
+/// let props = Props::deserialize(&*value).unwrap();
+/// assert_eq!(props, Props { name: "zbus".into(), count: 3 });
+///
+/// let count = u32::deserialize(&Value::U32(3)).unwrap();
+/// assert_eq!(count, 3);
+/// // Signatures must match.
+/// assert!(i32::deserialize(&Value::U32(3)).is_err());
+/// ```
+///
+/// [`DeserializeValue`]: crate::DeserializeValue
+/// [`OwnedValue`]: crate::OwnedValue
+impl<'de> Deserializer<'de> for &'de Value<'de> {
+    type Error = Error;
+
+    fn deserialize_any<V>(self, visitor: V) -> Result<V::Value>
+    where
+        V: Visitor<'de>,
+    {
+        match self {
+            Value::U8(v) => visitor.visit_u8(*v),
+            Value::Bool(v) => visitor.visit_bool(*v),
+            Value::I16(v) => visitor.visit_i16(*v),
+            Value::U16(v) => visitor.visit_u16(*v),
+            Value::I32(v) => visitor.visit_i32(*v),
+            Value::U32(v) => visitor.visit_u32(*v),
+            Value::I64(v) => visitor.visit_i64(*v),
+            Value::U64(v) => visitor.visit_u64(*v),
+            Value::F64(v) => visitor.visit_f64(*v),
+            Value::Str(_) | Value::Signature(_) | Value::ObjectPath(_) => {
+                self.deserialize_str(visitor)
+            }
+            Value::Value(v) => visitor.visit_seq(VariantDeserializer::new(v)),
+            Value::Array(_) => self.deserialize_seq(visitor),
+            Value::Dict(_) => self.deserialize_map(visitor),
+            Value::Structure(_) => self.deserialize_tuple(0, visitor),
+            #[cfg(feature = "gvariant")]
+            Value::Maybe(_) => self.deserialize_option(visitor),
+            #[cfg(unix)]
+            Value::Fd(_) => self.deserialize_i32(visitor),
+        }
+    }
+
+    deserialize_basic!(deserialize_bool, visit_bool, Bool, bool);
+    // No i8 type in D-Bus/GVariant, it's encoded as i16.
+    deserialize_basic!(deserialize_i8, visit_i16, I16, i16);
+    deserialize_basic!(deserialize_i16, visit_i16, I16, i16);
+    deserialize_basic!(deserialize_i64, visit_i64, I64, i64);
+    deserialize_basic!(deserialize_u8, visit_u8, U8, u8);
+    deserialize_basic!(deserialize_u16, visit_u16, U16, u16);
+    deserialize_basic!(deserialize_u32, visit_u32, U32, u32);
+    deserialize_basic!(deserialize_u64, visit_u64, U64, u64);
+    // No f32 type in D-Bus/GVariant, it's encoded as f64.
+    deserialize_basic!(deserialize_f32, visit_f64, F64, f64);
+    deserialize_basic!(deserialize_f64, visit_f64, F64, f64);
+
+    fn deserialize_i32<V>(self, visitor: V) -> Result<V::Value>
+    where
+        V: Visitor<'de>,
+    {
+        match self {
+            Value::I32(v) => visitor.visit_i32(*v),
+            #[cfg(unix)]
+            Value::Fd(fd) => visitor.visit_i32(fd.as_raw_fd()),
+            _ => Err(signature_mismatch(self, "`i` or `h`")),
+        }
+    }
+
+    fn deserialize_char<V>(self, visitor: V) -> Result<V::Value>
+    where
+        V: Visitor<'de>,
+    {
+        // No char type in D-Bus/GVariant, it's encoded as a string.
+        self.deserialize_str(visitor)
+    }
+
+    fn deserialize_str<V>(self, visitor: V) -> Result<V::Value>
+    where
+        V: Visitor<'de>,
+    {
+        match self {
+            Value::Str(s) => visitor.visit_borrowed_str(s.as_str()),
+            Value::Signature(s) => visitor.visit_borrowed_str(s.as_str()),
+            Value::ObjectPath(p) => visitor.visit_borrowed_str(p.as_str()),
+            _ => Err(signature_mismatch(
+                self,
+                &format!(
+                    "`{}`, `{}` or `{}`",
+                    <&str>::SIGNATURE_STR,
+                    ObjectPath::SIGNATURE_STR,
+                    Signature::SIGNATURE_STR,
+                ),
+            )),
+        }
+    }
+
+    fn deserialize_string<V>(self, visitor: V) -> Result<V::Value>
+    where
+        V: Visitor<'de>,
+    {
+        self.deserialize_str(visitor)
+    }
+
+    fn deserialize_bytes<V>(self, visitor: V) -> Result<V::Value>
+    where
+        V: Visitor<'de>,
+    {
+        self.deserialize_seq(visitor)
+    }
+
+    fn deserialize_byte_buf<V>(self, visitor: V) -> Result<V::Value>
+    where
+        V: Visitor<'de>,
+    {
+        self.deserialize_seq(visitor)
+    }
+
+    fn deserialize_option<V>(self, #[allow(unused)] visitor: V) -> Result<V::Value>
+    where
+        V: Visitor<'de>,
+    {
+        match self {
+            #[cfg(feature = "gvariant")]
+            Value::Maybe(maybe) => match maybe.inner() {
+                Some(v) => visitor.visit_some(v),
+                None => visitor.visit_none(),
+            },
+            #[cfg(feature = "option-as-array")]
+            Value::Array(array) if array.len() <= 1 => match array.inner().first() {
+                Some(v) => visitor.visit_some(v),
+                None => visitor.visit_none(),
+            },
+            _ => Err(signature_mismatch(self, "an optional type")),
+        }
+    }
+
+    fn deserialize_unit<V>(self, _visitor: V) -> Result<V::Value>
+    where
+        V: Visitor<'de>,
+    {
+        // Unit types are encoded as nothing, so no value can be one.
+        Err(signature_mismatch(self, "a unit type"))
+    }
+
+    fn deserialize_unit_struct<V>(self, _name: &'static str, visitor: V) -> Result<V::Value>
+    where
+        V: Visitor<'de>,
+    {
+        self.deserialize_unit(visitor)
+    }
+
+    fn deserialize_newtype_struct<V>(self, _name: &'static str, visitor: V) -> Result<V::Value>
+    where
+        V: Visitor<'de>,
+    {
+        visitor.visit_newtype_struct(self)
+    }
+
+    fn deserialize_seq<V>(self, visitor: V) -> Result<V::Value>
+    where
+        V: Visitor<'de>,
+    {
+        match self {
+            Value::Array(array) => visit_seq(array.inner(), visitor),
+            // Dictionary entries are deserialized as key-value pairs.
+            Value::Dict(dict) => {
+                let mut seq = MapDeserializer::new(dict.iter());
+                let value = visitor.visit_seq(&mut seq)?;
+                seq.end()?;
+
+                Ok(value)
+            }
+            _ => Err(signature_mismatch(self, "`a`")),
+        }
+    }
+
+    fn deserialize_tuple<V>(self, _len: usize, visitor: V) -> Result<V::Value>
+    where
+        V: Visitor<'de>,
+    {
+        match self {
+            Value::Structure(structure) => visit_seq(structure.fields(), visitor),
+            _ => Err(signature_mismatch(self, "`(`")),
+        }
+    }
+
+    fn deserialize_tuple_struct<V>(
+        self,
+        _name: &'static str,
+        len: usize,
+        visitor: V,
+    ) -> Result<V::Value>
+    where
+        V: Visitor<'de>,
+    {
+        self.deserialize_tuple(len, visitor)
+    }
+
+    fn deserialize_map<V>(self, visitor: V) -> Result<V::Value>
+    where
+        V: Visitor<'de>,
+    {
+        match self {
+            Value::Dict(dict) => {
+                let mut map = MapDeserializer::new(dict.iter());
+                let value = visitor.visit_map(&mut map)?;
+                map.end()?;
+
+                Ok(value)
+            }
+            _ => Err(signature_mismatch(self, "`a{`")),
+        }
+    }
+
+    fn deserialize_struct<V>(
+        self,
+        name: &'static str,
+        fields: &'static [&'static str],
+        visitor: V,
+    ) -> Result<V::Value>
+    where
+        V: Visitor<'de>,
+    {
+        match self {
+            // `DeserializeValue`, deserializing the contents of the variant.
+            Value::Value(v) if name == "zvariant::Value" => {
+                visitor.visit_seq(VariantDeserializer::new(v))
+            }
+            Value::Dict(_) => self.deserialize_map(visitor),
+            // Unit structures are encoded as a `0u8`.
+            Value::U8(0) if fields.is_empty() => visit_seq(&[], visitor),
+            _ => self.deserialize_tuple(fields.len(), visitor),
+        }
+    }
+
+    fn deserialize_enum<V>(
+        self,
+        _name: &'static str,
+        _variants: &'static [&'static str],
+        visitor: V,
+    ) -> Result<V::Value>
+    where
+        V: Visitor<'de>,
+    {
+        match self {
+            Value::U32(_) | Value::Str(_) => visitor.visit_enum(EnumDeserializer {
+                variant: self,
+                fields: None,
+            }),
+            // Enum variants with data are encoded as a structure with the variant index as the
+            // first field.
+            Value::Structure(structure) => match structure.fields() {
+                [variant @ Value::U32(_), fields] => visitor.visit_enum(EnumDeserializer {
+                    variant,
+                    fields: Some(fields),
+                }),
+                _ => Err(signature_mismatch(self, "`(u`")),
+            },
+            _ => Err(signature_mismatch(self, "`u`, `s` or `(u`")),
+        }
+    }
+
+    fn deserialize_identifier<V>(self, visitor: V) -> Result<V::Value>
+    where
+        V: Visitor<'de>,
+    {
+        match self {
+            Value::U32(v) => visitor.visit_u32(*v),
+            _ => self.deserialize_str(visitor),
+        }
+    }
+
+    fn deserialize_ignored_any<V>(self, visitor: V) -> Result<V::Value>
+    where
+        V: Visitor<'de>,
+    {
+        visitor.visit_unit()
+    }
+
+    fn is_human_readable(&self) -> bool {
+        false
+    }
+}
+
+impl<'de> IntoDeserializer<'de, Error> for &'de Value<'de> {
+    type Deserializer = Self;
+
+    fn into_deserializer(self) -> Self {
+        self
+    }
+}
+
+fn signature_mismatch(value: &Value<'_>, expected: &str) -> Error {
+    Error::SignatureMismatch(
+        value.value_signature().to_owned(),
+        format!("expected {expected}"),
+    )
+}
+
+fn visit_seq<'de, V>(values: &'de [Value<'de>], visitor: V) -> Result<V::Value>
+where
+    V: Visitor<'de>,
+{
+    let mut seq = SeqDeserializer::new(values.iter());
+    let value = visitor.visit_seq(&mut seq)?;
+    seq.end()?;
+
+    Ok(value)
+}
+
+// A `Value` is deserialized as a sequence of its signature, followed by the value.
+struct VariantDeserializer<'de> {
+    value: &'de Value<'de>,
+    signature_done: bool,
+    value_done: bool,
+}
+
+impl<'de> VariantDeserializer<'de> {
+    fn new(value: &'de Value<'de>) -> Self {
+        Self {
+            value,
+            signature_done: false,
+            value_done: false,
+        }
+    }
+}
+
+impl<'de> SeqAccess<'de> for VariantDeserializer<'de> {
+    type Error = Error;
+
+    fn next_element_seed<T>(&mut self, seed: T) -> Result<Option<T::Value>>
+    where
+        T: DeserializeSeed<'de>,
+    {
+        if !self.signature_done {
+            self.signature_done = true;
+            let signature = self.value.value_signature().as_str().to_string();
+
+            seed.deserialize(signature.into_deserializer()).map(Some)
+        } else if !self.value_done {
+            self.value_done = true;
+
+            seed.deserialize(self.value).map(Some)
+        } else {
+            Ok(None)
+        }
+    }
+
+    fn size_hint(&self) -> Option<usize> {
+        Some(2 - self.signature_done as usize - self.value_done as usize)
+    }
+}
+
+struct EnumDeserializer<'de> {
+    variant: &'de Value<'de>,
+    fields: Option<&'de Value<'de>>,
+}
+
+impl<'de> EnumAccess<'de> for EnumDeserializer<'de> {
+    type Error = Error;
+    type Variant = Self;
+
+    fn variant_seed<V>(self, seed: V) -> Result<(V::Value, Self)>
+    where
+        V: DeserializeSeed<'de>,
+    {
+        seed.deserialize(self.variant).map(|v| (v, self))
+    }
+}
+
+impl<'de> VariantAccess<'de> for EnumDeserializer<'de> {
+    type Error = Error;
+
+    fn unit_variant(self) -> Result<()> {
+        Ok(())
+    }
+
+    fn newtype_variant_seed<T>(self, seed: T) -> Result<T::Value>
+    where
+        T: DeserializeSeed<'de>,
+    {
+        seed.deserialize(self.fields()?)
+    }
+
+    fn tuple_variant<V>(self, len: usize, visitor: V) -> Result<V::Value>
+    where
+        V: Visitor<'de>,
+    {
+        self.fields()?.deserialize_tuple(len, visitor)
+    }
+
+    fn struct_variant<V>(self, fields: &'static [&'static str], visitor: V) -> Result<V::Value>
+    where
+        V: Visitor<'de>,
+    {
+        self.fields()?.deserialize_struct("", fields, visitor)
+    }
+}
+
+impl<'de> EnumDeserializer<'de> {
+    fn fields(&self) -> Result<&'de Value<'de>> {
+        self.fields.ok_or_else(|| {
+            de::Error::invalid_type(de::Unexpected::UnitVariant, &"a non-unit variant")
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+
+    use serde::{Deserialize, Serialize};
+
+    use crate::{
+        from_value, serialized::Context, to_bytes, to_value, to_value_for_signature,
+        DeserializeDict, Error, OwnedValue, SerializeDict, SerializeValue, Structure, Type, Value,
+    };
+
+    fn round_trip<'a, T>(value: &T, expected: Value<'a>)
+    where
+        T: Serialize + Deserialize<'a> + Type + PartialEq + std::fmt::Debug,
+    {
+        let serialized = to_value(value).unwrap();
+        assert_eq!(serialized, expected);
+
+        // Should be the same as going through the bytes.
+        let ctxt = Context::<byteorder::LE>::new_dbus(0);
+        let encoded = to_bytes(ctxt, &SerializeValue(value)).unwrap();
+        let decoded: Value<'_> = encoded.deserialize().unwrap().0;
+        assert_eq!(serialized, decoded);
+
+        let expected = Box::leak(Box::new(expected));
+        assert_eq!(&T::deserialize(&*expected).unwrap(), value);
+    }
+
+    #[test]
+    fn basic() {
+        round_trip(&true, Value::Bool(true));
+        round_trip(&-1_i16, Value::I16(-1));
+        round_trip(&u64::MAX, Value::U64(u64::MAX));
+        round_trip(&1.5, Value::F64(1.5));
+        round_trip(&"hello", Value::from("hello"));
+        round_trip(&String::from("hello"), Value::from("hello"));
+
+        assert_eq!(to_value(&-2_i8).unwrap(), Value::I16(-2));
+        assert_eq!(i8::deserialize(&Value::I16(-2)).unwrap(), -2);
+        assert_eq!(f32::deserialize(&Value::F64(0.5)).unwrap(), 0.5);
+        assert_eq!(
+            to_value_for_signature("o", "/org/zbus").unwrap(),
+            Value::from(crate::ObjectPath::try_from("/org/zbus").unwrap())
+        );
+        assert_eq!(
+            to_value_for_signature("g", "a{sv}").unwrap(),
+            Value::from(crate::Signature::try_from("a{sv}").unwrap())
+        );
+
+        // Borrowing from the value.
+        let value = Value::from(String::from("borrowed"));
+        let s = <&str>::deserialize(&value).unwrap();
+        assert_eq!(s, "borrowed");
+    }
+
+    #[test]
+    fn containers() {
+        round_trip(&vec![1_u32, 2, 3], Value::new(vec![1_u32, 2, 3]));
+        round_trip(&Vec::<String>::new(), Value::new(Vec::<String>::new()));
+        round_trip(
+            &(1_u8, "two", (3_i64, vec![4.0])),
+            Value::new((1_u8, "two", (3_i64, vec![4.0]))),
+        );
+
+        let mut map = HashMap::new();
+        map.insert(1_u32, "one");
+        map.insert(2, "two");
+        round_trip(&map, Value::from(map.clone()));
+
+        #[derive(Debug, PartialEq, Deserialize, Serialize, Type)]
+        struct Struct<'s> {
+            field1: u16,
+            field2: &'s str,
+            field3: Vec<(u8, bool)>,
+        }
+        let s = Struct {
+            field1: 1,
+            field2: "two",
+            field3: vec![(3, true)],
+        };
+        round_trip(&s, Value::new((1_u16, "two", vec![(3_u8, true)])));
+
+        #[derive(Debug, PartialEq, Deserialize, Serialize, Type)]
+        struct Newtype(Vec<u8>);
+        round_trip(&Newtype(vec![1, 2]), Value::new(vec![1_u8, 2]));
+    }
+
+    #[test]
+    fn variants() {
+        let value = Value::new(Value::new(42));
+        round_trip(&Value::new(42), value);
+
+        let mut map = HashMap::new();
+        map.insert("a", Value::new(1_u8));
+        map.insert("b", Value::new("b"));
+        let value = Value::from(map);
+        let decoded = HashMap::<&str, OwnedValue>::deserialize(&value).unwrap();
+        assert_eq!(*decoded["a"], Value::U8(1));
+        assert_eq!(*decoded["b"], Value::from("b"));
+        assert_eq!(to_value(&decoded).unwrap(), value);
+
+        #[derive(Debug, PartialEq, DeserializeDict, SerializeDict, Type)]
+        #[zvariant(signature = "a{sv}")]
+        struct Dict {
+            a: u8,
+            b: String,
+            c: Option<u32>,
+        }
+        let dict = Dict {
+            a: 1,
+            b: "b".into(),
+            c: None,
+        };
+        assert_eq!(to_value(&dict).unwrap(), value);
+        assert_eq!(Dict::deserialize(&value).unwrap(), dict);
+        // Mismatching types of the variants in the dictionary.
+        assert!(HashMap::<&str, u8>::deserialize(&value).is_err());
+    }
+
+    #[test]
+    fn enums() {
+        #[derive(Debug, PartialEq, Deserialize, Serialize, Type)]
+        enum Unit {
+            Variant1,
+            Variant2,
+        }
+        round_trip(&Unit::Variant2, Value::U32(1));
+
+        #[derive(Debug, PartialEq, Deserialize, Serialize, Type)]
+        #[zvariant(signature = "s")]
+        enum StrEnum {
+            Variant1,
+            Variant2,
+        }
+        round_trip(&StrEnum::Variant2, Value::from("Variant2"));
+
+        #[derive(Debug, PartialEq, Deserialize, Serialize, Type)]
+        enum NewType {
+            Variant1(f64),
+            Variant2(f64),
+        }
+        round_trip(&NewType::Variant2(0.5), Value::new((1_u32, 0.5)));
+
+        #[derive(Debug, PartialEq, Deserialize, Serialize, Type)]
+        enum StructFields {
+            Variant1(u16, i64),
+            Variant2 { field1: u16, field2: i64 },
+        }
+        let expected = |i: u32| Value::from(Structure::from((i, (1_u16, 2_i64))));
+        round_trip(&StructFields::Variant1(1, 2), expected(0));
+        round_trip(
+            &StructFields::Variant2 {
+                field1: 1,
+                field2: 2,
+            },
+            expected(1),
+        );
+    }
+
+    #[cfg(all(feature = "gvariant", not(feature = "option-as-array")))]
+    #[test]
+    fn maybe() {
+        assert_eq!(
+            to_value(&Some(5_i32)).unwrap(),
+            Value::Maybe(crate::Maybe::just(Value::I32(5)))
+        );
+        let value = to_value(&None::<&str>).unwrap();
+        assert_eq!(
+            value,
+            Value::Maybe(crate::Maybe::nothing(<&str>::signature()))
+        );
+        assert_eq!(Option::<&str>::deserialize(&value).unwrap(), None);
+    }
+
+    #[cfg(feature = "option-as-array")]
+    #[test]
+    fn option_as_array() {
+        assert_eq!(to_value(&Some(5_i32)).unwrap(), Value::new(vec![5_i32]));
+        let value = to_value(&None::<&str>).unwrap();
+        assert_eq!(value, Value::new(Vec::<&str>::new()));
+        assert_eq!(Option::<&str>::deserialize(&value).unwrap(), None);
+        assert_eq!(
+            Option::<i32>::deserialize(&Value::new(vec![5_i32])).unwrap(),
+            Some(5)
+        );
+    }
+
+    #[test]
+    fn errors() {
+        assert!(matches!(
+            i32::deserialize(&Value::U32(3)),
+            Err(Error::SignatureMismatch(s, msg)) if s == "u" && msg == "expected `i` or `h`"
+        ));
+        assert_eq!(
+            <(u8, u8)>::deserialize(&Value::new((1_u8, 2_u8, 3_u8))),
+            Err(Error::Message(
+                "invalid length 3, expected 2 elements in sequence".to_string()
+            ))
+        );
+        assert!(matches!(
+            to_value_for_signature("s", &5_u32),
+            Err(Error::SignatureMismatch(s, msg)) if s == "s" && msg == "expected `u`"
+        ));
+        assert!(to_value_for_signature("(uu)", &(1_u32,)).is_err());
+        assert!(to_value_for_signature("(u)", &(1_u32, 2_u32)).is_err());
+        assert!(to_value_for_signature("o", "not a path").is_err());
+        assert!(to_value_for_signature("uu", &1_u32).is_err());
+
+        // Unit types can't be represented as a value.
+        assert!(<()>::deserialize(&Value::U8(0)).is_err());
+        // Variants are not sequences.
+        assert!(Vec::<String>::deserialize(&Value::new(Value::new("s"))).is_err());
+        assert!(<(String, u8)>::deserialize(&Value::new(Value::new(5_u8))).is_err());
+    }
+
+    #[test]
+    fn dict_as_seq() {
+        let mut map = HashMap::new();
+        map.insert(1_u32, "one");
+        let value = Value::from(map);
+        assert_eq!(
+            Vec::<(u32, &str)>::deserialize(&value).unwrap(),
+            vec![(1, "one")]
+        );
+        assert_eq!(
+            to_value_for_signature("a{us}", &vec![(1_u32, "one")]).unwrap(),
+            value
+        );
+    }
+
+    #[test]
+    fn signature_check() {
+        let value = Value::new(Vec::<u32>::new());
+        assert_eq!(from_value::<Vec<u32>>(&value).unwrap(), Vec::<u32>::new());
+        // Nothing to check the type of the elements against.
+        assert_eq!(
+            Vec::<String>::deserialize(&value).unwrap(),
+            Vec::<String>::new()
+        );
+        assert!(matches!(
+            from_value::<Vec<String>>(&value),
+            Err(Error::SignatureMismatch(s, msg)) if s == "au" && msg == "expected `as`"
+        ));
+
+        let value = Value::new(HashMap::<String, Value<'_>>::new());
+        assert!(from_value::<HashMap<String, OwnedValue>>(&value).is_ok());
+        assert!(from_value::<HashMap<String, String>>(&value).is_err());
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn fd() {
+        use std::os::fd::AsRawFd;
+
+        use crate::Fd;
+
+        let file = std::fs::File::open("/dev/null").unwrap();
+        let value = to_value(&Fd::from(&file)).unwrap();
+        match &value {
+            Value::Fd(fd) => assert_ne!(fd.as_raw_fd(), file.as_raw_fd()),
+            value => panic!("unexpected value: {value:?}"),
+        }
+
+        // Only an `Fd` can be serialized as a file descriptor.
+        assert!(matches!(
+            to_value_for_signature("h", &file.as_raw_fd()),
+            Err(Error::SignatureMismatch(s, msg)) if s == "h" && msg == "expected an `Fd`"
+        ));
+    }
+}
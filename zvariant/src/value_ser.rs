@@ -0,0 +1,642 @@
+use serde::ser::{
+    Serialize, SerializeMap, SerializeSeq, SerializeStruct, SerializeStructVariant, SerializeTuple,
+    SerializeTupleStruct, SerializeTupleVariant, Serializer,
+};
+
+#[cfg(unix)]
+use std::os::fd::BorrowedFd;
+
+use crate::{
+    signature_parser::SignatureParser, utils::*, Array, Basic, Dict, DynamicType, Error,
+    ObjectPath, Result, Signature, Str, StructureBuilder, Type, Value,
+};
+
+#[cfg(unix)]
+use crate::{fd::FD_NEWTYPE_NAME, Fd};
+
+#[cfg(feature = "gvariant")]
+use crate::Maybe;
+
+/// Serialize `T` to a [`Value`].
+///
+/// Unlike the `From` conversions, this works for any type that implements [`Serialize`] and
+/// [`DynamicType`], including derived structures and enums, without first encoding it to bytes.
+/// The resulting value can be converted back to `T` through its [`Deserializer`] implementation.
+///
+/// # Examples
+///
+/// ```
+/// use serde::{Deserialize, Serialize};
+/// use zvariant::{to_value, Type, Value};
+///
+/// #[derive(Debug, PartialEq, Deserialize, Serialize, Type)]
+/// struct Point {
+///     x: i32,
+///     y: i32,
+/// }
+///
+/// let point = Point { x: 1, y: 2 };
+/// let value = to_value(&point).unwrap();
+/// assert_eq!(value, Value::new((1, 2)));
+/// assert_eq!(Point::deserialize(&value).unwrap(), point);
+/// ```
+///
+/// [`Deserializer`]: serde::Deserializer
+pub fn to_value<T>(value: &T) -> Result<Value<'static>>
+where
+    T: ?Sized + Serialize + DynamicType,
+{
+    to_value_for_signature(value.dynamic_signature(), value)
+}
+
+/// Serialize `T` that has the given signature, to a [`Value`].
+///
+/// Use this function instead of [`to_value`] if the value being serialized does not implement
+/// [`DynamicType`].
+pub fn to_value_for_signature<'s, S, T>(signature: S, value: &T) -> Result<Value<'static>>
+where
+    S: TryInto<Signature<'s>>,
+    S::Error: Into<Error>,
+    T: ?Sized + Serialize,
+{
+    let signature = signature.try_into().map_err(Into::into)?;
+
+    value.serialize(ValueSerializer::new(signature.to_owned())?)
+}
+
+/// Our [`Value`] serialization implementation.
+///
+/// Serializes a single value of the given signature.
+struct ValueSerializer {
+    signature: Signature<'static>,
+    // Whether the value is the raw file descriptor of an `Fd`.
+    #[cfg(unix)]
+    fd: bool,
+}
+
+impl ValueSerializer {
+    fn new(signature: Signature<'static>) -> Result<Self> {
+        let len = SignatureParser::new(signature.as_ref())
+            .next_signature()?
+            .len();
+        if len != signature.len() {
+            return Err(Error::SignatureMismatch(
+                signature,
+                "expected a single complete type".to_string(),
+            ));
+        }
+
+        Ok(Self {
+            signature,
+            #[cfg(unix)]
+            fd: false,
+        })
+    }
+
+    fn first_char(&self) -> char {
+        self.signature.as_bytes()[0] as char
+    }
+
+    fn mismatch(self, expected: &str) -> Error {
+        Error::SignatureMismatch(self.signature, format!("expected {expected}"))
+    }
+
+    fn basic<T>(self, value: T) -> Result<Value<'static>>
+    where
+        T: Basic + Into<Value<'static>>,
+    {
+        if self.signature == T::SIGNATURE_STR {
+            Ok(value.into())
+        } else {
+            Err(self.mismatch(&format!("`{}`", T::SIGNATURE_STR)))
+        }
+    }
+}
+
+impl Serializer for ValueSerializer {
+    type Ok = Value<'static>;
+    type Error = Error;
+
+    type SerializeSeq = SeqSerializer;
+    type SerializeTuple = StructureSerializer;
+    type SerializeTupleStruct = StructureSerializer;
+    type SerializeTupleVariant = StructureSerializer;
+    type SerializeMap = DictSerializer;
+    type SerializeStruct = StructSerializer;
+    type SerializeStructVariant = StructureSerializer;
+
+    fn serialize_bool(self, v: bool) -> Result<Value<'static>> {
+        self.basic(v)
+    }
+
+    fn serialize_i8(self, v: i8) -> Result<Value<'static>> {
+        // No i8 type in D-Bus/GVariant, let's pretend it's i16
+        self.basic(v as i16)
+    }
+
+    fn serialize_i16(self, v: i16) -> Result<Value<'static>> {
+        self.basic(v)
+    }
+
+    fn serialize_i32(self, v: i32) -> Result<Value<'static>> {
+        #[cfg(unix)]
+        if self.signature == Fd::SIGNATURE_STR {
+            // Any other `i32` could be closed or not be a file descriptor at all.
+            if !self.fd {
+                return Err(self.mismatch("an `Fd`"));
+            }
+            // SAFETY: The `Fd` being serialized keeps the FD open for the duration of this call, to
+            // duplicate it.
+            let fd = unsafe { BorrowedFd::borrow_raw(v) };
+            let fd = fd.try_clone_to_owned()?;
+
+            return Ok(Value::Fd(Fd::Owned(fd)));
+        }
+
+        self.basic(v)
+    }
+
+    fn serialize_i64(self, v: i64) -> Result<Value<'static>> {
+        self.basic(v)
+    }
+
+    fn serialize_u8(self, v: u8) -> Result<Value<'static>> {
+        self.basic(v)
+    }
+
+    fn serialize_u16(self, v: u16) -> Result<Value<'static>> {
+        self.basic(v)
+    }
+
+    fn serialize_u32(self, v: u32) -> Result<Value<'static>> {
+        self.basic(v)
+    }
+
+    fn serialize_u64(self, v: u64) -> Result<Value<'static>> {
+        self.basic(v)
+    }
+
+    fn serialize_f32(self, v: f32) -> Result<Value<'static>> {
+        self.basic(v as f64)
+    }
+
+    fn serialize_f64(self, v: f64) -> Result<Value<'static>> {
+        self.basic(v)
+    }
+
+    fn serialize_char(self, v: char) -> Result<Value<'static>> {
+        // No char type in D-Bus/GVariant, let's pretend it's a string
+        self.serialize_str(&v.to_string())
+    }
+
+    fn serialize_str(self, v: &str) -> Result<Value<'static>> {
+        match self.signature.as_str() {
+            <&str>::SIGNATURE_STR => Ok(Value::Str(Str::from(v.to_string()))),
+            ObjectPath::SIGNATURE_STR => ObjectPath::try_from(v.to_string()).map(Value::ObjectPath),
+            Signature::SIGNATURE_STR => Signature::try_from(v.to_string()).map(Value::Signature),
+            _ => Err(self.mismatch("`s`, `o` or `g`")),
+        }
+    }
+
+    fn serialize_bytes(self, v: &[u8]) -> Result<Value<'static>> {
+        let mut seq = self.serialize_seq(Some(v.len()))?;
+        for byte in v {
+            seq.serialize_element(byte)?;
+        }
+
+        seq.end()
+    }
+
+    fn serialize_none(self) -> Result<Value<'static>> {
+        match self.first_char() {
+            #[cfg(feature = "gvariant")]
+            MAYBE_SIGNATURE_CHAR => Ok(Value::Maybe(Maybe::nothing_full_signature(self.signature))),
+            #[cfg(feature = "option-as-array")]
+            ARRAY_SIGNATURE_CHAR => self.serialize_seq(Some(0))?.end(),
+            _ => Err(self.mismatch("an optional type")),
+        }
+    }
+
+    fn serialize_some<T>(self, #[allow(unused)] value: &T) -> Result<Value<'static>>
+    where
+        T: ?Sized + Serialize,
+    {
+        match self.first_char() {
+            #[cfg(feature = "gvariant")]
+            MAYBE_SIGNATURE_CHAR => {
+                let child = ValueSerializer::new(self.signature.slice(1..))?;
+                let value = value.serialize(child)?;
+
+                Ok(Value::Maybe(Maybe::just_full_signature(
+                    value,
+                    self.signature,
+                )))
+            }
+            #[cfg(feature = "option-as-array")]
+            ARRAY_SIGNATURE_CHAR => {
+                let mut seq = self.serialize_seq(Some(1))?;
+                seq.serialize_element(value)?;
+
+                seq.end()
+            }
+            _ => Err(self.mismatch("an optional type")),
+        }
+    }
+
+    fn serialize_unit(self) -> Result<Value<'static>> {
+        Err(self.mismatch("a type that can be represented as a `Value`"))
+    }
+
+    fn serialize_unit_struct(self, _name: &'static str) -> Result<Value<'static>> {
+        self.serialize_unit()
+    }
+
+    fn serialize_unit_variant(
+        self,
+        _name: &'static str,
+        variant_index: u32,
+        variant: &'static str,
+    ) -> Result<Value<'static>> {
+        if self.first_char() == <&str>::SIGNATURE_CHAR {
+            self.serialize_str(variant)
+        } else {
+            self.basic(variant_index)
+        }
+    }
+
+    fn serialize_newtype_struct<T>(
+        #[allow(unused_mut)] mut self,
+        #[allow(unused)] name: &'static str,
+        value: &T,
+    ) -> Result<Value<'static>>
+    where
+        T: ?Sized + Serialize,
+    {
+        #[cfg(unix)]
+        {
+            self.fd = name == FD_NEWTYPE_NAME;
+        }
+
+        value.serialize(self)
+    }
+
+    fn serialize_newtype_variant<T>(
+        self,
+        _name: &'static str,
+        variant_index: u32,
+        _variant: &'static str,
+        value: &T,
+    ) -> Result<Value<'static>>
+    where
+        T: ?Sized + Serialize,
+    {
+        let mut structure = StructureSerializer::enum_variant(self.signature, variant_index)?;
+        structure.push(value)?;
+
+        structure.finish()
+    }
+
+    fn serialize_seq(self, _len: Option<usize>) -> Result<SeqSerializer> {
+        if self.first_char() != ARRAY_SIGNATURE_CHAR {
+            return Err(self.mismatch(&format!("`{ARRAY_SIGNATURE_CHAR}`")));
+        }
+
+        if self.signature.as_bytes()[1] == DICT_ENTRY_SIG_START_CHAR as u8 {
+            DictSerializer::new(self.signature)
+                .map(Box::new)
+                .map(SeqSerializer::Dict)
+        } else {
+            let element_signature = self.signature.slice(1..);
+
+            Ok(SeqSerializer::Array(
+                Array::new_full_signature(self.signature),
+                element_signature,
+            ))
+        }
+    }
+
+    fn serialize_tuple(self, _len: usize) -> Result<StructureSerializer> {
+        StructureSerializer::new(self.signature)
+    }
+
+    fn serialize_tuple_struct(
+        self,
+        _name: &'static str,
+        _len: usize,
+    ) -> Result<StructureSerializer> {
+        StructureSerializer::new(self.signature)
+    }
+
+    fn serialize_tuple_variant(
+        self,
+        _name: &'static str,
+        variant_index: u32,
+        _variant: &'static str,
+        _len: usize,
+    ) -> Result<StructureSerializer> {
+        StructureSerializer::enum_variant_fields(self.signature, variant_index)
+    }
+
+    fn serialize_map(self, _len: Option<usize>) -> Result<DictSerializer> {
+        DictSerializer::new(self.signature)
+    }
+
+    fn serialize_struct(self, _name: &'static str, len: usize) -> Result<StructSerializer> {
+        match self.first_char() {
+            VARIANT_SIGNATURE_CHAR => Ok(StructSerializer::Variant(None, None)),
+            STRUCT_SIG_START_CHAR | DICT_ENTRY_SIG_START_CHAR => {
+                StructureSerializer::new(self.signature).map(StructSerializer::Structure)
+            }
+            ARRAY_SIGNATURE_CHAR => DictSerializer::new(self.signature).map(StructSerializer::Dict),
+            // Unit structures are encoded as a `0u8`.
+            _ if len == 0 => self.basic(0_u8).map(|_| StructSerializer::Unit),
+            _ => Err(self.mismatch(&format!(
+                "`{VARIANT_SIGNATURE_CHAR}`, `{STRUCT_SIG_START_CHAR}` or `{ARRAY_SIGNATURE_CHAR}`",
+            ))),
+        }
+    }
+
+    fn serialize_struct_variant(
+        self,
+        _name: &'static str,
+        variant_index: u32,
+        _variant: &'static str,
+        _len: usize,
+    ) -> Result<StructureSerializer> {
+        StructureSerializer::enum_variant_fields(self.signature, variant_index)
+    }
+
+    fn is_human_readable(&self) -> bool {
+        false
+    }
+}
+
+enum SeqSerializer {
+    Array(Array<'static>, Signature<'static>),
+    Dict(Box<DictSerializer>),
+}
+
+impl SerializeSeq for SeqSerializer {
+    type Ok = Value<'static>;
+    type Error = Error;
+
+    fn serialize_element<T>(&mut self, value: &T) -> Result<()>
+    where
+        T: ?Sized + Serialize,
+    {
+        match self {
+            SeqSerializer::Array(array, element_signature) => {
+                let element = value.serialize(ValueSerializer::new(element_signature.clone())?)?;
+
+                array.append(element)
+            }
+            SeqSerializer::Dict(dict) => {
+                // Dictionary entries are serialized as structures.
+                let signature = dict.dict.full_signature().slice(1..);
+                let entry = value.serialize(ValueSerializer::new(signature.to_owned())?)?;
+                let (key, value) = match entry {
+                    Value::Structure(entry) => {
+                        let mut fields = entry.into_fields().into_iter();
+                        match (fields.next(), fields.next()) {
+                            (Some(key), Some(value)) => (key, value),
+                            _ => return Err(Error::IncorrectType),
+                        }
+                    }
+                    _ => return Err(Error::IncorrectType),
+                };
+
+                dict.dict.append(key, value)
+            }
+        }
+    }
+
+    fn end(self) -> Result<Value<'static>> {
+        match self {
+            SeqSerializer::Array(array, _) => Ok(Value::Array(array)),
+            SeqSerializer::Dict(dict) => SerializeMap::end(*dict),
+        }
+    }
+}
+
+struct StructureSerializer {
+    signature: Signature<'static>,
+    fields: std::vec::IntoIter<Signature<'static>>,
+    builder: StructureBuilder<'static>,
+    // The enum variant structure, we're serializing the fields of.
+    variant: Option<Box<StructureSerializer>>,
+}
+
+impl StructureSerializer {
+    fn new(signature: Signature<'static>) -> Result<Self> {
+        let c = signature.as_bytes()[0] as char;
+        if c != STRUCT_SIG_START_CHAR && c != DICT_ENTRY_SIG_START_CHAR {
+            return Err(Error::SignatureMismatch(
+                signature,
+                format!("expected `{STRUCT_SIG_START_CHAR}` or `{DICT_ENTRY_SIG_START_CHAR}`"),
+            ));
+        }
+
+        let mut fields = vec![];
+        let mut parser = SignatureParser::new(signature.slice(1..signature.len() - 1));
+        while !parser.done() {
+            fields.push(parser.parse_next_signature()?);
+        }
+
+        Ok(Self {
+            signature,
+            fields: fields.into_iter(),
+            builder: StructureBuilder::new(),
+            variant: None,
+        })
+    }
+
+    // Enum variants are encoded as a structure with the variant index as the first field.
+    fn enum_variant(signature: Signature<'static>, variant_index: u32) -> Result<Self> {
+        let mut structure = Self::new(signature)?;
+        structure.push(&variant_index)?;
+
+        Ok(structure)
+    }
+
+    // Fields of tuple and struct variants are encoded as a structure after the variant index.
+    fn enum_variant_fields(signature: Signature<'static>, variant_index: u32) -> Result<Self> {
+        let mut variant = Self::enum_variant(signature, variant_index)?;
+        let mut fields = Self::new(variant.next_field()?)?;
+        fields.variant = Some(Box::new(variant));
+
+        Ok(fields)
+    }
+
+    fn next_field(&mut self) -> Result<Signature<'static>> {
+        self.fields.next().ok_or_else(|| {
+            Error::SignatureMismatch(self.signature.clone(), "expected fewer fields".to_string())
+        })
+    }
+
+    fn push<T>(&mut self, value: &T) -> Result<()>
+    where
+        T: ?Sized + Serialize,
+    {
+        let signature = self.next_field()?;
+        let field = value.serialize(ValueSerializer::new(signature)?)?;
+        self.builder.push_value(field);
+
+        Ok(())
+    }
+
+    fn finish(self) -> Result<Value<'static>> {
+        if self.fields.len() != 0 {
+            return Err(Error::SignatureMismatch(
+                self.signature,
+                "expected more fields".to_string(),
+            ));
+        }
+
+        let structure = Value::Structure(self.builder.build_with_signature(self.signature));
+        match self.variant {
+            Some(mut variant) => {
+                variant.builder.push_value(structure);
+
+                variant.finish()
+            }
+            None => Ok(structure),
+        }
+    }
+}
+
+macro_rules! serialize_structure_impl {
+    ($trait:ident, $method:ident $(, $key:ident)?) => {
+        impl $trait for StructureSerializer {
+            type Ok = Value<'static>;
+            type Error = Error;
+
+            fn $method<T>(&mut self, $($key: &'static str,)? value: &T) -> Result<()>
+            where
+                T: ?Sized + Serialize,
+            {
+                self.push(value)
+            }
+
+            fn end(self) -> Result<Value<'static>> {
+                self.finish()
+            }
+        }
+    };
+}
+
+serialize_structure_impl!(SerializeTuple, serialize_element);
+serialize_structure_impl!(SerializeTupleStruct, serialize_field);
+serialize_structure_impl!(SerializeTupleVariant, serialize_field);
+serialize_structure_impl!(SerializeStructVariant, serialize_field, _key);
+
+struct DictSerializer {
+    dict: Dict<'static, 'static>,
+    key_signature: Signature<'static>,
+    value_signature: Signature<'static>,
+    key: Option<Value<'static>>,
+}
+
+impl DictSerializer {
+    fn new(signature: Signature<'static>) -> Result<Self> {
+        if !signature.starts_with("a{") {
+            return Err(Error::SignatureMismatch(
+                signature,
+                format!("expected `{ARRAY_SIGNATURE_CHAR}{DICT_ENTRY_SIG_START_CHAR}`"),
+            ));
+        }
+        let key_signature = signature.slice(2..3);
+        let value_signature = signature.slice(3..signature.len() - 1);
+
+        Ok(Self {
+            dict: Dict::new_full_signature(signature),
+            key_signature,
+            value_signature,
+            key: None,
+        })
+    }
+}
+
+impl SerializeMap for DictSerializer {
+    type Ok = Value<'static>;
+    type Error = Error;
+
+    fn serialize_key<T>(&mut self, key: &T) -> Result<()>
+    where
+        T: ?Sized + Serialize,
+    {
+        let key = key.serialize(ValueSerializer::new(self.key_signature.clone())?)?;
+        self.key = Some(key);
+
+        Ok(())
+    }
+
+    fn serialize_value<T>(&mut self, value: &T) -> Result<()>
+    where
+        T: ?Sized + Serialize,
+    {
+        let key = self
+            .key
+            .take()
+            .expect("`serialize_key` must be called before `serialize_value`");
+        let value = value.serialize(ValueSerializer::new(self.value_signature.clone())?)?;
+
+        self.dict.append(key, value)
+    }
+
+    fn end(self) -> Result<Value<'static>> {
+        Ok(Value::Dict(self.dict))
+    }
+}
+
+enum StructSerializer {
+    Structure(StructureSerializer),
+    Dict(DictSerializer),
+    // A `Value` itself: the signature of the value, followed by the value.
+    Variant(Option<Signature<'static>>, Option<Value<'static>>),
+    Unit,
+}
+
+impl SerializeStruct for StructSerializer {
+    type Ok = Value<'static>;
+    type Error = Error;
+
+    fn serialize_field<T>(&mut self, key: &'static str, value: &T) -> Result<()>
+    where
+        T: ?Sized + Serialize,
+    {
+        match self {
+            StructSerializer::Structure(structure) => structure.push(value),
+            StructSerializer::Dict(dict) => dict.serialize_entry(key, value),
+            StructSerializer::Variant(signature, inner) => match signature.take() {
+                None => {
+                    let signature_serializer = ValueSerializer::new(Signature::signature())?;
+                    match value.serialize(signature_serializer)? {
+                        Value::Signature(s) => *signature = Some(s),
+                        _ => unreachable!("serializer only returns signatures"),
+                    }
+
+                    Ok(())
+                }
+                Some(s) => {
+                    *inner = Some(value.serialize(ValueSerializer::new(s)?)?);
+
+                    Ok(())
+                }
+            },
+            StructSerializer::Unit => Err(Error::SignatureMismatch(
+                u8::signature(),
+                "expected no fields".to_string(),
+            )),
+        }
+    }
+
+    fn end(self) -> Result<Value<'static>> {
+        match self {
+            StructSerializer::Structure(structure) => structure.finish(),
+            StructSerializer::Dict(dict) => SerializeMap::end(dict),
+            StructSerializer::Variant(_, Some(inner)) => Ok(Value::Value(Box::new(inner))),
+            StructSerializer::Variant(_, None) => Err(Error::IncorrectType),
+            StructSerializer::Unit => Ok(Value::U8(0)),
+        }
+    }
+}
@@ -1,7 +1,9 @@
-use proc_macro2::{Span, TokenStream};
+use proc_macro2::TokenStream;
 use quote::{format_ident, quote, ToTokens};
-use syn::{punctuated::Punctuated, spanned::Spanned, Data, DeriveInput, Error, Field};
-use zvariant_utils::{case, macros};
+use syn::{
+    spanned::Spanned, Data, DeriveInput, Error, Field, GenericArgument, Path, PathArguments, Type,
+};
+use zvariant_utils::macros;
 
 use crate::utils::*;
 
@@ -15,19 +17,63 @@ fn dict_name_for_field(
     } else {
         let ident = f.ident.as_ref().unwrap().to_string();
 
-        match rename_all_attr {
-            Some("lowercase") => Ok(ident.to_ascii_lowercase()),
-            Some("UPPERCASE") => Ok(ident.to_ascii_uppercase()),
-            Some("PascalCase") => Ok(case::pascal_or_camel_case(&ident, true)),
-            Some("camelCase") => Ok(case::pascal_or_camel_case(&ident, false)),
-            Some("snake_case") => Ok(case::snake_case(&ident)),
-            None => Ok(ident),
-            Some(other) => Err(Error::new(
-                f.span(),
-                format!("invalid `rename_all` attribute value {other}"),
-            )),
+        rename_identifier(ident, f.span(), rename_all_attr)
+    }
+}
+
+// The type of the value in the dictionary: `T` for `Option<T>` fields, as `None` values are
+// skipped, and the field's own type otherwise.
+fn value_type(f: &Field) -> &Type {
+    if let Type::Path(path) = &f.ty {
+        let segment = path.path.segments.last().unwrap();
+        if segment.ident == "Option" {
+            if let PathArguments::AngleBracketed(args) = &segment.arguments {
+                if let Some(GenericArgument::Type(ty)) = args.args.first() {
+                    return ty;
+                }
+            }
+        }
+    }
+
+    &f.ty
+}
+
+fn parse_path(path: &str, f: &Field) -> Result<Path, Error> {
+    syn::parse_str(path).map_err(|e| Error::new(f.span(), format!("invalid path `{path}`: {e}")))
+}
+
+// The signature of the value of a field with a `signature` attribute, or of `value_type`.
+fn value_signature(
+    signature: Option<&str>,
+    value_type: &Type,
+    f: &Field,
+    zv: &TokenStream,
+) -> Result<TokenStream, Error> {
+    match signature {
+        Some(signature) => {
+            let signature = parse_signature(signature, f.span())?;
+            let signature = parsed_signature_to_tokens(&signature, zv);
+
+            Ok(quote! { &#signature })
         }
+        None => Ok(quote! { <#value_type as #zv::Type>::SIGNATURE }),
+    }
+}
+
+fn check_flatten_attributes(f: &Field, attrs: &FieldAttributes) -> Result<(), Error> {
+    if attrs.rename.is_some()
+        || attrs.default
+        || attrs.skip_serializing_if.is_some()
+        || attrs.with.is_some()
+        || attrs.signature.is_some()
+    {
+        return Err(Error::new(
+            f.span(),
+            "`flatten` can not be combined with other field attributes",
+        ));
     }
+
+    Ok(())
 }
 
 pub fn expand_serialize_derive(input: DeriveInput) -> Result<TokenStream, Error> {
@@ -39,34 +85,122 @@ pub fn expand_serialize_derive(input: DeriveInput) -> Result<TokenStream, Error>
     let StructAttributes { rename_all, .. } = StructAttributes::parse(&input.attrs)?;
 
     let zv = zvariant_path();
+    let (_, ty_generics, where_clause) = input.generics.split_for_impl();
+    let wrapper_generics = generics_with_lifetime(&input.generics, "'__a");
+    let (wrapper_impl_generics, wrapper_ty_generics, _) = wrapper_generics.split_for_impl();
+    let struct_name = &name;
     let mut entries = quote! {};
     let mut num_entries: usize = 0;
 
     for f in &data.fields {
-        let FieldAttributes { rename } = FieldAttributes::parse(&f.attrs)?;
-
+        let attrs = FieldAttributes::parse(&f.attrs)?;
         let name = &f.ident;
-        let dict_name = dict_name_for_field(f, rename, rename_all.as_deref())?;
 
+        if attrs.flatten {
+            check_flatten_attributes(f, &attrs)?;
+
+            entries.extend(quote! {
+                for (key, value) in &self.#name {
+                    map.serialize_entry(key, value)?;
+                }
+            });
+
+            continue;
+        }
+
+        let FieldAttributes {
+            rename,
+            skip_serializing_if,
+            with,
+            signature,
+            ..
+        } = attrs;
+        let dict_name = dict_name_for_field(f, rename, rename_all.as_deref())?;
         let is_option = macros::ty_is_option(&f.ty);
 
-        let e = if is_option {
+        let (wrapper, value) = if with.is_some() || signature.is_some() {
+            // Serialize through a wrapper, which provides the custom signature and/or
+            // serialization. It needs to carry the generics of the struct as the field type may
+            // refer to them.
+            let value_type = value_type(f);
+            let signature = value_signature(signature.as_deref(), value_type, f, &zv)?;
+            let serialize = match with {
+                Some(with) => {
+                    let with = parse_path(&with, f)?;
+
+                    quote! { #with::serialize(self.value, serializer) }
+                }
+                None => quote! {
+                    #zv::export::serde::ser::Serialize::serialize(self.value, serializer)
+                },
+            };
+            let wrapper = quote! {
+                struct __SerializeWith #wrapper_impl_generics #where_clause {
+                    value: &'__a #value_type,
+                    phantom: ::std::marker::PhantomData<#struct_name #ty_generics>,
+                }
+
+                impl #wrapper_impl_generics #zv::export::serde::ser::Serialize
+                    for __SerializeWith #wrapper_ty_generics #where_clause
+                {
+                    fn serialize<__S>(
+                        &self,
+                        serializer: __S,
+                    ) -> ::std::result::Result<__S::Ok, __S::Error>
+                    where
+                        __S: #zv::export::serde::ser::Serializer,
+                    {
+                        #serialize
+                    }
+                }
+
+                impl #wrapper_impl_generics #zv::Type for __SerializeWith #wrapper_ty_generics
+                #where_clause
+                {
+                    const SIGNATURE: &'static #zv::ParsedSignature = #signature;
+                }
+            };
+            let value = quote! {
+                &__SerializeWith { value, phantom: ::std::marker::PhantomData }
+            };
+
+            (wrapper, value)
+        } else {
+            (quote! {}, quote! { value })
+        };
+
+        let mut e = quote! {
+            #wrapper
+            map.serialize_entry(#dict_name, &#zv::SerializeValue(#value))?;
+        };
+        e = if is_option {
             quote! {
-                if self.#name.is_some() {
-                    map.serialize_entry(#dict_name, &#zv::SerializeValue(self.#name.as_ref().unwrap()))?;
+                if let ::std::option::Option::Some(value) = &self.#name {
+                    #e
                 }
             }
         } else {
             quote! {
-                map.serialize_entry(#dict_name, &#zv::SerializeValue(&self.#name))?;
+                {
+                    let value = &self.#name;
+                    #e
+                }
             }
         };
+        if let Some(skip_serializing_if) = skip_serializing_if {
+            let skip_serializing_if = parse_path(&skip_serializing_if, f)?;
+            e = quote! {
+                if !#skip_serializing_if(&self.#name) {
+                    #e
+                }
+            };
+        }
 
         entries.extend(e);
         num_entries += 1;
     }
 
-    let generics = input.generics;
+    let generics = input.generics.clone();
     let (impl_generics, ty_generics, where_clause) = generics.split_for_impl();
 
     let num_entries = num_entries.to_token_stream();
@@ -104,30 +238,116 @@ pub fn expand_deserialize_derive(input: DeriveInput) -> Result<TokenStream, Erro
 
     let visitor = format_ident!("{}Visitor", name);
     let zv = zvariant_path();
+    let (visitor_impl_generics, ty_generics, visitor_where_clause) =
+        input.generics.split_for_impl();
+    let generics = generics_with_lifetime(&input.generics, "'de");
+    let (impl_generics, de_ty_generics, where_clause) = generics.split_for_impl();
     let mut fields = Vec::new();
     let mut req_fields = Vec::new();
+    let mut default_fields = Vec::new();
     let mut dict_names = Vec::new();
     let mut entries = Vec::new();
+    let mut flatten_field = None;
+    let struct_name = &name;
 
     for f in &data.fields {
-        let FieldAttributes { rename } = FieldAttributes::parse(&f.attrs)?;
-
+        let attrs = FieldAttributes::parse(&f.attrs)?;
         let name = &f.ident;
-        let dict_name = dict_name_for_field(f, rename, rename_all.as_deref())?;
 
+        if attrs.flatten {
+            check_flatten_attributes(f, &attrs)?;
+            if deny_unknown_fields {
+                return Err(Error::new(
+                    f.span(),
+                    "`flatten` can not be combined with `deny_unknown_fields`",
+                ));
+            }
+            if flatten_field.is_some() {
+                return Err(Error::new(f.span(), "only one field can be flattened"));
+            }
+
+            flatten_field = Some(f);
+            continue;
+        }
+
+        let FieldAttributes {
+            rename,
+            default,
+            with,
+            signature,
+            ..
+        } = attrs;
+        let dict_name = dict_name_for_field(f, rename, rename_all.as_deref())?;
         let is_option = macros::ty_is_option(&f.ty);
 
-        entries.push(quote! {
-            #dict_name => {
-                // FIXME: add an option about strict parsing (instead of silently skipping the field)
-                #name = access.next_value::<#zv::DeserializeValue<_>>().map(|v| v.0).ok();
+        let e = if with.is_some() || signature.is_some() {
+            // See the comment on `__SerializeWith` above.
+            let value_type = value_type(f);
+            let signature = value_signature(signature.as_deref(), value_type, f, &zv)?;
+            let deserialize = match with {
+                Some(with) => {
+                    let with = parse_path(&with, f)?;
+
+                    quote! { #with::deserialize(deserializer) }
+                }
+                None => quote! {
+                    <#value_type as #zv::export::serde::de::Deserialize<'de>>::deserialize(
+                        deserializer,
+                    )
+                },
+            };
+
+            quote! {
+                #dict_name => {
+                    struct __DeserializeWith #impl_generics #where_clause {
+                        value: #value_type,
+                        phantom: ::std::marker::PhantomData<(#struct_name #ty_generics, &'de ())>,
+                    }
+
+                    impl #impl_generics #zv::export::serde::de::Deserialize<'de>
+                        for __DeserializeWith #de_ty_generics #where_clause
+                    {
+                        fn deserialize<__D>(
+                            deserializer: __D,
+                        ) -> ::std::result::Result<Self, __D::Error>
+                        where
+                            __D: #zv::export::serde::de::Deserializer<'de>,
+                        {
+                            #deserialize.map(|value| __DeserializeWith {
+                                value,
+                                phantom: ::std::marker::PhantomData,
+                            })
+                        }
+                    }
+
+                    impl #impl_generics #zv::Type for __DeserializeWith #de_ty_generics
+                    #where_clause
+                    {
+                        const SIGNATURE: &'static #zv::ParsedSignature = #signature;
+                    }
+
+                    #name = access
+                        .next_value::<#zv::DeserializeValue<__DeserializeWith #de_ty_generics>>()
+                        .map(|v| v.0.value)
+                        .ok();
+                }
+            }
+        } else {
+            quote! {
+                #dict_name => {
+                    // FIXME: add an option about strict parsing (instead of silently skipping the field)
+                    #name = access.next_value::<#zv::DeserializeValue<_>>().map(|v| v.0).ok();
+                }
             }
-        });
+        };
+        entries.push(e);
 
         dict_names.push(dict_name);
         fields.push(name);
 
-        if !is_option {
+        if default && !is_option {
+            default_fields.push(name);
+        } else if !is_option {
             req_fields.push(name);
         }
     }
@@ -143,6 +363,15 @@ pub fn expand_deserialize_derive(input: DeriveInput) -> Result<TokenStream, Erro
                 );
             }
         }
+    } else if let Some(f) = flatten_field {
+        let name = &f.ident;
+
+        quote! {
+            unknown => {
+                let value = access.next_value()?;
+                #name.insert(::std::convert::From::from(unknown), value);
+            }
+        }
     } else {
         quote! {
             unknown => {
@@ -152,20 +381,13 @@ pub fn expand_deserialize_derive(input: DeriveInput) -> Result<TokenStream, Erro
     };
     entries.push(fallback);
 
-    let (_, ty_generics, _) = input.generics.split_for_impl();
-    let mut generics = input.generics.clone();
-    let def = syn::LifetimeDef {
-        attrs: Vec::new(),
-        lifetime: syn::Lifetime::new("'de", Span::call_site()),
-        colon_token: None,
-        bounds: Punctuated::new(),
-    };
-    generics.params = Some(syn::GenericParam::Lifetime(def))
-        .into_iter()
-        .chain(generics.params)
-        .collect();
+    let flatten_init = flatten_field.map(|f| {
+        let name = &f.ident;
+        let ty = &f.ty;
 
-    let (impl_generics, _, where_clause) = generics.split_for_impl();
+        quote! { let mut #name: #ty = ::std::default::Default::default(); }
+    });
+    let flatten_name = flatten_field.map(|f| &f.ident);
 
     Ok(quote! {
         #[allow(deprecated)]
@@ -176,7 +398,9 @@ pub fn expand_deserialize_derive(input: DeriveInput) -> Result<TokenStream, Erro
             where
                 D: #zv::export::serde::de::Deserializer<'de>,
             {
-                struct #visitor #ty_generics(::std::marker::PhantomData<#name #ty_generics>);
+                struct #visitor #visitor_impl_generics(
+                    ::std::marker::PhantomData<#name #ty_generics>,
+                ) #visitor_where_clause;
 
                 impl #impl_generics #zv::export::serde::de::Visitor<'de> for #visitor #ty_generics {
                     type Value = #name #ty_generics;
@@ -193,6 +417,7 @@ pub fn expand_deserialize_derive(input: DeriveInput) -> Result<TokenStream, Erro
                         M: #zv::export::serde::de::MapAccess<'de>,
                     {
                         #( let mut #fields = ::std::default::Default::default(); )*
+                        #flatten_init
 
                         // does not check duplicated fields, since those shouldn't exist in stream
                         while let ::std::option::Option::Some(key) = access.next_key::<&str>()? {
@@ -210,8 +435,9 @@ pub fn expand_deserialize_derive(input: DeriveInput) -> Result<TokenStream, Erro
                                 ),
                             );
                         };)*
+                        #(let #default_fields = #default_fields.unwrap_or_default();)*
 
-                        ::std::result::Result::Ok(#name { #(#fields),* })
+                        ::std::result::Result::Ok(#name { #(#fields,)* #flatten_name })
                     }
                 }
 
@@ -14,6 +14,7 @@ use proc_macro::TokenStream;
 use syn::{self, DeriveInput};
 
 mod dict;
+mod tagged;
 mod r#type;
 mod utils;
 mod value;
@@ -158,10 +159,15 @@ mod value;
 /// assert_eq!(decoded, StrEnum::Variant2);
 /// ```
 ///
+/// Enums with differently-shaped variants can be encoded with a `(sv)` or `(uv)` signature, if
+/// [`SerializeEnum`] and [`DeserializeEnum`] are used in place of the serde derives.
+///
 /// [`Type`]: https://docs.rs/zvariant/latest/zvariant/trait.Type.html
 /// [`Serialize`]: https://docs.serde.rs/serde/trait.Serialize.html
 /// [`Deserialize`]: https://docs.serde.rs/serde/de/trait.Deserialize.html
 /// [serde_repr]: https://crates.io/crates/serde_repr
+/// [`SerializeEnum`]: derive.SerializeEnum.html
+/// [`DeserializeEnum`]: derive.DeserializeEnum.html
 #[proc_macro_derive(Type, attributes(zvariant))]
 pub fn type_macro_derive(input: TokenStream) -> TokenStream {
     let ast: DeriveInput = syn::parse(input).unwrap();
@@ -228,6 +234,67 @@ pub fn type_macro_derive(input: TokenStream) -> TokenStream {
 /// * `"camelCase"`
 /// * `"snake_case"`
 ///
+/// # Field attributes
+///
+/// Besides `rename`, the following attributes are supported on fields:
+///
+/// * `skip_serializing_if = "path"`: the field is left out of the dictionary if the given function
+///   returns `true` for it. `Option` fields are always left out when they're `None`.
+/// * `flatten`: the entries of the field, a map such as `HashMap<String, OwnedValue>`, are added to
+///   the dictionary.
+/// * `with = "module"`: the field is serialized through `module::serialize`, just like serde's
+///   `with` attribute.
+/// * `signature = "signature"`: the signature of the field's value in the dictionary. By default,
+///   it's the signature of the field's type (or of `T` for an `Option<T>` field). You'd typically
+///   need it together with `with`.
+///
+/// ```
+/// use std::{collections::HashMap, time::Duration};
+/// use zvariant::{
+///     serialized::Context, to_bytes, DeserializeDict, OwnedValue, SerializeDict, Type, Value,
+/// };
+/// use byteorder::LE;
+///
+/// mod secs {
+///     use serde::{Deserialize, Deserializer, Serialize, Serializer};
+///     use std::time::Duration;
+///
+///     pub fn serialize<S: Serializer>(d: &Duration, s: S) -> Result<S::Ok, S::Error> {
+///         d.as_secs().serialize(s)
+///     }
+///
+///     pub fn deserialize<'de, D: Deserializer<'de>>(d: D) -> Result<Duration, D::Error> {
+///         u64::deserialize(d).map(Duration::from_secs)
+///     }
+/// }
+///
+/// #[derive(SerializeDict, DeserializeDict, Type, PartialEq, Debug)]
+/// #[zvariant(signature = "a{sv}")]
+/// struct Options {
+///     #[zvariant(default, skip_serializing_if = "Vec::is_empty")]
+///     choices: Vec<String>,
+///     #[zvariant(with = "secs", signature = "t")]
+///     timeout: Duration,
+///     #[zvariant(flatten)]
+///     other: HashMap<String, OwnedValue>,
+/// }
+///
+/// let options = Options {
+///     choices: vec![],
+///     timeout: Duration::from_secs(30),
+///     other: HashMap::from([("modal".to_string(), OwnedValue::from(true))]),
+/// };
+/// let ctxt = Context::<LE>::new_dbus(0);
+/// let encoded = to_bytes(ctxt, &options).unwrap();
+/// let dict: HashMap<String, Value> = encoded.deserialize().unwrap().0;
+/// assert_eq!(dict.len(), 2);
+/// assert_eq!(dict["timeout"], Value::U64(30));
+/// assert_eq!(dict["modal"], Value::Bool(true));
+///
+/// let decoded: Options = encoded.deserialize().unwrap().0;
+/// assert_eq!(decoded, options);
+/// ```
+///
 /// [`Serialize`]: https://docs.serde.rs/serde/trait.Serialize.html
 #[proc_macro_derive(SerializeDict, attributes(zvariant))]
 pub fn serialize_dict_macro_derive(input: TokenStream) -> TokenStream {
@@ -296,7 +363,23 @@ pub fn serialize_dict_macro_derive(input: TokenStream) -> TokenStream {
 /// * `"camelCase"`
 /// * `"snake_case"`
 ///
+/// # Field attributes
+///
+/// Besides `rename`, the following attributes are supported on fields:
+///
+/// * `default`: if the dictionary lacks the field, `Default::default()` is used instead of failing.
+///   `Option` fields are always optional.
+/// * `flatten`: all the entries of the dictionary that don't correspond to any other field are
+///   collected into the field, a map such as `HashMap<String, OwnedValue>`. It can't be combined
+///   with the `deny_unknown_fields` attribute.
+/// * `with = "module"`: the field is deserialized through `module::deserialize`, just like serde's
+///   `with` attribute.
+/// * `signature = "signature"`: the expected signature of the field's value in the dictionary.
+///
+/// See the [`SerializeDict`] documentation for an example.
+///
 /// [`Deserialize`]: https://docs.serde.rs/serde/de/trait.Deserialize.html
+/// [`SerializeDict`]: derive.SerializeDict.html
 #[proc_macro_derive(DeserializeDict, attributes(zvariant))]
 pub fn deserialize_dict_macro_derive(input: TokenStream) -> TokenStream {
     let input: DeriveInput = syn::parse(input).unwrap();
@@ -305,6 +388,84 @@ pub fn deserialize_dict_macro_derive(input: TokenStream) -> TokenStream {
         .into()
 }
 
+/// Adds [`Serialize`] implementation to enums with data-carrying variants.
+///
+/// By default, zvariant encodes an enum variant as its index, followed by its fields in a
+/// structure, which requires all variants of the enum to have the same fields. This macro
+/// instead encodes each variant together with its fields in a variant (`v`), so the variants
+/// can have differently-shaped fields. The enum needs to be annotated with one of:
+///
+/// * `#[zvariant(signature = "(sv)")]`: the variant is tagged by its name.
+/// * `#[zvariant(signature = "(uv)")]`: the variant is tagged by its (0-based) index.
+///
+/// The [`Type`] macro picks up the same attribute, and the [`Value`] and [`OwnedValue`] macros
+/// use the same encoding. The fields of a variant are encoded as a structure, unless the variant
+/// has exactly one unnamed field, in which case the field is encoded as is. Unit variants are
+/// encoded as a structure without fields, which is a `0u8` (signature `y`), since signatures can't
+/// express an empty structure.
+///
+/// The names used as tags can be modified through the `rename_all` attribute on the enum and
+/// the `rename` attribute on a variant, as with [`SerializeDict`].
+///
+/// # Example
+///
+/// ```
+/// use zvariant::{serialized::Context, to_bytes, DeserializeEnum, SerializeEnum, Type, Value};
+/// use byteorder::LE;
+///
+/// #[derive(Debug, PartialEq, SerializeEnum, DeserializeEnum, Type)]
+/// #[zvariant(signature = "(sv)", rename_all = "lowercase")]
+/// enum Shape {
+///     Circle(f64),
+///     Rectangle { width: f64, height: f64 },
+///     #[zvariant(rename = "nothing")]
+///     Empty,
+/// }
+///
+/// assert_eq!(Shape::signature(), "(sv)");
+///
+/// let ctxt = Context::<LE>::new_dbus(0);
+/// let shape = Shape::Rectangle {
+///     width: 2.0,
+///     height: 3.0,
+/// };
+/// let encoded = to_bytes(ctxt, &shape).unwrap();
+/// let (tag, value): (String, Value) = encoded.deserialize().unwrap().0;
+/// assert_eq!(tag, "rectangle");
+/// assert_eq!(value.value_signature(), "(dd)");
+///
+/// let decoded: Shape = encoded.deserialize().unwrap().0;
+/// assert_eq!(decoded, shape);
+/// ```
+///
+/// [`Serialize`]: https://docs.serde.rs/serde/trait.Serialize.html
+/// [`Type`]: derive.Type.html
+/// [`Value`]: derive.Value.html
+/// [`OwnedValue`]: derive.OwnedValue.html
+/// [`SerializeDict`]: derive.SerializeDict.html
+#[proc_macro_derive(SerializeEnum, attributes(zvariant))]
+pub fn serialize_enum_macro_derive(input: TokenStream) -> TokenStream {
+    let input: DeriveInput = syn::parse(input).unwrap();
+    tagged::expand_serialize_derive(input)
+        .unwrap_or_else(|err| err.to_compile_error())
+        .into()
+}
+
+/// Adds [`Deserialize`] implementation to enums with data-carrying variants.
+///
+/// The counterpart of [`SerializeEnum`]. See its documentation for the supported attributes and
+/// an example.
+///
+/// [`Deserialize`]: https://docs.serde.rs/serde/de/trait.Deserialize.html
+/// [`SerializeEnum`]: derive.SerializeEnum.html
+#[proc_macro_derive(DeserializeEnum, attributes(zvariant))]
+pub fn deserialize_enum_macro_derive(input: TokenStream) -> TokenStream {
+    let input: DeriveInput = syn::parse(input).unwrap();
+    tagged::expand_deserialize_derive(input)
+        .unwrap_or_else(|err| err.to_compile_error())
+        .into()
+}
+
 /// Implements conversions for your type to/from [`Value`].
 ///
 /// Implements `TryFrom<Value>` and `Into<Value>` for your type.
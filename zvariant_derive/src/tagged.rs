@@ -0,0 +1,271 @@
+use proc_macro2::TokenStream;
+use quote::{format_ident, quote, ToTokens};
+use syn::{spanned::Spanned, Data, DataEnum, DeriveInput, Error, Fields, Ident, Variant};
+
+use crate::utils::*;
+
+// The bits of the generated code that depend on the shape of a variant.
+struct VariantInfo {
+    // The wire tag of the variant, a string or `u32` literal.
+    tag: TokenStream,
+    // The pattern matching the variant, binding its fields to `__f0`, `__f1` etc.
+    pattern: TokenStream,
+    // An expression of type `&T`, where `T: Type + Serialize` is the content of the variant.
+    content: TokenStream,
+    // The type the content is deserialized as.
+    content_type: TokenStream,
+    // Expression constructing the variant from `__content`.
+    constructor: TokenStream,
+}
+
+fn parse_enum(input: &DeriveInput) -> Result<(&DataEnum, EnumTag, Option<String>), Error> {
+    let data = match &input.data {
+        Data::Enum(data) => data,
+        _ => return Err(Error::new(input.span(), "only enums supported")),
+    };
+    if data.variants.is_empty() {
+        return Err(Error::new(
+            input.span(),
+            "enums without variants not supported",
+        ));
+    }
+
+    let StructAttributes {
+        signature,
+        rename_all,
+        ..
+    } = StructAttributes::parse(&input.attrs)?;
+    let tag = EnumTag::from_signature(signature.as_deref()).ok_or_else(|| {
+        Error::new(
+            input.ident.span(),
+            "expected `#[zvariant(signature = \"(sv)\")]` or `#[zvariant(signature = \"(uv)\")]`",
+        )
+    })?;
+
+    Ok((data, tag, rename_all))
+}
+
+/// The wire tag of `variant`, the `index`-th variant of its enum: a string or `u32` literal.
+pub fn variant_tag(
+    variant: &Variant,
+    index: u32,
+    tag: EnumTag,
+    rename_all: Option<&str>,
+) -> Result<TokenStream, Error> {
+    match tag {
+        EnumTag::Name => {
+            let VariantAttributes { rename } = VariantAttributes::parse(&variant.attrs)?;
+            let tag = match rename {
+                Some(rename) => rename,
+                None => rename_identifier(variant.ident.to_string(), variant.span(), rename_all)?,
+            };
+
+            Ok(tag.to_token_stream())
+        }
+        EnumTag::Index => Ok(quote! { #index }),
+    }
+}
+
+fn variant_info(
+    name: &Ident,
+    variant: &Variant,
+    index: u32,
+    tag: EnumTag,
+    rename_all: Option<&str>,
+) -> Result<VariantInfo, Error> {
+    let tag = variant_tag(variant, index, tag, rename_all)?;
+    let variant_name = &variant.ident;
+    let zv = zvariant_path();
+
+    let bindings: Vec<_> = (0..variant.fields.len())
+        .map(|i| format_ident!("__f{}", i))
+        .collect();
+    let types: Vec<_> = variant.fields.iter().map(|f| &f.ty).collect();
+    let info = match &variant.fields {
+        Fields::Unit => VariantInfo {
+            tag,
+            pattern: quote! { #name::#variant_name },
+            content: quote! { &#zv::EmptyStructure {} },
+            content_type: quote! { #zv::EmptyStructure },
+            constructor: quote! { #name::#variant_name },
+        },
+        Fields::Unnamed(_) if bindings.len() == 1 => VariantInfo {
+            tag,
+            pattern: quote! { #name::#variant_name(__f0) },
+            content: quote! { __f0 },
+            content_type: types[0].to_token_stream(),
+            constructor: quote! { #name::#variant_name(__content) },
+        },
+        Fields::Unnamed(_) => VariantInfo {
+            tag,
+            pattern: quote! { #name::#variant_name(#(#bindings),*) },
+            content: quote! { &(#(#bindings,)*) },
+            content_type: quote! { (#(#types,)*) },
+            constructor: quote! {{
+                let (#(#bindings,)*) = __content;
+
+                #name::#variant_name(#(#bindings),*)
+            }},
+        },
+        Fields::Named(_) => {
+            let field_names: Vec<_> = variant.fields.iter().map(|f| &f.ident).collect();
+
+            VariantInfo {
+                tag,
+                pattern: quote! { #name::#variant_name { #(#field_names: #bindings),* } },
+                content: quote! { &(#(#bindings,)*) },
+                content_type: quote! { (#(#types,)*) },
+                constructor: quote! {{
+                    let (#(#bindings,)*) = __content;
+
+                    #name::#variant_name { #(#field_names: #bindings),* }
+                }},
+            }
+        }
+    };
+
+    Ok(info)
+}
+
+fn variant_infos(
+    name: &Ident,
+    data: &DataEnum,
+    tag: EnumTag,
+    rename_all: Option<&str>,
+) -> Result<Vec<VariantInfo>, Error> {
+    data.variants
+        .iter()
+        .enumerate()
+        .map(|(i, variant)| variant_info(name, variant, i as u32, tag, rename_all))
+        .collect()
+}
+
+pub fn expand_serialize_derive(input: DeriveInput) -> Result<TokenStream, Error> {
+    let (data, tag, rename_all) = parse_enum(&input)?;
+    let name = &input.ident;
+    let zv = zvariant_path();
+    let infos = variant_infos(name, data, tag, rename_all.as_deref())?;
+    let patterns = infos.iter().map(|info| &info.pattern);
+    let tags = infos.iter().map(|info| &info.tag);
+    let contents = infos.iter().map(|info| &info.content);
+    let (impl_generics, ty_generics, where_clause) = input.generics.split_for_impl();
+
+    Ok(quote! {
+        #[allow(deprecated)]
+        impl #impl_generics #zv::export::serde::ser::Serialize for #name #ty_generics
+        #where_clause
+        {
+            fn serialize<S>(&self, serializer: S) -> ::std::result::Result<S::Ok, S::Error>
+            where
+                S: #zv::export::serde::ser::Serializer,
+            {
+                use #zv::export::serde::ser::SerializeStruct;
+
+                let mut structure = serializer.serialize_struct(::std::stringify!(#name), 2)?;
+                match self {
+                    #(
+                        #patterns => {
+                            structure.serialize_field("tag", &#tags)?;
+                            structure.serialize_field("value", &#zv::SerializeValue(#contents))?;
+                        }
+                    )*
+                }
+                structure.end()
+            }
+        }
+    })
+}
+
+pub fn expand_deserialize_derive(input: DeriveInput) -> Result<TokenStream, Error> {
+    let (data, tag, rename_all) = parse_enum(&input)?;
+    let name = &input.ident;
+    let zv = zvariant_path();
+    let infos = variant_infos(name, data, tag, rename_all.as_deref())?;
+    let tags: Vec<_> = infos.iter().map(|info| &info.tag).collect();
+    let content_types = infos.iter().map(|info| &info.content_type);
+    let constructors = infos.iter().map(|info| &info.constructor);
+
+    let visitor = format_ident!("{}Visitor", name);
+    let (visitor_impl_generics, ty_generics, visitor_where_clause) =
+        input.generics.split_for_impl();
+    let generics = generics_with_lifetime(&input.generics, "'de");
+    let (impl_generics, _, where_clause) = generics.split_for_impl();
+
+    let (tag_type, unknown_tag) = match tag {
+        EnumTag::Name => (
+            quote! { &str },
+            quote! {
+                <A::Error as #zv::export::serde::de::Error>::unknown_variant(
+                    __tag,
+                    &[#(#tags),*],
+                )
+            },
+        ),
+        EnumTag::Index => (
+            quote! { u32 },
+            quote! {
+                <A::Error as #zv::export::serde::de::Error>::invalid_value(
+                    #zv::export::serde::de::Unexpected::Unsigned(__tag as u64),
+                    &self,
+                )
+            },
+        ),
+    };
+    let expecting = format!("enum {name}");
+
+    Ok(quote! {
+        #[allow(deprecated)]
+        impl #impl_generics #zv::export::serde::de::Deserialize<'de> for #name #ty_generics
+        #where_clause
+        {
+            fn deserialize<D>(deserializer: D) -> ::std::result::Result<Self, D::Error>
+            where
+                D: #zv::export::serde::de::Deserializer<'de>,
+            {
+                struct #visitor #visitor_impl_generics(
+                    ::std::marker::PhantomData<#name #ty_generics>,
+                ) #visitor_where_clause;
+
+                impl #impl_generics #zv::export::serde::de::Visitor<'de> for #visitor #ty_generics {
+                    type Value = #name #ty_generics;
+
+                    fn expecting(&self, formatter: &mut ::std::fmt::Formatter) -> ::std::fmt::Result {
+                        formatter.write_str(#expecting)
+                    }
+
+                    fn visit_seq<A>(self, mut seq: A) -> ::std::result::Result<Self::Value, A::Error>
+                    where
+                        A: #zv::export::serde::de::SeqAccess<'de>,
+                    {
+                        let __tag: #tag_type = seq.next_element()?.ok_or_else(|| {
+                            <A::Error as #zv::export::serde::de::Error>::invalid_length(0, &self)
+                        })?;
+                        let __missing_content = || {
+                            <A::Error as #zv::export::serde::de::Error>::invalid_length(1, &self)
+                        };
+
+                        ::std::result::Result::Ok(match __tag {
+                            #(
+                                #tags => {
+                                    let __content = seq
+                                        .next_element::<#zv::DeserializeValue<#content_types>>()?
+                                        .ok_or_else(__missing_content)?
+                                        .0;
+
+                                    #constructors
+                                }
+                            )*
+                            _ => return ::std::result::Result::Err(#unknown_tag),
+                        })
+                    }
+                }
+
+                deserializer.deserialize_struct(
+                    ::std::stringify!(#name),
+                    &["tag", "value"],
+                    #visitor(::std::marker::PhantomData),
+                )
+            }
+        }
+    })
+}
@@ -11,25 +11,21 @@ pub fn expand_derive(ast: DeriveInput) -> Result<TokenStream, Error> {
 
     let zv = zvariant_path();
     if let Some(signature) = signature {
-        let signature = match signature.as_str() {
-            "dict" => "a{sv}".to_string(),
-            _ => signature,
-        };
+        let span = ast
+            .attrs
+            .iter()
+            .find(|attr| attr.path.is_ident("zvariant"))
+            .map(|attr| attr.span())
+            .unwrap_or_else(|| ast.ident.span());
+        let signature = parse_signature(&signature, span)?;
 
         // Signature already provided, easy then!
+        let signature = parsed_signature_to_tokens(&signature, &zv);
         let name = ast.ident;
         let (impl_generics, ty_generics, where_clause) = ast.generics.split_for_impl();
         return Ok(quote! {
             impl #impl_generics #zv::Type for #name #ty_generics #where_clause {
-                #[inline]
-                fn signature() -> #zv::Signature<'static> {
-                    // FIXME: Would be nice if we had a parsed `Signature` in the macro code already so
-                    // it's checked at the build time but currently that's not easily possible w/o
-                    // zvariant_derive requiring zvaraint and we don't want it as it creates a cyclic
-                    // dep. Maybe we can find a way to share the `Signature` type between the two
-                    // crates?
-                    #zv::Signature::from_static_str(#signature).unwrap()
-                }
+                const SIGNATURE: &'static #zv::ParsedSignature = &#signature;
             }
         });
     }
@@ -69,14 +65,14 @@ fn impl_struct(
 
     Ok(quote! {
         impl #impl_generics #zv::Type for #name #ty_generics #where_clause {
-            #[inline]
-            fn signature() -> #zv::Signature<'static> {
-                #signature
-            }
+            const SIGNATURE: &'static #zv::ParsedSignature = #signature;
         }
     })
 }
 
+// The returned tokens are an expression of type `&'static ParsedSignature`. Nested signatures are
+// built with the variant constructors, rather than the `static_*` constructor functions, as only
+// the former are promoted to `'static` in a `const` initializer.
 fn signature_for_struct(
     fields: &Fields,
     zv: &TokenStream,
@@ -92,32 +88,22 @@ fn signature_for_struct(
     let inner_impl = if new_type {
         quote! {
             #(
-                <#field_types as #zv::Type>::signature()
+                <#field_types as #zv::Type>::SIGNATURE
              )*
         }
     } else {
         quote! {
-            let mut s = <::std::string::String as ::std::convert::From<_>>::from("(");
-            #(
-                s.push_str(<#field_types as #zv::Type>::signature().as_str());
-            )*
-            s.push_str(")");
-
-            #zv::Signature::from_string_unchecked(s)
+            &#zv::ParsedSignature::Structure(#zv::parsed_signature::Fields::Static {
+                fields: &[#(<#field_types as #zv::Type>::SIGNATURE),*],
+            })
         }
     };
 
     if insert_enum_variant {
         quote! {
-            let inner_signature = {
-                #inner_impl
-            };
-            let mut s = <::std::string::String as ::std::convert::From<_>>::from("(");
-            s.push_str(<u32 as #zv::Type>::signature().as_str());
-            s.push_str(inner_signature.as_str());
-            s.push_str(")");
-
-            #zv::Signature::from_string_unchecked(s)
+            &#zv::ParsedSignature::Structure(#zv::parsed_signature::Fields::Static {
+                fields: &[<u32 as #zv::Type>::SIGNATURE, #inner_impl],
+            })
         }
     } else {
         inner_impl
@@ -133,10 +119,7 @@ fn impl_unit_struct(
 
     Ok(quote! {
         impl #impl_generics #zv::Type for #name #ty_generics #where_clause {
-            #[inline]
-            fn signature() -> #zv::Signature<'static> {
-                #zv::Signature::from_static_str_unchecked("")
-            }
+            const SIGNATURE: &'static #zv::ParsedSignature = &#zv::ParsedSignature::Unit;
         }
     })
 }
@@ -150,10 +133,7 @@ fn impl_empty_struct(
 
     Ok(quote! {
         impl #impl_generics #zv::Type for #name #ty_generics #where_clause {
-            #[inline]
-            fn signature() -> #zv::Signature<'static> {
-                #zv::Signature::from_static_str_unchecked("y")
-            }
+            const SIGNATURE: &'static #zv::ParsedSignature = &#zv::ParsedSignature::U8;
         }
     })
 }
@@ -176,7 +156,9 @@ fn impl_enum(
         if sig?.to_string() != signature.to_string() {
             return Err(Error::new(
                 name.span(),
-                "all variants must have the same number and type of fields",
+                "all variants must have the same number and type of fields, unless the enum \
+                 is encoded with `#[zvariant(signature = \"(sv)\")]` or \
+                 `#[zvariant(signature = \"(uv)\")]`",
             ));
         }
     }
@@ -185,10 +167,7 @@ fn impl_enum(
 
     Ok(quote! {
         impl #impl_generics #zv::Type for #name #ty_generics #where_clause {
-            #[inline]
-            fn signature() -> #zv::Signature<'static> {
-                #signature
-            }
+            const SIGNATURE: &'static #zv::ParsedSignature = #signature;
         }
     })
 }
@@ -206,7 +185,7 @@ fn signature_for_variant(
                 None => quote! { u32 },
             };
 
-            Ok(quote! { <#repr as #zv::Type>::signature() })
+            Ok(quote! { <#repr as #zv::Type>::SIGNATURE })
         }
         Fields::Named(_) => Ok(signature_for_struct(&variant.fields, zv, true)),
         Fields::Unnamed(_) => Ok(signature_for_struct(&variant.fields, zv, true)),
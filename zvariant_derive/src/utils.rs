@@ -1,7 +1,8 @@
-use proc_macro2::TokenStream;
+use proc_macro2::{Span, TokenStream};
 use proc_macro_crate::{crate_name, FoundCrate};
 use quote::{format_ident, quote};
-use zvariant_utils::def_attrs;
+use syn::{punctuated::Punctuated, Error, Generics};
+use zvariant_utils::{case, def_attrs, signature::ParsedSignature};
 
 pub fn zvariant_path() -> TokenStream {
     if let Ok(FoundCrate::Name(name)) = crate_name("zvariant") {
@@ -15,11 +16,144 @@ pub fn zvariant_path() -> TokenStream {
     }
 }
 
+/// Parse the value of a `signature` attribute, where `dict` is an alias for `a{sv}`.
+pub fn parse_signature(signature: &str, span: Span) -> Result<ParsedSignature, Error> {
+    match signature {
+        "dict" => Ok(ParsedSignature::dict(
+            ParsedSignature::Str,
+            ParsedSignature::Variant,
+        )),
+        _ => signature
+            .parse()
+            .map_err(|e| Error::new(span, format!("invalid signature `{signature}`: {e}"))),
+    }
+}
+
+/// How the variants of an enum with data-carrying variants are tagged on the wire.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum EnumTag {
+    /// `(sv)`: the name of the variant, followed by its fields in a variant.
+    Name,
+    /// `(uv)`: the index of the variant, followed by its fields in a variant.
+    Index,
+}
+
+impl EnumTag {
+    /// The tagging selected by the `signature` attribute of an enum, if any.
+    pub fn from_signature(signature: Option<&str>) -> Option<Self> {
+        match signature {
+            Some("(sv)") => Some(EnumTag::Name),
+            Some("(uv)") => Some(EnumTag::Index),
+            _ => None,
+        }
+    }
+}
+
+/// Apply a `rename_all` attribute value to `ident`.
+pub fn rename_identifier(
+    ident: String,
+    span: Span,
+    rename_all_attr: Option<&str>,
+) -> Result<String, Error> {
+    match rename_all_attr {
+        Some("lowercase") => Ok(ident.to_ascii_lowercase()),
+        Some("UPPERCASE") => Ok(ident.to_ascii_uppercase()),
+        Some("PascalCase") => Ok(case::pascal_or_camel_case(&ident, true)),
+        Some("camelCase") => Ok(case::pascal_or_camel_case(&ident, false)),
+        Some("snake_case") => Ok(case::snake_case(&ident)),
+        None => Ok(ident),
+        Some(other) => Err(Error::new(
+            span,
+            format!("invalid `rename_all` attribute value {other}"),
+        )),
+    }
+}
+
+// `generics` with `lifetime` prepended.
+pub fn generics_with_lifetime(generics: &Generics, lifetime: &str) -> Generics {
+    let def = syn::LifetimeDef {
+        attrs: Vec::new(),
+        lifetime: syn::Lifetime::new(lifetime, Span::call_site()),
+        colon_token: None,
+        bounds: Punctuated::new(),
+    };
+    let mut generics = generics.clone();
+    generics.params = Some(syn::GenericParam::Lifetime(def))
+        .into_iter()
+        .chain(generics.params)
+        .collect();
+
+    generics
+}
+
+// Tokens constructing `signature` as a `ParsedSignature` value.
+pub fn parsed_signature_to_tokens(signature: &ParsedSignature, zv: &TokenStream) -> TokenStream {
+    let child_tokens = |child: &ParsedSignature| {
+        let child = parsed_signature_to_tokens(child, zv);
+
+        quote! { #zv::parsed_signature::Child::Static { child: &#child } }
+    };
+
+    match signature {
+        ParsedSignature::Unit => quote! { #zv::ParsedSignature::Unit },
+        ParsedSignature::U8 => quote! { #zv::ParsedSignature::U8 },
+        ParsedSignature::Bool => quote! { #zv::ParsedSignature::Bool },
+        ParsedSignature::I16 => quote! { #zv::ParsedSignature::I16 },
+        ParsedSignature::U16 => quote! { #zv::ParsedSignature::U16 },
+        ParsedSignature::I32 => quote! { #zv::ParsedSignature::I32 },
+        ParsedSignature::U32 => quote! { #zv::ParsedSignature::U32 },
+        ParsedSignature::I64 => quote! { #zv::ParsedSignature::I64 },
+        ParsedSignature::U64 => quote! { #zv::ParsedSignature::U64 },
+        ParsedSignature::F64 => quote! { #zv::ParsedSignature::F64 },
+        ParsedSignature::Str => quote! { #zv::ParsedSignature::Str },
+        ParsedSignature::Signature => quote! { #zv::ParsedSignature::Signature },
+        ParsedSignature::ObjectPath => quote! { #zv::ParsedSignature::ObjectPath },
+        ParsedSignature::Variant => quote! { #zv::ParsedSignature::Variant },
+        ParsedSignature::Fd => quote! { #zv::ParsedSignature::Fd },
+        ParsedSignature::Array(child) => {
+            let child = child_tokens(child);
+
+            quote! { #zv::ParsedSignature::Array(#child) }
+        }
+        ParsedSignature::Dict { key, value } => {
+            let key = child_tokens(key);
+            let value = child_tokens(value);
+
+            quote! { #zv::ParsedSignature::Dict { key: #key, value: #value } }
+        }
+        ParsedSignature::Structure(fields) => {
+            let fields = fields
+                .iter()
+                .map(|field| parsed_signature_to_tokens(field, zv));
+
+            quote! {
+                #zv::ParsedSignature::Structure(#zv::parsed_signature::Fields::Static {
+                    fields: &[#(&#fields),*],
+                })
+            }
+        }
+        ParsedSignature::Maybe(child) => {
+            let child = child_tokens(child);
+
+            quote! { #zv::ParsedSignature::Maybe(#child) }
+        }
+    }
+}
+
 def_attrs! {
     crate zvariant;
 
     /// Attributes defined on structures.
     pub StructAttributes("struct") { signature str, rename_all str, deny_unknown_fields none };
     /// Attributes defined on fields.
-    pub FieldAttributes("field") { rename str };
+    pub FieldAttributes("field") {
+        rename str,
+        default none,
+        flatten none,
+        skip_serializing_if str,
+        with str,
+        signature str
+    };
+    /// Attributes defined on enum variants.
+    pub VariantAttributes("variant") { rename str };
 }
@@ -1,11 +1,11 @@
 use proc_macro2::{Span, TokenStream};
-use quote::{quote, ToTokens};
+use quote::{format_ident, quote, ToTokens};
 use syn::{
     self, spanned::Spanned, Attribute, Data, DataEnum, DeriveInput, Error, Expr, Fields, Generics,
     Ident, Lifetime, LifetimeDef,
 };
 
-use crate::utils::*;
+use crate::{tagged::variant_tag, utils::*};
 
 pub enum ValueType {
     Value,
@@ -227,11 +227,28 @@ fn impl_struct(
 fn impl_enum(
     value_type: ValueType,
     name: Ident,
-    _generics: Generics,
+    generics: Generics,
     attrs: Vec<Attribute>,
     data: &DataEnum,
     zv: &TokenStream,
 ) -> Result<TokenStream, Error> {
+    let StructAttributes {
+        signature,
+        rename_all,
+        ..
+    } = StructAttributes::parse(&attrs)?;
+    if let Some(tag) = EnumTag::from_signature(signature.as_deref()) {
+        return impl_tagged_enum(
+            value_type,
+            name,
+            generics,
+            tag,
+            rename_all.as_deref(),
+            data,
+            zv,
+        );
+    }
+
     let repr: TokenStream = match attrs.iter().find(|attr| attr.path.is_ident("repr")) {
         Some(repr_attr) => repr_attr.parse_args()?,
         None => quote! { u32 },
@@ -260,7 +277,13 @@ fn impl_enum(
                 };
                 variant_values.push(value);
             }
-            _ => return Err(Error::new(variant.span(), "must be a unit variant")),
+            _ => {
+                return Err(Error::new(
+                    variant.span(),
+                    "must be a unit variant, unless the enum is encoded with \
+                     `#[zvariant(signature = \"(sv)\")]` or `#[zvariant(signature = \"(uv)\")]`",
+                ))
+            }
         }
     }
 
@@ -325,3 +348,160 @@ fn impl_enum(
         #into_value
     })
 }
+
+// Enums with data-carrying variants, encoded as a structure of the variant's tag and its fields
+// in a variant, like `SerializeEnum` does.
+fn impl_tagged_enum(
+    value_type: ValueType,
+    name: Ident,
+    generics: Generics,
+    tag: EnumTag,
+    rename_all: Option<&str>,
+    data: &DataEnum,
+    zv: &TokenStream,
+) -> Result<TokenStream, Error> {
+    if generics.params.iter().next().is_some() {
+        return Err(Error::new(
+            generics.span(),
+            "generic enums with data-carrying variants not supported",
+        ));
+    }
+
+    let mut tags = vec![];
+    let mut patterns = vec![];
+    let mut contents = vec![];
+    let mut constructors = vec![];
+    for (i, variant) in data.variants.iter().enumerate() {
+        tags.push(variant_tag(variant, i as u32, tag, rename_all)?);
+
+        let variant_name = &variant.ident;
+        let bindings: Vec<_> = (0..variant.fields.len())
+            .map(|i| format_ident!("__f{}", i))
+            .collect();
+        let num_fields = bindings.len();
+        let structure_content = quote! {
+            #zv::Value::from(
+                #zv::StructureBuilder::new()
+                #(
+                    .add_field(#bindings)
+                )*
+                .build()
+            )
+        };
+        let structure_fields = quote! {
+            let mut fields = content.downcast::<#zv::Structure>()?.into_fields();
+            if fields.len() != #num_fields {
+                return ::std::result::Result::Err(#zv::Error::IncorrectType);
+            }
+        };
+        let downcasts: Vec<_> = bindings
+            .iter()
+            .map(|_| quote! { fields.remove(0).downcast()? })
+            .collect();
+        match &variant.fields {
+            Fields::Unit => {
+                patterns.push(quote! { #name::#variant_name });
+                // Structures without fields are represented as a `0u8`.
+                contents.push(quote! { #zv::Value::from(0u8) });
+                constructors.push(quote! { #name::#variant_name });
+            }
+            Fields::Unnamed(_) if num_fields == 1 => {
+                patterns.push(quote! { #name::#variant_name(__f0) });
+                contents.push(quote! { #zv::Value::from(__f0) });
+                constructors.push(quote! { #name::#variant_name(content.downcast()?) });
+            }
+            Fields::Unnamed(_) => {
+                patterns.push(quote! { #name::#variant_name(#(#bindings),*) });
+                contents.push(structure_content);
+                constructors.push(quote! {{
+                    #structure_fields
+
+                    #name::#variant_name(#(#downcasts),*)
+                }});
+            }
+            Fields::Named(_) => {
+                let field_names: Vec<_> = variant.fields.iter().map(|f| &f.ident).collect();
+                patterns.push(quote! { #name::#variant_name { #(#field_names: #bindings),* } });
+                contents.push(structure_content);
+                constructors.push(quote! {{
+                    #structure_fields
+
+                    #name::#variant_name {
+                        #(
+                            #field_names: #downcasts
+                        ),*
+                    }
+                }});
+            }
+        }
+    }
+
+    let (tag_type, tag_match) = match tag {
+        EnumTag::Name => (quote! { #zv::Str }, quote! { tag.as_str() }),
+        EnumTag::Index => (quote! { u32 }, quote! { tag }),
+    };
+    let into_value = quote! {
+        let (tag, content) = match e {
+            #(
+                #patterns => (#zv::Value::from(#tags), #contents)
+            ),*
+        };
+
+        #zv::Value::from(
+            #zv::StructureBuilder::new()
+                .append_field(tag)
+                .append_field(#zv::Value::Value(::std::boxed::Box::new(content)))
+                .build(),
+        )
+    };
+    let (value_type, into_value) = match value_type {
+        ValueType::Value => (
+            quote! { #zv::Value<'_> },
+            quote! {
+                impl ::std::convert::From<#name> for #zv::Value<'_> {
+                    #[inline]
+                    fn from(e: #name) -> Self {
+                        #into_value
+                    }
+                }
+            },
+        ),
+        ValueType::OwnedValue => (
+            quote! { #zv::OwnedValue },
+            quote! {
+                impl ::std::convert::TryFrom<#name> for #zv::OwnedValue {
+                    type Error = #zv::Error;
+
+                    #[inline]
+                    fn try_from(e: #name) -> #zv::Result<Self> {
+                        <#zv::OwnedValue as ::std::convert::TryFrom<_>>::try_from({ #into_value })
+                    }
+                }
+            },
+        ),
+    };
+
+    Ok(quote! {
+        impl ::std::convert::TryFrom<#value_type> for #name {
+            type Error = #zv::Error;
+
+            fn try_from(value: #value_type) -> #zv::Result<Self> {
+                let mut fields = #zv::Structure::try_from(value)?.into_fields();
+                if fields.len() != 2 {
+                    return ::std::result::Result::Err(#zv::Error::IncorrectType);
+                }
+                let content = fields.remove(1);
+                let tag: #tag_type = fields.remove(0).downcast()?;
+
+                ::std::result::Result::Ok(match #tag_match {
+                    #(
+                        #tags => #constructors,
+                    )*
+                    _ => return ::std::result::Result::Err(#zv::Error::IncorrectType),
+                })
+            }
+        }
+
+        #into_value
+    })
+}
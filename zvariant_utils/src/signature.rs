@@ -0,0 +1,856 @@
+//! Contains a parsed representation of D-Bus and GVariant signatures.
+//!
+//! Unlike the string-based `zvariant::Signature`, [`ParsedSignature`] is a tree of the types that
+//! make up the signature. Since it can be constructed in `const` contexts, the signature of a
+//! (derived or built-in) type can be computed at compile time instead of being built and parsed
+//! at runtime.
+
+use std::{
+    fmt::{self, Display, Write},
+    ops::Deref,
+    str::FromStr,
+};
+
+/// The maximum length of a signature, in bytes.
+pub const MAX_SIGNATURE_LEN: usize = 255;
+
+/// The maximum nesting depth of arrays (including dictionaries) in a signature.
+pub const MAX_ARRAY_DEPTH: u8 = 32;
+
+/// The maximum nesting depth of structures (including dictionary entries) in a signature.
+pub const MAX_STRUCT_DEPTH: u8 = 32;
+
+/// A parsed signature.
+///
+/// Each variant corresponds to one complete type. The empty signature is represented by
+/// [`ParsedSignature::Unit`] while a signature that consists of more than one complete type (e.g.
+/// the body signature of a message) is represented as a [`ParsedSignature::Structure`].
+///
+/// # Examples
+///
+/// ```
+/// use zvariant_utils::signature::ParsedSignature;
+///
+/// let parsed: ParsedSignature = "a{sv}".parse().unwrap();
+/// match &parsed {
+///     ParsedSignature::Dict { key, value } => {
+///         assert_eq!(**key, ParsedSignature::Str);
+///         assert_eq!(**value, ParsedSignature::Variant);
+///     }
+///     _ => unreachable!(),
+/// }
+/// assert_eq!(parsed.to_string(), "a{sv}");
+/// assert_eq!(parsed, *"a{sv}");
+///
+/// // Signatures can also be built at compile time.
+/// const STRUCT: &ParsedSignature =
+///     &ParsedSignature::static_structure(&[&ParsedSignature::U32, &ParsedSignature::Str]);
+/// const ARRAY_OF_STRUCTS: &ParsedSignature = &ParsedSignature::static_array(STRUCT);
+/// assert_eq!(ARRAY_OF_STRUCTS.to_string(), "a(us)");
+/// ```
+#[derive(Debug, Clone)]
+pub enum ParsedSignature {
+    /// The signature of the unit type, i.e. the empty signature.
+    Unit,
+    /// `y`
+    U8,
+    /// `b`
+    Bool,
+    /// `n`
+    I16,
+    /// `q`
+    U16,
+    /// `i`
+    I32,
+    /// `u`
+    U32,
+    /// `x`
+    I64,
+    /// `t`
+    U64,
+    /// `d`
+    F64,
+    /// `s`
+    Str,
+    /// `g`
+    Signature,
+    /// `o`
+    ObjectPath,
+    /// `v`
+    Variant,
+    /// `h`
+    Fd,
+    /// An array (`a`) of the child type.
+    Array(Child),
+    /// A dictionary, i.e. an array of dict-entries (`a{}`).
+    Dict {
+        /// The signature of the key. Always a basic type.
+        key: Child,
+        /// The signature of the value.
+        value: Child,
+    },
+    /// A structure (`()`) of the given fields.
+    Structure(Fields),
+    /// A GVariant maybe (`m`) of the child type.
+    Maybe(Child),
+}
+
+impl ParsedSignature {
+    /// Create the signature of a basic type (or variant), from its signature character.
+    ///
+    /// # Panics
+    ///
+    /// If `c` is not the signature character of a basic type or a variant. Since this is a `const`
+    /// function, this results in a build failure when used in a `const` context.
+    pub const fn basic(c: char) -> Self {
+        match c {
+            'y' => Self::U8,
+            'b' => Self::Bool,
+            'n' => Self::I16,
+            'q' => Self::U16,
+            'i' => Self::I32,
+            'u' => Self::U32,
+            'x' => Self::I64,
+            't' => Self::U64,
+            'd' => Self::F64,
+            's' => Self::Str,
+            'g' => Self::Signature,
+            'o' => Self::ObjectPath,
+            'v' => Self::Variant,
+            'h' => Self::Fd,
+            _ => panic!("not a basic type signature character"),
+        }
+    }
+
+    /// Create an array signature from a static child signature.
+    ///
+    /// Note that in a `const` initializer, the result of these `static_*` functions can only be
+    /// borrowed as `'static` at the top level. Nested signatures need their own `const` or to be
+    /// constructed through the enum variants directly.
+    pub const fn static_array(child: &'static ParsedSignature) -> Self {
+        Self::Array(Child::Static { child })
+    }
+
+    /// Create a dictionary signature from static key and value signatures.
+    pub const fn static_dict(
+        key: &'static ParsedSignature,
+        value: &'static ParsedSignature,
+    ) -> Self {
+        Self::Dict {
+            key: Child::Static { child: key },
+            value: Child::Static { child: value },
+        }
+    }
+
+    /// Create a structure signature from static field signatures.
+    pub const fn static_structure(fields: &'static [&'static ParsedSignature]) -> Self {
+        Self::Structure(Fields::Static { fields })
+    }
+
+    /// Create a maybe signature from a static child signature.
+    pub const fn static_maybe(child: &'static ParsedSignature) -> Self {
+        Self::Maybe(Child::Static { child })
+    }
+
+    /// Create an array signature.
+    pub fn array(child: ParsedSignature) -> Self {
+        Self::Array(Child::Dynamic {
+            child: Box::new(child),
+        })
+    }
+
+    /// Create a dictionary signature.
+    pub fn dict(key: ParsedSignature, value: ParsedSignature) -> Self {
+        Self::Dict {
+            key: Child::Dynamic {
+                child: Box::new(key),
+            },
+            value: Child::Dynamic {
+                child: Box::new(value),
+            },
+        }
+    }
+
+    /// Create a structure signature.
+    pub fn structure<F>(fields: F) -> Self
+    where
+        F: IntoIterator<Item = ParsedSignature>,
+    {
+        Self::Structure(Fields::Dynamic {
+            fields: fields.into_iter().collect(),
+        })
+    }
+
+    /// Create a maybe signature.
+    pub fn maybe(child: ParsedSignature) -> Self {
+        Self::Maybe(Child::Dynamic {
+            child: Box::new(child),
+        })
+    }
+
+    /// Parse a signature from its string form, given as bytes.
+    ///
+    /// Besides being well-formed, the signature must not be longer than [`MAX_SIGNATURE_LEN`] or
+    /// nest arrays or structures deeper than [`MAX_ARRAY_DEPTH`] and [`MAX_STRUCT_DEPTH`].
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self, Error> {
+        if bytes.len() > MAX_SIGNATURE_LEN {
+            return Err(Error::TooLong(bytes.len()));
+        }
+
+        if bytes.is_empty() {
+            return Ok(Self::Unit);
+        }
+
+        let mut parser = Parser {
+            bytes,
+            pos: 0,
+            array_depth: 0,
+            struct_depth: 0,
+        };
+        let first = parser.parse_complete_type()?;
+        if parser.pos == bytes.len() {
+            // The common case of a single complete type, which doesn't need a `Vec`.
+            return Ok(first);
+        }
+
+        let mut fields = vec![first];
+        while parser.pos < bytes.len() {
+            fields.push(parser.parse_complete_type()?);
+        }
+
+        Ok(Self::structure(fields))
+    }
+
+    /// The signature string of this signature, if it doesn't need to be built.
+    ///
+    /// This is the case for the unit type, the basic types and the variant type.
+    pub const fn as_static_str(&self) -> Option<&'static str> {
+        let s = match self {
+            Self::Unit => "",
+            Self::U8 => "y",
+            Self::Bool => "b",
+            Self::I16 => "n",
+            Self::U16 => "q",
+            Self::I32 => "i",
+            Self::U32 => "u",
+            Self::I64 => "x",
+            Self::U64 => "t",
+            Self::F64 => "d",
+            Self::Str => "s",
+            Self::Signature => "g",
+            Self::ObjectPath => "o",
+            Self::Variant => "v",
+            Self::Fd => "h",
+            Self::Array(_) | Self::Dict { .. } | Self::Structure(_) | Self::Maybe(_) => {
+                return None
+            }
+        };
+
+        Some(s)
+    }
+
+    /// The length of the string form of this signature, in bytes.
+    pub fn string_len(&self) -> usize {
+        match self {
+            Self::Unit => 0,
+            Self::Array(child) | Self::Maybe(child) => 1 + child.string_len(),
+            Self::Dict { key, value } => 3 + key.string_len() + value.string_len(),
+            Self::Structure(fields) => 2 + fields.iter().map(Self::string_len).sum::<usize>(),
+            _ => 1,
+        }
+    }
+
+    /// Whether this is the signature of a basic type.
+    ///
+    /// Only basic types can be used as dictionary keys.
+    pub const fn is_basic(&self) -> bool {
+        !matches!(
+            self,
+            Self::Unit
+                | Self::Variant
+                | Self::Array(_)
+                | Self::Dict { .. }
+                | Self::Structure(_)
+                | Self::Maybe(_)
+        )
+    }
+
+    /// Whether values of this type are always encoded with the same size in the GVariant format.
+    pub fn is_fixed_sized(&self) -> bool {
+        match self {
+            Self::U8
+            | Self::Bool
+            | Self::I16
+            | Self::U16
+            | Self::I32
+            | Self::U32
+            | Self::I64
+            | Self::U64
+            | Self::F64
+            | Self::Fd => true,
+            Self::Structure(fields) => fields.iter().all(Self::is_fixed_sized),
+            Self::Unit
+            | Self::Str
+            | Self::Signature
+            | Self::ObjectPath
+            | Self::Variant
+            | Self::Array(_)
+            | Self::Dict { .. }
+            | Self::Maybe(_) => false,
+        }
+    }
+}
+
+impl Display for ParsedSignature {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if let Some(s) = self.as_static_str() {
+            return f.write_str(s);
+        }
+
+        match self {
+            Self::Array(child) => {
+                f.write_char('a')?;
+                child.fmt(f)
+            }
+            Self::Dict { key, value } => {
+                f.write_str("a{")?;
+                key.fmt(f)?;
+                value.fmt(f)?;
+                f.write_char('}')
+            }
+            Self::Structure(fields) => {
+                f.write_char('(')?;
+                for field in fields.iter() {
+                    field.fmt(f)?;
+                }
+                f.write_char(')')
+            }
+            Self::Maybe(child) => {
+                f.write_char('m')?;
+                child.fmt(f)
+            }
+            _ => unreachable!("basic types have a static string"),
+        }
+    }
+}
+
+impl FromStr for ParsedSignature {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Self::from_bytes(s.as_bytes())
+    }
+}
+
+impl TryFrom<&str> for ParsedSignature {
+    type Error = Error;
+
+    fn try_from(s: &str) -> Result<Self, Self::Error> {
+        Self::from_bytes(s.as_bytes())
+    }
+}
+
+impl PartialEq for ParsedSignature {
+    fn eq(&self, other: &Self) -> bool {
+        match (self, other) {
+            (Self::Array(a), Self::Array(b)) | (Self::Maybe(a), Self::Maybe(b)) => **a == **b,
+            (
+                Self::Dict {
+                    key: key_a,
+                    value: value_a,
+                },
+                Self::Dict {
+                    key: key_b,
+                    value: value_b,
+                },
+            ) => **key_a == **key_b && **value_a == **value_b,
+            (Self::Structure(a), Self::Structure(b)) => {
+                a.len() == b.len() && a.iter().zip(b.iter()).all(|(a, b)| a == b)
+            }
+            (Self::Array(_), _)
+            | (Self::Maybe(_), _)
+            | (Self::Dict { .. }, _)
+            | (Self::Structure(_), _) => false,
+            _ => self.as_static_str() == other.as_static_str(),
+        }
+    }
+}
+
+impl Eq for ParsedSignature {}
+
+impl PartialEq<str> for ParsedSignature {
+    fn eq(&self, other: &str) -> bool {
+        // Compare against the string form without allocating it.
+        struct Matcher<'s>(&'s str);
+
+        impl Write for Matcher<'_> {
+            fn write_str(&mut self, s: &str) -> fmt::Result {
+                match self.0.strip_prefix(s) {
+                    Some(rest) => {
+                        self.0 = rest;
+
+                        Ok(())
+                    }
+                    None => Err(fmt::Error),
+                }
+            }
+        }
+
+        let mut matcher = Matcher(other);
+        write!(matcher, "{self}").is_ok() && matcher.0.is_empty()
+    }
+}
+
+impl PartialEq<&str> for ParsedSignature {
+    fn eq(&self, other: &&str) -> bool {
+        self == *other
+    }
+}
+
+/// The child signature of a single-child container signature.
+///
+/// Dereferences to the child's [`ParsedSignature`].
+#[derive(Debug, Clone)]
+pub enum Child {
+    /// A child signature known at compile time.
+    Static {
+        /// The child signature.
+        child: &'static ParsedSignature,
+    },
+    /// A child signature built at runtime.
+    Dynamic {
+        /// The child signature.
+        child: Box<ParsedSignature>,
+    },
+}
+
+impl Deref for Child {
+    type Target = ParsedSignature;
+
+    fn deref(&self) -> &ParsedSignature {
+        match self {
+            Self::Static { child } => child,
+            Self::Dynamic { child } => child,
+        }
+    }
+}
+
+/// The field signatures of a structure signature.
+#[derive(Debug, Clone)]
+pub enum Fields {
+    /// Field signatures known at compile time.
+    Static {
+        /// The field signatures.
+        fields: &'static [&'static ParsedSignature],
+    },
+    /// Field signatures built at runtime.
+    Dynamic {
+        /// The field signatures.
+        fields: Box<[ParsedSignature]>,
+    },
+}
+
+impl Fields {
+    /// The number of fields.
+    pub fn len(&self) -> usize {
+        match self {
+            Self::Static { fields } => fields.len(),
+            Self::Dynamic { fields } => fields.len(),
+        }
+    }
+
+    /// Whether there are no fields.
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Get the field signature at `index`.
+    pub fn get(&self, index: usize) -> Option<&ParsedSignature> {
+        match self {
+            Self::Static { fields } => fields.get(index).copied(),
+            Self::Dynamic { fields } => fields.get(index),
+        }
+    }
+
+    /// Iterate over the field signatures.
+    pub fn iter(&self) -> impl Iterator<Item = &ParsedSignature> + '_ {
+        (0..self.len()).filter_map(|i| self.get(i))
+    }
+}
+
+/// The string form of a [`ParsedSignature`], built at compile time.
+///
+/// Since its contents are stored inline, the string form of a signature constant can be kept in a
+/// constant as well and hence be used without building it at runtime.
+///
+/// # Examples
+///
+/// ```
+/// use zvariant_utils::signature::{ParsedSignature, SignatureBuf};
+///
+/// const STRUCT: &ParsedSignature =
+///     &ParsedSignature::static_structure(&[&ParsedSignature::U32, &ParsedSignature::Str]);
+/// const ARRAY_OF_STRUCTS: &ParsedSignature = &ParsedSignature::static_array(STRUCT);
+/// const BUF: &SignatureBuf = &SignatureBuf::new(ARRAY_OF_STRUCTS);
+/// assert_eq!(BUF.as_str(), "a(us)");
+/// ```
+#[derive(Debug, Clone, Copy)]
+pub struct SignatureBuf {
+    bytes: [u8; MAX_SIGNATURE_LEN],
+    len: usize,
+}
+
+impl SignatureBuf {
+    /// Build the string form of `signature`.
+    ///
+    /// # Panics
+    ///
+    /// If `signature` contains a child built at runtime or its string form is longer than
+    /// [`MAX_SIGNATURE_LEN`]. Since this is a `const` function, this results in a build failure
+    /// when used in a `const` context.
+    pub const fn new(signature: &ParsedSignature) -> Self {
+        Self {
+            bytes: [0; MAX_SIGNATURE_LEN],
+            len: 0,
+        }
+        .push_signature(signature)
+    }
+
+    /// The string form of the signature.
+    pub fn as_str(&self) -> &str {
+        std::str::from_utf8(&self.bytes[..self.len]).expect("non-ASCII signature character")
+    }
+
+    // `const fn` can't take `&mut self` on our MSRV so these take and return `self` instead.
+    const fn push(mut self, byte: u8) -> Self {
+        if self.len == MAX_SIGNATURE_LEN {
+            panic!("signature is too long");
+        }
+        self.bytes[self.len] = byte;
+        self.len += 1;
+
+        self
+    }
+
+    const fn push_signature(self, signature: &ParsedSignature) -> Self {
+        match signature {
+            ParsedSignature::Unit => self,
+            ParsedSignature::Array(child) => self.push(b'a').push_child(child),
+            ParsedSignature::Dict { key, value } => self
+                .push(b'a')
+                .push(b'{')
+                .push_child(key)
+                .push_child(value)
+                .push(b'}'),
+            ParsedSignature::Structure(fields) => {
+                let fields = match fields {
+                    Fields::Static { fields } => fields,
+                    Fields::Dynamic { .. } => panic!("structure fields built at runtime"),
+                };
+                let mut buf = self.push(b'(');
+                let mut i = 0;
+                while i < fields.len() {
+                    buf = buf.push_signature(fields[i]);
+                    i += 1;
+                }
+
+                buf.push(b')')
+            }
+            ParsedSignature::Maybe(child) => self.push(b'm').push_child(child),
+            _ => match signature.as_static_str() {
+                Some(s) => self.push(s.as_bytes()[0]),
+                None => panic!("container signature without a child"),
+            },
+        }
+    }
+
+    const fn push_child(self, child: &Child) -> Self {
+        match child {
+            Child::Static { child } => self.push_signature(child),
+            Child::Dynamic { .. } => panic!("child signature built at runtime"),
+        }
+    }
+}
+
+/// Errors from parsing a signature.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum Error {
+    /// The signature is longer than [`MAX_SIGNATURE_LEN`]. Contains the actual length.
+    TooLong(usize),
+    /// The signature ended before a complete type.
+    UnexpectedEnd,
+    /// Unexpected character at the given position.
+    UnexpectedChar(usize, char),
+    /// Structure without any fields, starting at the given position.
+    EmptyStructure(usize),
+    /// Dictionary key, at the given position, that is not of a basic type.
+    NonBasicKey(usize),
+    /// Array, at the given position, nested deeper than [`MAX_ARRAY_DEPTH`].
+    ArrayTooDeep(usize),
+    /// Structure or dictionary entry, at the given position, nested deeper than
+    /// [`MAX_STRUCT_DEPTH`].
+    StructureTooDeep(usize),
+}
+
+impl Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Error::TooLong(len) => write!(
+                f,
+                "signature is {len} bytes long, while at most {MAX_SIGNATURE_LEN} are allowed"
+            ),
+            Error::UnexpectedEnd => f.write_str("signature ended before a complete type"),
+            Error::UnexpectedChar(pos, c) => {
+                write!(
+                    f,
+                    "unexpected character `{c}` at position {pos} in signature"
+                )
+            }
+            Error::EmptyStructure(pos) => {
+                write!(f, "structure without fields at position {pos} in signature")
+            }
+            Error::NonBasicKey(pos) => {
+                write!(
+                    f,
+                    "dict-entry key at position {pos} in signature is not a basic type"
+                )
+            }
+            Error::ArrayTooDeep(pos) => write!(
+                f,
+                "array at position {pos} in signature is nested more than \
+                 {MAX_ARRAY_DEPTH} levels deep"
+            ),
+            Error::StructureTooDeep(pos) => write!(
+                f,
+                "structure at position {pos} in signature is nested more than \
+                 {MAX_STRUCT_DEPTH} levels deep"
+            ),
+        }
+    }
+}
+
+impl std::error::Error for Error {}
+
+struct Parser<'b> {
+    bytes: &'b [u8],
+    pos: usize,
+    array_depth: u8,
+    struct_depth: u8,
+}
+
+impl Parser<'_> {
+    fn next_byte(&mut self) -> Result<u8, Error> {
+        let b = *self.bytes.get(self.pos).ok_or(Error::UnexpectedEnd)?;
+        self.pos += 1;
+
+        Ok(b)
+    }
+
+    fn peek_byte(&self) -> Option<u8> {
+        self.bytes.get(self.pos).copied()
+    }
+
+    fn parse_complete_type(&mut self) -> Result<ParsedSignature, Error> {
+        let start = self.pos;
+        let signature = match self.next_byte()? {
+            b'a' if self.peek_byte() == Some(b'{') => {
+                self.enter_array(start)?;
+                self.enter_structure(start + 1)?;
+                self.pos += 1;
+                let key_pos = self.pos;
+                let key = self.parse_complete_type()?;
+                if !key.is_basic() {
+                    return Err(Error::NonBasicKey(key_pos));
+                }
+                let value = self.parse_complete_type()?;
+                self.expect_byte(b'}')?;
+                self.struct_depth -= 1;
+                self.array_depth -= 1;
+
+                ParsedSignature::dict(key, value)
+            }
+            b'a' => {
+                self.enter_array(start)?;
+                let child = self.parse_complete_type()?;
+                self.array_depth -= 1;
+
+                ParsedSignature::array(child)
+            }
+            b'm' => ParsedSignature::maybe(self.parse_complete_type()?),
+            b'(' => {
+                self.enter_structure(start)?;
+                let mut fields = Vec::new();
+                while self.peek_byte() != Some(b')') {
+                    fields.push(self.parse_complete_type()?);
+                }
+                self.pos += 1;
+                if fields.is_empty() {
+                    return Err(Error::EmptyStructure(start));
+                }
+                self.struct_depth -= 1;
+
+                ParsedSignature::structure(fields)
+            }
+            b @ (b'y' | b'b' | b'n' | b'q' | b'i' | b'u' | b'x' | b't' | b'd' | b's' | b'g'
+            | b'o' | b'v' | b'h') => ParsedSignature::basic(b as char),
+            b => return Err(Error::UnexpectedChar(start, b as char)),
+        };
+
+        Ok(signature)
+    }
+
+    fn enter_array(&mut self, pos: usize) -> Result<(), Error> {
+        if self.array_depth == MAX_ARRAY_DEPTH {
+            return Err(Error::ArrayTooDeep(pos));
+        }
+        self.array_depth += 1;
+
+        Ok(())
+    }
+
+    fn enter_structure(&mut self, pos: usize) -> Result<(), Error> {
+        if self.struct_depth == MAX_STRUCT_DEPTH {
+            return Err(Error::StructureTooDeep(pos));
+        }
+        self.struct_depth += 1;
+
+        Ok(())
+    }
+
+    fn expect_byte(&mut self, expected: u8) -> Result<(), Error> {
+        let pos = self.pos;
+        match self.next_byte()? {
+            b if b == expected => Ok(()),
+            b => Err(Error::UnexpectedChar(pos, b as char)),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn valid() {
+        for s in [
+            "",
+            "y",
+            "a{sv}",
+            "a{oa{sa{sv}}}",
+            "a(ua(yt))",
+            "(y(bn)a{qi})",
+            "((((s))))",
+            "aaa{s(vh)}",
+            "mam(sv)",
+        ] {
+            let parsed = ParsedSignature::from_bytes(s.as_bytes()).unwrap();
+            assert_eq!(parsed.to_string(), s);
+            assert_eq!(parsed.string_len(), s.len());
+        }
+
+        let parsed: ParsedSignature = "a{sa(ix)}".parse().unwrap();
+        let ParsedSignature::Dict { key, value } = &parsed else {
+            panic!("expected a dict, got {parsed:?}");
+        };
+        assert_eq!(**key, ParsedSignature::Str);
+        let ParsedSignature::Array(child) = &**value else {
+            panic!("expected an array, got {value:?}");
+        };
+        assert_eq!(
+            **child,
+            ParsedSignature::structure([ParsedSignature::I32, ParsedSignature::I64])
+        );
+
+        // Multiple complete types are treated as a structure.
+        let parsed: ParsedSignature = "sa{sv}(u)".parse().unwrap();
+        assert_eq!(parsed, *"(sa{sv}(u))");
+    }
+
+    #[test]
+    fn invalid() {
+        let parse = |s: &str| ParsedSignature::from_bytes(s.as_bytes());
+
+        // Unbalanced parentheses and braces.
+        assert_eq!(parse("(s"), Err(Error::UnexpectedEnd));
+        assert_eq!(parse("((s)"), Err(Error::UnexpectedEnd));
+        assert_eq!(parse("s)"), Err(Error::UnexpectedChar(1, ')')));
+        assert_eq!(parse("(s))"), Err(Error::UnexpectedChar(3, ')')));
+        assert_eq!(parse("a{sv"), Err(Error::UnexpectedEnd));
+        assert_eq!(parse("a{svs}"), Err(Error::UnexpectedChar(4, 's')));
+        assert_eq!(parse("a{s}"), Err(Error::UnexpectedChar(3, '}')));
+        // Dict entries are only allowed as array elements.
+        assert_eq!(parse("{sv}"), Err(Error::UnexpectedChar(0, '{')));
+        assert_eq!(parse("(s{sv})"), Err(Error::UnexpectedChar(2, '{')));
+        assert_eq!(parse("ma{sv}{sv}"), Err(Error::UnexpectedChar(6, '{')));
+        // Empty structures.
+        assert_eq!(parse("()"), Err(Error::EmptyStructure(0)));
+        assert_eq!(parse("a()"), Err(Error::EmptyStructure(1)));
+        assert_eq!(parse("(s())"), Err(Error::EmptyStructure(2)));
+        // Non-basic dict keys.
+        assert_eq!(parse("a{vs}"), Err(Error::NonBasicKey(2)));
+        assert_eq!(parse("a{(s)s}"), Err(Error::NonBasicKey(2)));
+        // Incomplete and unknown types.
+        assert_eq!(parse("a"), Err(Error::UnexpectedEnd));
+        assert_eq!(parse("m"), Err(Error::UnexpectedEnd));
+        assert_eq!(parse("sz"), Err(Error::UnexpectedChar(1, 'z')));
+    }
+
+    #[test]
+    fn length_limit() {
+        let max = "y".repeat(MAX_SIGNATURE_LEN);
+        let parsed = ParsedSignature::from_bytes(max.as_bytes()).unwrap();
+        assert_eq!(parsed.string_len(), MAX_SIGNATURE_LEN + 2);
+
+        let too_long = "y".repeat(MAX_SIGNATURE_LEN + 1);
+        assert_eq!(
+            ParsedSignature::from_bytes(too_long.as_bytes()),
+            Err(Error::TooLong(MAX_SIGNATURE_LEN + 1))
+        );
+    }
+
+    #[test]
+    fn depth_limits() {
+        let depth = MAX_ARRAY_DEPTH as usize;
+        let arrays = format!("{}y", "a".repeat(depth));
+        ParsedSignature::from_bytes(arrays.as_bytes()).unwrap();
+        let arrays = format!("a{arrays}");
+        assert_eq!(
+            ParsedSignature::from_bytes(arrays.as_bytes()),
+            Err(Error::ArrayTooDeep(depth))
+        );
+        // Dictionaries count as arrays.
+        let dicts = format!("{}y{}", "a{y".repeat(depth), "}".repeat(depth));
+        ParsedSignature::from_bytes(dicts.as_bytes()).unwrap();
+        let dicts = format!("a{{y{dicts}}}");
+        assert_eq!(
+            ParsedSignature::from_bytes(dicts.as_bytes()),
+            Err(Error::ArrayTooDeep(depth * 3))
+        );
+
+        let depth = MAX_STRUCT_DEPTH as usize;
+        let structs = format!("{}y{}", "(".repeat(depth), ")".repeat(depth));
+        ParsedSignature::from_bytes(structs.as_bytes()).unwrap();
+        let structs = format!("({structs})");
+        assert_eq!(
+            ParsedSignature::from_bytes(structs.as_bytes()),
+            Err(Error::StructureTooDeep(depth))
+        );
+        // Dict entries count as structures.
+        let entries = format!("{}a{{yy}}{}", "(".repeat(depth), ")".repeat(depth));
+        assert_eq!(
+            ParsedSignature::from_bytes(entries.as_bytes()),
+            Err(Error::StructureTooDeep(depth + 1))
+        );
+
+        // Both limits can be reached at the same time.
+        let both = format!(
+            "{}{}y{}",
+            "a".repeat(MAX_ARRAY_DEPTH as usize),
+            "(".repeat(depth),
+            ")".repeat(depth)
+        );
+        let parsed = ParsedSignature::from_bytes(both.as_bytes()).unwrap();
+        assert_eq!(parsed.to_string(), both);
+    }
+}